@@ -0,0 +1,97 @@
+use std::fs;
+
+/// Container a process belongs to, as derived from its cgroup path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ContainerRef {
+    pub id: String,
+    /// Runtime-prefixed short id, e.g. `docker:0123456789ab`.
+    pub name: String,
+}
+
+/// Reads `/proc/<pid>/cgroup`; missing or unreadable files yield `None`.
+pub(crate) fn container_for_pid(pid: i32) -> Option<ContainerRef> {
+    let content = fs::read_to_string(format!("/proc/{pid}/cgroup")).ok()?;
+    parse_cgroup(&content)
+}
+
+/// Handles both v1 (`12:memory:/docker/<id>`) and v2 (`0::/system.slice/docker-<id>.scope`)
+/// lines. The first line carrying a container id wins.
+pub(crate) fn parse_cgroup(content: &str) -> Option<ContainerRef> {
+    content.lines().find_map(|line| {
+        let path = line.splitn(3, ':').nth(2)?;
+        container_from_path(path)
+    })
+}
+
+fn container_from_path(path: &str) -> Option<ContainerRef> {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    for (idx, segment) in segments.iter().enumerate().rev() {
+        let segment = segment.strip_suffix(".scope").unwrap_or(segment);
+        let (runtime, id) = match segment.rsplit_once('-') {
+            Some((prefix, id)) => (runtime_name(prefix), id),
+            // `/docker/<id>` or `/kubepods/.../<id>`: runtime comes from the parent.
+            None => (
+                idx.checked_sub(1)
+                    .map(|parent| runtime_name(segments[parent]))
+                    .unwrap_or("container"),
+                segment,
+            ),
+        };
+        if is_container_id(id) {
+            return Some(ContainerRef {
+                id: id.to_string(),
+                name: format!("{runtime}:{}", &id[..12]),
+            });
+        }
+    }
+    None
+}
+
+fn runtime_name(prefix: &str) -> &'static str {
+    match prefix {
+        "docker" => "docker",
+        "libpod" | "libpod-conmon" => "podman",
+        "cri-containerd" => "containerd",
+        "crio" | "crio-conmon" => "crio",
+        p if p.starts_with("kubepods") => "kubepods",
+        _ => "container",
+    }
+}
+
+fn is_container_id(s: &str) -> bool {
+    s.len() == 64 && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "3f2a9c1b7d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8";
+
+    #[test]
+    fn parses_v1_and_v2_layouts() {
+        let v1 = format!("12:memory:/docker/{ID}\n11:cpu,cpuacct:/docker/{ID}\n");
+        let v2 = format!("0::/system.slice/docker-{ID}.scope\n");
+        let podman =
+            format!("0::/user.slice/user-1000.slice/user@1000.service/libpod-{ID}.scope\n");
+        for (content, name) in [
+            (v1, "docker:3f2a9c1b7d4e"),
+            (v2, "docker:3f2a9c1b7d4e"),
+            (podman, "podman:3f2a9c1b7d4e"),
+        ] {
+            let found = parse_cgroup(&content).expect("container found");
+            assert_eq!(found.id, ID);
+            assert_eq!(found.name, name);
+        }
+    }
+
+    #[test]
+    fn host_processes_have_no_container() {
+        assert_eq!(
+            parse_cgroup("0::/user.slice/user-1000.slice/session-2.scope\n"),
+            None
+        );
+        assert_eq!(parse_cgroup("12:memory:/\n"), None);
+        assert_eq!(parse_cgroup(""), None);
+    }
+}
@@ -1,6 +1,28 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::ffi::OsString;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::file_id::FileIdentity;
+use crate::redact::RedactPattern;
+use crate::socket::{self, SocketPerms};
+
+pub const DEFAULT_PROC_POLL_MS: u64 = 750;
+pub const MIN_PROC_POLL_MS: u64 = 100;
+pub const DEFAULT_PASSWD_REFRESH_SECS: u64 = 60;
+pub const DEFAULT_FS_COALESCE_MS: u64 = 250;
+pub const MIN_FS_COALESCE_MS: u64 = 10;
+/// Proc batches count up from 1; fs batches start here to stay out of their way.
+pub const DEFAULT_FS_BATCH_ID_BASE: u64 = 50_000;
+/// Matches the historic kernel default for `fs.inotify.max_user_watches`.
+pub const DEFAULT_FS_MAX_WATCHES: usize = 8192;
+pub const DEFAULT_FS_POLL_MS: u64 = 5000;
+pub const MIN_FS_POLL_MS: u64 = 500;
+/// Broadcast events kept for viewers that reconnect.
+pub const DEFAULT_RESUME_BUFFER: usize = 20_000;
+/// Where a Windows agent listens without `--listen`; it has no unix sockets.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub const DEFAULT_TCP_LISTEN: &str = "127.0.0.1:7040";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AgentMode {
@@ -18,12 +40,67 @@ impl AgentMode {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    #[default]
+    Human,
+    /// One JSON object per line, event fields at the top level.
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(input: &str) -> Result<Self> {
+        match input {
+            "human" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            _ => anyhow::bail!("invalid log format: {input} (expected human|json)"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AgentConfig {
     pub mode: AgentMode,
     pub includes: Vec<PathBuf>,
     pub excludes: Vec<PathBuf>,
+    /// Extra include/exclude lines, re-read on `ReloadPathPolicy`.
+    pub policy_file: Option<PathBuf>,
     pub uds_path: Option<PathBuf>,
+    /// Linux abstract socket name (without `@`), used instead of `uds_path`.
+    pub abstract_socket: Option<String>,
+    /// `host:port` to accept viewers over TCP instead of a unix socket.
+    pub listen: Option<String>,
+    /// Cap on `Opens` edges per process; `None` means unlimited.
+    #[cfg_attr(windows, allow(dead_code))]
+    pub max_fd_edges_per_process: Option<usize>,
+    pub proc_poll: Duration,
+    pub passwd_refresh: Duration,
+    /// Window over which fs events for the same path are merged into one batch.
+    pub fs_coalesce: Duration,
+    /// First batch id used by the fs watcher.
+    pub fs_batch_id_base: u64,
+    /// Inotify watches the fs watcher may hold; directories beyond it are polled.
+    pub fs_max_watches: usize,
+    /// Scan interval for directories that fell back to polling.
+    pub fs_poll: Duration,
+    /// Print one wire `Snapshot` as JSON to stdout and exit.
+    pub once: bool,
+    /// File holding the shared secret clients must send in `Hello`.
+    pub token_file: Option<PathBuf>,
+    pub log_format: LogFormat,
+    /// `host:port` for the Prometheus `/metrics` endpoint; off when unset.
+    pub metrics_addr: Option<String>,
+    /// Events buffered for resuming viewers; 0 always sends a full snapshot.
+    pub resume_buffer: usize,
+    /// Mode and group applied to the UDS after bind.
+    pub socket: SocketPerms,
+    /// Key file nodes by path or by device + inode.
+    pub file_identity: FileIdentity,
+    /// Emit a node per thread (`/proc/<pid>/task`); off by default for volume.
+    #[cfg_attr(windows, allow(dead_code))]
+    pub threads: bool,
+    /// Path globs whose wildcard segments are hashed before leaving the host.
+    pub redact: Vec<RedactPattern>,
 }
 
 pub fn parse_args() -> Result<AgentConfig> {
@@ -37,7 +114,26 @@ where
     let mut mode = AgentMode::User;
     let mut includes = Vec::new();
     let mut excludes = Vec::new();
+    let mut policy_file = None;
     let mut uds_path = None;
+    let mut abstract_socket = None;
+    let mut listen = None;
+    let mut max_fd_edges_per_process = None;
+    let mut proc_poll_ms = DEFAULT_PROC_POLL_MS;
+    let mut passwd_refresh_secs = DEFAULT_PASSWD_REFRESH_SECS;
+    let mut fs_coalesce_ms = DEFAULT_FS_COALESCE_MS;
+    let mut fs_batch_id_base = DEFAULT_FS_BATCH_ID_BASE;
+    let mut fs_max_watches = DEFAULT_FS_MAX_WATCHES;
+    let mut fs_poll_ms = DEFAULT_FS_POLL_MS;
+    let mut once = false;
+    let mut token_file = None;
+    let mut log_format = LogFormat::default();
+    let mut metrics_addr = None;
+    let mut resume_buffer = DEFAULT_RESUME_BUFFER;
+    let mut socket = SocketPerms::default();
+    let mut file_identity = FileIdentity::default();
+    let mut threads = false;
+    let mut redact = Vec::new();
     let mut args = args.into_iter();
 
     while let Some(arg) = args.next() {
@@ -51,6 +147,11 @@ where
                 anyhow::bail!("--exclude expects a path");
             };
             excludes.push(PathBuf::from(path));
+        } else if arg == "--policy-file" {
+            let Some(path) = args.next() else {
+                anyhow::bail!("--policy-file expects a path");
+            };
+            policy_file = Some(PathBuf::from(path));
         } else if arg == "--mode" {
             let Some(value) = args.next() else {
                 anyhow::bail!("--mode expects user|privileged");
@@ -62,19 +163,203 @@ where
                 anyhow::bail!("--uds expects a path");
             };
             uds_path = Some(PathBuf::from(path));
+        } else if arg == "--abstract-socket" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--abstract-socket expects a name");
+            };
+            if !cfg!(target_os = "linux") {
+                anyhow::bail!("--abstract-socket is only supported on Linux");
+            }
+            abstract_socket = Some(socket::parse_abstract_name(&value.to_string_lossy())?);
+        } else if arg == "--listen" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--listen expects host:port");
+            };
+            let value = value.to_string_lossy().to_string();
+            if !value.contains(':') {
+                anyhow::bail!("invalid --listen: {value} (expected host:port)");
+            }
+            listen = Some(value);
+        } else if arg == "--max-fd-edges-per-process" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--max-fd-edges-per-process expects a number");
+            };
+            let value = value.to_string_lossy();
+            let n = value
+                .parse::<usize>()
+                .map_err(|_| anyhow::anyhow!("invalid --max-fd-edges-per-process: {value}"))?;
+            max_fd_edges_per_process = Some(n);
+        } else if arg == "--proc-poll-ms" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--proc-poll-ms expects milliseconds");
+            };
+            let value = value.to_string_lossy();
+            proc_poll_ms = value
+                .parse::<u64>()
+                .map_err(|_| anyhow::anyhow!("invalid --proc-poll-ms: {value}"))?;
+        } else if arg == "--passwd-refresh-secs" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--passwd-refresh-secs expects seconds");
+            };
+            let value = value.to_string_lossy();
+            passwd_refresh_secs = value
+                .parse::<u64>()
+                .map_err(|_| anyhow::anyhow!("invalid --passwd-refresh-secs: {value}"))?;
+        } else if arg == "--fs-coalesce-ms" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--fs-coalesce-ms expects milliseconds");
+            };
+            let value = value.to_string_lossy();
+            fs_coalesce_ms = value
+                .parse::<u64>()
+                .map_err(|_| anyhow::anyhow!("invalid --fs-coalesce-ms: {value}"))?;
+        } else if arg == "--fs-batch-id-base" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--fs-batch-id-base expects a number");
+            };
+            let value = value.to_string_lossy();
+            fs_batch_id_base = value
+                .parse::<u64>()
+                .map_err(|_| anyhow::anyhow!("invalid --fs-batch-id-base: {value}"))?;
+        } else if arg == "--fs-max-watches" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--fs-max-watches expects a number");
+            };
+            let value = value.to_string_lossy();
+            fs_max_watches = value
+                .parse::<usize>()
+                .map_err(|_| anyhow::anyhow!("invalid --fs-max-watches: {value}"))?;
+        } else if arg == "--fs-poll-ms" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--fs-poll-ms expects milliseconds");
+            };
+            let value = value.to_string_lossy();
+            fs_poll_ms = value
+                .parse::<u64>()
+                .map_err(|_| anyhow::anyhow!("invalid --fs-poll-ms: {value}"))?;
+        } else if arg == "--once" {
+            once = true;
+        } else if arg == "--token-file" {
+            let Some(path) = args.next() else {
+                anyhow::bail!("--token-file expects a path");
+            };
+            token_file = Some(PathBuf::from(path));
+        } else if arg == "--log-format" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--log-format expects human|json");
+            };
+            let value = value.to_string_lossy();
+            log_format = LogFormat::parse(&value)?;
+        } else if arg == "--metrics-addr" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--metrics-addr expects host:port");
+            };
+            let value = value.to_string_lossy().to_string();
+            if !value.contains(':') {
+                anyhow::bail!("invalid --metrics-addr: {value} (expected host:port)");
+            }
+            metrics_addr = Some(value);
+        } else if arg == "--resume-buffer" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--resume-buffer expects a number of events");
+            };
+            let value = value.to_string_lossy();
+            resume_buffer = value
+                .parse::<usize>()
+                .map_err(|_| anyhow::anyhow!("invalid --resume-buffer: {value}"))?;
+        } else if arg == "--socket-mode" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--socket-mode expects an octal mode");
+            };
+            socket.mode = socket::parse_mode(&value.to_string_lossy())?;
+        } else if arg == "--socket-group" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--socket-group expects a group name");
+            };
+            socket.group = Some(value.to_string_lossy().to_string());
+        } else if arg == "--file-identity" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--file-identity expects path or inode");
+            };
+            file_identity = FileIdentity::parse(&value.to_string_lossy())?;
+        } else if arg == "--threads" {
+            threads = true;
+        } else if arg == "--redact" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--redact expects a glob");
+            };
+            redact.push(RedactPattern::parse(&value.to_string_lossy())?);
         } else {
             anyhow::bail!("unknown argument: {:?}", arg);
         }
     }
 
+    if uds_path.is_some() && abstract_socket.is_some() {
+        anyhow::bail!("--uds and --abstract-socket are mutually exclusive");
+    }
+    if listen.is_some() && (uds_path.is_some() || abstract_socket.is_some()) {
+        anyhow::bail!("--listen and --uds/--abstract-socket are mutually exclusive");
+    }
+
     Ok(AgentConfig {
         mode,
         includes,
         excludes,
+        policy_file,
         uds_path,
+        abstract_socket,
+        listen,
+        max_fd_edges_per_process,
+        proc_poll: Duration::from_millis(proc_poll_ms.max(MIN_PROC_POLL_MS)),
+        passwd_refresh: Duration::from_secs(passwd_refresh_secs.max(1)),
+        fs_coalesce: Duration::from_millis(fs_coalesce_ms.max(MIN_FS_COALESCE_MS)),
+        fs_batch_id_base,
+        fs_max_watches,
+        fs_poll: Duration::from_millis(fs_poll_ms.max(MIN_FS_POLL_MS)),
+        once,
+        token_file,
+        log_format,
+        metrics_addr,
+        resume_buffer,
+        socket,
+        file_identity,
+        threads,
+        redact,
     })
 }
 
+/// Shared secret from `--token-file`, else `SPACEGRAPH_TOKEN`; surrounding whitespace is
+/// ignored and an empty secret counts as none.
+pub fn resolve_token(token_file: Option<&Path>) -> Result<Option<String>> {
+    let raw = match token_file {
+        Some(path) => std::fs::read_to_string(path)
+            .with_context(|| format!("read token file {}", path.display()))?,
+        None => std::env::var("SPACEGRAPH_TOKEN").unwrap_or_default(),
+    };
+    let token = raw.trim();
+    Ok((!token.is_empty()).then(|| token.to_string()))
+}
+
+/// Refuses a TCP listen address other hosts can reach unless a token guards it; plain
+/// TCP has no file permissions to fall back on.
+pub fn check_listen_exposure(addr: &str, token_set: bool) -> Result<()> {
+    if token_set || is_loopback_listen(addr) {
+        return Ok(());
+    }
+    anyhow::bail!(
+        "--listen {addr} is reachable from other hosts; set --token-file or SPACEGRAPH_TOKEN, \
+         or listen on a loopback address"
+    )
+}
+
+fn is_loopback_listen(addr: &str) -> bool {
+    if let Ok(sock) = addr.parse::<std::net::SocketAddr>() {
+        return sock.ip().is_loopback();
+    }
+    addr.rsplit_once(':')
+        .is_some_and(|(host, _)| host.eq_ignore_ascii_case("localhost"))
+}
+
 pub fn default_includes(mode: AgentMode) -> Vec<PathBuf> {
     match mode {
         AgentMode::User | AgentMode::Privileged => vec![
@@ -110,6 +395,7 @@ pub fn default_excludes(mode: AgentMode) -> Vec<PathBuf> {
     }
 }
 
+#[cfg_attr(not(unix), allow(dead_code))]
 pub fn should_warn_privileged_without_root(mode: AgentMode, euid: u32) -> bool {
     matches!(mode, AgentMode::Privileged) && euid != 0
 }
@@ -126,6 +412,99 @@ mod tests {
         assert_eq!(config.mode, AgentMode::Privileged);
     }
 
+    #[test]
+    fn parses_log_format_flag() {
+        let config = parse_args_from(Vec::<OsString>::new()).expect("config parsed");
+        assert_eq!(config.log_format, LogFormat::Human);
+
+        let args = vec![OsString::from("--log-format"), OsString::from("json")];
+        let config = parse_args_from(args).expect("config parsed");
+        assert_eq!(config.log_format, LogFormat::Json);
+
+        let bad = vec![OsString::from("--log-format"), OsString::from("xml")];
+        assert!(parse_args_from(bad).is_err());
+    }
+
+    #[test]
+    fn parses_listen_flag() {
+        let config = parse_args_from(Vec::<OsString>::new()).expect("config parsed");
+        assert_eq!(config.listen, None);
+
+        let args = vec![OsString::from("--listen"), OsString::from("0.0.0.0:7040")];
+        let config = parse_args_from(args).expect("config parsed");
+        assert_eq!(config.listen.as_deref(), Some("0.0.0.0:7040"));
+
+        for bad in [
+            vec!["--listen"],
+            vec!["--listen", "7040"],
+            vec!["--listen", "127.0.0.1:7040", "--uds", "/tmp/test.sock"],
+        ] {
+            let bad: Vec<OsString> = bad.into_iter().map(OsString::from).collect();
+            assert!(parse_args_from(bad.clone()).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn parses_metrics_addr_flag() {
+        let config = parse_args_from(Vec::<OsString>::new()).expect("config parsed");
+        assert_eq!(config.metrics_addr, None);
+
+        let args = vec![
+            OsString::from("--metrics-addr"),
+            OsString::from("127.0.0.1:9464"),
+        ];
+        let config = parse_args_from(args).expect("config parsed");
+        assert_eq!(config.metrics_addr.as_deref(), Some("127.0.0.1:9464"));
+
+        let bad = vec![OsString::from("--metrics-addr"), OsString::from("9464")];
+        assert!(parse_args_from(bad).is_err());
+    }
+
+    #[test]
+    fn parses_resume_buffer_flag() {
+        let config = parse_args_from(Vec::<OsString>::new()).expect("config parsed");
+        assert_eq!(config.resume_buffer, DEFAULT_RESUME_BUFFER);
+
+        let args = vec![OsString::from("--resume-buffer"), OsString::from("0")];
+        let config = parse_args_from(args).expect("config parsed");
+        assert_eq!(config.resume_buffer, 0);
+
+        let bad = vec![OsString::from("--resume-buffer"), OsString::from("lots")];
+        assert!(parse_args_from(bad).is_err());
+    }
+
+    #[test]
+    fn parses_socket_permission_flags() {
+        let config = parse_args_from(Vec::<OsString>::new()).expect("config parsed");
+        assert_eq!(config.socket, SocketPerms::default());
+
+        let args = vec![
+            OsString::from("--socket-mode"),
+            OsString::from("0660"),
+            OsString::from("--socket-group"),
+            OsString::from("spacegraph"),
+        ];
+        let config = parse_args_from(args).expect("config parsed");
+        assert_eq!(config.socket.mode, 0o660);
+        assert_eq!(config.socket.group.as_deref(), Some("spacegraph"));
+
+        let bad = vec![OsString::from("--socket-mode"), OsString::from("999")];
+        assert!(parse_args_from(bad).is_err());
+    }
+
+    #[test]
+    fn parses_file_identity_flag() {
+        let config = parse_args_from(Vec::<OsString>::new()).expect("config parsed");
+        assert_eq!(config.file_identity, FileIdentity::Path);
+
+        let args = vec![OsString::from("--file-identity"), OsString::from("inode")];
+        let config = parse_args_from(args).expect("config parsed");
+        assert_eq!(config.file_identity, FileIdentity::Inode);
+
+        let bad = vec![OsString::from("--file-identity"), OsString::from("hash")];
+        assert!(parse_args_from(bad).is_err());
+    }
+
     #[test]
     fn default_excludes_include_cni_only_in_user_mode() {
         let user = default_excludes(AgentMode::User);
@@ -149,4 +528,178 @@ mod tests {
         let config = parse_args_from(args).expect("config parsed");
         assert_eq!(config.uds_path, Some(PathBuf::from("/tmp/test.sock")));
     }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parses_abstract_socket_flag() {
+        let args = vec![
+            OsString::from("--abstract-socket"),
+            OsString::from("@spacegraph"),
+        ];
+        let config = parse_args_from(args).expect("config parsed");
+        assert_eq!(config.abstract_socket.as_deref(), Some("spacegraph"));
+        assert_eq!(config.uds_path, None);
+
+        for bad in [
+            vec!["--abstract-socket"],
+            vec!["--abstract-socket", "@"],
+            vec!["--abstract-socket", "sg", "--uds", "/tmp/test.sock"],
+        ] {
+            let bad: Vec<OsString> = bad.into_iter().map(OsString::from).collect();
+            assert!(parse_args_from(bad.clone()).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn parses_max_fd_edges_flag() {
+        let args = vec![
+            OsString::from("--max-fd-edges-per-process"),
+            OsString::from("64"),
+        ];
+        let config = parse_args_from(args).expect("config parsed");
+        assert_eq!(config.max_fd_edges_per_process, Some(64));
+
+        let bad = vec![
+            OsString::from("--max-fd-edges-per-process"),
+            OsString::from("lots"),
+        ];
+        assert!(parse_args_from(bad).is_err());
+    }
+
+    #[test]
+    fn proc_poll_defaults_and_clamps() {
+        let config = parse_args_from(Vec::<OsString>::new()).expect("config parsed");
+        assert_eq!(
+            config.proc_poll,
+            Duration::from_millis(DEFAULT_PROC_POLL_MS)
+        );
+
+        let args = vec![OsString::from("--proc-poll-ms"), OsString::from("5")];
+        let config = parse_args_from(args).expect("config parsed");
+        assert_eq!(config.proc_poll, Duration::from_millis(MIN_PROC_POLL_MS));
+
+        let args = vec![OsString::from("--proc-poll-ms"), OsString::from("2000")];
+        let config = parse_args_from(args).expect("config parsed");
+        assert_eq!(config.proc_poll, Duration::from_millis(2000));
+    }
+
+    #[test]
+    fn fs_coalesce_defaults_and_clamps() {
+        let config = parse_args_from(Vec::<OsString>::new()).expect("config parsed");
+        assert_eq!(
+            config.fs_coalesce,
+            Duration::from_millis(DEFAULT_FS_COALESCE_MS)
+        );
+        assert_eq!(config.fs_batch_id_base, DEFAULT_FS_BATCH_ID_BASE);
+
+        let args = vec![
+            OsString::from("--fs-coalesce-ms"),
+            OsString::from("1"),
+            OsString::from("--fs-batch-id-base"),
+            OsString::from("900000"),
+        ];
+        let config = parse_args_from(args).expect("config parsed");
+        assert_eq!(
+            config.fs_coalesce,
+            Duration::from_millis(MIN_FS_COALESCE_MS)
+        );
+        assert_eq!(config.fs_batch_id_base, 900_000);
+
+        let bad = vec![OsString::from("--fs-coalesce-ms"), OsString::from("soon")];
+        assert!(parse_args_from(bad).is_err());
+    }
+
+    #[test]
+    fn fs_watch_budget_and_poll_interval() {
+        let config = parse_args_from(Vec::<OsString>::new()).expect("config parsed");
+        assert_eq!(config.fs_max_watches, DEFAULT_FS_MAX_WATCHES);
+        assert_eq!(config.fs_poll, Duration::from_millis(DEFAULT_FS_POLL_MS));
+
+        let args = vec![
+            OsString::from("--fs-max-watches"),
+            OsString::from("100"),
+            OsString::from("--fs-poll-ms"),
+            OsString::from("10"),
+        ];
+        let config = parse_args_from(args).expect("config parsed");
+        assert_eq!(config.fs_max_watches, 100);
+        assert_eq!(config.fs_poll, Duration::from_millis(MIN_FS_POLL_MS));
+
+        let bad = vec![OsString::from("--fs-max-watches"), OsString::from("-1")];
+        assert!(parse_args_from(bad).is_err());
+    }
+
+    #[test]
+    fn parses_once_flag() {
+        let config = parse_args_from(Vec::<OsString>::new()).expect("config parsed");
+        assert!(!config.once);
+
+        let args = vec![
+            OsString::from("--once"),
+            OsString::from("--mode"),
+            OsString::from("privileged"),
+        ];
+        let config = parse_args_from(args).expect("config parsed");
+        assert!(config.once);
+        assert_eq!(config.mode, AgentMode::Privileged);
+    }
+
+    #[test]
+    fn threads_are_opt_in() {
+        let config = parse_args_from(Vec::<OsString>::new()).expect("config parsed");
+        assert!(!config.threads);
+        let config = parse_args_from(vec![OsString::from("--threads")]).expect("config parsed");
+        assert!(config.threads);
+    }
+
+    #[test]
+    fn parses_token_file_flag() {
+        let args = vec![
+            OsString::from("--token-file"),
+            OsString::from("/etc/spacegraph/token"),
+        ];
+        let config = parse_args_from(args).expect("config parsed");
+        assert_eq!(
+            config.token_file,
+            Some(PathBuf::from("/etc/spacegraph/token"))
+        );
+        assert!(parse_args_from(vec![OsString::from("--token-file")]).is_err());
+    }
+
+    #[test]
+    fn non_loopback_listen_needs_a_token() {
+        assert!(check_listen_exposure("0.0.0.0:7040", false).is_err());
+        assert!(check_listen_exposure("[::]:7040", false).is_err());
+        assert!(check_listen_exposure("viewer-host:7040", false).is_err());
+        assert!(check_listen_exposure("0.0.0.0:7040", true).is_ok());
+    }
+
+    #[test]
+    fn loopback_listen_runs_without_a_token() {
+        for addr in ["127.0.0.1:7040", "[::1]:7040", "localhost:7040"] {
+            assert!(check_listen_exposure(addr, false).is_ok(), "{addr}");
+        }
+    }
+
+    #[test]
+    fn parses_policy_file_flag() {
+        let args = vec![
+            OsString::from("--policy-file"),
+            OsString::from("/etc/spacegraph/policy"),
+        ];
+        let config = parse_args_from(args).expect("config parsed");
+        assert_eq!(
+            config.policy_file,
+            Some(PathBuf::from("/etc/spacegraph/policy"))
+        );
+        assert!(parse_args_from(vec![OsString::from("--policy-file")]).is_err());
+    }
+
+    #[test]
+    fn parses_repeated_redact_globs() {
+        let args = ["--redact", "/home/*/.ssh", "--redact", "**/*secret*"].map(OsString::from);
+        let config = parse_args_from(args).expect("config parsed");
+        assert_eq!(config.redact.len(), 2);
+        assert!(parse_args_from(["--redact", "/home/alice"].map(OsString::from)).is_err());
+    }
 }
@@ -0,0 +1,187 @@
+use spacegraph_core::{ControlCmd, Delta, Msg};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::mpsc;
+
+use crate::resume::{self, ResumeBuffer};
+
+/// Agent-wide switches flipped by viewer control commands.
+#[derive(Debug, Default)]
+pub struct Control {
+    paused: AtomicBool,
+}
+
+impl Control {
+    /// While paused, watcher batches are dropped instead of broadcast.
+    pub fn paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Applies switch commands; returns false for anything else. Policy reloads are
+    /// routed by [`run`].
+    pub fn apply(&self, cmd: &ControlCmd) -> bool {
+        match cmd {
+            ControlCmd::Pause => self.paused.store(true, Ordering::Relaxed),
+            ControlCmd::Resume => self.paused.store(false, Ordering::Relaxed),
+            ControlCmd::SetPollInterval(_) | ControlCmd::ReloadPathPolicy => return false,
+        }
+        true
+    }
+}
+
+/// Lets one watcher's messages through unless paused, switching only between batches so
+/// a viewer never gets a `BatchBegin` without its `BatchEnd`.
+#[derive(Debug, Default)]
+pub struct PauseGate {
+    in_batch: bool,
+    dropping: bool,
+}
+
+impl PauseGate {
+    /// Whether `msg` is broadcast; `paused` is only looked at outside a batch.
+    pub fn pass(&mut self, msg: &Msg, paused: bool) -> bool {
+        match msg {
+            Msg::Event {
+                delta: Delta::BatchBegin { .. },
+            } if !self.in_batch => {
+                self.in_batch = true;
+                self.dropping = paused;
+            }
+            Msg::Event {
+                delta: Delta::BatchEnd { .. },
+            } if self.in_batch => {
+                self.in_batch = false;
+                return !self.dropping;
+            }
+            _ if !self.in_batch => return !paused,
+            _ => {}
+        }
+        !self.dropping
+    }
+}
+
+/// Applies commands forwarded by the server until every connection is gone;
+/// `ReloadPathPolicy` is passed on to `reload`.
+///
+/// Resuming starts a new resume session: events dropped while paused are missing from
+/// the buffer, so reconnecting viewers must load a snapshot instead of replaying.
+pub async fn run(
+    mut rx: mpsc::UnboundedReceiver<ControlCmd>,
+    control: Arc<Control>,
+    reload: mpsc::UnboundedSender<()>,
+    resume: Arc<Mutex<ResumeBuffer>>,
+) {
+    while let Some(cmd) = rx.recv().await {
+        let was_paused = control.paused();
+        if cmd == ControlCmd::ReloadPathPolicy {
+            tracing::info!(?cmd, "control_applied");
+            let _ = reload.send(());
+        } else if control.apply(&cmd) {
+            if was_paused && !control.paused() {
+                resume
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .restart(resume::new_session_id());
+            }
+            tracing::info!(?cmd, paused = control.paused(), "control_applied");
+        } else {
+            tracing::warn!(?cmd, "control_unsupported");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pause_and_resume_toggle_broadcasting() {
+        let control = Control::default();
+        assert!(!control.paused());
+        assert!(control.apply(&ControlCmd::Pause));
+        assert!(control.paused());
+        assert!(control.apply(&ControlCmd::Pause));
+        assert!(control.paused());
+        assert!(control.apply(&ControlCmd::Resume));
+        assert!(!control.paused());
+    }
+
+    fn event(delta: Delta) -> Msg {
+        Msg::Event { delta }
+    }
+
+    fn batch(id: u64) -> Vec<Msg> {
+        vec![
+            event(Delta::BatchBegin { id }),
+            event(Delta::RemoveNode {
+                id: spacegraph_core::NodeId(format!("n{id}")),
+            }),
+            event(Delta::BatchEnd { id }),
+        ]
+    }
+
+    #[test]
+    fn pause_mid_batch_waits_for_the_batch_end() {
+        let mut gate = PauseGate::default();
+        let [begin, delta, end] = <[Msg; 3]>::try_from(batch(1)).expect("three messages");
+
+        assert!(gate.pass(&begin, false));
+        // paused after BatchBegin: the rest of the batch still goes out
+        assert!(gate.pass(&delta, true));
+        assert!(gate.pass(&end, true));
+
+        // the next batch starts paused and is dropped whole, even if resumed halfway
+        let passed: Vec<bool> = batch(2)
+            .iter()
+            .zip([true, false, false])
+            .map(|(msg, paused)| gate.pass(msg, paused))
+            .collect();
+        assert_eq!(passed, vec![false, false, false]);
+
+        assert!(batch(3).iter().all(|msg| gate.pass(msg, false)));
+        // messages outside a batch follow the switch directly
+        assert!(!gate.pass(&Msg::Ping, true));
+        assert!(gate.pass(&Msg::Ping, false));
+    }
+
+    async fn buffer_after(cmds: &[ControlCmd]) -> ResumeBuffer {
+        let mut buffer = ResumeBuffer::new(7, 100);
+        batch(1).into_iter().for_each(|msg| buffer.push(msg));
+        let resume = Arc::new(Mutex::new(buffer));
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (reload_tx, _reload_rx) = mpsc::unbounded_channel();
+        for cmd in cmds {
+            tx.send(cmd.clone()).unwrap();
+        }
+        drop(tx);
+        run(
+            rx,
+            Arc::new(Control::default()),
+            reload_tx,
+            Arc::clone(&resume),
+        )
+        .await;
+        Arc::try_unwrap(resume).unwrap().into_inner().unwrap()
+    }
+
+    #[tokio::test]
+    async fn resuming_after_a_pause_starts_a_new_resume_session() {
+        let buffer = buffer_after(&[ControlCmd::Pause, ControlCmd::Resume]).await;
+        assert_ne!(buffer.session(), 7);
+        assert!(buffer.since(7, 1).is_none());
+
+        // nothing was dropped without a pause
+        let buffer = buffer_after(&[ControlCmd::Resume]).await;
+        assert_eq!(buffer.session(), 7);
+        assert!(buffer.since(7, 1).is_some());
+    }
+
+    #[test]
+    fn unsupported_commands_leave_state_alone() {
+        let control = Control::default();
+        control.apply(&ControlCmd::Pause);
+        assert!(!control.apply(&ControlCmd::SetPollInterval(100)));
+        assert!(!control.apply(&ControlCmd::ReloadPathPolicy));
+        assert!(control.paused());
+    }
+}
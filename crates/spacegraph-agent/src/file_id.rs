@@ -0,0 +1,108 @@
+//! How file nodes are keyed: by path (default) or by device + inode, which keeps a node
+//! across renames and log rotation.
+
+use anyhow::{bail, Result};
+use spacegraph_core::{id_file, id_file_by_inode, NodeId};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileIdentity {
+    #[default]
+    Path,
+    Inode,
+}
+
+impl FileIdentity {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "path" => Ok(Self::Path),
+            "inode" => Ok(Self::Inode),
+            other => bail!("invalid file identity {other:?} (expected path or inode)"),
+        }
+    }
+
+    /// Id for the file at `path`; stats it only when keying by inode.
+    pub fn id(self, node_id: &str, path: &str) -> NodeId {
+        match self {
+            Self::Path => id_file(node_id, path),
+            Self::Inode => {
+                let (dev, ino) = dev_ino(path);
+                select_file_id(node_id, path, dev, ino)
+            }
+        }
+    }
+
+    /// Whether ids can outlive their path, so removals need the id seen at upsert time.
+    pub fn by_inode(self) -> bool {
+        self == Self::Inode
+    }
+}
+
+/// Inode 0 means the stat failed (gone, no permission, `socket:[..]` pseudo paths), so
+/// the path is all there is to key on.
+fn select_file_id(node_id: &str, path: &str, dev: u64, ino: u64) -> NodeId {
+    if ino == 0 {
+        id_file(node_id, path)
+    } else {
+        id_file_by_inode(node_id, dev, ino)
+    }
+}
+
+#[cfg(unix)]
+fn dev_ino(path: &str) -> (u64, u64) {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path)
+        .map(|m| (m.dev(), m.ino()))
+        .unwrap_or((0, 0))
+}
+
+#[cfg(not(unix))]
+fn dev_ino(_path: &str) -> (u64, u64) {
+    (0, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inode_is_preferred_and_zero_falls_back_to_path() {
+        assert_eq!(
+            select_file_id("n", "/var/log/app.log", 2049, 77),
+            id_file_by_inode("n", 2049, 77)
+        );
+        assert_eq!(
+            select_file_id("n", "/var/log/app.log", 2049, 0),
+            id_file("n", "/var/log/app.log")
+        );
+        assert_eq!(
+            FileIdentity::Inode.id("n", "socket:[4242]"),
+            id_file("n", "socket:[4242]")
+        );
+        assert_eq!(
+            FileIdentity::Path.id("n", "/etc/hosts"),
+            id_file("n", "/etc/hosts")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn inode_id_survives_rename() {
+        let dir = std::env::temp_dir();
+        let old = dir.join(format!("spacegraph-rename-{}.log", std::process::id()));
+        let new = old.with_extension("log.1");
+        std::fs::write(&old, b"x").expect("write");
+        let before = FileIdentity::Inode.id("n", old.to_str().unwrap());
+        std::fs::rename(&old, &new).expect("rename");
+        let after = FileIdentity::Inode.id("n", new.to_str().unwrap());
+        let _ = std::fs::remove_file(&new);
+        assert_eq!(before, after);
+        assert_ne!(before, id_file("n", old.to_str().unwrap()));
+    }
+
+    #[test]
+    fn parses_identity_names() {
+        assert_eq!(FileIdentity::parse("inode").unwrap(), FileIdentity::Inode);
+        assert_eq!(FileIdentity::parse("path").unwrap(), FileIdentity::Path);
+        assert!(FileIdentity::parse("hash").is_err());
+    }
+}
@@ -1,19 +1,56 @@
+#[cfg(not(windows))]
+mod cgroup;
 mod config;
+mod control;
+mod file_id;
+mod metrics;
 mod path_policy;
+mod redact;
+mod resume;
 mod server;
+#[cfg(not(windows))]
 mod snapshot;
+mod socket;
+#[cfg(not(windows))]
+mod threads;
+#[cfg(not(windows))]
+mod users;
 mod watch_fs;
+#[cfg(not(windows))]
 mod watch_proc;
+#[cfg(windows)]
+mod watch_proc_windows;
 
 use anyhow::Result;
-use config::{default_excludes, default_includes, parse_args, should_warn_privileged_without_root};
+use config::{
+    check_listen_exposure, default_excludes, default_includes, parse_args, resolve_token,
+    AgentMode, LogFormat, DEFAULT_TCP_LISTEN,
+};
+use control::{Control, PauseGate};
+use metrics::Metrics;
 use path_policy::PathPolicy;
-use spacegraph_core::{Capabilities, Delta, Msg, NodeIdentity};
-use std::sync::Arc;
+use redact::Redactor;
+use resume::ResumeBuffer;
+use spacegraph_core::{Capabilities, Delta, Msg, NodeIdentity, PROTOCOL_VERSION};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex, PoisonError};
 use tokio::sync::{broadcast, mpsc};
 
-fn init_tracing() {
-    let _ = tracing_subscriber::fmt::try_init();
+fn init_tracing(once: bool, format: LogFormat) {
+    let builder = tracing_subscriber::fmt();
+    // Keep stdout clean for the JSON snapshot in --once mode.
+    let _ = match (format, once) {
+        (LogFormat::Human, false) => builder.try_init(),
+        (LogFormat::Human, true) => builder.with_writer(std::io::stderr).try_init(),
+        (LogFormat::Json, false) => builder.json().flatten_event(true).try_init(),
+        (LogFormat::Json, true) => builder
+            .json()
+            .flatten_event(true)
+            .with_writer(std::io::stderr)
+            .try_init(),
+    };
 }
 
 fn default_uds_path() -> String {
@@ -40,32 +77,61 @@ fn default_node_id() -> String {
         })
 }
 
+/// CLI roots plus the policy file's lines; mode defaults fill whichever side is empty.
+fn build_policy(
+    mode: AgentMode,
+    includes: &[PathBuf],
+    excludes: &[PathBuf],
+    policy_file: Option<&Path>,
+) -> Result<PathPolicy> {
+    let mut includes = includes.to_vec();
+    let mut excludes = excludes.to_vec();
+    if let Some(path) = policy_file {
+        let (file_includes, file_excludes) = path_policy::load_policy_file(path)?;
+        includes.extend(file_includes);
+        excludes.extend(file_excludes);
+    }
+    if includes.is_empty() {
+        includes = default_includes(mode);
+    }
+    if excludes.is_empty() {
+        excludes = default_excludes(mode);
+    }
+    let mut policy = PathPolicy::new(includes, excludes);
+    policy.normalize();
+    Ok(policy)
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    init_tracing();
-    let node_id = default_node_id();
     let config = parse_args()?;
-    let sock_path = config
-        .uds_path
-        .as_ref()
-        .map(|path| path.to_string_lossy().to_string())
-        .unwrap_or_else(default_uds_path);
-    let default_roots = default_includes(config.mode);
-
-    let includes = if config.includes.is_empty() {
-        default_roots.clone()
-    } else {
-        config.includes
-    };
-    let excludes = if config.excludes.is_empty() {
-        default_excludes(config.mode)
-    } else {
-        config.excludes
+    init_tracing(config.once, config.log_format);
+    let node_id = default_node_id();
+    // Only Linux accepts --abstract-socket, so elsewhere this is always the file path.
+    let abstract_path = config.abstract_socket.as_deref().map(socket::abstract_path);
+    let sock_path = abstract_path.unwrap_or_else(|| {
+        config
+            .uds_path
+            .as_ref()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_else(default_uds_path)
+    });
+    let endpoint = match config.listen.clone() {
+        Some(addr) => server::Endpoint::Tcp(addr),
+        None if cfg!(windows) => server::Endpoint::Tcp(DEFAULT_TCP_LISTEN.to_string()),
+        None => server::Endpoint::Uds(sock_path),
     };
-
-    let mut policy = PathPolicy::new(includes, excludes);
-    policy.normalize();
-    let policy = Arc::new(policy);
+    let token = resolve_token(config.token_file.as_deref())?;
+    let token_required = token.is_some();
+    if let server::Endpoint::Tcp(addr) = &endpoint {
+        check_listen_exposure(addr, token_required)?;
+    }
+    let policy = build_policy(
+        config.mode,
+        &config.includes,
+        &config.excludes,
+        config.policy_file.as_deref(),
+    )?;
 
     let watch_roots = policy.includes().to_vec();
     let effective_root_count = watch_roots
@@ -80,17 +146,49 @@ async fn main() -> Result<()> {
         "path policy configured"
     );
 
-    if should_warn_privileged_without_root(config.mode, unsafe { libc::geteuid() }) {
+    #[cfg(unix)]
+    if config::should_warn_privileged_without_root(config.mode, unsafe { libc::geteuid() }) {
         tracing::warn!(
             "Privileged mode requested but not running as root; some paths will be skipped."
         );
     }
 
-    // Clean stale socket
-    let _ = std::fs::remove_file(&sock_path);
-
     // Build initial snapshot
-    let (snap_nodes, snap_edges) = snapshot::build_snapshot(&node_id, &policy, config.mode)?;
+    #[cfg(not(windows))]
+    let (mut snap_nodes, mut snap_edges) = snapshot::build_snapshot(
+        &node_id,
+        &policy,
+        config.mode,
+        config.max_fd_edges_per_process,
+        config.file_identity,
+        config.threads,
+    )?;
+    #[cfg(windows)]
+    let (mut snap_nodes, mut snap_edges) = watch_proc_windows::build_snapshot(&node_id);
+    // before redaction: the proc watcher diffs against unredacted /proc reads
+    #[cfg(not(windows))]
+    let proc_seed = watch_proc::SnapshotSeed::new(&snap_nodes, &snap_edges);
+    let redactor = Arc::new(Redactor::new(config.redact.clone())?);
+    redactor.snapshot(&mut snap_nodes, &mut snap_edges);
+
+    if config.once {
+        let msg = Msg::Snapshot {
+            nodes: snap_nodes,
+            edges: snap_edges,
+        };
+        let mut stdout = std::io::stdout().lock();
+        serde_json::to_writer(&mut stdout, &msg)?;
+        writeln!(stdout)?;
+        return Ok(());
+    }
+
+    // Clean stale socket; abstract ones vanish with their last descriptor.
+    if let server::Endpoint::Uds(path) = &endpoint {
+        if !socket::is_abstract(path) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
     let snapshot_node_count = snap_nodes.len();
     let snapshot_node_events: Vec<Msg> = snap_nodes
         .iter()
@@ -113,32 +211,93 @@ async fn main() -> Result<()> {
             .to_string(),
         platform: std::env::consts::OS.to_string(),
         arch: std::env::consts::ARCH.to_string(),
+        protocol: PROTOCOL_VERSION,
     };
     let caps = Capabilities {
-        procfs: true,
-        fd_edges: true,
+        procfs: !cfg!(windows),
+        fd_edges: !cfg!(windows),
         fs_notify: true,
         proc_poll: true,
         ebpf: false,
         cloud: false,
-        windows: false,
+        windows: cfg!(windows),
+        compression: true,
     };
     let identity_msg = Msg::Identity { ident, caps };
 
     // Event bus (broadcast so multiple viewers can subscribe)
     let (bus_tx, _bus_rx) = broadcast::channel::<Msg>(32_768);
 
-    // Serve UDS early so viewers can connect
+    let resume = Arc::new(Mutex::new(ResumeBuffer::new(
+        resume::new_session_id(),
+        config.resume_buffer,
+    )));
+
+    let metrics = Arc::new(Metrics::default());
+    if let Some(addr) = config.metrics_addr.clone() {
+        let metrics = Arc::clone(&metrics);
+        tokio::spawn(async move {
+            if let Err(err) = metrics::serve(addr, metrics).await {
+                tracing::warn!(error = %err, "metrics endpoint stopped");
+            }
+        });
+    }
+
+    // Viewer control commands (pause/resume, ...)
+    let control = Arc::new(Control::default());
+    let (control_tx, control_rx) = mpsc::unbounded_channel();
+    let (reload_tx, mut reload_rx) = mpsc::unbounded_channel::<()>();
+    tokio::spawn(control::run(
+        control_rx,
+        Arc::clone(&control),
+        reload_tx,
+        Arc::clone(&resume),
+    ));
+
+    // ReloadPathPolicy: rebuild the policy and hand it to the fs watcher
+    let (policy_tx, policy_rx) = mpsc::unbounded_channel::<PathPolicy>();
+    {
+        let (mode, includes, excludes) = (
+            config.mode,
+            config.includes.clone(),
+            config.excludes.clone(),
+        );
+        let policy_file = config.policy_file.clone();
+        tokio::spawn(async move {
+            while reload_rx.recv().await.is_some() {
+                if policy_file.is_none() {
+                    tracing::warn!("path policy reload requested, but no --policy-file is set");
+                    continue;
+                }
+                match build_policy(mode, &includes, &excludes, policy_file.as_deref()) {
+                    Ok(policy) => {
+                        let _ = policy_tx.send(policy);
+                    }
+                    Err(err) => tracing::warn!(error = %err, "path policy reload failed"),
+                }
+            }
+        });
+    }
+
+    // Serve early so viewers can connect
     let server_handle = {
-        let sock_path = sock_path.clone();
+        let endpoint = endpoint.clone();
         let bus_tx = bus_tx.clone();
+        let resume = Arc::clone(&resume);
+        let metrics = Arc::clone(&metrics);
+        let socket_perms = config.socket.clone();
         tokio::spawn(async move {
             server::run(
-                &sock_path,
+                &endpoint,
+                token,
                 identity_msg,
                 snapshot_msg,
                 snapshot_node_events,
                 bus_tx,
+                resume,
+                control_tx,
+                metrics,
+                &socket_perms,
             )
             .await
         })
@@ -151,15 +310,48 @@ async fn main() -> Result<()> {
     watch_fs::spawn(
         &node_id,
         config.mode,
-        Arc::clone(&policy),
+        policy.clone(),
         watch_roots,
+        watch_fs::FsWatchOptions {
+            coalesce: config.fs_coalesce,
+            batch_id_base: config.fs_batch_id_base,
+            max_watches: config.fs_max_watches,
+            poll_interval: config.fs_poll,
+            file_identity: config.file_identity,
+        },
+        Arc::clone(&metrics),
         fs_tx,
+        policy_rx,
+    )?;
+    #[cfg(not(windows))]
+    watch_proc::spawn(
+        &node_id,
+        config.max_fd_edges_per_process,
+        config.file_identity,
+        config.threads,
+        config.proc_poll,
+        config.passwd_refresh,
+        Arc::clone(&metrics),
+        proc_seed,
+        proc_tx,
+    )?;
+    #[cfg(windows)]
+    watch_proc_windows::spawn(
+        &node_id,
+        config.proc_poll,
+        config.passwd_refresh,
+        Arc::clone(&metrics),
+        proc_tx,
     )?;
-    watch_proc::spawn(&node_id, proc_tx)?;
 
     tracing::info!(
-        uds_path = %sock_path,
+        %endpoint,
         mode = ?config.mode,
+        proc_poll_ms = config.proc_poll.as_millis() as u64,
+        passwd_refresh_secs = config.passwd_refresh.as_secs(),
+        fs_coalesce_ms = config.fs_coalesce.as_millis() as u64,
+        fs_max_watches = config.fs_max_watches,
+        token_required,
         include_root_count = policy.includes().len(),
         exclude_root_count = policy.excludes().len(),
         snapshot_node_count,
@@ -170,23 +362,46 @@ async fn main() -> Result<()> {
     // Forward watcher channels → broadcast bus
     {
         let bus_tx = bus_tx.clone();
+        let resume = Arc::clone(&resume);
+        let control = Arc::clone(&control);
+        let metrics = Arc::clone(&metrics);
+        let redactor = Arc::clone(&redactor);
         tokio::spawn(async move {
-            forward_to_bus(fs_rx, bus_tx).await;
+            forward_to_bus(fs_rx, bus_tx, resume, control, metrics, redactor).await;
         });
     }
     {
         let bus_tx = bus_tx.clone();
         tokio::spawn(async move {
-            forward_to_bus(proc_rx, bus_tx).await;
+            forward_to_bus(proc_rx, bus_tx, resume, control, metrics, redactor).await;
         });
     }
 
     server_handle.await?
 }
 
-async fn forward_to_bus(mut rx: mpsc::Receiver<Msg>, bus_tx: broadcast::Sender<Msg>) {
-    while let Some(msg) = rx.recv().await {
-        // ignore lagging viewers
-        let _ = bus_tx.send(msg);
+async fn forward_to_bus(
+    mut rx: mpsc::Receiver<Msg>,
+    bus_tx: broadcast::Sender<Msg>,
+    resume: Arc<Mutex<ResumeBuffer>>,
+    control: Arc<Control>,
+    metrics: Arc<Metrics>,
+    redactor: Arc<Redactor>,
+) {
+    let mut gate = PauseGate::default();
+    while let Some(mut msg) = rx.recv().await {
+        // Watchers keep running while paused; their batches are dropped whole.
+        if !gate.pass(&msg, control.paused()) {
+            continue;
+        }
+        redactor.msg(&mut msg);
+        // Buffer and broadcast under one lock so a reconnecting viewer sees no gap or repeat.
+        let mut buffer = resume.lock().unwrap_or_else(PoisonError::into_inner);
+        buffer.push(msg.clone());
+        // ignore lagging viewers; without any receiver nothing was broadcast
+        if bus_tx.send(msg).is_ok() {
+            metrics.events_broadcast.fetch_add(1, Ordering::Relaxed);
+        }
+        drop(buffer);
     }
 }
@@ -0,0 +1,203 @@
+use anyhow::{Context, Result};
+use std::fmt::{Display, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Self-monitoring counters shared by the watchers and the server.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    pub events_broadcast: AtomicU64,
+    pub viewers_connected: AtomicU64,
+    pub fs_watched_paths: AtomicU64,
+    pub fs_polled_roots: AtomicU64,
+    pub fs_skipped_permission: AtomicU64,
+    pub fs_skipped_watch_limit: AtomicU64,
+    pub proc_polls: AtomicU64,
+    /// Microseconds spent in the most recent proc poll.
+    pub proc_poll_last_us: AtomicU64,
+    pub proc_poll_total_us: AtomicU64,
+}
+
+impl Metrics {
+    pub fn record_proc_poll(&self, took: Duration) {
+        let us = took.as_micros().min(u64::MAX as u128) as u64;
+        self.proc_polls.fetch_add(1, Ordering::Relaxed);
+        self.proc_poll_last_us.store(us, Ordering::Relaxed);
+        self.proc_poll_total_us.fetch_add(us, Ordering::Relaxed);
+    }
+
+    /// Prometheus text exposition format (0.0.4).
+    pub fn render(&self) -> String {
+        let get = |v: &AtomicU64| v.load(Ordering::Relaxed);
+        let secs = |v: &AtomicU64| get(v) as f64 / 1_000_000.0;
+        let mut out = String::new();
+        metric(
+            &mut out,
+            "spacegraph_agent_events_broadcast_total",
+            "counter",
+            "Watcher messages broadcast to viewers.",
+            get(&self.events_broadcast),
+        );
+        metric(
+            &mut out,
+            "spacegraph_agent_viewers_connected",
+            "gauge",
+            "Viewers currently connected.",
+            get(&self.viewers_connected),
+        );
+        metric(
+            &mut out,
+            "spacegraph_agent_fs_watched_paths",
+            "gauge",
+            "Directories with an inotify watch.",
+            get(&self.fs_watched_paths),
+        );
+        metric(
+            &mut out,
+            "spacegraph_agent_fs_polled_roots",
+            "gauge",
+            "Subtrees watched by polling after the watch budget ran out.",
+            get(&self.fs_polled_roots),
+        );
+        metric(
+            &mut out,
+            "spacegraph_agent_fs_skipped_permission_total",
+            "counter",
+            "Paths skipped because of missing permissions.",
+            get(&self.fs_skipped_permission),
+        );
+        metric(
+            &mut out,
+            "spacegraph_agent_fs_skipped_watch_limit_total",
+            "counter",
+            "Directories the kernel refused a watch for.",
+            get(&self.fs_skipped_watch_limit),
+        );
+        metric(
+            &mut out,
+            "spacegraph_agent_proc_polls_total",
+            "counter",
+            "Completed process polls.",
+            get(&self.proc_polls),
+        );
+        metric(
+            &mut out,
+            "spacegraph_agent_proc_poll_duration_seconds",
+            "gauge",
+            "Duration of the last process poll.",
+            secs(&self.proc_poll_last_us),
+        );
+        metric(
+            &mut out,
+            "spacegraph_agent_proc_poll_duration_seconds_total",
+            "counter",
+            "Time spent in process polls.",
+            secs(&self.proc_poll_total_us),
+        );
+        out
+    }
+}
+
+fn metric(out: &mut String, name: &str, kind: &str, help: &str, value: impl Display) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+/// Answers one request; anything but `GET /metrics` gets a 404/405.
+fn http_response(request: &[u8], metrics: &Metrics) -> String {
+    let line = request.split(|b| *b == b'\n').next().unwrap_or_default();
+    let line = String::from_utf8_lossy(line);
+    let mut parts = line.split_whitespace();
+    let method = parts.next();
+    let path = parts.next().map(|p| p.split('?').next().unwrap_or(p));
+    let (status, body) = match (method, path) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", metrics.render()),
+        (Some("GET"), _) => ("404 Not Found", "not found\n".to_string()),
+        _ => ("405 Method Not Allowed", "method not allowed\n".to_string()),
+    };
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+async fn respond(mut stream: TcpStream, metrics: &Metrics) -> std::io::Result<()> {
+    // Scrapers send the request line in the first segment; headers and body are ignored.
+    let mut buf = [0u8; 1024];
+    let n = stream.read(&mut buf).await?;
+    stream
+        .write_all(http_response(&buf[..n], metrics).as_bytes())
+        .await?;
+    stream.shutdown().await
+}
+
+/// Serves `/metrics` on `addr` until the listener fails.
+pub async fn serve(addr: String, metrics: Arc<Metrics>) -> Result<()> {
+    let listener = TcpListener::bind(&addr)
+        .await
+        .with_context(|| format!("bind metrics endpoint {addr}"))?;
+    tracing::info!(
+        event_type = "metrics_listening",
+        metrics_addr = %addr,
+        "metrics endpoint listening"
+    );
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let metrics = Arc::clone(&metrics);
+        tokio::spawn(async move {
+            if let Err(err) = respond(stream, &metrics).await {
+                tracing::debug!(error = %err, "metrics request failed");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_known_counters_in_text_format() {
+        let metrics = Metrics::default();
+        metrics.events_broadcast.store(42, Ordering::Relaxed);
+        metrics.viewers_connected.store(2, Ordering::Relaxed);
+        metrics.fs_watched_paths.store(128, Ordering::Relaxed);
+        metrics.fs_skipped_permission.store(3, Ordering::Relaxed);
+        metrics.record_proc_poll(Duration::from_millis(250));
+        metrics.record_proc_poll(Duration::from_millis(500));
+
+        let text = metrics.render();
+        let lines: Vec<&str> = text.lines().collect();
+        for expected in [
+            "# TYPE spacegraph_agent_events_broadcast_total counter",
+            "spacegraph_agent_events_broadcast_total 42",
+            "# TYPE spacegraph_agent_viewers_connected gauge",
+            "spacegraph_agent_viewers_connected 2",
+            "spacegraph_agent_fs_watched_paths 128",
+            "spacegraph_agent_fs_skipped_permission_total 3",
+            "spacegraph_agent_fs_skipped_watch_limit_total 0",
+            "spacegraph_agent_proc_polls_total 2",
+            "spacegraph_agent_proc_poll_duration_seconds 0.5",
+            "spacegraph_agent_proc_poll_duration_seconds_total 0.75",
+        ] {
+            assert!(lines.contains(&expected), "missing {expected:?} in\n{text}");
+        }
+        // Every sample has HELP and TYPE lines.
+        assert_eq!(lines.len(), 9 * 3);
+    }
+
+    #[test]
+    fn only_get_metrics_is_served() {
+        let metrics = Metrics::default();
+        let ok = http_response(b"GET /metrics HTTP/1.1\r\nHost: x\r\n\r\n", &metrics);
+        assert!(ok.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(ok.ends_with(&metrics.render()));
+        assert!(http_response(b"GET /metrics?x=1 HTTP/1.1\r\n", &metrics).contains("200 OK"));
+        assert!(http_response(b"GET / HTTP/1.1\r\n", &metrics).contains("404 Not Found"));
+        assert!(http_response(b"POST /metrics HTTP/1.1\r\n", &metrics).contains("405"));
+    }
+}
@@ -1,3 +1,4 @@
+use anyhow::{Context, Result};
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
@@ -64,6 +65,61 @@ impl PathPolicy {
     }
 }
 
+/// Include roots that appear or disappear between two policies.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RootDiff {
+    pub added: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+}
+
+impl RootDiff {
+    pub fn between(old: &PathPolicy, new: &PathPolicy) -> Self {
+        Self {
+            added: new
+                .includes
+                .iter()
+                .filter(|root| !old.includes.contains(root))
+                .cloned()
+                .collect(),
+            removed: old
+                .includes
+                .iter()
+                .filter(|root| !new.includes.contains(root))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// Reads `include <path>` / `exclude <path>` lines; `#` starts a comment.
+pub fn load_policy_file(path: &Path) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("read policy file {}", path.display()))?;
+    parse_policy_file(&text).with_context(|| format!("parse policy file {}", path.display()))
+}
+
+fn parse_policy_file(text: &str) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let mut includes = Vec::new();
+    let mut excludes = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (kind, path) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let path = path.trim();
+        if path.is_empty() {
+            anyhow::bail!("line {}: missing path", idx + 1);
+        }
+        match kind {
+            "include" => includes.push(PathBuf::from(path)),
+            "exclude" => excludes.push(PathBuf::from(path)),
+            other => anyhow::bail!("line {}: unknown directive {other:?}", idx + 1),
+        }
+    }
+    Ok((includes, excludes))
+}
+
 fn normalize_path(path: &Path) -> PathBuf {
     if path.exists() {
         path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
@@ -82,7 +138,7 @@ fn normalize_exclude_path(path: &Path) -> PathBuf {
 
 #[cfg(test)]
 mod tests {
-    use super::PathPolicy;
+    use super::{parse_policy_file, PathPolicy, RootDiff};
     use std::fs;
     use std::path::PathBuf;
 
@@ -167,4 +223,31 @@ mod tests {
 
         assert!(!policy.should_watch(&target));
     }
+
+    #[test]
+    fn root_diff_reports_added_and_removed_includes() {
+        let old = PathPolicy::new(
+            vec![PathBuf::from("/srv/a"), PathBuf::from("/srv/b")],
+            Vec::new(),
+        );
+        let new = PathPolicy::new(
+            vec![PathBuf::from("/srv/b"), PathBuf::from("/srv/c")],
+            vec![PathBuf::from("/srv/b/tmp")],
+        );
+
+        let diff = RootDiff::between(&old, &new);
+        assert_eq!(diff.added, vec![PathBuf::from("/srv/c")]);
+        assert_eq!(diff.removed, vec![PathBuf::from("/srv/a")]);
+        assert_eq!(RootDiff::between(&new, &new), RootDiff::default());
+    }
+
+    #[test]
+    fn policy_file_lines_parse() {
+        let (includes, excludes) =
+            parse_policy_file("# roots\ninclude /etc\n\nexclude /etc/ssl # keys\n").unwrap();
+        assert_eq!(includes, vec![PathBuf::from("/etc")]);
+        assert_eq!(excludes, vec![PathBuf::from("/etc/ssl")]);
+        assert!(parse_policy_file("watch /etc").is_err());
+        assert!(parse_policy_file("include").is_err());
+    }
 }
@@ -0,0 +1,393 @@
+//! `--redact` globs: matching path segments are replaced by a hash before anything leaves
+//! the host, both in file paths and in the ids built from them, and in process exe and
+//! cmdline paths.
+
+use anyhow::{bail, Context, Result};
+use spacegraph_core::hash::{keyed_u64, HashKey};
+use spacegraph_core::{id_file, parse_node_id, Delta, Edge, Msg, Node, NodeId, ParsedId};
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+/// Absolute glob split into segments. `*` and `?` match within one segment and `**` any
+/// number of segments; the segments a `*`/`?` segment matched are redacted. A pattern
+/// matches a path prefix, so everything below a matched directory is covered too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedactPattern {
+    segments: Vec<String>,
+}
+
+impl RedactPattern {
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let segments: Vec<String> = pattern
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect();
+        if !segments.iter().any(|s| redacts(s)) {
+            bail!("redact pattern {pattern:?} names no segment to hide (use * or ?)");
+        }
+        Ok(Self { segments })
+    }
+
+    /// Indices of the redacted segments if the pattern matches a prefix of `segs`.
+    fn hits(&self, segs: &[&str]) -> Option<Vec<usize>> {
+        match_from(&self.segments, segs, 0)
+    }
+}
+
+fn redacts(segment: &str) -> bool {
+    segment != "**" && segment.contains(['*', '?'])
+}
+
+fn match_from(pat: &[String], segs: &[&str], pos: usize) -> Option<Vec<usize>> {
+    let Some((p, rest)) = pat.split_first() else {
+        return Some(Vec::new());
+    };
+    if p == "**" {
+        return (pos..=segs.len()).find_map(|next| match_from(rest, segs, next));
+    }
+    let seg = segs.get(pos)?;
+    if !segment_matches(p.as_bytes(), seg.as_bytes()) {
+        return None;
+    }
+    let mut hits = match_from(rest, segs, pos + 1)?;
+    if redacts(p) {
+        hits.push(pos);
+    }
+    Some(hits)
+}
+
+fn segment_matches(p: &[u8], s: &[u8]) -> bool {
+    match (p.split_first(), s.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => {
+            segment_matches(rest, s) || (!s.is_empty() && segment_matches(p, &s[1..]))
+        }
+        (Some((b'?', rest)), Some((_, s_rest))) => segment_matches(rest, s_rest),
+        (Some((c, rest)), Some((d, s_rest))) if c == d => segment_matches(rest, s_rest),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    patterns: Vec<RedactPattern>,
+    /// Drawn per agent run, so viewers cannot test guessed segments against a hash; a
+    /// segment still hashes the same for the whole run and edges keep connecting.
+    key: HashKey,
+}
+
+impl Redactor {
+    pub fn new(patterns: Vec<RedactPattern>) -> Result<Self> {
+        let mut key = HashKey::default();
+        getrandom::getrandom(&mut key).context("draw the redaction key")?;
+        Ok(Self::with_key(patterns, key))
+    }
+
+    pub fn with_key(patterns: Vec<RedactPattern>, key: HashKey) -> Self {
+        Self { patterns, key }
+    }
+
+    /// `path` with every segment hit by a pattern replaced by `<redacted-xxxxxxxx>`.
+    /// Relative and pseudo paths (`socket:[..]`) are left alone.
+    pub fn path<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        let Some(rel) = path.strip_prefix('/') else {
+            return Cow::Borrowed(path);
+        };
+        if self.patterns.is_empty() {
+            return Cow::Borrowed(path);
+        }
+        let segs: Vec<&str> = rel.split('/').collect();
+        let hits: BTreeSet<usize> = self
+            .patterns
+            .iter()
+            .filter_map(|p| p.hits(&segs))
+            .flatten()
+            .collect();
+        if hits.is_empty() {
+            return Cow::Borrowed(path);
+        }
+        let mut out = String::with_capacity(path.len());
+        for (i, seg) in segs.iter().enumerate() {
+            out.push('/');
+            if hits.contains(&i) {
+                let hash = keyed_u64(&self.key, seg.as_bytes());
+                out.push_str(&format!("<redacted-{hash:016x}>"));
+            } else {
+                out.push_str(seg);
+            }
+        }
+        Cow::Owned(out)
+    }
+
+    pub fn id(&self, id: &mut NodeId) {
+        let redacted = match parse_node_id(id) {
+            Some(ParsedId::File { host, path }) => match self.path(path) {
+                Cow::Owned(path) => Some(id_file(host, &path)),
+                Cow::Borrowed(_) => None,
+            },
+            _ => None,
+        };
+        if let Some(redacted) = redacted {
+            *id = redacted;
+        }
+    }
+
+    /// `cmdline` with [`Self::path`] applied to every argument that is an absolute path,
+    /// on its own or as the value of `name=/path`.
+    pub fn cmdline<'a>(&self, cmdline: &'a str) -> Cow<'a, str> {
+        if self.patterns.is_empty() {
+            return Cow::Borrowed(cmdline);
+        }
+        let mut out = String::with_capacity(cmdline.len());
+        let mut changed = false;
+        for (i, arg) in cmdline.split(' ').enumerate() {
+            if i > 0 {
+                out.push(' ');
+            }
+            let start = match arg.find('=') {
+                Some(eq) if arg[eq + 1..].starts_with('/') => eq + 1,
+                _ => 0,
+            };
+            let (head, path) = arg.split_at(start);
+            out.push_str(head);
+            match self.path(path) {
+                Cow::Owned(p) => {
+                    out.push_str(&p);
+                    changed = true;
+                }
+                Cow::Borrowed(p) => out.push_str(p),
+            }
+        }
+        if changed {
+            Cow::Owned(out)
+        } else {
+            Cow::Borrowed(cmdline)
+        }
+    }
+
+    pub fn node(&self, node: &mut Node) {
+        match node {
+            Node::File {
+                path, link_target, ..
+            } => {
+                if let Cow::Owned(p) = self.path(path) {
+                    *path = p;
+                }
+                if let Some(target) = link_target {
+                    if let Cow::Owned(t) = self.path(target) {
+                        *target = t;
+                    }
+                }
+            }
+            Node::Process { exe, cmdline, .. } => {
+                if let Cow::Owned(e) = self.path(exe) {
+                    *exe = e;
+                }
+                if let Cow::Owned(c) = self.cmdline(cmdline) {
+                    *cmdline = c;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn edge(&self, edge: &mut Edge) {
+        self.id(&mut edge.from);
+        self.id(&mut edge.to);
+    }
+
+    pub fn snapshot(&self, nodes: &mut [(NodeId, Node)], edges: &mut [Edge]) {
+        if self.patterns.is_empty() {
+            return;
+        }
+        for (id, node) in nodes.iter_mut() {
+            self.id(id);
+            self.node(node);
+        }
+        for edge in edges.iter_mut() {
+            self.edge(edge);
+        }
+    }
+
+    pub fn msg(&self, msg: &mut Msg) {
+        if self.patterns.is_empty() {
+            return;
+        }
+        match msg {
+            Msg::Event { delta } => match delta {
+                Delta::UpsertNode { id, node } => {
+                    self.id(id);
+                    self.node(node);
+                }
+                Delta::RemoveNode { id } => self.id(id),
+                Delta::UpsertEdge { edge } | Delta::RemoveEdge { edge } => self.edge(edge),
+                Delta::BatchBegin { .. } | Delta::BatchEnd { .. } => {}
+            },
+            Msg::Snapshot { nodes, edges } | Msg::SnapshotChunk { nodes, edges } => {
+                self.snapshot(nodes, edges)
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::{EdgeKind, FileCategory, FileKind};
+
+    fn redactor_with_key(patterns: &[&str], key: HashKey) -> Redactor {
+        Redactor::with_key(
+            patterns
+                .iter()
+                .map(|p| RedactPattern::parse(p).expect("pattern"))
+                .collect(),
+            key,
+        )
+    }
+
+    fn redactor(patterns: &[&str]) -> Redactor {
+        redactor_with_key(patterns, [1; 32])
+    }
+
+    #[test]
+    fn redacted_paths_get_stable_opaque_ids() {
+        let r = redactor(&["/home/*/.ssh"]);
+        let path = "/home/alice/.ssh/id_ed25519";
+        let redacted = r.path(path).into_owned();
+        assert!(redacted.starts_with("/home/<redacted-"), "{redacted}");
+        assert!(redacted.ends_with("/.ssh/id_ed25519"), "{redacted}");
+        assert!(!redacted.contains("alice"));
+        assert_eq!(r.path(path), redactor(&["/home/*/.ssh"]).path(path));
+        // 64 hex bits between the markers
+        let hash = &redacted["/home/<redacted-".len()..redacted.find('>').unwrap()];
+        assert_eq!(hash.len(), 16, "{redacted}");
+
+        let mut a = id_file("n", path);
+        let mut b = id_file("n", path);
+        r.id(&mut a);
+        r.id(&mut b);
+        assert_eq!(a, b);
+        assert_eq!(a, id_file("n", &redacted));
+    }
+
+    #[test]
+    fn unmatched_and_pseudo_paths_are_untouched() {
+        let r = redactor(&["/home/*/.ssh", "**/*secret*"]);
+        for path in ["/home/alice/notes.txt", "/etc/hosts", "socket:[4242]"] {
+            assert!(
+                matches!(r.path(path), Cow::Borrowed(p) if p == path),
+                "{path}"
+            );
+        }
+        assert!(r
+            .path("/srv/app/db-secret.json")
+            .starts_with("/srv/app/<redacted-"));
+
+        let mut id = NodeId("n:process:pid:1".to_string());
+        r.id(&mut id);
+        assert_eq!(id.0, "n:process:pid:1");
+    }
+
+    #[test]
+    fn events_redact_node_and_edge_ids_alike() {
+        let r = redactor(&["/home/*"]);
+        let file = id_file("n", "/home/bob/.bashrc");
+        let mut upsert = Msg::Event {
+            delta: Delta::UpsertNode {
+                id: file.clone(),
+                node: Node::File {
+                    path: "/home/bob/.bashrc".to_string(),
+                    inode: 1,
+                    kind: FileKind::Regular,
+                    category: FileCategory::Unknown,
+                    link_target: None,
+                },
+            },
+        };
+        let mut edge = Msg::Event {
+            delta: Delta::UpsertEdge {
+                edge: Edge {
+                    from: NodeId("n:process:pid:1".to_string()),
+                    to: file,
+                    kind: EdgeKind::Opens {
+                        fd: 3,
+                        mode: "r".to_string(),
+                    },
+                },
+            },
+        };
+        r.msg(&mut upsert);
+        r.msg(&mut edge);
+
+        let (
+            Msg::Event {
+                delta:
+                    Delta::UpsertNode {
+                        id,
+                        node: Node::File { path, .. },
+                    },
+            },
+            Msg::Event {
+                delta: Delta::UpsertEdge { edge },
+            },
+        ) = (upsert, edge)
+        else {
+            panic!("unexpected messages");
+        };
+        assert!(!path.contains("bob"));
+        assert_eq!(edge.to, id);
+    }
+
+    #[test]
+    fn hashes_depend_on_the_per_run_key() {
+        let path = "/home/alice/.ssh/id_ed25519";
+        let a = redactor_with_key(&["/home/*"], [1; 32]);
+        let b = redactor_with_key(&["/home/*"], [2; 32]);
+        assert_ne!(a.path(path), b.path(path));
+
+        let fresh = |p: &str| {
+            let patterns = vec![RedactPattern::parse("/home/*").expect("pattern")];
+            Redactor::new(patterns).expect("key").path(p).into_owned()
+        };
+        assert_ne!(fresh(path), fresh(path));
+    }
+
+    #[test]
+    fn process_exe_and_cmdline_paths_are_redacted() {
+        let r = redactor(&["/home/*"]);
+        let mut node = Node::Process {
+            pid: 7,
+            ppid: 1,
+            exe: "/home/alice/bin/tool".to_string(),
+            cmdline: "tool --config=/home/alice/.toolrc /home/alice/data -v".to_string(),
+            uid: 1000,
+            rss_kb: 0,
+            utime_ticks: 0,
+            container: None,
+        };
+        r.node(&mut node);
+
+        let Node::Process { exe, cmdline, .. } = node else {
+            panic!("not a process");
+        };
+        assert!(exe.starts_with("/home/<redacted-") && exe.ends_with("/bin/tool"));
+        assert!(!cmdline.contains("alice"), "{cmdline}");
+        assert!(
+            cmdline.starts_with("tool --config=/home/<redacted-"),
+            "{cmdline}"
+        );
+        assert!(cmdline.ends_with("/data -v"), "{cmdline}");
+
+        let plain = "/usr/bin/env FOO=bar ./run /etc/hosts";
+        assert!(matches!(r.cmdline(plain), Cow::Borrowed(c) if c == plain));
+    }
+
+    #[test]
+    fn patterns_without_wildcards_are_rejected() {
+        assert!(RedactPattern::parse("/home/alice").is_err());
+        assert!(RedactPattern::parse("**").is_err());
+        assert!(RedactPattern::parse("/home/*").is_ok());
+    }
+}
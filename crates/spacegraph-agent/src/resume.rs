@@ -0,0 +1,129 @@
+use spacegraph_core::{Delta, Msg};
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Recent broadcast events, so a reconnecting viewer can catch up without a snapshot.
+#[derive(Debug)]
+pub struct ResumeBuffer {
+    session: u64,
+    capacity: usize,
+    msgs: VecDeque<Msg>,
+}
+
+impl ResumeBuffer {
+    pub fn new(session: u64, capacity: usize) -> Self {
+        Self {
+            session,
+            capacity,
+            msgs: VecDeque::new(),
+        }
+    }
+
+    pub fn session(&self) -> u64 {
+        self.session
+    }
+
+    /// Forgets the history under a new session, so every resume point before now fails.
+    pub fn restart(&mut self, session: u64) {
+        self.session = session;
+        self.msgs.clear();
+    }
+
+    pub fn push(&mut self, msg: Msg) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.msgs.len() >= self.capacity {
+            self.msgs.pop_front();
+        }
+        self.msgs.push_back(msg);
+    }
+
+    /// Everything broadcast after `BatchEnd { id: batch }`. `None` means the viewer needs
+    /// a full snapshot: it talked to another agent instance, or the batch was evicted.
+    pub fn since(&self, session: u64, batch: u64) -> Option<Vec<Msg>> {
+        if session != self.session {
+            return None;
+        }
+        let end = self.msgs.iter().rposition(|msg| {
+            matches!(
+                msg,
+                Msg::Event {
+                    delta: Delta::BatchEnd { id }
+                } if *id == batch
+            )
+        })?;
+        Some(self.msgs.iter().skip(end + 1).cloned().collect())
+    }
+}
+
+/// Differs between agent runs, so resume points from a previous run are never trusted.
+pub fn new_session_id() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    nanos ^ (u64::from(std::process::id()) << 32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::NodeId;
+
+    fn event(delta: Delta) -> Msg {
+        Msg::Event { delta }
+    }
+
+    fn batch(id: u64, node: &str) -> Vec<Msg> {
+        vec![
+            event(Delta::BatchBegin { id }),
+            event(Delta::RemoveNode {
+                id: NodeId(node.to_string()),
+            }),
+            event(Delta::BatchEnd { id }),
+        ]
+    }
+
+    fn removed(msgs: &[Msg]) -> Vec<&str> {
+        msgs.iter()
+            .filter_map(|msg| match msg {
+                Msg::Event {
+                    delta: Delta::RemoveNode { id },
+                } => Some(id.0.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn buffered_batch_resumes_with_later_deltas() {
+        let mut buffer = ResumeBuffer::new(7, 100);
+        for (id, node) in [(1, "a"), (2, "b"), (3, "c")] {
+            batch(id, node).into_iter().for_each(|m| buffer.push(m));
+        }
+
+        let replay = buffer.since(7, 1).expect("batch 1 is buffered");
+        assert_eq!(replay.len(), 6);
+        assert_eq!(removed(&replay), vec!["b", "c"]);
+        assert!(buffer.since(7, 3).expect("up to date").is_empty());
+    }
+
+    #[test]
+    fn falls_back_to_snapshot_when_history_is_missing() {
+        let mut buffer = ResumeBuffer::new(7, 3);
+        for (id, node) in [(1, "a"), (2, "b")] {
+            batch(id, node).into_iter().for_each(|m| buffer.push(m));
+        }
+        // Capacity 3 evicted batch 1 entirely.
+        assert!(buffer.since(7, 1).is_none());
+        assert!(buffer.since(7, 2).is_some());
+        // Unknown batch, or a resume point from another agent run.
+        assert!(buffer.since(7, 99).is_none());
+        assert!(buffer.since(8, 2).is_none());
+
+        let mut disabled = ResumeBuffer::new(7, 0);
+        batch(1, "a").into_iter().for_each(|m| disabled.push(m));
+        assert!(disabled.since(7, 1).is_none());
+    }
+}
@@ -1,104 +1,516 @@
-use anyhow::Result;
-use spacegraph_core::Msg;
-
-#[cfg(unix)]
-use anyhow::Context;
-#[cfg(unix)]
+use anyhow::{Context, Result};
 use futures_util::{SinkExt, StreamExt};
-#[cfg(unix)]
-use std::sync::atomic::{AtomicUsize, Ordering};
+use spacegraph_core::frame::{decode_frame, encode_frame, MAX_FRAME_LENGTH};
+use spacegraph_core::{chunk_snapshot, ControlCmd, Msg, SNAPSHOT_CHUNK_ITEMS};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
 #[cfg(unix)]
 use tokio::net::UnixListener;
-#[cfg(unix)]
+use tokio::sync::mpsc;
 use tokio_util::codec::{Framed, LengthDelimitedCodec};
 
-#[cfg(unix)]
+use crate::metrics::Metrics;
+use crate::resume::ResumeBuffer;
+use crate::socket::{self, SocketPerms};
+
+/// How long a new connection may take to send its first frame.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where viewers connect: a unix socket (file or abstract) or a TCP address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Uds(String),
+    Tcp(String),
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Uds(path) => f.write_str(&socket::display_path(path)),
+            Self::Tcp(addr) => write!(f, "tcp://{addr}"),
+        }
+    }
+}
+
+trait Conn: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Conn for T {}
+
+enum Listener {
+    #[cfg(unix)]
+    Uds(UnixListener),
+    Tcp(TcpListener),
+}
+
+impl Listener {
+    async fn bind(endpoint: &Endpoint, socket_perms: &SocketPerms) -> Result<Self> {
+        match endpoint {
+            #[cfg(unix)]
+            Endpoint::Uds(path) => {
+                let listener =
+                    UnixListener::bind(path).with_context(|| format!("bind UDS {endpoint}"))?;
+                // Abstract sockets have no inode, so there is nothing to chmod.
+                if !socket::is_abstract(path) {
+                    socket::apply(std::path::Path::new(path), socket_perms)?;
+                }
+                Ok(Self::Uds(listener))
+            }
+            #[cfg(not(unix))]
+            Endpoint::Uds(_) => {
+                let _ = socket_perms;
+                anyhow::bail!("unix sockets need a unix platform; use --listen host:port")
+            }
+            Endpoint::Tcp(addr) => {
+                let listener = TcpListener::bind(addr)
+                    .await
+                    .with_context(|| format!("bind {endpoint}"))?;
+                Ok(Self::Tcp(listener))
+            }
+        }
+    }
+
+    async fn accept(&self) -> std::io::Result<Box<dyn Conn>> {
+        Ok(match self {
+            #[cfg(unix)]
+            Self::Uds(listener) => Box::new(listener.accept().await?.0),
+            Self::Tcp(listener) => {
+                let (stream, _) = listener.accept().await?;
+                // Frames are small and latency matters more than packet count.
+                stream.set_nodelay(true)?;
+                Box::new(stream)
+            }
+        })
+    }
+}
+
+/// Options the client asked for in its `Hello`.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Handshake {
+    compress: bool,
+    chunked: bool,
+    /// Agent session and last applied batch of a reconnecting viewer.
+    resume: Option<(u64, u64)>,
+}
+
+/// Validates the client's first message. Without a configured token anything is accepted
+/// (older viewers may skip the `Hello`); with one, only a `Hello` carrying it is.
+fn check_hello(first: Option<&Msg>, token: Option<&str>) -> Result<Handshake, &'static str> {
+    let hello = match first {
+        Some(Msg::Hello {
+            compression,
+            snapshot_chunks,
+            token: got,
+            session,
+            resume_after_batch,
+            ..
+        }) => Some((
+            Handshake {
+                compress: *compression,
+                chunked: *snapshot_chunks,
+                resume: session.zip(*resume_after_batch),
+            },
+            got.as_deref(),
+        )),
+        _ => None,
+    };
+    if let Some(expected) = token {
+        match hello {
+            Some((_, Some(got))) if tokens_match(expected, got) => {}
+            Some((_, Some(_))) => return Err("invalid token"),
+            _ => return Err("token required"),
+        }
+    }
+    Ok(hello.map(|(handshake, _)| handshake).unwrap_or_default())
+}
+
+/// Reads the client's first frame. A client that stays silent for `limit` counts as one
+/// without a `Hello`, so it cannot hold up the accept loop.
+async fn read_hello<S>(framed: &mut Framed<S, LengthDelimitedCodec>, limit: Duration) -> Option<Msg>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    match tokio::time::timeout(limit, framed.next()).await {
+        Ok(Some(Ok(bytes))) => decode_frame(&bytes).ok(),
+        Ok(_) => None,
+        Err(_) => {
+            tracing::warn!(
+                event_type = "client_hello_timeout",
+                timeout_ms = limit.as_millis() as u64,
+                "client sent no hello in time"
+            );
+            None
+        }
+    }
+}
+
+/// Compares every byte so the position of the first mismatch isn't observable.
+fn tokens_match(expected: &str, got: &str) -> bool {
+    expected.len() == got.len()
+        && expected
+            .bytes()
+            .zip(got.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// What a connected client asked for in one frame.
+#[derive(Debug, PartialEq, Eq)]
+enum ClientFrame {
+    Ping,
+    Control(ControlCmd),
+    Ignore,
+}
+
+fn classify_client_frame(msg: Option<Msg>) -> ClientFrame {
+    match msg {
+        Some(Msg::Ping) => ClientFrame::Ping,
+        Some(Msg::Control { cmd }) => ClientFrame::Control(cmd),
+        _ => ClientFrame::Ignore,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
-    sock_path: &str,
+    endpoint: &Endpoint,
+    token: Option<String>,
     identity_msg: Msg,
     snapshot_msg: Msg,
     snapshot_node_events: Vec<Msg>,
     bus_tx: tokio::sync::broadcast::Sender<Msg>,
+    resume: Arc<Mutex<ResumeBuffer>>,
+    control_tx: mpsc::UnboundedSender<ControlCmd>,
+    metrics: Arc<Metrics>,
+    socket_perms: &SocketPerms,
 ) -> Result<()> {
-    let listener =
-        UnixListener::bind(sock_path).with_context(|| format!("bind UDS {sock_path}"))?;
-    let active_clients = AtomicUsize::new(0);
+    let listener = Listener::bind(endpoint, socket_perms).await?;
+    let active_clients = &metrics.viewers_connected;
     let (snapshot_nodes_count, snapshot_edges_count) = match &snapshot_msg {
         Msg::Snapshot { nodes, edges } => (nodes.len(), edges.len()),
         _ => (0, 0),
     };
+    // Clients that announce chunk support get the snapshot in bounded frames.
+    let chunk_msgs = match &snapshot_msg {
+        Msg::Snapshot { nodes, edges } => chunk_snapshot(nodes, edges, SNAPSHOT_CHUNK_ITEMS),
+        _ => Vec::new(),
+    };
 
-    // Restrict perms: 0600
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let _ = std::fs::set_permissions(sock_path, std::fs::Permissions::from_mode(0o600));
-    }
-
-    tracing::info!(uds_path = %sock_path, "listening");
+    tracing::info!(%endpoint, "listening");
 
     loop {
-        let (stream, _) = listener.accept().await?;
-        let mut framed = Framed::new(stream, LengthDelimitedCodec::new());
-        let client_count = active_clients.fetch_add(1, Ordering::SeqCst) + 1;
-        tracing::info!(count = client_count, "client_connected");
+        let stream = listener.accept().await?;
+        let mut framed = Framed::new(
+            stream,
+            LengthDelimitedCodec::builder()
+                .max_frame_length(MAX_FRAME_LENGTH)
+                .new_codec(),
+        );
 
-        // Per-connection receiver
-        let mut bus_rx = bus_tx.subscribe();
+        // Expect optional hello/request; only a configured token makes it mandatory.
+        let first = read_hello(&mut framed, HELLO_TIMEOUT).await;
+        let (compress, chunked, resume_point) = match check_hello(first.as_ref(), token.as_deref())
+        {
+            Ok(h) => (h.compress, h.chunked && !chunk_msgs.is_empty(), h.resume),
+            Err(reason) => {
+                tracing::warn!(reason, "client_rejected");
+                let error = Msg::Error {
+                    message: reason.to_string(),
+                };
+                if let Ok(bytes) = encode_frame(&error, false) {
+                    let _ = framed.send(bytes.into()).await;
+                }
+                continue;
+            }
+        };
 
-        // Expect optional hello/request, but MVP is tolerant.
-        if let Some(Ok(bytes)) = framed.next().await {
-            let _ = serde_json::from_slice::<Msg>(&bytes); // ignore errors
-        }
+        let client_count = active_clients.fetch_add(1, Ordering::SeqCst) + 1;
+        tracing::info!(
+            event_type = "client_connected",
+            client_count,
+            "client_connected"
+        );
+
+        // Subscribe under the buffer lock so the replay ends exactly where the live stream starts.
+        let (mut bus_rx, session, replay) = {
+            let buffer = resume.lock().unwrap_or_else(PoisonError::into_inner);
+            let replay = resume_point.and_then(|(session, batch)| buffer.since(session, batch));
+            (bus_tx.subscribe(), buffer.session(), replay)
+        };
+        let resumed_after = resume_point
+            .filter(|_| replay.is_some())
+            .map(|(_, batch)| batch);
 
-        // Send hello + identity + snapshot
+        // Send hello + identity uncompressed; everything after honors the negotiated mode.
         framed
             .send(
-                serde_json::to_vec(&Msg::Hello {
-                    version: "0.1.0".into(),
-                })?
+                encode_frame(
+                    &Msg::Hello {
+                        version: "0.1.0".into(),
+                        compression: true,
+                        snapshot_chunks: true,
+                        token: None,
+                        session: Some(session),
+                        resume_after_batch: resumed_after,
+                    },
+                    false,
+                )?
                 .into(),
             )
             .await?;
         framed
-            .send(serde_json::to_vec(&identity_msg)?.into())
+            .send(encode_frame(&identity_msg, false)?.into())
             .await?;
-        framed
-            .send(serde_json::to_vec(&snapshot_msg)?.into())
-            .await?;
-        for msg in snapshot_node_events.iter() {
-            framed.send(serde_json::to_vec(msg)?.into()).await?;
+        if let Some(replay) = replay {
+            for msg in replay.iter() {
+                framed.send(encode_frame(msg, compress)?.into()).await?;
+            }
+            tracing::info!(
+                event_type = "resume_sent",
+                resume_after_batch = resumed_after,
+                event_count = replay.len(),
+                compress,
+                "resumed_from_buffer"
+            );
+        } else {
+            if chunked {
+                for msg in chunk_msgs.iter() {
+                    framed.send(encode_frame(msg, compress)?.into()).await?;
+                }
+            } else {
+                framed
+                    .send(encode_frame(&snapshot_msg, compress)?.into())
+                    .await?;
+            }
+            for msg in snapshot_node_events.iter() {
+                framed.send(encode_frame(msg, compress)?.into()).await?;
+            }
+            tracing::info!(
+                event_type = "snapshot_sent",
+                node_count = snapshot_nodes_count,
+                edge_count = snapshot_edges_count,
+                compress,
+                chunked,
+                resume_requested = resume_point.is_some(),
+                "sent_snapshot"
+            );
         }
-        tracing::info!(
-            nodes = snapshot_nodes_count,
-            edges = snapshot_edges_count,
-            "sent_snapshot"
-        );
 
-        // Stream deltas
+        // Stream deltas; answer viewer heartbeats and pass on control commands.
         loop {
-            match bus_rx.recv().await {
-                Ok(msg) => {
-                    if framed.send(serde_json::to_vec(&msg)?.into()).await.is_err() {
-                        break;
+            tokio::select! {
+                frame = framed.next() => match frame {
+                    Some(Ok(bytes)) => match classify_client_frame(decode_frame(&bytes).ok()) {
+                        ClientFrame::Ping => {
+                            if framed
+                                .send(encode_frame(&Msg::Pong, compress)?.into())
+                                .await
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
+                        ClientFrame::Control(cmd) => {
+                            tracing::info!(?cmd, "control_received");
+                            let _ = control_tx.send(cmd);
+                        }
+                        ClientFrame::Ignore => {}
+                    },
+                    _ => break,
+                },
+                msg = bus_rx.recv() => match msg {
+                    Ok(msg) => {
+                        if framed
+                            .send(encode_frame(&msg, compress)?.into())
+                            .await
+                            .is_err()
+                        {
+                            break;
+                        }
                     }
-                }
-                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
-                Err(_) => break,
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(_) => break,
+                },
             }
         }
         let client_count = active_clients.fetch_sub(1, Ordering::SeqCst) - 1;
-        tracing::info!(count = client_count, "client_disconnected");
+        tracing::info!(
+            event_type = "client_disconnected",
+            client_count,
+            "client_disconnected"
+        );
     }
 }
 
-#[cfg(not(unix))]
-pub async fn run(
-    _sock_path: &str,
-    _identity_msg: Msg,
-    _snapshot_msg: Msg,
-    _snapshot_node_events: Vec<Msg>,
-    _bus_tx: tokio::sync::broadcast::Sender<Msg>,
-) -> Result<()> {
-    anyhow::bail!("UDS server is only supported on unix platforms")
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hello(token: Option<&str>) -> Msg {
+        Msg::Hello {
+            version: "0.1.0".into(),
+            compression: true,
+            snapshot_chunks: true,
+            token: token.map(str::to_string),
+            session: None,
+            resume_after_batch: None,
+        }
+    }
+
+    #[test]
+    fn handshake_without_configured_token_is_tolerant() {
+        assert_eq!(
+            check_hello(Some(&hello(None)), None),
+            Ok(Handshake {
+                compress: true,
+                chunked: true,
+                resume: None,
+            })
+        );
+        assert_eq!(check_hello(None, None), Ok(Handshake::default()));
+        assert_eq!(
+            check_hello(Some(&Msg::RequestSnapshot), None),
+            Ok(Handshake::default())
+        );
+    }
+
+    #[test]
+    fn handshake_carries_resume_point_only_with_session() {
+        let resume = |session, batch| Msg::Hello {
+            version: "0.1.0".into(),
+            compression: false,
+            snapshot_chunks: true,
+            token: None,
+            session,
+            resume_after_batch: batch,
+        };
+        let ok = check_hello(Some(&resume(Some(7), Some(42))), None).expect("accepted");
+        assert_eq!(ok.resume, Some((7, 42)));
+        let no_session = check_hello(Some(&resume(None, Some(42))), None).expect("accepted");
+        assert_eq!(no_session.resume, None);
+    }
+
+    #[test]
+    fn handshake_accepts_matching_token() {
+        let accepted = check_hello(Some(&hello(Some("s3cret"))), Some("s3cret"));
+        assert_eq!(
+            accepted,
+            Ok(Handshake {
+                compress: true,
+                chunked: true,
+                resume: None,
+            })
+        );
+    }
+
+    #[test]
+    fn handshake_rejects_missing_or_wrong_token() {
+        assert_eq!(
+            check_hello(Some(&hello(Some("guess"))), Some("s3cret")),
+            Err("invalid token")
+        );
+        assert_eq!(
+            check_hello(Some(&hello(Some("s3cre"))), Some("s3cret")),
+            Err("invalid token")
+        );
+        assert_eq!(
+            check_hello(Some(&hello(None)), Some("s3cret")),
+            Err("token required")
+        );
+        assert_eq!(check_hello(None, Some("s3cret")), Err("token required"));
+    }
+
+    #[test]
+    fn client_frames_dispatch_pings_and_control_commands() {
+        assert_eq!(classify_client_frame(Some(Msg::Ping)), ClientFrame::Ping);
+        assert_eq!(
+            classify_client_frame(Some(Msg::Control {
+                cmd: ControlCmd::Pause,
+            })),
+            ClientFrame::Control(ControlCmd::Pause)
+        );
+        assert_eq!(
+            classify_client_frame(Some(Msg::RequestSnapshot)),
+            ClientFrame::Ignore
+        );
+        assert_eq!(classify_client_frame(None), ClientFrame::Ignore);
+    }
+
+    #[test]
+    fn control_as_first_frame_counts_as_missing_hello() {
+        let control = Msg::Control {
+            cmd: ControlCmd::Resume,
+        };
+        assert_eq!(check_hello(Some(&control), None), Ok(Handshake::default()));
+        assert_eq!(
+            check_hello(Some(&control), Some("s3cret")),
+            Err("token required")
+        );
+    }
+
+    #[tokio::test]
+    async fn silent_client_times_out_as_missing_hello() {
+        let (_client, server) = tokio::io::duplex(1024);
+        let mut framed = Framed::new(server, LengthDelimitedCodec::new());
+
+        let first = read_hello(&mut framed, Duration::from_millis(20)).await;
+
+        assert!(first.is_none());
+        assert_eq!(
+            check_hello(first.as_ref(), Some("s3cret")),
+            Err("token required")
+        );
+    }
+
+    #[tokio::test]
+    async fn hello_within_the_timeout_is_read() {
+        let (client, server) = tokio::io::duplex(1024);
+        let mut client = Framed::new(client, LengthDelimitedCodec::new());
+        let frame = encode_frame(&hello(Some("s3cret")), false).expect("encode");
+        client.send(frame.into()).await.expect("send");
+        let mut framed = Framed::new(server, LengthDelimitedCodec::new());
+
+        let first = read_hello(&mut framed, Duration::from_secs(5)).await;
+
+        assert!(check_hello(first.as_ref(), Some("s3cret")).is_ok());
+    }
+
+    #[tokio::test]
+    async fn tcp_endpoint_accepts_framed_viewers() {
+        let endpoint = Endpoint::Tcp("127.0.0.1:0".to_string());
+        let listener = Listener::bind(&endpoint, &SocketPerms::default())
+            .await
+            .expect("bind");
+        #[allow(irrefutable_let_patterns)]
+        let Listener::Tcp(tcp) = &listener
+        else {
+            panic!("tcp endpoint bound a unix socket");
+        };
+        let addr = tcp.local_addr().expect("local addr");
+        tokio::spawn(async move {
+            let stream = tokio::net::TcpStream::connect(addr).await.expect("connect");
+            let mut framed = Framed::new(stream, LengthDelimitedCodec::new());
+            let ping = encode_frame(&Msg::Ping, false).expect("encode");
+            framed.send(ping.into()).await.expect("send");
+        });
+
+        let conn = listener.accept().await.expect("accept");
+        let mut framed = Framed::new(conn, LengthDelimitedCodec::new());
+        let frame = framed.next().await.expect("frame").expect("read");
+        assert_eq!(
+            classify_client_frame(decode_frame(&frame).ok()),
+            ClientFrame::Ping
+        );
+    }
+
+    #[tokio::test]
+    async fn tcp_endpoint_reports_an_address_in_use() {
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = taken.local_addr().expect("local addr").to_string();
+        let err = Listener::bind(&Endpoint::Tcp(addr.clone()), &SocketPerms::default())
+            .await
+            .err()
+            .expect("address in use");
+        assert!(err.to_string().contains(&addr), "{err}");
+    }
 }
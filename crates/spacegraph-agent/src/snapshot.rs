@@ -1,13 +1,18 @@
 use anyhow::{Context, Result};
 use procfs::process::Process;
-use spacegraph_core::{id_file, id_process, id_user, Edge, EdgeKind, FileKind, Node, NodeId};
+use spacegraph_core::{id_container, id_process, id_user, Edge, EdgeKind, FileKind, Node, NodeId};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io;
 use std::path::Path;
 
+use crate::cgroup::container_for_pid;
 use crate::config::AgentMode;
+use crate::file_id::FileIdentity;
 use crate::path_policy::PathPolicy;
+use crate::threads::thread_detail;
+use crate::users::UserNames;
+use crate::watch_fs::{file_category, symlink_target};
 
 fn parse_passwd(mode: AgentMode) -> Result<HashMap<u32, String>> {
     let content = match fs::read_to_string("/etc/passwd") {
@@ -49,6 +54,28 @@ fn file_kind_from_path(p: &str) -> FileKind {
     }
 }
 
+fn file_node(path: &str) -> Node {
+    let link_target = symlink_target(path);
+    Node::File {
+        path: path.to_string(),
+        inode: inode_for_path(path),
+        kind: if link_target.is_some() {
+            FileKind::Symlink
+        } else {
+            file_kind_from_path(path)
+        },
+        category: file_category(path),
+        link_target,
+    }
+}
+
+fn rss_kb_for(pr: &Process) -> u64 {
+    // statm reports pages; convert to KiB for display.
+    pr.statm()
+        .map(|m| m.resident * procfs::page_size() / 1024)
+        .unwrap_or(0)
+}
+
 fn inode_for_path(path: &str) -> u64 {
     fs::metadata(path)
         .map(|m| {
@@ -72,10 +99,15 @@ fn is_permission_denied(error: &io::Error) -> bool {
 fn log_permission_denied(mode: AgentMode, path: &str, context: &str) {
     match mode {
         AgentMode::User => {
-            tracing::debug!(path = %path, "{context} (permission denied)");
+            tracing::debug!(
+                event_type = "proc_permission_denied",
+                path = %path,
+                "{context} (permission denied)"
+            );
         }
         AgentMode::Privileged => {
             tracing::warn!(
+                event_type = "proc_permission_denied",
                 path = %path,
                 "{context} (permission denied; run with sudo or adjust permissions)"
             );
@@ -114,16 +146,43 @@ fn fd_flags(pid: i32, fd: i32) -> Option<i64> {
     None
 }
 
-pub fn build_snapshot(node_id: &str, policy: &PathPolicy, mode: AgentMode) -> Result<SnapshotData> {
+/// Keep at most `max` fd entries, lowest fd numbers first. Returns the kept entries and
+/// how many were dropped.
+pub(crate) fn cap_fd_entries(
+    mut entries: Vec<(i32, String)>,
+    max: Option<usize>,
+) -> (Vec<(i32, String)>, usize) {
+    let Some(max) = max else {
+        return (entries, 0);
+    };
+    if entries.len() <= max {
+        return (entries, 0);
+    }
+    entries.sort_by_key(|(fd, _)| *fd);
+    let dropped = entries.len() - max;
+    entries.truncate(max);
+    (entries, dropped)
+}
+
+pub fn build_snapshot(
+    node_id: &str,
+    policy: &PathPolicy,
+    mode: AgentMode,
+    max_fd_edges: Option<usize>,
+    file_identity: FileIdentity,
+    threads: bool,
+) -> Result<SnapshotData> {
     // Procfs is always scanned; filesystem filtering only applies to file paths below.
-    let passwd = if policy.should_watch(Path::new("/etc/passwd")) {
+    let mut users = UserNames::new(if policy.should_watch(Path::new("/etc/passwd")) {
         parse_passwd(mode).unwrap_or_default()
     } else {
         HashMap::new()
-    };
+    });
 
     let mut nodes: HashMap<NodeId, Node> = HashMap::new();
     let mut edges: HashSet<Edge> = HashSet::new();
+    let mut fd_edges_dropped = 0usize;
+    let mut capped_processes = 0usize;
 
     // Users from passwd that appear as process owners will be added on demand.
     // Processes:
@@ -155,7 +214,11 @@ pub fn build_snapshot(node_id: &str, policy: &PathPolicy, mode: AgentMode) -> Re
             .map(|v| v.join(" "))
             .unwrap_or_else(|| stat.comm.clone());
 
-        let proc_id = id_process(node_id, pid);
+        let rss_kb = rss_kb_for(&pr);
+        let utime_ticks = stat.utime;
+        let container = container_for_pid(pid);
+
+        let proc_id = id_process(node_id, pid, Some(stat.starttime));
 
         nodes.insert(
             proc_id.clone(),
@@ -165,14 +228,30 @@ pub fn build_snapshot(node_id: &str, policy: &PathPolicy, mode: AgentMode) -> Re
                 exe: exe.clone(),
                 cmdline,
                 uid,
+                rss_kb,
+                utime_ticks,
+                container: container.as_ref().map(|c| c.id.clone()),
             },
         );
 
+        // container node + membership edge
+        if let Some(c) = container {
+            let container_id = id_container(node_id, &c.id);
+            nodes
+                .entry(container_id.clone())
+                .or_insert(Node::Container {
+                    id: c.id,
+                    name: c.name,
+                });
+            edges.insert(Edge {
+                from: proc_id.clone(),
+                to: container_id,
+                kind: EdgeKind::MemberOf,
+            });
+        }
+
         // user node + edge
-        let uname = passwd
-            .get(&uid)
-            .cloned()
-            .unwrap_or_else(|| format!("uid{uid}"));
+        let uname = users.name(uid);
         let user_id = id_user(node_id, uid);
         nodes
             .entry(user_id.clone())
@@ -185,12 +264,10 @@ pub fn build_snapshot(node_id: &str, policy: &PathPolicy, mode: AgentMode) -> Re
 
         // exe as file node + edge
         if should_keep_path(policy, &exe) {
-            let exe_file_id = id_file(node_id, &exe);
-            nodes.entry(exe_file_id.clone()).or_insert(Node::File {
-                path: exe.clone(),
-                inode: inode_for_path(&exe),
-                kind: file_kind_from_path(&exe),
-            });
+            let exe_file_id = file_identity.id(node_id, &exe);
+            nodes
+                .entry(exe_file_id.clone())
+                .or_insert_with(|| file_node(&exe));
             edges.insert(Edge {
                 from: proc_id.clone(),
                 to: exe_file_id,
@@ -199,32 +276,178 @@ pub fn build_snapshot(node_id: &str, policy: &PathPolicy, mode: AgentMode) -> Re
         }
 
         // fd edges
-        add_fd_edges(node_id, policy, mode, &pr, &proc_id, &mut nodes, &mut edges);
+        let dropped = add_fd_edges(
+            node_id,
+            policy,
+            mode,
+            max_fd_edges,
+            file_identity,
+            &pr,
+            &proc_id,
+            &mut nodes,
+            &mut edges,
+        );
+        if dropped > 0 {
+            fd_edges_dropped += dropped;
+            capped_processes += 1;
+        }
+
+        if threads {
+            let (thread_nodes, thread_edges) = thread_detail(node_id, pid, &proc_id);
+            nodes.extend(thread_nodes);
+            edges.extend(thread_edges);
+        }
     }
 
+    if fd_edges_dropped > 0 {
+        tracing::info!(
+            event_type = "snapshot_fd_cap",
+            dropped_count = fd_edges_dropped,
+            capped_process_count = capped_processes,
+            max_fd_edges_per_process = ?max_fd_edges,
+            "snapshot fd edges capped"
+        );
+    }
+
+    add_parent_edges(&nodes, &mut edges);
+    add_root_links(node_id, policy, file_identity, &mut nodes);
+    add_link_edges(node_id, policy, file_identity, &mut nodes, &mut edges);
+
     Ok((nodes.into_iter().collect(), edges.into_iter().collect()))
 }
 
+fn add_parent_edges(nodes: &HashMap<NodeId, Node>, edges: &mut HashSet<Edge>) {
+    // Process ids carry the start time, so the parent is found by pid rather than rebuilt.
+    let by_pid: HashMap<i32, &NodeId> = nodes
+        .iter()
+        .filter_map(|(id, node)| match node {
+            Node::Process { pid, .. } => Some((*pid, id)),
+            _ => None,
+        })
+        .collect();
+    for (child_id, node) in nodes.iter() {
+        let Node::Process { ppid, .. } = node else {
+            continue;
+        };
+        if *ppid <= 0 {
+            continue;
+        }
+        // Parent may have exited between the scan and now; skip dangling edges.
+        let Some(parent_id) = by_pid.get(ppid) else {
+            continue;
+        };
+        edges.insert(Edge {
+            from: (*parent_id).clone(),
+            to: child_id.clone(),
+            kind: EdgeKind::ParentOf,
+        });
+    }
+}
+
+/// Adds file nodes for the symlinks below the watched roots, walked without following
+/// links. Exe and fd paths come out of `/proc` already resolved, so this is where
+/// symlinks enter the snapshot; an empty include list walks nothing.
+fn add_root_links(
+    node_id: &str,
+    policy: &PathPolicy,
+    file_identity: FileIdentity,
+    nodes: &mut HashMap<NodeId, Node>,
+) {
+    let mut stack = policy.includes().to_vec();
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !policy.should_watch(&path) {
+                continue;
+            }
+            let Ok(meta) = fs::symlink_metadata(&path) else {
+                continue;
+            };
+            if meta.is_dir() {
+                stack.push(path);
+            } else if meta.file_type().is_symlink() {
+                let Some(link) = path.to_str() else {
+                    continue;
+                };
+                nodes
+                    .entry(file_identity.id(node_id, link))
+                    .or_insert_with(|| file_node(link));
+            }
+        }
+    }
+}
+
+/// Adds `PointsTo` edges from symlink file nodes to their targets, following chains.
+/// Targets outside the path policy are left out.
+fn add_link_edges(
+    node_id: &str,
+    policy: &PathPolicy,
+    file_identity: FileIdentity,
+    nodes: &mut HashMap<NodeId, Node>,
+    edges: &mut HashSet<Edge>,
+) {
+    let mut pending: Vec<(NodeId, String)> = nodes
+        .iter()
+        .filter_map(|(id, node)| match node {
+            Node::File {
+                link_target: Some(target),
+                ..
+            } => Some((id.clone(), target.clone())),
+            _ => None,
+        })
+        .collect();
+    while let Some((link_id, target)) = pending.pop() {
+        if !should_keep_path(policy, &target) {
+            continue;
+        }
+        let target_id = file_identity.id(node_id, &target);
+        if !nodes.contains_key(&target_id) {
+            let node = file_node(&target);
+            if let Node::File {
+                link_target: Some(next),
+                ..
+            } = &node
+            {
+                pending.push((target_id.clone(), next.clone()));
+            }
+            nodes.insert(target_id.clone(), node);
+        }
+        edges.insert(Edge {
+            from: link_id,
+            to: target_id,
+            kind: EdgeKind::PointsTo,
+        });
+    }
+}
+
+/// Adds `Opens` edges for a process and returns how many were dropped by the cap.
+#[allow(clippy::too_many_arguments)]
 fn add_fd_edges(
     node_id: &str,
     policy: &PathPolicy,
     mode: AgentMode,
+    max_fd_edges: Option<usize>,
+    file_identity: FileIdentity,
     pr: &Process,
     proc_id: &NodeId,
     nodes: &mut HashMap<NodeId, Node>,
     edges: &mut HashSet<Edge>,
-) {
+) -> usize {
     let pid = pr.pid();
     let fd_dir = format!("/proc/{pid}/fd");
     let entries = match fs::read_dir(&fd_dir) {
         Ok(e) => e,
         Err(err) if is_permission_denied(&err) => {
             log_permission_denied(mode, &fd_dir, "skipping fd dir");
-            return;
+            return 0;
         }
-        Err(_) => return,
+        Err(_) => return 0,
     };
 
+    let mut fds: Vec<(i32, String)> = Vec::new();
     for ent in entries.flatten() {
         let name = ent.file_name();
         let fd: i32 = match name.to_string_lossy().parse() {
@@ -240,13 +463,15 @@ fn add_fd_edges(
         if !should_keep_path(policy, &target) {
             continue;
         }
+        fds.push((fd, target));
+    }
 
-        let f_id = id_file(node_id, &target);
-        nodes.entry(f_id.clone()).or_insert(Node::File {
-            path: target.clone(),
-            inode: inode_for_path(&target),
-            kind: file_kind_from_path(&target),
-        });
+    let (fds, dropped) = cap_fd_entries(fds, max_fd_edges);
+    for (fd, target) in fds {
+        let f_id = file_identity.id(node_id, &target);
+        nodes
+            .entry(f_id.clone())
+            .or_insert_with(|| file_node(&target));
 
         let mode = fd_flags(pid, fd)
             .map(fd_mode_from_flags)
@@ -257,6 +482,7 @@ fn add_fd_edges(
             kind: EdgeKind::Opens { fd, mode },
         });
     }
+    dropped
 }
 
 fn should_keep_path(policy: &PathPolicy, path: &str) -> bool {
@@ -266,3 +492,157 @@ fn should_keep_path(policy: &PathPolicy, path: &str) -> bool {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::id_file;
+
+    fn process(pid: i32, ppid: i32) -> Node {
+        Node::Process {
+            pid,
+            ppid,
+            exe: "/bin/true".to_string(),
+            cmdline: "true".to_string(),
+            uid: 0,
+            rss_kb: 0,
+            utime_ticks: 0,
+            container: None,
+        }
+    }
+
+    #[test]
+    fn parent_edge_links_known_parent_to_child() {
+        let mut nodes = HashMap::new();
+        nodes.insert(id_process("n", 1, Some(5)), process(1, 0));
+        nodes.insert(id_process("n", 42, Some(900)), process(42, 1));
+        let mut edges = HashSet::new();
+
+        add_parent_edges(&nodes, &mut edges);
+
+        assert_eq!(edges.len(), 1);
+        assert!(edges.contains(&Edge {
+            from: id_process("n", 1, Some(5)),
+            to: id_process("n", 42, Some(900)),
+            kind: EdgeKind::ParentOf,
+        }));
+    }
+
+    #[test]
+    fn parent_edge_skipped_when_parent_missing() {
+        let mut nodes = HashMap::new();
+        nodes.insert(id_process("n", 42, Some(900)), process(42, 7));
+        let mut edges = HashSet::new();
+
+        add_parent_edges(&nodes, &mut edges);
+
+        assert!(edges.is_empty());
+    }
+
+    #[test]
+    fn link_edges_point_to_targets_inside_policy() {
+        let link = |path: &str, target: &str| Node::File {
+            path: path.to_string(),
+            inode: 0,
+            kind: FileKind::Symlink,
+            category: Default::default(),
+            link_target: Some(target.to_string()),
+        };
+        let policy = PathPolicy::new(vec!["/srv/sg-test".into()], Vec::new());
+        let mut nodes = HashMap::new();
+        nodes.insert(
+            id_file("n", "/srv/sg-test/current"),
+            link("/srv/sg-test/current", "/srv/sg-test/v2"),
+        );
+        nodes.insert(
+            id_file("n", "/srv/sg-test/outside"),
+            link("/srv/sg-test/outside", "/opt/elsewhere"),
+        );
+        let mut edges = HashSet::new();
+
+        add_link_edges("n", &policy, FileIdentity::Path, &mut nodes, &mut edges);
+
+        assert_eq!(edges.len(), 1);
+        assert!(edges.contains(&Edge {
+            from: id_file("n", "/srv/sg-test/current"),
+            to: id_file("n", "/srv/sg-test/v2"),
+            kind: EdgeKind::PointsTo,
+        }));
+        assert!(nodes.contains_key(&id_file("n", "/srv/sg-test/v2")));
+        assert!(!nodes.contains_key(&id_file("n", "/opt/elsewhere")));
+    }
+
+    #[test]
+    fn snapshot_links_symlinks_below_the_watched_roots() {
+        let root =
+            std::env::temp_dir().join(format!("spacegraph-snap-links-{}", std::process::id()));
+        let hidden = root.join("hidden");
+        fs::create_dir_all(&hidden).unwrap();
+        fs::write(root.join("v2"), b"x").unwrap();
+        std::os::unix::fs::symlink("v2", root.join("current")).unwrap();
+        std::os::unix::fs::symlink("/opt/sg-elsewhere", root.join("outside")).unwrap();
+        std::os::unix::fs::symlink("../v2", hidden.join("link")).unwrap();
+        let path = |name: &str| root.join(name).to_str().unwrap().to_string();
+        let mut policy = PathPolicy::new(vec![root.clone()], vec![hidden.clone()]);
+        policy.normalize();
+
+        let (nodes, edges) = build_snapshot(
+            "n",
+            &policy,
+            AgentMode::User,
+            None,
+            FileIdentity::Path,
+            false,
+        )
+        .unwrap();
+        let _ = fs::remove_dir_all(&root);
+
+        let node = |p: &str| {
+            nodes
+                .iter()
+                .find(|(id, _)| *id == id_file("n", p))
+                .map(|(_, node)| node)
+        };
+        assert!(matches!(
+            node(&path("current")),
+            Some(Node::File { kind: FileKind::Symlink, link_target: Some(t), .. }) if *t == path("v2")
+        ));
+        assert!(node(&path("v2")).is_some());
+        assert!(edges.contains(&Edge {
+            from: id_file("n", &path("current")),
+            to: id_file("n", &path("v2")),
+            kind: EdgeKind::PointsTo,
+        }));
+
+        // Outside targets, excluded subtrees and plain files add nothing of their own.
+        assert!(node(&path("outside")).is_some());
+        assert!(node("/opt/sg-elsewhere").is_none());
+        assert!(node(&path("hidden/link")).is_none());
+        let points_to = edges
+            .iter()
+            .filter(|e| e.kind == EdgeKind::PointsTo)
+            .count();
+        assert_eq!(points_to, 1);
+    }
+
+    #[test]
+    fn cap_fd_entries_keeps_lowest_fds() {
+        let fds = vec![
+            (9, "/tmp/nine".to_string()),
+            (0, "/dev/null".to_string()),
+            (4, "socket:[123]".to_string()),
+            (2, "/dev/pts/0".to_string()),
+        ];
+
+        let (kept, dropped) = cap_fd_entries(fds.clone(), Some(2));
+        assert_eq!(dropped, 2);
+        assert_eq!(
+            kept,
+            vec![(0, "/dev/null".to_string()), (2, "/dev/pts/0".to_string())]
+        );
+
+        let (kept, dropped) = cap_fd_entries(fds.clone(), None);
+        assert_eq!(dropped, 0);
+        assert_eq!(kept.len(), 4);
+    }
+}
@@ -0,0 +1,180 @@
+use anyhow::Result;
+#[cfg(unix)]
+use std::path::Path;
+
+pub const DEFAULT_SOCKET_MODE: u32 = 0o600;
+/// `sun_path` holds 108 bytes, one of which is the leading NUL of an abstract name.
+const MAX_ABSTRACT_NAME: usize = 107;
+
+/// Permissions applied to the UDS right after bind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketPerms {
+    pub mode: u32,
+    /// Group that should own the socket, resolved by name.
+    pub group: Option<String>,
+}
+
+impl Default for SocketPerms {
+    fn default() -> Self {
+        Self {
+            mode: DEFAULT_SOCKET_MODE,
+            group: None,
+        }
+    }
+}
+
+/// Accepts `660`, `0660` and `0o660`; only permission bits are allowed.
+pub fn parse_mode(input: &str) -> Result<u32> {
+    let digits = input.strip_prefix("0o").unwrap_or(input);
+    let mode = u32::from_str_radix(digits, 8)
+        .map_err(|_| anyhow::anyhow!("invalid socket mode: {input} (expected octal, e.g. 0660)"))?;
+    if mode > 0o777 {
+        anyhow::bail!("invalid socket mode: {input} (at most 0777)");
+    }
+    Ok(mode)
+}
+
+/// Name for `--abstract-socket`; the usual `@` notation is accepted and dropped.
+pub fn parse_abstract_name(input: &str) -> Result<String> {
+    let name = input.strip_prefix('@').unwrap_or(input);
+    if name.is_empty() {
+        anyhow::bail!("invalid abstract socket name: {input:?} (empty)");
+    }
+    if name.contains('\0') {
+        anyhow::bail!("invalid abstract socket name: {input:?} (contains NUL)");
+    }
+    if name.len() > MAX_ABSTRACT_NAME {
+        anyhow::bail!(
+            "invalid abstract socket name: {input:?} (at most {MAX_ABSTRACT_NAME} bytes)"
+        );
+    }
+    Ok(name.to_string())
+}
+
+/// Tokio binds a path starting with NUL in the abstract namespace on Linux.
+pub fn abstract_path(name: &str) -> String {
+    format!("\0{name}")
+}
+
+pub fn is_abstract(sock_path: &str) -> bool {
+    sock_path.starts_with('\0')
+}
+
+/// `@name` for abstract sockets, so logs never carry the NUL byte.
+pub fn display_path(sock_path: &str) -> String {
+    match sock_path.strip_prefix('\0') {
+        Some(name) => format!("@{name}"),
+        None => sock_path.to_string(),
+    }
+}
+
+/// Other platforms have no UDS server, so the flags are accepted and ignored there.
+#[cfg(unix)]
+pub fn apply(path: &Path, perms: &SocketPerms) -> Result<()> {
+    use anyhow::Context;
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    if let Some(group) = &perms.group {
+        chown_group(path, group)?;
+    }
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(perms.mode))
+        .with_context(|| format!("chmod {}", path.display()))?;
+
+    let meta = std::fs::metadata(path).with_context(|| format!("stat {}", path.display()))?;
+    tracing::info!(
+        uds_path = %path.display(),
+        mode = %format!("{:04o}", meta.mode() & 0o777),
+        uid = meta.uid(),
+        gid = meta.gid(),
+        "socket permissions"
+    );
+    Ok(())
+}
+
+#[cfg(all(unix, feature = "socket-group"))]
+fn chown_group(path: &Path, group: &str) -> Result<()> {
+    use anyhow::Context;
+    use nix::unistd::{chown, Group};
+
+    let gid = Group::from_name(group)
+        .with_context(|| format!("look up group {group}"))?
+        .ok_or_else(|| anyhow::anyhow!("unknown group: {group}"))?
+        .gid;
+    chown(path, None, Some(gid))
+        .with_context(|| format!("chown {} to group {group}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(all(unix, not(feature = "socket-group")))]
+fn chown_group(_path: &Path, group: &str) -> Result<()> {
+    anyhow::bail!("--socket-group {group} needs the socket-group feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_octal_modes() {
+        assert_eq!(parse_mode("600").unwrap(), 0o600);
+        assert_eq!(parse_mode("0660").unwrap(), 0o660);
+        assert_eq!(parse_mode("0o640").unwrap(), 0o640);
+        assert!(parse_mode("0800").is_err());
+        assert!(parse_mode("1777").is_err());
+        assert!(parse_mode("rw-------").is_err());
+        assert!(parse_mode("").is_err());
+    }
+
+    #[test]
+    fn parses_abstract_socket_names() {
+        assert_eq!(parse_abstract_name("spacegraph").unwrap(), "spacegraph");
+        assert_eq!(parse_abstract_name("@spacegraph").unwrap(), "spacegraph");
+        assert!(parse_abstract_name("").is_err());
+        assert!(parse_abstract_name("@").is_err());
+        assert!(parse_abstract_name("a\0b").is_err());
+        assert!(parse_abstract_name(&"x".repeat(MAX_ABSTRACT_NAME)).is_ok());
+        assert!(parse_abstract_name(&"x".repeat(MAX_ABSTRACT_NAME + 1)).is_err());
+    }
+
+    #[test]
+    fn abstract_paths_are_shown_with_an_at_sign() {
+        assert!(!is_abstract("/tmp/spacegraph.sock"));
+        assert_eq!(display_path("/tmp/spacegraph.sock"), "/tmp/spacegraph.sock");
+        assert!(is_abstract("\0spacegraph"));
+        assert_eq!(display_path("\0spacegraph"), "@spacegraph");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn binds_and_connects_in_the_abstract_namespace() {
+        let path = abstract_path(&format!("spacegraph-test-{}", std::process::id()));
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        let client = tokio::net::UnixStream::connect(&path);
+        let (accepted, connected) = tokio::join!(listener.accept(), client);
+        accepted.unwrap();
+        connected.unwrap();
+        // Nothing was created on disk.
+        assert!(!std::path::Path::new(&path[1..]).exists());
+    }
+
+    #[cfg(all(unix, feature = "socket-group"))]
+    #[test]
+    fn applies_mode_and_own_group() {
+        use nix::unistd::{getegid, Group};
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        let path = std::env::temp_dir().join(format!("spacegraph-perms-{}", std::process::id()));
+        std::fs::write(&path, b"").unwrap();
+        // Our own primary group is always a valid chown target.
+        let group = Group::from_gid(getegid()).unwrap().map(|g| g.name);
+        let perms = SocketPerms { mode: 0o640, group };
+
+        let applied = apply(&path, &perms);
+        let meta = std::fs::metadata(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        applied.unwrap();
+        assert_eq!(meta.permissions().mode() & 0o777, 0o640);
+        assert_eq!(meta.gid(), getegid().as_raw());
+    }
+}
@@ -0,0 +1,79 @@
+use spacegraph_core::{id_thread, Edge, EdgeKind, Node, NodeId};
+use std::fs;
+
+/// Reads `/proc/<pid>/task`; `(tid, name)` of every thread but the main one, by tid.
+pub(crate) fn list_threads(pid: i32) -> Vec<(i32, String)> {
+    let Ok(entries) = fs::read_dir(format!("/proc/{pid}/task")) else {
+        return Vec::new();
+    };
+    parse_tasks(
+        pid,
+        entries.flatten().map(|ent| {
+            let comm = fs::read_to_string(ent.path().join("comm")).unwrap_or_default();
+            (ent.file_name().to_string_lossy().to_string(), comm)
+        }),
+    )
+}
+
+/// Task directory names paired with their `comm`. The main thread (tid == pid) is the
+/// process node itself and is left out.
+fn parse_tasks<I>(pid: i32, tasks: I) -> Vec<(i32, String)>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let mut out: Vec<(i32, String)> = tasks
+        .into_iter()
+        .filter_map(|(dir, comm)| {
+            let tid = dir.parse::<i32>().ok()?;
+            (tid != pid).then(|| (tid, comm.trim_end().to_string()))
+        })
+        .collect();
+    out.sort_by_key(|(tid, _)| *tid);
+    out
+}
+
+/// Thread nodes of `pid` plus a `ThreadOf` edge from each to `proc_id`.
+pub(crate) fn thread_detail(
+    node_id: &str,
+    pid: i32,
+    proc_id: &NodeId,
+) -> (Vec<(NodeId, Node)>, Vec<Edge>) {
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    for (tid, name) in list_threads(pid) {
+        let id = id_thread(node_id, pid, tid);
+        edges.push(Edge {
+            from: id.clone(),
+            to: proc_id.clone(),
+            kind: EdgeKind::ThreadOf,
+        });
+        nodes.push((id, Node::Thread { tid, pid, name }));
+    }
+    (nodes, edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn task_listing_skips_main_thread_and_junk() {
+        let tasks = [
+            ("4243", "tokio-runtime-w\n"),
+            ("4242", "agent\n"),
+            ("4250", "notify-rs inoti\n"),
+            ("oops", "x\n"),
+            ("4244", ""),
+        ]
+        .map(|(dir, comm)| (dir.to_string(), comm.to_string()));
+
+        assert_eq!(
+            parse_tasks(4242, tasks),
+            vec![
+                (4243, "tokio-runtime-w".to_string()),
+                (4244, String::new()),
+                (4250, "notify-rs inoti".to_string()),
+            ]
+        );
+    }
+}
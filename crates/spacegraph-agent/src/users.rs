@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+/// Resolves uids to user names: the parsed `/etc/passwd` map first, then an NSS lookup
+/// (LDAP/SSSD/...) when the `nss` feature is enabled. Misses are cached either way.
+#[derive(Debug, Default)]
+pub struct UserNames {
+    passwd: HashMap<u32, String>,
+    cache: HashMap<u32, String>,
+}
+
+impl UserNames {
+    pub fn new(passwd: HashMap<u32, String>) -> Self {
+        Self {
+            passwd,
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn set_passwd(&mut self, passwd: HashMap<u32, String>) {
+        self.passwd = passwd;
+        self.cache.clear();
+    }
+
+    pub fn name(&mut self, uid: u32) -> String {
+        self.name_with(uid, lookup_nss)
+    }
+
+    fn name_with(&mut self, uid: u32, lookup: impl FnOnce(u32) -> Option<String>) -> String {
+        if let Some(name) = self.passwd.get(&uid) {
+            return name.clone();
+        }
+        self.cache
+            .entry(uid)
+            .or_insert_with(|| lookup(uid).unwrap_or_else(|| format!("uid{uid}")))
+            .clone()
+    }
+}
+
+#[cfg(all(unix, feature = "nss"))]
+fn lookup_nss(uid: u32) -> Option<String> {
+    use nix::unistd::{Uid, User};
+    User::from_uid(Uid::from_raw(uid))
+        .ok()
+        .flatten()
+        .map(|user| user.name)
+}
+
+#[cfg(not(all(unix, feature = "nss")))]
+fn lookup_nss(_uid: u32) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passwd_entries_win_over_lookup() {
+        let mut users = UserNames::new(HashMap::from([(0, "root".to_string())]));
+        let name = users.name_with(0, |_| panic!("lookup should not run"));
+        assert_eq!(name, "root");
+    }
+
+    #[test]
+    fn cache_miss_falls_back_and_is_cached() {
+        let mut users = UserNames::default();
+
+        assert_eq!(
+            users.name_with(5000, |_| Some("ldapuser".into())),
+            "ldapuser"
+        );
+        assert_eq!(users.name_with(5000, |_| panic!("cached")), "ldapuser");
+
+        assert_eq!(users.name_with(6000, |_| None), "uid6000");
+        assert_eq!(users.name_with(6000, |_| panic!("cached")), "uid6000");
+    }
+}
@@ -1,15 +1,18 @@
 use anyhow::Result;
 use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use spacegraph_core::{id_file, Delta, FileKind, Msg, Node};
-use std::collections::HashMap;
+use spacegraph_core::{Delta, Edge, EdgeKind, FileCategory, FileKind, Msg, Node, NodeId};
+use std::collections::{HashMap, HashSet};
 use std::io;
-use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::sync::mpsc;
 
 use crate::config::AgentMode;
-use crate::path_policy::PathPolicy;
+use crate::file_id::FileIdentity;
+use crate::metrics::Metrics;
+use crate::path_policy::{PathPolicy, RootDiff};
 fn inode_for_path(path: &str) -> u64 {
     std::fs::metadata(path)
         .map(|m| {
@@ -20,18 +23,123 @@ fn inode_for_path(path: &str) -> u64 {
             }
             #[cfg(not(unix))]
             {
+                let _ = m;
                 0
             }
         })
         .unwrap_or(0)
 }
 
+const CONFIG_EXTS: &[&str] = &[
+    "conf", "cfg", "cnf", "ini", "toml", "yaml", "yml", "service", "rules",
+];
+const SCRIPT_EXTS: &[&str] = &["sh", "bash", "zsh", "py", "pl", "rb", "js", "lua"];
+const BINARY_EXTS: &[&str] = &["so", "a", "o", "ko", "exe", "dll", "bin"];
+const DATA_EXTS: &[&str] = &[
+    "db", "sqlite", "json", "csv", "xml", "dat", "txt", "gz", "zst", "tar",
+];
+
+/// Classifies a file by extension and location; stats the path only for the
+/// executable bit.
+pub(crate) fn file_category(path: &str) -> FileCategory {
+    let pseudo = ["socket:", "pipe:", "anon_inode:", "/dev/", "/proc/"];
+    if pseudo.iter().any(|prefix| path.starts_with(prefix)) {
+        return FileCategory::Unknown;
+    }
+    category_from_path(path, is_executable(path))
+}
+
+fn category_from_path(path: &str, executable: bool) -> FileCategory {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let ext = name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    let ext = ext.as_str();
+
+    if ext == "log" || path.starts_with("/var/log/") {
+        FileCategory::Log
+    } else if SCRIPT_EXTS.contains(&ext) {
+        FileCategory::Script
+    } else if CONFIG_EXTS.contains(&ext) {
+        FileCategory::Config
+    } else if BINARY_EXTS.contains(&ext) || name.contains(".so.") {
+        FileCategory::Binary
+    } else if DATA_EXTS.contains(&ext) {
+        FileCategory::Data
+    } else if path.starts_with("/etc/") {
+        FileCategory::Config
+    } else if executable {
+        FileCategory::Binary
+    } else {
+        FileCategory::Unknown
+    }
+}
+
+/// Target of `path` if it is a symlink, made absolute against the link's directory.
+pub(crate) fn symlink_target(path: &str) -> Option<String> {
+    let meta = std::fs::symlink_metadata(path).ok()?;
+    if !meta.file_type().is_symlink() {
+        return None;
+    }
+    let target = std::fs::read_link(path).ok()?;
+    Some(resolve_link_target(Path::new(path), &target))
+}
+
+/// Joins a relative link target onto the link's parent and folds `.`/`..` lexically;
+/// the target itself is never touched, so dangling links still resolve.
+fn resolve_link_target(link: &Path, target: &Path) -> String {
+    let joined = match link.parent() {
+        Some(parent) if target.is_relative() => parent.join(target),
+        _ => target.to_path_buf(),
+    };
+    let mut out = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out.to_string_lossy().to_string()
+}
+
+fn is_executable(path: &str) -> bool {
+    std::fs::metadata(path)
+        .map(|m| {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                m.is_file() && m.permissions().mode() & 0o111 != 0
+            }
+            #[cfg(not(unix))]
+            {
+                m.is_file() && path.to_ascii_lowercase().ends_with(".exe")
+            }
+        })
+        .unwrap_or(false)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Action {
     Upsert,
     Remove,
 }
 
+/// Folds a new event for a path into its pending action: a Remove overrides a pending
+/// Upsert, and a later Upsert (the path was recreated) overrides a pending Remove.
+fn merge_action(pending: Action, incoming: Action) -> Action {
+    if pending != Action::Remove && incoming == Action::Remove {
+        Action::Remove
+    } else if incoming == Action::Upsert {
+        Action::Upsert
+    } else {
+        pending
+    }
+}
+
 fn classify(kind: &EventKind) -> Option<Action> {
     // MVP mapping: Create/Modify => Upsert, Remove/Rename => Remove/Upsert depending on direction
     // notify 6 often delivers "Modify(Name(...))" for renames; we conservatively:
@@ -79,10 +187,15 @@ fn is_notify_watch_limit(error: &notify::Error) -> bool {
 fn log_permission_denied(mode: AgentMode, path: &Path, context: &str) {
     match mode {
         AgentMode::User => {
-            tracing::debug!(path = %path.display(), "{context} (permission denied)");
+            tracing::debug!(
+                event_type = "fs_permission_denied",
+                path = %path.display(),
+                "{context} (permission denied)"
+            );
         }
         AgentMode::Privileged => {
             tracing::warn!(
+                event_type = "fs_permission_denied",
                 path = %path.display(),
                 "{context} (permission denied; run with sudo or adjust permissions)"
             );
@@ -92,16 +205,59 @@ fn log_permission_denied(mode: AgentMode, path: &Path, context: &str) {
 
 fn log_watch_limit(path: &Path, context: &str) {
     tracing::warn!(
+        event_type = "fs_watch_limit",
         path = %path.display(),
         "{context} (watch limit reached; increase fs.inotify.max_user_watches)"
     );
 }
 
+/// Tuning for [`spawn`].
+#[derive(Debug, Clone, Copy)]
+pub struct FsWatchOptions {
+    /// Window over which events for the same path are merged into one batch.
+    pub coalesce: Duration,
+    pub batch_id_base: u64,
+    /// Inotify watches to hold at most; further directories are polled.
+    pub max_watches: usize,
+    pub poll_interval: Duration,
+    pub file_identity: FileIdentity,
+}
+
 #[derive(Default)]
 struct WatchStats {
     watched: usize,
     skipped_permission: usize,
     skipped_watch_limit: usize,
+    budget: usize,
+    /// Set once the budget is spent or the kernel refused a watch; no further
+    /// inotify watches are attempted after that.
+    exhausted: bool,
+    /// Subtrees handed to the polling fallback.
+    polled: Vec<PathBuf>,
+    /// Directories holding a watch, so a policy reload can drop them again.
+    dirs: HashSet<PathBuf>,
+}
+
+impl WatchStats {
+    fn with_budget(budget: usize) -> Self {
+        Self {
+            budget,
+            ..Self::default()
+        }
+    }
+
+    fn can_watch(&self) -> bool {
+        !self.exhausted && self.watched < self.budget
+    }
+
+    /// Hands `path` and everything below it to the poller. Returns true on the first
+    /// fallback so the caller logs the transition once.
+    fn fall_back_to_polling(&mut self, path: PathBuf) -> bool {
+        let first = !self.exhausted;
+        self.exhausted = true;
+        self.polled.push(path);
+        first
+    }
 }
 
 fn add_watch_recursive(
@@ -117,10 +273,24 @@ fn add_watch_recursive(
         if !policy.should_watch(&path) {
             continue;
         }
+        if !stats.can_watch() {
+            let budget = stats.budget;
+            if stats.fall_back_to_polling(path.clone()) {
+                tracing::warn!(
+                    event_type = "fs_watch_budget",
+                    path = %path.display(),
+                    budget,
+                    "FS watcher: watch budget reached; polling remaining directories"
+                );
+            }
+            continue;
+        }
 
         match watcher.watch(&path, RecursiveMode::NonRecursive) {
             Ok(()) => {
-                stats.watched += 1;
+                if stats.dirs.insert(path.clone()) {
+                    stats.watched += 1;
+                }
             }
             Err(err) if is_notify_permission_denied(&err) => {
                 stats.skipped_permission += 1;
@@ -129,7 +299,9 @@ fn add_watch_recursive(
             }
             Err(err) if is_notify_watch_limit(&err) => {
                 stats.skipped_watch_limit += 1;
-                log_watch_limit(&path, "skipping path");
+                if stats.fall_back_to_polling(path.clone()) {
+                    log_watch_limit(&path, "polling remaining directories");
+                }
                 continue;
             }
             Err(err) => return Err(err.into()),
@@ -178,18 +350,127 @@ fn add_watch_recursive(
     Ok(())
 }
 
+/// Policy shared by the notify callback, the poller and the coalescer; swapped on reload.
+type SharedPolicy = Arc<RwLock<PathPolicy>>;
+
+fn read_policy(policy: &SharedPolicy) -> RwLockReadGuard<'_, PathPolicy> {
+    policy.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Modification time and size per path, as seen by one polling scan.
+type ScanState = HashMap<String, (Option<SystemTime>, u64)>;
+
+/// Stats everything below `roots` that the policy allows, without following symlinks.
+fn scan_tree(roots: &[PathBuf], policy: &PathPolicy) -> ScanState {
+    let mut out = ScanState::new();
+    let mut stack = roots.to_vec();
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !policy.should_watch(&path) {
+                continue;
+            }
+            let Ok(meta) = std::fs::symlink_metadata(&path) else {
+                continue;
+            };
+            if meta.is_dir() {
+                stack.push(path.clone());
+            }
+            if let Some(s) = path.to_str() {
+                out.insert(s.to_string(), (meta.modified().ok(), meta.len()));
+            }
+        }
+    }
+    out
+}
+
+/// Changes between two scans, in the shape the notify callback produces.
+fn diff_scans(prev: &ScanState, next: &ScanState) -> Vec<(String, Action)> {
+    let mut out: Vec<(String, Action)> = next
+        .iter()
+        .filter(|(path, stat)| prev.get(*path) != Some(*stat))
+        .map(|(path, _)| (path.clone(), Action::Upsert))
+        .chain(
+            prev.keys()
+                .filter(|path| !next.contains_key(*path))
+                .map(|path| (path.clone(), Action::Remove)),
+        )
+        .collect();
+    out.sort_by(|a, b| a.0.cmp(&b.0));
+    out
+}
+
+/// Stat-based fallback for subtrees without inotify watches. The first scan only sets
+/// the baseline; later scans feed differences into the coalescer.
+fn spawn_poller(
+    roots: Vec<PathBuf>,
+    policy: SharedPolicy,
+    interval: Duration,
+    raw_tx: mpsc::Sender<(String, Action)>,
+) {
+    let root_count = roots.len();
+    let roots = Arc::new(roots);
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(interval);
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut prev: Option<ScanState> = None;
+        loop {
+            tick.tick().await;
+            let (roots, policy) = (Arc::clone(&roots), read_policy(&policy).clone());
+            let Ok(next) = tokio::task::spawn_blocking(move || scan_tree(&roots, &policy)).await
+            else {
+                return;
+            };
+            if let Some(prev) = prev.as_ref() {
+                let changes = diff_scans(prev, &next);
+                if !changes.is_empty() {
+                    tracing::debug!(
+                        event_type = "fs_poll",
+                        root_count,
+                        change_count = changes.len(),
+                        "polled directories changed"
+                    );
+                }
+                for change in changes {
+                    if raw_tx.send(change).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            prev = Some(next);
+        }
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn spawn(
     node_id: &str,
     mode: AgentMode,
-    policy: Arc<PathPolicy>,
+    policy: PathPolicy,
     roots: Vec<PathBuf>,
+    opts: FsWatchOptions,
+    metrics: Arc<Metrics>,
     tx: mpsc::Sender<Msg>,
+    reload: mpsc::UnboundedReceiver<PathPolicy>,
 ) -> Result<()> {
     let node_id = node_id.to_string();
+    let FsWatchOptions {
+        coalesce,
+        batch_id_base,
+        max_watches,
+        poll_interval,
+        file_identity,
+    } = opts;
 
     // notify callback thread -> tokio channel
     let (raw_tx, mut raw_rx) = tokio::sync::mpsc::channel::<(String, Action)>(8192);
+    let poll_tx = raw_tx.clone();
+    let reload_tx = raw_tx.clone();
 
+    let policy: SharedPolicy = Arc::new(RwLock::new(policy));
     let policy_for_events = Arc::clone(&policy);
     let mut watcher: RecommendedWatcher = Watcher::new(
         move |res: std::result::Result<notify::Event, notify::Error>| {
@@ -199,7 +480,7 @@ pub fn spawn(
                     None => return,
                 };
                 for p in event.paths {
-                    if !policy_for_events.should_watch(&p) {
+                    if !read_policy(&policy_for_events).should_watch(&p) {
                         continue;
                     }
                     if let Some(s) = p.to_str() {
@@ -212,41 +493,65 @@ pub fn spawn(
         notify::Config::default(),
     )?;
 
-    let mut stats = WatchStats::default();
+    let mut stats = WatchStats::with_budget(max_watches);
+    let initial = read_policy(&policy).clone();
     for path in roots {
         if path.exists() {
-            add_watch_recursive(&mut watcher, &path, &policy, mode, &mut stats)?;
+            add_watch_recursive(&mut watcher, &path, &initial, mode, &mut stats)?;
         }
     }
     tracing::info!(
+        event_type = "fs_watch_summary",
         watched_count = stats.watched,
-        skipped_permission = stats.skipped_permission,
-        skipped_watch_limit = stats.skipped_watch_limit,
+        skipped_permission_count = stats.skipped_permission,
+        skipped_watch_limit_count = stats.skipped_watch_limit,
+        polled_root_count = stats.polled.len(),
         "FS watcher: initial watch summary"
     );
+    let (m, relaxed) = (&metrics, Ordering::Relaxed);
+    m.fs_watched_paths.store(stats.watched as u64, relaxed);
+    m.fs_polled_roots.store(stats.polled.len() as u64, relaxed);
+    m.fs_skipped_permission
+        .fetch_add(stats.skipped_permission as u64, relaxed);
+    m.fs_skipped_watch_limit
+        .fetch_add(stats.skipped_watch_limit as u64, relaxed);
+    if !stats.polled.is_empty() {
+        spawn_poller(
+            std::mem::take(&mut stats.polled),
+            Arc::clone(&policy),
+            poll_interval,
+            poll_tx,
+        );
+    }
+
+    spawn_reloader(
+        watcher,
+        stats,
+        Arc::clone(&policy),
+        mode,
+        reload,
+        reload_tx,
+        Arc::clone(&metrics),
+    );
 
-    // Coalescer: 250ms window
+    // Coalescer: one batch per `coalesce` window
     tokio::spawn(async move {
         let mut pending: HashMap<String, Action> = HashMap::new();
-        let mut tick = tokio::time::interval(Duration::from_millis(250));
+        let mut tick = tokio::time::interval(coalesce);
         tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
 
-        let mut batch_id: u64 = 50_000;
+        let mut batch_id: u64 = batch_id_base;
         let mut last_log = Instant::now() - Duration::from_secs(1);
+        // Inode ids cannot be derived from a path that is already gone; remember them.
+        let mut ids_by_path: HashMap<String, NodeId> = HashMap::new();
 
         loop {
             tokio::select! {
                 Some((path, action)) = raw_rx.recv() => {
-                    // Coalesce rule:
-                    // - Remove dominates Upsert (if something is removed, keep Remove)
-                    // - otherwise latest Upsert wins
-                    pending.entry(path).and_modify(|a| {
-                        if *a != Action::Remove && action == Action::Remove {
-                            *a = Action::Remove;
-                        } else if action == Action::Upsert {
-                            *a = Action::Upsert;
-                        }
-                    }).or_insert(action);
+                    pending
+                        .entry(path)
+                        .and_modify(|a| *a = merge_action(*a, action))
+                        .or_insert(action);
                 }
                 _ = tick.tick() => {
                     if pending.is_empty() {
@@ -265,18 +570,51 @@ pub fn spawn(
 
                     let _ = tx.send(Msg::Event{ delta: Delta::BatchBegin{ id: batch_id }}).await;
 
-                    for (path, action) in pending.drain() {
-                        let id = id_file(&node_id, &path);
+                    let mut batch: Vec<(String, Action)> = pending.drain().collect();
+                    // Upserts first: a rename is remove(old) + upsert(new) of one inode,
+                    // and that node has to survive the remove.
+                    batch.sort_by_key(|(_, action)| *action == Action::Remove);
+                    let mut upserted: HashSet<NodeId> = HashSet::new();
+                    for (path, action) in batch {
                         match action {
                             Action::Upsert => {
+                                let id = file_identity.id(&node_id, &path);
+                                if file_identity.by_inode() {
+                                    ids_by_path.insert(path.clone(), id.clone());
+                                    upserted.insert(id.clone());
+                                }
+                                let link_target = symlink_target(&path);
                                 let node = Node::File {
                                     path: path.clone(),
                                     inode: inode_for_path(&path),
-                                    kind: FileKind::Unknown,
+                                    kind: if link_target.is_some() { FileKind::Symlink } else { FileKind::Unknown },
+                                    category: file_category(&path),
+                                    link_target: link_target.clone(),
                                 };
-                                let _ = tx.send(Msg::Event{ delta: Delta::UpsertNode{ id, node }}).await;
+                                let _ = tx.send(Msg::Event{ delta: Delta::UpsertNode{ id: id.clone(), node }}).await;
+
+                                // link -> target, when the target is something we'd watch anyway
+                                if let Some(target) = link_target.filter(|t| read_policy(&policy).should_watch(Path::new(t))) {
+                                    let target_id = file_identity.id(&node_id, &target);
+                                    let node = Node::File {
+                                        path: target.clone(),
+                                        inode: inode_for_path(&target),
+                                        kind: FileKind::Unknown,
+                                        category: file_category(&target),
+                                        link_target: None,
+                                    };
+                                    let _ = tx.send(Msg::Event{ delta: Delta::UpsertNode{ id: target_id.clone(), node }}).await;
+                                    let edge = Edge { from: id, to: target_id, kind: EdgeKind::PointsTo };
+                                    let _ = tx.send(Msg::Event{ delta: Delta::UpsertEdge{ edge }}).await;
+                                }
                             }
                             Action::Remove => {
+                                let id = ids_by_path
+                                    .remove(&path)
+                                    .unwrap_or_else(|| file_identity.id(&node_id, &path));
+                                if upserted.contains(&id) {
+                                    continue;
+                                }
                                 let _ = tx.send(Msg::Event{ delta: Delta::RemoveNode{ id }}).await;
                             }
                         }
@@ -285,11 +623,11 @@ pub fn spawn(
                     let _ = tx.send(Msg::Event{ delta: Delta::BatchEnd{ id: batch_id }}).await;
                     if last_log.elapsed() >= Duration::from_secs(1) {
                         tracing::debug!(
-                            event_type = "fs",
+                            event_type = "fs_batch",
                             batch_id,
-                            total,
-                            upserts,
-                            removes,
+                            event_count = total,
+                            upsert_count = upserts,
+                            remove_count = removes,
                             "broadcast batch"
                         );
                         last_log = Instant::now();
@@ -300,15 +638,216 @@ pub fn spawn(
         }
     });
 
-    // keep watcher alive
-    std::mem::forget(watcher);
     Ok(())
 }
 
+/// Owns the watcher and re-arms it for each policy received on `reload`: watches the
+/// new policy rejects are dropped, new roots are walked, and files that fell out of
+/// the policy are removed through the coalescer. The poller keeps its subtrees.
+fn spawn_reloader(
+    mut watcher: RecommendedWatcher,
+    mut stats: WatchStats,
+    policy: SharedPolicy,
+    mode: AgentMode,
+    mut reload: mpsc::UnboundedReceiver<PathPolicy>,
+    raw_tx: mpsc::Sender<(String, Action)>,
+    metrics: Arc<Metrics>,
+) {
+    tokio::spawn(async move {
+        while let Some(next) = reload.recv().await {
+            let prev = std::mem::replace(
+                &mut *policy.write().unwrap_or_else(PoisonError::into_inner),
+                next.clone(),
+            );
+            let diff = RootDiff::between(&prev, &next);
+
+            let dropped: Vec<PathBuf> = stats
+                .dirs
+                .iter()
+                .filter(|dir| !next.should_watch(dir))
+                .cloned()
+                .collect();
+            for dir in &dropped {
+                let _ = watcher.unwatch(dir);
+                stats.dirs.remove(dir);
+            }
+            stats.watched = stats.dirs.len();
+            for root in next.includes().iter().filter(|root| root.exists()) {
+                if let Err(err) = add_watch_recursive(&mut watcher, root, &next, mode, &mut stats) {
+                    tracing::warn!(path = %root.display(), error = %err, "FS watcher: re-arm failed");
+                }
+            }
+            metrics
+                .fs_watched_paths
+                .store(stats.watched as u64, Ordering::Relaxed);
+
+            // Old view minus new view, plus whatever the added roots hold.
+            let added = diff.added.clone();
+            let scan = tokio::task::spawn_blocking(move || {
+                let before = scan_tree(prev.includes(), &prev);
+                let mut changes: Vec<(String, Action)> = before
+                    .into_keys()
+                    .filter(|path| !next.should_watch(Path::new(path)))
+                    .map(|path| (path, Action::Remove))
+                    .collect();
+                changes.extend(
+                    scan_tree(&added, &next)
+                        .into_keys()
+                        .map(|path| (path, Action::Upsert)),
+                );
+                changes
+            })
+            .await;
+            let changes = scan.unwrap_or_default();
+
+            tracing::info!(
+                event_type = "fs_policy_reload",
+                added_roots = ?diff.added,
+                removed_roots = ?diff.removed,
+                dropped_watch_count = dropped.len(),
+                watched_count = stats.watched,
+                change_count = changes.len(),
+                "FS watcher: path policy reloaded"
+            );
+            for change in changes {
+                if raw_tx.send(change).await.is_err() {
+                    break;
+                }
+            }
+        }
+        // keep watcher alive once nobody can reload anymore
+        std::mem::forget(watcher);
+    });
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{is_permission_denied, is_watch_limit_error};
+    use super::{
+        category_from_path, diff_scans, is_permission_denied, is_watch_limit_error, merge_action,
+        Action, ScanState, WatchStats,
+    };
+    use spacegraph_core::FileCategory;
     use std::io;
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn category_uses_extension_before_location() {
+        assert_eq!(
+            category_from_path("/var/log/syslog", false),
+            FileCategory::Log
+        );
+        assert_eq!(category_from_path("/tmp/app.LOG", false), FileCategory::Log);
+        assert_eq!(
+            category_from_path("/etc/init.d/x.sh", true),
+            FileCategory::Script
+        );
+        assert_eq!(
+            category_from_path("/etc/passwd", false),
+            FileCategory::Config
+        );
+        assert_eq!(
+            category_from_path("/home/u/app.toml", false),
+            FileCategory::Config
+        );
+        assert_eq!(
+            category_from_path("/usr/lib/libc.so.6", false),
+            FileCategory::Binary
+        );
+        assert_eq!(
+            category_from_path("/usr/bin/ls", true),
+            FileCategory::Binary
+        );
+        assert_eq!(
+            category_from_path("/srv/data.csv", false),
+            FileCategory::Data
+        );
+        assert_eq!(
+            category_from_path(r"C:\Windows\notepad.exe", false),
+            FileCategory::Binary
+        );
+        assert_eq!(
+            category_from_path("/srv/README", false),
+            FileCategory::Unknown
+        );
+    }
+
+    #[test]
+    fn remove_overrides_pending_upsert_until_recreated() {
+        assert_eq!(merge_action(Action::Upsert, Action::Remove), Action::Remove);
+        assert_eq!(merge_action(Action::Remove, Action::Remove), Action::Remove);
+        assert_eq!(merge_action(Action::Upsert, Action::Upsert), Action::Upsert);
+        assert_eq!(merge_action(Action::Remove, Action::Upsert), Action::Upsert);
+    }
+
+    #[test]
+    fn watch_budget_switches_to_polling_once() {
+        let mut stats = WatchStats::with_budget(2);
+        assert!(stats.can_watch());
+        stats.watched += 2;
+        assert!(!stats.can_watch());
+        assert!(stats.fall_back_to_polling(PathBuf::from("/srv/a")));
+        assert!(!stats.fall_back_to_polling(PathBuf::from("/srv/b")));
+        assert_eq!(
+            stats.polled,
+            vec![PathBuf::from("/srv/a"), PathBuf::from("/srv/b")]
+        );
+
+        // A kernel refusal below the budget stops further watch attempts too.
+        let mut stats = WatchStats::with_budget(100);
+        stats.watched += 1;
+        assert!(stats.fall_back_to_polling(PathBuf::from("/var")));
+        assert!(!stats.can_watch());
+
+        assert!(!WatchStats::with_budget(0).can_watch());
+    }
+
+    #[test]
+    fn scan_diff_reports_new_changed_and_missing_paths() {
+        let t0 = SystemTime::UNIX_EPOCH;
+        let t1 = t0 + Duration::from_secs(1);
+        let prev: ScanState = [
+            ("/a".to_string(), (Some(t0), 1)),
+            ("/b".to_string(), (Some(t0), 1)),
+            ("/c".to_string(), (Some(t0), 1)),
+        ]
+        .into();
+        let next: ScanState = [
+            ("/a".to_string(), (Some(t0), 1)),
+            ("/b".to_string(), (Some(t1), 1)),
+            ("/d".to_string(), (Some(t1), 4)),
+        ]
+        .into();
+        assert_eq!(
+            diff_scans(&prev, &next),
+            vec![
+                ("/b".to_string(), Action::Upsert),
+                ("/c".to_string(), Action::Remove),
+                ("/d".to_string(), Action::Upsert),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn link_targets_resolve_against_link_directory() {
+        use super::resolve_link_target;
+        use std::path::Path;
+
+        let link = Path::new("/etc/nginx/sites-enabled/default");
+        assert_eq!(
+            resolve_link_target(link, Path::new("../sites-available/default")),
+            "/etc/nginx/sites-available/default"
+        );
+        assert_eq!(
+            resolve_link_target(link, Path::new("/srv/site.conf")),
+            "/srv/site.conf"
+        );
+        assert_eq!(
+            resolve_link_target(Path::new("/etc/localtime"), Path::new("./zone")),
+            "/etc/zone"
+        );
+    }
 
     #[test]
     fn permission_denied_is_detected() {
@@ -1,15 +1,33 @@
 use anyhow::Result;
 use procfs::process::Process;
 use spacegraph_core::{
-    id_file, id_process, id_user, Delta, Edge, EdgeKind, FileKind, Msg, Node, NodeId,
+    id_container, id_process, id_user, Delta, Edge, EdgeKind, FileKind, Msg, Node, NodeId,
 };
+use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::future::Future;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
+use crate::cgroup::container_for_pid;
+use crate::file_id::FileIdentity;
+use crate::metrics::Metrics;
+use crate::snapshot::cap_fd_entries;
+use crate::threads::thread_detail;
+use crate::users::UserNames;
+use crate::watch_fs::file_category;
+
 type ProcDetail = (Vec<(NodeId, Node)>, Vec<Edge>);
 
+/// Re-read `/proc/<pid>/fd` for known processes every this many ticks.
+const FD_DIFF_EVERY_TICKS: u64 = 8;
+/// Upper bound on processes rescanned per fd diff pass.
+const FD_DIFF_MAX_PIDS: usize = 32;
+/// Give up on a single pid's `/proc` detail after this long and send the fallback node.
+const DETAIL_TIMEOUT: Duration = Duration::from_secs(2);
+
 fn list_pids() -> HashSet<i32> {
     let mut set = HashSet::new();
     if let Ok(rd) = std::fs::read_dir("/proc") {
@@ -22,6 +40,16 @@ fn list_pids() -> HashSet<i32> {
     set
 }
 
+/// Id for the process currently holding `pid`; falls back to the bare pid when
+/// `/proc/<pid>/stat` is already gone.
+fn current_process_id(node_id: &str, pid: i32) -> NodeId {
+    let start = Process::new(pid)
+        .and_then(|p| p.stat())
+        .ok()
+        .map(|s| s.starttime);
+    id_process(node_id, pid, start)
+}
+
 fn parse_passwd() -> HashMap<u32, String> {
     let mut map = HashMap::new();
     let content = fs::read_to_string("/etc/passwd").unwrap_or_default();
@@ -51,6 +79,13 @@ fn file_kind_from_path(p: &str) -> FileKind {
     }
 }
 
+fn rss_kb_for(pr: &Process) -> u64 {
+    // statm reports pages; convert to KiB for display.
+    pr.statm()
+        .map(|m| m.resident * procfs::page_size() / 1024)
+        .unwrap_or(0)
+}
+
 fn inode_for_path(path: &str) -> u64 {
     fs::metadata(path)
         .map(|m| {
@@ -94,9 +129,12 @@ fn fd_flags(pid: i32, fd: i32) -> Option<i64> {
     None
 }
 
+#[allow(clippy::too_many_arguments)]
 fn add_fd_edges(
     node_id: &str,
     pid: i32,
+    max_fd_edges: Option<usize>,
+    file_identity: FileIdentity,
     proc_id: &NodeId,
     nodes: &mut Vec<(NodeId, Node)>,
     edges: &mut Vec<Edge>,
@@ -108,6 +146,7 @@ fn add_fd_edges(
         Err(_) => return,
     };
 
+    let mut fds: Vec<(i32, String)> = Vec::new();
     for ent in entries.flatten() {
         let name = ent.file_name();
         let fd: i32 = match name.to_string_lossy().parse() {
@@ -119,8 +158,20 @@ fn add_fd_edges(
             Ok(t) => t.to_string_lossy().to_string(),
             Err(_) => continue,
         };
+        fds.push((fd, target));
+    }
 
-        let f_id = id_file(node_id, &target);
+    let (fds, dropped) = cap_fd_entries(fds, max_fd_edges);
+    if dropped > 0 {
+        tracing::debug!(
+            event_type = "proc_fd_cap",
+            pid,
+            dropped_count = dropped,
+            "fd edges capped"
+        );
+    }
+    for (fd, target) in fds {
+        let f_id = file_identity.id(node_id, &target);
         if seen_nodes.insert(f_id.clone()) {
             nodes.push((
                 f_id.clone(),
@@ -128,6 +179,8 @@ fn add_fd_edges(
                     path: target.clone(),
                     inode: inode_for_path(&target),
                     kind: file_kind_from_path(&target),
+                    category: file_category(&target),
+                    link_target: None,
                 },
             ));
         }
@@ -143,9 +196,88 @@ fn add_fd_edges(
     }
 }
 
+fn is_opens(edge: &Edge) -> bool {
+    matches!(edge.kind, EdgeKind::Opens { .. })
+}
+
+fn diff_fd_edges(old: &HashSet<Edge>, new: &HashSet<Edge>) -> (Vec<Edge>, Vec<Edge>) {
+    let added = new.difference(old).cloned().collect();
+    let removed = old.difference(new).cloned().collect();
+    (added, removed)
+}
+
+/// An fd that points at the same file as before but was reopened with another mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FdTransition {
+    old: Edge,
+    new: Edge,
+}
+
+fn opens_fd(edge: &Edge) -> Option<i32> {
+    match edge.kind {
+        EdgeKind::Opens { fd, .. } => Some(fd),
+        _ => None,
+    }
+}
+
+/// Moves the fds that kept their target but changed mode (`r` -> `rw`) out of one
+/// pid's `added`/`removed` diff, so they are not mistaken for a close and an open.
+fn take_fd_transitions(added: &mut Vec<Edge>, removed: &mut Vec<Edge>) -> Vec<FdTransition> {
+    let mut closed: HashMap<i32, Edge> = HashMap::with_capacity(removed.len());
+    let mut kept_removed = Vec::with_capacity(removed.len());
+    for edge in removed.drain(..) {
+        match opens_fd(&edge) {
+            Some(fd) => {
+                if let Some(other) = closed.insert(fd, edge) {
+                    kept_removed.push(other);
+                }
+            }
+            None => kept_removed.push(edge),
+        }
+    }
+
+    let mut transitions = Vec::new();
+    added.retain(|edge| {
+        let Some(fd) = opens_fd(edge) else {
+            return true;
+        };
+        if let Entry::Occupied(slot) = closed.entry(fd) {
+            if slot.get().to == edge.to {
+                transitions.push(FdTransition {
+                    old: slot.remove(),
+                    new: edge.clone(),
+                });
+                return false;
+            }
+        }
+        true
+    });
+    kept_removed.extend(closed.into_values());
+    *removed = kept_removed;
+    transitions.sort_by_key(|t| opens_fd(&t.new));
+    transitions
+}
+
+/// Pick up to `FD_DIFF_MAX_PIDS` cached pids after `cursor`, wrapping around.
+fn fd_diff_round(cached: &HashMap<i32, HashSet<Edge>>, cursor: i32) -> Vec<i32> {
+    let mut pids: Vec<i32> = cached.keys().copied().collect();
+    pids.sort_unstable();
+    let start = pids.partition_point(|&p| p <= cursor);
+    pids.iter()
+        .cycle()
+        .skip(start)
+        .take(pids.len().min(FD_DIFF_MAX_PIDS))
+        .copied()
+        .collect()
+}
+
 fn collect_process_detail(
     node_id: &str,
-    passwd: &HashMap<u32, String>,
+    users: &mut UserNames,
+    proc_ids: &HashMap<i32, NodeId>,
+    max_fd_edges: Option<usize>,
+    file_identity: FileIdentity,
+    threads: bool,
     pid: i32,
 ) -> Option<ProcDetail> {
     let pr = Process::new(pid).ok()?;
@@ -167,7 +299,11 @@ fn collect_process_detail(
         .map(|v| v.join(" "))
         .unwrap_or_else(|| stat.comm.clone());
 
-    let proc_id = id_process(node_id, pid);
+    let rss_kb = rss_kb_for(&pr);
+    let utime_ticks = stat.utime;
+    let container = container_for_pid(pid);
+
+    let proc_id = id_process(node_id, pid, Some(stat.starttime));
 
     let mut nodes: Vec<(NodeId, Node)> = Vec::new();
     let mut edges: Vec<Edge> = Vec::new();
@@ -182,15 +318,34 @@ fn collect_process_detail(
             exe: exe.clone(),
             cmdline,
             uid,
+            rss_kb,
+            utime_ticks,
+            container: container.as_ref().map(|c| c.id.clone()),
         },
     ));
     seen_nodes.insert(proc_id.clone());
 
+    // container node + membership edge
+    if let Some(c) = container {
+        let container_id = id_container(node_id, &c.id);
+        if seen_nodes.insert(container_id.clone()) {
+            nodes.push((
+                container_id.clone(),
+                Node::Container {
+                    id: c.id,
+                    name: c.name,
+                },
+            ));
+        }
+        edges.push(Edge {
+            from: proc_id.clone(),
+            to: container_id,
+            kind: EdgeKind::MemberOf,
+        });
+    }
+
     // user node + edge
-    let uname = passwd
-        .get(&uid)
-        .cloned()
-        .unwrap_or_else(|| format!("uid{uid}"));
+    let uname = users.name(uid);
     let user_id = id_user(node_id, uid);
     if seen_nodes.insert(user_id.clone()) {
         nodes.push((user_id.clone(), Node::User { uid, name: uname }));
@@ -201,8 +356,17 @@ fn collect_process_detail(
         kind: EdgeKind::RunsAs,
     });
 
+    // parent process edge (only when the parent is part of the current scan)
+    if let Some(parent_id) = proc_ids.get(&ppid).filter(|_| ppid > 0) {
+        edges.push(Edge {
+            from: parent_id.clone(),
+            to: proc_id.clone(),
+            kind: EdgeKind::ParentOf,
+        });
+    }
+
     // exe as file + edge
-    let exe_file_id = id_file(node_id, &exe);
+    let exe_file_id = file_identity.id(node_id, &exe);
     if seen_nodes.insert(exe_file_id.clone()) {
         nodes.push((
             exe_file_id.clone(),
@@ -210,6 +374,8 @@ fn collect_process_detail(
                 path: exe.clone(),
                 inode: inode_for_path(&exe),
                 kind: file_kind_from_path(&exe),
+                category: file_category(&exe),
+                link_target: None,
             },
         ));
     }
@@ -223,125 +389,652 @@ fn collect_process_detail(
     add_fd_edges(
         node_id,
         pid,
+        max_fd_edges,
+        file_identity,
         &proc_id,
         &mut nodes,
         &mut edges,
         &mut seen_nodes,
     );
 
+    if threads {
+        let (thread_nodes, thread_edges) = thread_detail(node_id, pid, &proc_id);
+        nodes.extend(thread_nodes);
+        edges.extend(thread_edges);
+    }
+
     Some((nodes, edges))
 }
 
-pub fn spawn(node_id: &str, tx: mpsc::Sender<Msg>) -> Result<()> {
+/// Minimal node for a pid whose detail could not be read (vanished or stalled).
+fn fallback_process_node(pid: i32) -> Node {
+    Node::Process {
+        pid,
+        ppid: 0,
+        exe: "<unknown>".into(),
+        cmdline: "<new>".into(),
+        uid: 0,
+        rss_kb: 0,
+        utime_ticks: 0,
+        container: None,
+    }
+}
+
+/// Upsert for a new pid whose `/proc` detail vanished quickly or stalled: the minimal
+/// node, under the id the poll loop already tracks for the pid.
+fn fallback_upsert(node_id: &str, pid: i32, proc_ids: &HashMap<i32, NodeId>) -> Delta {
+    let id = proc_ids
+        .get(&pid)
+        .cloned()
+        .unwrap_or_else(|| id_process(node_id, pid, None));
+    Delta::UpsertNode {
+        id,
+        node: fallback_process_node(pid),
+    }
+}
+
+/// What an fd round rereads for a known pid: its threads (with `--threads`) and fds.
+struct FdRoundDetail {
+    threads: Option<ProcDetail>,
+    fds: ProcDetail,
+}
+
+fn collect_fd_round_detail(
+    node_id: &str,
+    pid: i32,
+    proc_id: &NodeId,
+    max_fd_edges: Option<usize>,
+    file_identity: FileIdentity,
+    threads: bool,
+) -> FdRoundDetail {
+    let threads = threads.then(|| thread_detail(node_id, pid, proc_id));
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    let mut seen_nodes = HashSet::new();
+    add_fd_edges(
+        node_id,
+        pid,
+        max_fd_edges,
+        file_identity,
+        proc_id,
+        &mut nodes,
+        &mut edges,
+        &mut seen_nodes,
+    );
+    FdRoundDetail {
+        threads,
+        fds: (nodes, edges),
+    }
+}
+
+/// Awaits `collect` for at most `limit`, so one hanging `/proc` entry cannot stall the
+/// poll loop. A timeout is logged and reported like a vanished pid.
+async fn detail_within<T>(
+    pid: i32,
+    limit: Duration,
+    collect: impl Future<Output = Option<T>>,
+) -> Option<T> {
+    match tokio::time::timeout(limit, collect).await {
+        Ok(detail) => detail,
+        Err(_) => {
+            tracing::warn!(
+                event_type = "proc_detail_timeout",
+                pid,
+                timeout_ms = limit.as_millis() as u64,
+                "process detail timed out"
+            );
+            None
+        }
+    }
+}
+
+/// Wraps one poll's deltas in `BatchBegin`/`BatchEnd`; a poll without changes sends
+/// nothing, so it neither uses up a batch id nor fills the resume buffer.
+fn batch_msgs(id: u64, deltas: Vec<Delta>) -> Vec<Msg> {
+    if deltas.is_empty() {
+        return Vec::new();
+    }
+    let mut msgs = Vec::with_capacity(deltas.len() + 2);
+    msgs.push(Msg::Event {
+        delta: Delta::BatchBegin { id },
+    });
+    msgs.extend(deltas.into_iter().map(|delta| Msg::Event { delta }));
+    msgs.push(Msg::Event {
+        delta: Delta::BatchEnd { id },
+    });
+    msgs
+}
+
+/// Per-pid caches taken from the startup snapshot, so processes that were already
+/// running get fd diffs and thread cleanup like the ones started later.
+#[derive(Debug, Default)]
+pub struct SnapshotSeed {
+    procs: HashMap<i32, NodeId>,
+    opens: HashMap<i32, HashSet<Edge>>,
+    threads: HashMap<i32, HashSet<NodeId>>,
+}
+
+impl SnapshotSeed {
+    pub fn new(nodes: &[(NodeId, Node)], edges: &[Edge]) -> Self {
+        let mut procs = HashMap::new();
+        let mut threads: HashMap<i32, HashSet<NodeId>> = HashMap::new();
+        for (id, node) in nodes {
+            match node {
+                Node::Process { pid, .. } => {
+                    procs.insert(*pid, id.clone());
+                }
+                Node::Thread { pid, .. } => {
+                    threads.entry(*pid).or_default().insert(id.clone());
+                }
+                _ => {}
+            }
+        }
+        // every process takes part in fd rounds, also the ones without open files
+        let mut opens: HashMap<i32, HashSet<Edge>> =
+            procs.keys().map(|pid| (*pid, HashSet::new())).collect();
+        let pids: HashMap<&NodeId, i32> = procs.iter().map(|(pid, id)| (id, *pid)).collect();
+        for edge in edges.iter().filter(|e| is_opens(e)) {
+            if let Some(pid) = pids.get(&edge.from) {
+                opens.entry(*pid).or_default().insert(edge.clone());
+            }
+        }
+        Self {
+            procs,
+            opens,
+            threads,
+        }
+    }
+
+    /// The fd and thread caches for the pids that still run the snapshot's process.
+    fn into_caches(
+        self,
+        proc_ids: &HashMap<i32, NodeId>,
+    ) -> (HashMap<i32, HashSet<Edge>>, HashMap<i32, HashSet<NodeId>>) {
+        let Self {
+            procs,
+            opens,
+            threads,
+        } = self;
+        let current = |pid: &i32| {
+            procs
+                .get(pid)
+                .is_some_and(|id| proc_ids.get(pid) == Some(id))
+        };
+        (
+            opens.into_iter().filter(|(pid, _)| current(pid)).collect(),
+            threads
+                .into_iter()
+                .filter(|(pid, _)| current(pid))
+                .collect(),
+        )
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn spawn(
+    node_id: &str,
+    max_fd_edges: Option<usize>,
+    file_identity: FileIdentity,
+    threads: bool,
+    poll: Duration,
+    passwd_refresh: Duration,
+    metrics: Arc<Metrics>,
+    seed: SnapshotSeed,
+    tx: mpsc::Sender<Msg>,
+) -> Result<()> {
     let node_id = node_id.to_string();
 
     tokio::spawn(async move {
         let mut prev = list_pids();
+        let mut proc_ids: HashMap<i32, NodeId> = prev
+            .iter()
+            .map(|&pid| (pid, current_process_id(&node_id, pid)))
+            .collect();
         let mut batch_id: u64 = 1;
-        let mut passwd = parse_passwd();
+        let mut users = UserNames::new(parse_passwd());
+        let mut last_passwd = Instant::now();
         let mut last_log = Instant::now() - Duration::from_secs(1);
+        let mut tick: u64 = 0;
+        // Thread ids per pid, so they can be removed with their process.
+        let (mut fd_cache, mut thread_cache) = seed.into_caches(&proc_ids);
+        let mut fd_cursor: i32 = 0;
 
         loop {
-            tokio::time::sleep(Duration::from_millis(750)).await;
+            tokio::time::sleep(poll).await;
+            let poll_started = Instant::now();
 
             // refresh passwd occasionally (cheap, keeps usernames accurate)
-            if batch_id.is_multiple_of(80) {
-                passwd = parse_passwd();
+            if last_passwd.elapsed() >= passwd_refresh {
+                users.set_passwd(parse_passwd());
+                last_passwd = Instant::now();
             }
 
+            tick = tick.wrapping_add(1);
             let cur = list_pids();
 
-            let new_pids: Vec<i32> = cur.difference(&prev).copied().collect();
-            let gone_pids: Vec<i32> = prev.difference(&cur).copied().collect();
+            let mut new_pids: Vec<i32> = cur.difference(&prev).copied().collect();
+            let mut gone_threads: Vec<NodeId> = Vec::new();
+            let mut gone_ids: Vec<NodeId> = prev
+                .difference(&cur)
+                .map(|pid| {
+                    fd_cache.remove(pid);
+                    gone_threads.extend(thread_cache.remove(pid).into_iter().flatten());
+                    proc_ids
+                        .remove(pid)
+                        .unwrap_or_else(|| id_process(&node_id, *pid, None))
+                })
+                .collect();
+
+            let mut fd_round = if tick.is_multiple_of(FD_DIFF_EVERY_TICKS) {
+                fd_diff_round(&fd_cache, fd_cursor)
+            } else {
+                Vec::new()
+            };
+            // A pid reused between two polls never shows up in the set diff; a changed
+            // start time gives it away, so swap the old process for the new one.
+            fd_round.retain(|&pid| {
+                let fresh = current_process_id(&node_id, pid);
+                match proc_ids.get(&pid) {
+                    Some(old) if *old != fresh => {
+                        fd_cache.remove(&pid);
+                        gone_threads.extend(thread_cache.remove(&pid).into_iter().flatten());
+                        gone_ids.extend(proc_ids.remove(&pid));
+                        new_pids.push(pid);
+                        false
+                    }
+                    _ => true,
+                }
+            });
+            for &pid in &new_pids {
+                proc_ids.insert(pid, current_process_id(&node_id, pid));
+            }
             let new_pids_count = new_pids.len();
-            let gone_pids_count = gone_pids.len();
+            let gone_pids_count = gone_ids.len();
 
-            if new_pids.is_empty() && gone_pids.is_empty() {
+            if new_pids.is_empty() && gone_ids.is_empty() && fd_round.is_empty() {
                 prev = cur;
                 continue;
             }
 
-            // IMPORTANT: BatchBegin first
-            let _ = tx
-                .send(Msg::Event {
-                    delta: Delta::BatchBegin { id: batch_id },
-                })
-                .await;
-
+            // Collected first so a round without changes sends no empty batch.
+            let mut deltas: Vec<Delta> = Vec::new();
             let mut nodes_sent = 0usize;
             let mut edges_sent = 0usize;
 
-            // handle new pids with detail refresh
+            // handle new pids with detail refresh; collection runs on the blocking pool
+            // so a stalled pid only costs DETAIL_TIMEOUT
+            let known_ids = Arc::new(proc_ids.clone());
             for pid in new_pids {
-                if let Some((nodes, edges)) = collect_process_detail(&node_id, &passwd, pid) {
+                let task_node_id = node_id.clone();
+                let task_ids = Arc::clone(&known_ids);
+                let mut task_users = std::mem::take(&mut users);
+                let collect = async move {
+                    tokio::task::spawn_blocking(move || {
+                        let detail = collect_process_detail(
+                            &task_node_id,
+                            &mut task_users,
+                            &task_ids,
+                            max_fd_edges,
+                            file_identity,
+                            threads,
+                            pid,
+                        );
+                        (task_users, detail)
+                    })
+                    .await
+                    .ok()
+                };
+                let detail = match detail_within(pid, DETAIL_TIMEOUT, collect).await {
+                    Some((task_users, detail)) => {
+                        users = task_users;
+                        detail
+                    }
+                    None => {
+                        // the stalled task keeps the name cache; start a fresh one
+                        users = UserNames::new(parse_passwd());
+                        None
+                    }
+                };
+                if let Some((nodes, edges)) = detail {
                     nodes_sent += nodes.len();
                     edges_sent += edges.len();
+                    let opens: HashSet<Edge> =
+                        edges.iter().filter(|e| is_opens(e)).cloned().collect();
+                    fd_cache.insert(pid, opens);
+                    if threads {
+                        let tids = nodes
+                            .iter()
+                            .filter(|(_, node)| matches!(node, Node::Thread { .. }))
+                            .map(|(id, _)| id.clone());
+                        thread_cache.insert(pid, tids.collect());
+                    }
                     for (id, node) in nodes {
-                        let _ = tx
-                            .send(Msg::Event {
-                                delta: Delta::UpsertNode { id, node },
-                            })
-                            .await;
+                        deltas.push(Delta::UpsertNode { id, node });
                     }
                     for edge in edges {
-                        let _ = tx
-                            .send(Msg::Event {
-                                delta: Delta::UpsertEdge { edge },
-                            })
-                            .await;
+                        deltas.push(Delta::UpsertEdge { edge });
                     }
                 } else {
-                    // fallback minimal node if /proc vanished quickly
-                    let id = id_process(&node_id, pid);
-                    let node = Node::Process {
-                        pid,
-                        ppid: 0,
-                        exe: "<unknown>".into(),
-                        cmdline: "<new>".into(),
-                        uid: 0,
-                    };
                     nodes_sent += 1;
-                    let _ = tx
-                        .send(Msg::Event {
-                            delta: Delta::UpsertNode { id, node },
-                        })
-                        .await;
+                    deltas.push(fallback_upsert(&node_id, pid, &proc_ids));
                 }
             }
 
-            // handle gone pids
-            for pid in gone_pids {
-                let id: NodeId = id_process(&node_id, pid);
-                let _ = tx
-                    .send(Msg::Event {
-                        delta: Delta::RemoveNode { id },
+            // handle gone pids, threads first
+            for id in gone_threads.into_iter().chain(gone_ids) {
+                deltas.push(Delta::RemoveNode { id });
+            }
+
+            // periodic fd diff for already-known pids
+            let mut fd_changes = 0usize;
+            for pid in fd_round {
+                fd_cursor = pid;
+                let Some(proc_id) = proc_ids.get(&pid).cloned() else {
+                    continue;
+                };
+                // same blocking pool and timeout as new pids; a stalled pid waits for
+                // the next round
+                let task_node_id = node_id.clone();
+                let collect = async move {
+                    tokio::task::spawn_blocking(move || {
+                        collect_fd_round_detail(
+                            &task_node_id,
+                            pid,
+                            &proc_id,
+                            max_fd_edges,
+                            file_identity,
+                            threads,
+                        )
                     })
-                    .await;
+                    .await
+                    .ok()
+                };
+                let Some(detail) = detail_within(pid, DETAIL_TIMEOUT, collect).await else {
+                    continue;
+                };
+                if let Some((nodes, edges)) = detail.threads {
+                    let fresh: HashSet<NodeId> = nodes.iter().map(|(id, _)| id.clone()).collect();
+                    let cached = thread_cache.entry(pid).or_default();
+                    for id in cached.difference(&fresh) {
+                        deltas.push(Delta::RemoveNode { id: id.clone() });
+                    }
+                    for (id, node) in nodes.into_iter().filter(|(id, _)| !cached.contains(id)) {
+                        nodes_sent += 1;
+                        deltas.push(Delta::UpsertNode { id, node });
+                    }
+                    for edge in edges.into_iter().filter(|e| !cached.contains(&e.from)) {
+                        edges_sent += 1;
+                        deltas.push(Delta::UpsertEdge { edge });
+                    }
+                    *cached = fresh;
+                }
+                let (nodes, edges) = detail.fds;
+                let fresh: HashSet<Edge> = edges.into_iter().collect();
+                let Some(cached) = fd_cache.get_mut(&pid) else {
+                    continue;
+                };
+                let (mut added, mut removed) = diff_fd_edges(cached, &fresh);
+                if added.is_empty() && removed.is_empty() {
+                    continue;
+                }
+                fd_changes += added.len() + removed.len();
+                // mode changes go out as adjacent remove/upsert pairs so the viewer
+                // can tell them from unrelated opens
+                let transitions = take_fd_transitions(&mut added, &mut removed);
+                if !transitions.is_empty() {
+                    tracing::debug!(
+                        event_type = "proc_fd_mode_change",
+                        pid,
+                        transition_count = transitions.len(),
+                        "fd reopened with another mode"
+                    );
+                }
+
+                // only upsert file nodes that newly added edges point at
+                let targets: HashSet<&NodeId> = added.iter().map(|e| &e.to).collect();
+                for (id, node) in nodes {
+                    if targets.contains(&id) {
+                        nodes_sent += 1;
+                        deltas.push(Delta::UpsertNode { id, node });
+                    }
+                }
+                for edge in removed {
+                    deltas.push(Delta::RemoveEdge { edge });
+                }
+                for FdTransition { old, new } in transitions {
+                    edges_sent += 1;
+                    deltas.push(Delta::RemoveEdge { edge: old });
+                    deltas.push(Delta::UpsertEdge { edge: new });
+                }
+                for edge in added {
+                    edges_sent += 1;
+                    deltas.push(Delta::UpsertEdge { edge });
+                }
+                *cached = fresh;
             }
 
-            // IMPORTANT: BatchEnd last
-            let _ = tx
-                .send(Msg::Event {
-                    delta: Delta::BatchEnd { id: batch_id },
-                })
-                .await;
+            metrics.record_proc_poll(poll_started.elapsed());
+            prev = cur;
+            let msgs = batch_msgs(batch_id, deltas);
+            if msgs.is_empty() {
+                continue;
+            }
+            for msg in msgs {
+                let _ = tx.send(msg).await;
+            }
 
             if last_log.elapsed() >= Duration::from_secs(1) {
                 tracing::debug!(
-                    event_type = "proc",
+                    event_type = "proc_batch",
                     batch_id,
-                    new_pids = new_pids_count,
-                    removed_pids = gone_pids_count,
-                    nodes_sent,
-                    edges_sent,
+                    new_pid_count = new_pids_count,
+                    removed_pid_count = gone_pids_count,
+                    fd_change_count = fd_changes,
+                    node_count = nodes_sent,
+                    edge_count = edges_sent,
                     "broadcast batch"
                 );
                 last_log = Instant::now();
             }
             batch_id = batch_id.wrapping_add(1);
-            prev = cur;
         }
     });
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::{id_file, id_thread};
+
+    fn opens(fd: i32, path: &str) -> Edge {
+        Edge {
+            from: id_process("n", 1, Some(5)),
+            to: id_file("n", path),
+            kind: EdgeKind::Opens {
+                fd,
+                mode: "r".into(),
+            },
+        }
+    }
+
+    #[test]
+    fn diff_fd_edges_reports_only_changes() {
+        let old: HashSet<Edge> = [opens(0, "/dev/null"), opens(3, "/tmp/a")].into();
+        let new: HashSet<Edge> = [opens(0, "/dev/null"), opens(4, "/tmp/b")].into();
+
+        let (added, removed) = diff_fd_edges(&old, &new);
+        assert_eq!(added, vec![opens(4, "/tmp/b")]);
+        assert_eq!(removed, vec![opens(3, "/tmp/a")]);
+
+        let (added, removed) = diff_fd_edges(&new, &new);
+        assert!(added.is_empty());
+        assert!(removed.is_empty());
+    }
+
+    #[test]
+    fn fd_diff_round_wraps_after_cursor() {
+        let cached: HashMap<i32, HashSet<Edge>> = [
+            (10, HashSet::new()),
+            (20, HashSet::new()),
+            (30, HashSet::new()),
+        ]
+        .into();
+        assert_eq!(fd_diff_round(&cached, 20), vec![30, 10, 20]);
+        assert_eq!(fd_diff_round(&cached, 0), vec![10, 20, 30]);
+    }
+
+    fn opens_mode(fd: i32, path: &str, mode: &str) -> Edge {
+        Edge {
+            kind: EdgeKind::Opens {
+                fd,
+                mode: mode.into(),
+            },
+            ..opens(fd, path)
+        }
+    }
+
+    #[test]
+    fn fd_transitions_pair_only_same_target_mode_changes() {
+        let old: HashSet<Edge> =
+            [opens_mode(3, "/tmp/a", "r"), opens_mode(4, "/tmp/b", "r")].into();
+        // fd 3 reopened rw on the same file, fd 4 now points elsewhere
+        let new: HashSet<Edge> =
+            [opens_mode(3, "/tmp/a", "rw"), opens_mode(4, "/tmp/c", "w")].into();
+        let (mut added, mut removed) = diff_fd_edges(&old, &new);
+
+        let transitions = take_fd_transitions(&mut added, &mut removed);
+
+        assert_eq!(
+            transitions,
+            vec![FdTransition {
+                old: opens_mode(3, "/tmp/a", "r"),
+                new: opens_mode(3, "/tmp/a", "rw"),
+            }]
+        );
+        assert_eq!(added, vec![opens_mode(4, "/tmp/c", "w")]);
+        assert_eq!(removed, vec![opens_mode(4, "/tmp/b", "r")]);
+    }
+
+    #[test]
+    fn fd_transitions_leave_plain_opens_and_closes_alone() {
+        let old: HashSet<Edge> = [opens_mode(3, "/tmp/a", "r")].into();
+        let new: HashSet<Edge> = [opens_mode(5, "/tmp/a", "rw")].into();
+        let (mut added, mut removed) = diff_fd_edges(&old, &new);
+
+        assert!(take_fd_transitions(&mut added, &mut removed).is_empty());
+        assert_eq!(added, vec![opens_mode(5, "/tmp/a", "rw")]);
+        assert_eq!(removed, vec![opens_mode(3, "/tmp/a", "r")]);
+    }
+
+    fn process(pid: i32) -> Node {
+        Node::Process {
+            pid,
+            ppid: 1,
+            exe: "/usr/sbin/sshd".into(),
+            cmdline: "sshd".into(),
+            uid: 0,
+            rss_kb: 0,
+            utime_ticks: 0,
+            container: None,
+        }
+    }
+
+    #[test]
+    fn snapshot_processes_take_part_in_fd_rounds() {
+        let sshd = id_process("n", 1, Some(5));
+        let idle = id_process("n", 2, Some(6));
+        let nodes = vec![(sshd.clone(), process(1)), (idle.clone(), process(2))];
+        let edges = vec![opens(3, "/var/log/auth.log")];
+        let proc_ids: HashMap<i32, NodeId> = [(1, sshd), (2, idle)].into();
+
+        let (fd_cache, _) = SnapshotSeed::new(&nodes, &edges).into_caches(&proc_ids);
+
+        assert_eq!(fd_diff_round(&fd_cache, 0), vec![1, 2]);
+        assert_eq!(fd_cache[&1], [opens(3, "/var/log/auth.log")].into());
+        assert!(fd_cache[&2].is_empty());
+    }
+
+    #[test]
+    fn snapshot_seed_skips_pids_reused_since_the_snapshot() {
+        let old = id_process("n", 1, Some(5));
+        let thread = id_thread("n", 1, 2);
+        let nodes = vec![
+            (old, process(1)),
+            (
+                thread,
+                Node::Thread {
+                    tid: 2,
+                    pid: 1,
+                    name: "worker".into(),
+                },
+            ),
+        ];
+        let edges = vec![opens(3, "/var/log/auth.log")];
+        let proc_ids: HashMap<i32, NodeId> = [(1, id_process("n", 1, Some(9)))].into();
+
+        let (fd_cache, thread_cache) = SnapshotSeed::new(&nodes, &edges).into_caches(&proc_ids);
+
+        assert!(fd_cache.is_empty());
+        assert!(thread_cache.is_empty());
+    }
+
+    #[test]
+    fn only_polls_with_changes_become_batches() {
+        assert!(batch_msgs(7, Vec::new()).is_empty());
+
+        let msgs = batch_msgs(
+            7,
+            vec![Delta::RemoveNode {
+                id: id_process("n", 1, None),
+            }],
+        );
+        assert_eq!(msgs.len(), 3);
+        assert!(matches!(
+            msgs[0],
+            Msg::Event {
+                delta: Delta::BatchBegin { id: 7 }
+            }
+        ));
+        assert!(matches!(
+            msgs[2],
+            Msg::Event {
+                delta: Delta::BatchEnd { id: 7 }
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn slow_detail_is_cut_off_by_the_timeout() {
+        let started = Instant::now();
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            Some(fallback_process_node(1))
+        };
+        let detail = detail_within(42, Duration::from_millis(20), slow).await;
+        assert!(detail.is_none());
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // the timed-out pid still shows up, under the id the loop tracks for it
+        let tracked = id_process("n", 42, Some(7));
+        let proc_ids: HashMap<i32, NodeId> = [(42, tracked.clone())].into();
+        match fallback_upsert("n", 42, &proc_ids) {
+            Delta::UpsertNode {
+                id,
+                node: Node::Process { pid, exe, .. },
+            } => {
+                assert_eq!(id, tracked);
+                assert_eq!(pid, 42);
+                assert_eq!(exe, "<unknown>");
+            }
+            other => panic!("unexpected fallback {other:?}"),
+        }
+
+        let fast = async { Some(7) };
+        assert_eq!(
+            detail_within(42, Duration::from_secs(1), fast).await,
+            Some(7)
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use anyhow::Result;
+use spacegraph_core::{
+    id_file, id_process, id_user, Delta, Edge, EdgeKind, FileKind, Msg, Node, NodeId,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use sysinfo::{Process, System, Users};
+use tokio::sync::mpsc;
+
+use crate::metrics::Metrics;
+use crate::watch_fs::file_category;
+
+type ProcDetail = (Vec<(NodeId, Node)>, Vec<Edge>);
+
+/// Windows has SIDs instead of numeric uids; the trailing RID is stable per account.
+fn uid_from_sid(sid: &str) -> u32 {
+    sid.rsplit('-')
+        .next()
+        .and_then(|rid| rid.parse::<u32>().ok())
+        .unwrap_or(0)
+}
+
+fn process_detail(
+    node_id: &str,
+    users: &Users,
+    known_pids: &HashSet<i32>,
+    pid: i32,
+    pr: &Process,
+) -> ProcDetail {
+    let ppid = pr.parent().map(|p| p.as_u32() as i32).unwrap_or(0);
+    let exe = pr
+        .exe()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| "<unknown>".into());
+    let cmdline = if pr.cmd().is_empty() {
+        pr.name().to_string()
+    } else {
+        pr.cmd().join(" ")
+    };
+
+    let (uid, uname) = match pr.user_id() {
+        Some(sid) => {
+            let uid = uid_from_sid(&sid.to_string());
+            let name = users
+                .get_user_by_id(sid)
+                .map(|u| u.name().to_string())
+                .unwrap_or_else(|| format!("uid{uid}"));
+            (uid, name)
+        }
+        None => (0, "uid0".to_string()),
+    };
+
+    let proc_id = id_process(node_id, pid, None);
+    let mut nodes: Vec<(NodeId, Node)> = Vec::new();
+    let mut edges: Vec<Edge> = Vec::new();
+
+    nodes.push((
+        proc_id.clone(),
+        Node::Process {
+            pid,
+            ppid,
+            exe: exe.clone(),
+            cmdline,
+            uid,
+            rss_kb: pr.memory() / 1024,
+            utime_ticks: 0,
+            container: None,
+        },
+    ));
+
+    let user_id = id_user(node_id, uid);
+    nodes.push((user_id.clone(), Node::User { uid, name: uname }));
+    edges.push(Edge {
+        from: proc_id.clone(),
+        to: user_id,
+        kind: EdgeKind::RunsAs,
+    });
+
+    if ppid > 0 && known_pids.contains(&ppid) {
+        edges.push(Edge {
+            from: id_process(node_id, ppid, None),
+            to: proc_id.clone(),
+            kind: EdgeKind::ParentOf,
+        });
+    }
+
+    let exe_file_id = id_file(node_id, &exe);
+    let category = file_category(&exe);
+    nodes.push((
+        exe_file_id.clone(),
+        Node::File {
+            path: exe,
+            inode: 0,
+            kind: FileKind::Unknown,
+            category,
+            link_target: None,
+        },
+    ));
+    edges.push(Edge {
+        from: proc_id,
+        to: exe_file_id,
+        kind: EdgeKind::Execs,
+    });
+
+    (nodes, edges)
+}
+
+fn pid_set(sys: &System) -> HashSet<i32> {
+    sys.processes().keys().map(|p| p.as_u32() as i32).collect()
+}
+
+pub fn build_snapshot(node_id: &str) -> (Vec<(NodeId, Node)>, Vec<Edge>) {
+    let mut sys = System::new();
+    sys.refresh_processes();
+    let users = Users::new_with_refreshed_list();
+    let known = pid_set(&sys);
+
+    let mut nodes: HashMap<NodeId, Node> = HashMap::new();
+    let mut edges: HashSet<Edge> = HashSet::new();
+    for (pid, pr) in sys.processes() {
+        let (n, e) = process_detail(node_id, &users, &known, pid.as_u32() as i32, pr);
+        nodes.extend(n);
+        edges.extend(e);
+    }
+    (nodes.into_iter().collect(), edges.into_iter().collect())
+}
+
+pub fn spawn(
+    node_id: &str,
+    poll: Duration,
+    users_refresh: Duration,
+    metrics: Arc<Metrics>,
+    tx: mpsc::Sender<Msg>,
+) -> Result<()> {
+    let node_id = node_id.to_string();
+
+    tokio::spawn(async move {
+        let mut sys = System::new();
+        sys.refresh_processes();
+        let mut users = Users::new_with_refreshed_list();
+        let mut last_users = Instant::now();
+        let mut prev = pid_set(&sys);
+        let mut batch_id: u64 = 1;
+        let mut last_log = Instant::now() - Duration::from_secs(1);
+
+        loop {
+            tokio::time::sleep(poll).await;
+            let poll_started = Instant::now();
+
+            if last_users.elapsed() >= users_refresh {
+                users.refresh_list();
+                last_users = Instant::now();
+            }
+
+            sys.refresh_processes();
+            let cur = pid_set(&sys);
+
+            let new_pids: Vec<i32> = cur.difference(&prev).copied().collect();
+            let gone_pids: Vec<i32> = prev.difference(&cur).copied().collect();
+            let new_pids_count = new_pids.len();
+            let gone_pids_count = gone_pids.len();
+
+            if new_pids.is_empty() && gone_pids.is_empty() {
+                prev = cur;
+                continue;
+            }
+
+            let _ = tx
+                .send(Msg::Event {
+                    delta: Delta::BatchBegin { id: batch_id },
+                })
+                .await;
+
+            let mut nodes_sent = 0usize;
+            let mut edges_sent = 0usize;
+
+            for pid in new_pids {
+                let Some(pr) = sys.process(sysinfo::Pid::from_u32(pid as u32)) else {
+                    continue;
+                };
+                let (nodes, edges) = process_detail(&node_id, &users, &cur, pid, pr);
+                nodes_sent += nodes.len();
+                edges_sent += edges.len();
+                for (id, node) in nodes {
+                    let _ = tx
+                        .send(Msg::Event {
+                            delta: Delta::UpsertNode { id, node },
+                        })
+                        .await;
+                }
+                for edge in edges {
+                    let _ = tx
+                        .send(Msg::Event {
+                            delta: Delta::UpsertEdge { edge },
+                        })
+                        .await;
+                }
+            }
+
+            for pid in gone_pids {
+                let id: NodeId = id_process(&node_id, pid, None);
+                let _ = tx
+                    .send(Msg::Event {
+                        delta: Delta::RemoveNode { id },
+                    })
+                    .await;
+            }
+
+            let _ = tx
+                .send(Msg::Event {
+                    delta: Delta::BatchEnd { id: batch_id },
+                })
+                .await;
+            metrics.record_proc_poll(poll_started.elapsed());
+
+            if last_log.elapsed() >= Duration::from_secs(1) {
+                tracing::debug!(
+                    event_type = "proc_batch",
+                    batch_id,
+                    new_pid_count = new_pids_count,
+                    removed_pid_count = gone_pids_count,
+                    node_count = nodes_sent,
+                    edge_count = edges_sent,
+                    "broadcast batch"
+                );
+                last_log = Instant::now();
+            }
+            batch_id = batch_id.wrapping_add(1);
+            prev = cur;
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uid_from_sid_uses_trailing_rid() {
+        assert_eq!(
+            uid_from_sid("S-1-5-21-3623811015-3361044348-30300820-1013"),
+            1013
+        );
+        assert_eq!(uid_from_sid("S-1-5-18"), 18);
+        assert_eq!(uid_from_sid("garbage"), 0);
+    }
+}
@@ -0,0 +1,270 @@
+//! Frame payload encoding shared by agent and viewer.
+//!
+//! Plain frames are raw JSON (what pre-compression peers send). Compressed frames
+//! start with [`TAG_DEFLATE`], which can never be the first byte of a JSON document.
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde_json::Value;
+use std::io::{self, Read, Write};
+
+use crate::{Delta, EdgeKind, Msg, Node};
+
+pub const TAG_DEFLATE: u8 = 0x01;
+
+/// Largest frame either side accepts in its length-delimited codec.
+pub const MAX_FRAME_LENGTH: usize = 64 * 1024 * 1024;
+
+pub fn encode_frame(msg: &Msg, compress: bool) -> io::Result<Vec<u8>> {
+    let json = serde_json::to_vec(msg)?;
+    if !compress {
+        return Ok(json);
+    }
+    let mut out = vec![TAG_DEFLATE];
+    let mut encoder = DeflateEncoder::new(&mut out, Compression::fast());
+    encoder.write_all(&json)?;
+    encoder.finish()?;
+    Ok(out)
+}
+
+pub fn decode_frame(bytes: &[u8]) -> io::Result<Msg> {
+    match bytes.split_first() {
+        Some((&TAG_DEFLATE, rest)) => Ok(serde_json::from_slice(&inflate(rest)?)?),
+        _ => Ok(serde_json::from_slice(bytes)?),
+    }
+}
+
+fn inflate(compressed: &[u8]) -> io::Result<Vec<u8>> {
+    inflate_capped(compressed, MAX_FRAME_LENGTH)
+}
+
+/// Inflates at most `limit` bytes, so a small frame cannot expand without bound.
+fn inflate_capped(compressed: &[u8], limit: usize) -> io::Result<Vec<u8>> {
+    let mut json = Vec::new();
+    DeflateDecoder::new(compressed)
+        .take(limit as u64 + 1)
+        .read_to_end(&mut json)?;
+    if json.len() > limit {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("inflated frame exceeds max length ({limit} bytes)"),
+        ));
+    }
+    Ok(json)
+}
+
+/// A frame from a possibly newer peer: what could be decoded, plus the variants this
+/// build does not know (e.g. `Delta::Foo`).
+#[derive(Debug)]
+pub struct Decoded {
+    /// `None` when the whole message is unknown, e.g. an event with a new `Delta`.
+    pub msg: Option<Msg>,
+    pub unknown: Vec<String>,
+}
+
+/// Like [`decode_frame`], but tolerates variants added by newer protocol versions.
+/// Snapshots keep their known nodes and edges; frames that are malformed for any other
+/// reason still fail.
+pub fn decode_frame_lenient(bytes: &[u8]) -> io::Result<Decoded> {
+    let err = match decode_frame(bytes) {
+        Ok(msg) => {
+            return Ok(Decoded {
+                msg: Some(msg),
+                unknown: Vec::new(),
+            })
+        }
+        Err(err) => err,
+    };
+    let json = match bytes.split_first() {
+        Some((&TAG_DEFLATE, rest)) => inflate(rest)?,
+        _ => bytes.to_vec(),
+    };
+    let Ok(mut value) = serde_json::from_slice::<Value>(&json) else {
+        return Err(err);
+    };
+    let mut unknown = Vec::new();
+    if let Some(tag) = unknown_tag(&value, Msg::TAGS) {
+        unknown.push(format!("Msg::{tag}"));
+        return Ok(Decoded { msg: None, unknown });
+    }
+    let ty = value["type"].as_str().map(str::to_owned);
+    match ty.as_deref() {
+        Some("Event") => {
+            let delta = &value["data"]["delta"];
+            if let Some(tag) = unknown_tag(delta, Delta::TAGS) {
+                unknown.push(format!("Delta::{tag}"));
+            } else if let Some(tag) = unknown_tag(&delta["data"]["node"], Node::TAGS) {
+                unknown.push(format!("Node::{tag}"));
+            } else if let Some(tag) = unknown_tag(&delta["data"]["edge"]["kind"], EdgeKind::TAGS) {
+                unknown.push(format!("EdgeKind::{tag}"));
+            }
+            if unknown.is_empty() {
+                return Err(err);
+            }
+            Ok(Decoded { msg: None, unknown })
+        }
+        Some("Snapshot" | "SnapshotChunk") => {
+            if let Some(nodes) = value
+                .pointer_mut("/data/nodes")
+                .and_then(Value::as_array_mut)
+            {
+                nodes.retain(|pair| match unknown_tag(&pair[1], Node::TAGS) {
+                    Some(tag) => {
+                        unknown.push(format!("Node::{tag}"));
+                        false
+                    }
+                    None => true,
+                });
+            }
+            if let Some(edges) = value
+                .pointer_mut("/data/edges")
+                .and_then(Value::as_array_mut)
+            {
+                edges.retain(|edge| match unknown_tag(&edge["kind"], EdgeKind::TAGS) {
+                    Some(tag) => {
+                        unknown.push(format!("EdgeKind::{tag}"));
+                        false
+                    }
+                    None => true,
+                });
+            }
+            if unknown.is_empty() {
+                return Err(err);
+            }
+            let msg = serde_json::from_value(value)?;
+            Ok(Decoded {
+                msg: Some(msg),
+                unknown,
+            })
+        }
+        _ => Err(err),
+    }
+}
+
+/// The `type` tag of `value` when it is not one of the `known` variant tags.
+fn unknown_tag<'a>(value: &'a Value, known: &[&str]) -> Option<&'a str> {
+    let tag = value.get("type")?.as_str()?;
+    (!known.contains(&tag)).then_some(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compressed_frame_roundtrips() {
+        let msg = Msg::Hello {
+            version: "0.1.0".to_string(),
+            compression: true,
+            snapshot_chunks: false,
+            token: None,
+            session: None,
+            resume_after_batch: None,
+        };
+        let bytes = encode_frame(&msg, true).expect("encode");
+        assert_eq!(bytes[0], TAG_DEFLATE);
+        let decoded = decode_frame(&bytes).expect("decode");
+        assert!(matches!(
+            decoded,
+            Msg::Hello {
+                compression: true,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn inflated_size_is_capped() {
+        let compress = |len: usize| {
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+            encoder.write_all(&vec![b' '; len]).unwrap();
+            encoder.finish().unwrap()
+        };
+        assert_eq!(inflate_capped(&compress(1024), 1024).unwrap().len(), 1024);
+        let err = inflate_capped(&compress(1025), 1024).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("max length"), "{err}");
+    }
+
+    #[test]
+    fn plain_frame_is_raw_json() {
+        let bytes = encode_frame(&Msg::Ping, false).expect("encode");
+        assert_eq!(bytes, serde_json::to_vec(&Msg::Ping).unwrap());
+        assert!(matches!(decode_frame(&bytes).expect("decode"), Msg::Ping));
+    }
+
+    #[test]
+    fn garbage_frame_is_rejected() {
+        assert!(decode_frame(&[TAG_DEFLATE, 0xff, 0x00]).is_err());
+        assert!(decode_frame(b"not json").is_err());
+        assert!(decode_frame_lenient(b"not json").is_err());
+        assert!(decode_frame_lenient(
+            br#"{"type":"Event","data":{"delta":{"type":"BatchBegin","data":{}}}}"#
+        )
+        .is_err());
+        // A known node tag with broken content is an error, not a node to drop.
+        assert!(decode_frame_lenient(
+            br#"{"type":"Snapshot","data":{"nodes":[["f",{"type":"File","data":{}}]],"edges":[]}}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn unknown_variants_from_newer_peers_are_reported() {
+        let cases = [
+            (r#"{"type":"Telemetry","data":{"cpu":3}}"#, "Msg::Telemetry"),
+            (
+                r#"{"type":"Event","data":{"delta":{"type":"Rename","data":{"from":"a","to":"b"}}}}"#,
+                "Delta::Rename",
+            ),
+            (
+                r#"{"type":"Event","data":{"delta":{"type":"UpsertNode","data":{"id":"h:pod:1","node":{"type":"Pod","data":{"name":"x"}}}}}}"#,
+                "Node::Pod",
+            ),
+            (
+                r#"{"type":"Event","data":{"delta":{"type":"UpsertEdge","data":{"edge":{"from":"a","to":"b","kind":{"type":"Mounts"}}}}}}"#,
+                "EdgeKind::Mounts",
+            ),
+        ];
+        for (json, name) in cases {
+            let decoded = decode_frame_lenient(json.as_bytes()).expect(name);
+            assert!(decoded.msg.is_none(), "{name}");
+            assert_eq!(decoded.unknown, vec![name.to_string()]);
+        }
+    }
+
+    #[test]
+    fn snapshot_keeps_known_items_next_to_unknown_ones() {
+        let json = r#"{"type":"Snapshot","data":{
+            "nodes":[
+                ["h:user:0",{"type":"User","data":{"uid":0,"name":"root"}}],
+                ["h:pod:1",{"type":"Pod","data":{"name":"x"}}]
+            ],
+            "edges":[
+                {"from":"h:user:0","to":"h:pod:1","kind":{"type":"Mounts"}},
+                {"from":"h:user:0","to":"h:user:0","kind":{"type":"RunsAs"}}
+            ]}}"#;
+        let decoded = decode_frame_lenient(json.as_bytes()).expect("decode");
+        assert_eq!(decoded.unknown, vec!["Node::Pod", "EdgeKind::Mounts"]);
+        let Some(Msg::Snapshot { nodes, edges }) = decoded.msg else {
+            panic!("expected snapshot");
+        };
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(edges.len(), 1);
+    }
+
+    #[test]
+    fn unknown_file_kind_falls_back_to_unknown() {
+        let json = r#"{"type":"File","data":{"path":"/x","inode":1,"kind":"Whiteout","category":"Firmware"}}"#;
+        let node: crate::Node = serde_json::from_str(json).expect("decode");
+        assert!(matches!(
+            node,
+            crate::Node::File {
+                kind: crate::FileKind::Unknown,
+                category: crate::FileCategory::Unknown,
+                ..
+            }
+        ));
+    }
+}
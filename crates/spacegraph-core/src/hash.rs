@@ -0,0 +1,40 @@
+//! Hashes shared by the agent and the viewer.
+
+/// FNV-1a of `s`; unlike `DefaultHasher` it is the same across runs and Rust releases.
+/// Short inputs are trivial to recover from it, so it must not hide anything.
+pub fn stable_u32(s: &str) -> u32 {
+    s.bytes().fold(0x811c_9dc5, |h, b| {
+        (h ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Secret for [`keyed_u64`].
+pub type HashKey = [u8; blake3::KEY_LEN];
+
+/// First 64 bits of keyed BLAKE3: without `key`, guessed inputs cannot be checked
+/// against the output.
+pub fn keyed_u64(key: &HashKey, data: &[u8]) -> u64 {
+    let hash = blake3::keyed_hash(key, data);
+    let mut out = [0u8; 8];
+    out.copy_from_slice(&hash.as_bytes()[..8]);
+    u64::from_le_bytes(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_u32_is_fnv1a() {
+        assert_eq!(stable_u32(""), 0x811c_9dc5);
+        assert_eq!(stable_u32("a"), 0xe40c_292c);
+    }
+
+    #[test]
+    fn keyed_u64_depends_on_the_key() {
+        let key = [7u8; 32];
+        assert_eq!(keyed_u64(&key, b"alice"), keyed_u64(&key, b"alice"));
+        assert_ne!(keyed_u64(&key, b"alice"), keyed_u64(&key, b"bob"));
+        assert_ne!(keyed_u64(&key, b"alice"), keyed_u64(&[8u8; 32], b"alice"));
+    }
+}
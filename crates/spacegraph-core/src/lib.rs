@@ -1,5 +1,12 @@
 use serde::{Deserialize, Serialize};
 
+pub mod frame;
+pub mod hash;
+
+/// Wire protocol revision. Bump when `Msg`, `Delta`, `Node` or `EdgeKind` gain variants
+/// older peers cannot decode.
+pub const PROTOCOL_VERSION: u32 = 2;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct NodeId(pub String);
 
@@ -12,16 +19,46 @@ pub enum Node {
         exe: String,
         cmdline: String,
         uid: u32,
+        /// Resident set size in KiB (0 when unknown).
+        #[serde(default)]
+        rss_kb: u64,
+        /// Accumulated user-mode CPU time in clock ticks (0 when unknown).
+        #[serde(default)]
+        utime_ticks: u64,
+        /// Container id from `/proc/<pid>/cgroup`, if the process runs in one.
+        #[serde(default)]
+        container: Option<String>,
     },
     File {
         path: String,
         inode: u64,
         kind: FileKind,
+        /// Content classification; agents without it send `Unknown`.
+        #[serde(default)]
+        category: FileCategory,
+        /// Resolved target when `kind` is `Symlink`.
+        #[serde(default)]
+        link_target: Option<String>,
     },
     User {
         uid: u32,
         name: String,
     },
+    Container {
+        id: String,
+        name: String,
+    },
+    /// Task under `/proc/<pid>/task`; only sent by agents started with `--threads`.
+    Thread {
+        tid: i32,
+        pid: i32,
+        name: String,
+    },
+}
+
+impl Node {
+    /// Wire tags of all variants, for telling tags from newer peers apart.
+    pub const TAGS: &'static [&'static str] = &["Process", "File", "User", "Container", "Thread"];
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +68,20 @@ pub enum FileKind {
     Socket,
     Pipe,
     Device,
+    Symlink,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum FileCategory {
+    Config,
+    Log,
+    Binary,
+    Script,
+    Data,
+    #[default]
+    #[serde(other)]
     Unknown,
 }
 
@@ -44,9 +95,26 @@ pub struct Edge {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(tag = "type", content = "data")]
 pub enum EdgeKind {
-    Opens { fd: i32, mode: String }, // "r" | "w" | "rw" | "?"
+    Opens {
+        fd: i32,
+        mode: String,
+    }, // "r" | "w" | "rw" | "?"
     Execs,
     RunsAs,
+    ParentOf,
+    /// Process belongs to a container.
+    MemberOf,
+    /// Symlink resolves to a file.
+    PointsTo,
+    /// Thread belongs to its process.
+    ThreadOf,
+}
+
+impl EdgeKind {
+    /// Wire tags of all variants, for telling tags from newer peers apart.
+    pub const TAGS: &'static [&'static str] = &[
+        "Opens", "Execs", "RunsAs", "ParentOf", "MemberOf", "PointsTo", "ThreadOf",
+    ];
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,7 +128,19 @@ pub enum Delta {
     RemoveEdge { edge: Edge },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Delta {
+    /// Wire tags of all variants, for telling tags from newer peers apart.
+    pub const TAGS: &'static [&'static str] = &[
+        "BatchBegin",
+        "BatchEnd",
+        "UpsertNode",
+        "RemoveNode",
+        "UpsertEdge",
+        "RemoveEdge",
+    ];
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Capabilities {
     pub procfs: bool,
     pub fd_edges: bool,
@@ -69,6 +149,8 @@ pub struct Capabilities {
     pub ebpf: bool,
     pub cloud: bool,
     pub windows: bool,
+    #[serde(default)]
+    pub compression: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,6 +159,21 @@ pub struct NodeIdentity {
     pub hostname: String,
     pub platform: String,
     pub arch: String,
+    /// [`PROTOCOL_VERSION`] of the agent; 0 for agents that predate it.
+    #[serde(default)]
+    pub protocol: u32,
+}
+
+/// Commands a viewer sends to the agent over an established connection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum ControlCmd {
+    /// Process poll interval in milliseconds.
+    SetPollInterval(u64),
+    ReloadPathPolicy,
+    /// Stop broadcasting events to viewers until `Resume`.
+    Pause,
+    Resume,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -84,6 +181,22 @@ pub struct NodeIdentity {
 pub enum Msg {
     Hello {
         version: String,
+        /// Sender can decode deflate-compressed frames (see [`frame`]).
+        #[serde(default)]
+        compression: bool,
+        /// Sender understands snapshots split into [`Msg::SnapshotChunk`]s.
+        #[serde(default)]
+        snapshot_chunks: bool,
+        /// Shared secret; agents started with a token reject a missing or wrong one.
+        #[serde(default)]
+        token: Option<String>,
+        /// Agent instance id. Agents announce theirs; viewers echo it back when resuming.
+        #[serde(default)]
+        session: Option<u64>,
+        /// Last batch the viewer applied completely. The agent then sends only what
+        /// followed it, or a full snapshot when that is no longer buffered.
+        #[serde(default)]
+        resume_after_batch: Option<u64>,
     },
     Identity {
         ident: NodeIdentity,
@@ -94,21 +207,518 @@ pub enum Msg {
         nodes: Vec<(NodeId, Node)>,
         edges: Vec<Edge>,
     },
+    SnapshotBegin {
+        total_nodes: usize,
+        total_edges: usize,
+    },
+    SnapshotChunk {
+        nodes: Vec<(NodeId, Node)>,
+        edges: Vec<Edge>,
+    },
+    SnapshotEnd,
     Event {
         delta: Delta,
     },
     Ping,
     Pong,
+    Control {
+        cmd: ControlCmd,
+    },
+    /// Sent before the agent closes a connection it refuses to serve.
+    Error {
+        message: String,
+    },
+}
+
+impl Msg {
+    /// Wire tags of all variants, for telling tags from newer peers apart.
+    pub const TAGS: &'static [&'static str] = &[
+        "Hello",
+        "Identity",
+        "RequestSnapshot",
+        "Snapshot",
+        "SnapshotBegin",
+        "SnapshotChunk",
+        "SnapshotEnd",
+        "Event",
+        "Ping",
+        "Pong",
+        "Control",
+        "Error",
+    ];
+}
+
+/// Default number of items (nodes + edges) per [`Msg::SnapshotChunk`].
+pub const SNAPSHOT_CHUNK_ITEMS: usize = 2000;
+
+/// Splits a snapshot into `SnapshotBegin`, chunks of at most `max_items`
+/// nodes + edges (nodes first), and `SnapshotEnd`.
+pub fn chunk_snapshot(nodes: &[(NodeId, Node)], edges: &[Edge], max_items: usize) -> Vec<Msg> {
+    let max_items = max_items.max(1);
+    let mut out = vec![Msg::SnapshotBegin {
+        total_nodes: nodes.len(),
+        total_edges: edges.len(),
+    }];
+    let mut chunk_nodes = Vec::new();
+    let mut chunk_edges = Vec::new();
+    let mut items = nodes.iter().map(Ok).chain(edges.iter().map(Err)).peekable();
+    while let Some(item) = items.next() {
+        match item {
+            Ok(n) => chunk_nodes.push(n.clone()),
+            Err(e) => chunk_edges.push(e.clone()),
+        }
+        if chunk_nodes.len() + chunk_edges.len() == max_items || items.peek().is_none() {
+            out.push(Msg::SnapshotChunk {
+                nodes: std::mem::take(&mut chunk_nodes),
+                edges: std::mem::take(&mut chunk_edges),
+            });
+        }
+    }
+    out.push(Msg::SnapshotEnd);
+    out
 }
 
 /// Build globally unique IDs (scope = node_id).
-pub fn id_process(node_id: &str, pid: i32) -> NodeId {
-    NodeId(format!("{node_id}:process:pid:{pid}"))
+///
+/// `start_time` (clock ticks since boot, `/proc/<pid>/stat` field 22) tells a reused pid
+/// apart from the process that had it before; without it the id is the pid alone.
+pub fn id_process(node_id: &str, pid: i32, start_time: Option<u64>) -> NodeId {
+    match start_time {
+        Some(start) => NodeId(format!("{node_id}:process:pid:{pid}:{start}")),
+        None => NodeId(format!("{node_id}:process:pid:{pid}")),
+    }
+}
+pub fn id_thread(node_id: &str, pid: i32, tid: i32) -> NodeId {
+    NodeId(format!("{node_id}:thread:{pid}:{tid}"))
 }
 pub fn id_user(node_id: &str, uid: u32) -> NodeId {
     NodeId(format!("{node_id}:user:{uid}"))
 }
+pub fn id_container(node_id: &str, container_id: &str) -> NodeId {
+    NodeId(format!("{node_id}:container:{container_id}"))
+}
 pub fn id_file(node_id: &str, path: &str) -> NodeId {
-    // MVP: use raw path. Later you can hash/normalize for privacy.
+    // Raw path; the agent's `--redact` later hashes sensitive segments (see `redact.rs`).
     NodeId(format!("{node_id}:file:{path}"))
 }
+/// File keyed by device and inode, so it keeps its id across renames.
+pub fn id_file_by_inode(node_id: &str, dev: u64, ino: u64) -> NodeId {
+    NodeId(format!("{node_id}:inode:{dev}:{ino}"))
+}
+
+/// A scoped id split back into host, kind and key; the inverse of the `id_*` builders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParsedId<'a> {
+    /// `start` is unset for ids built without a start time (older agents, Windows).
+    Process {
+        host: &'a str,
+        pid: i32,
+        start: Option<u64>,
+    },
+    Thread {
+        host: &'a str,
+        pid: i32,
+        tid: i32,
+    },
+    User {
+        host: &'a str,
+        uid: u32,
+    },
+    Container {
+        host: &'a str,
+        id: &'a str,
+    },
+    /// The path is everything after `file:`, colons included.
+    File {
+        host: &'a str,
+        path: &'a str,
+    },
+    Inode {
+        host: &'a str,
+        dev: u64,
+        ino: u64,
+    },
+}
+
+impl<'a> ParsedId<'a> {
+    pub fn host(&self) -> &'a str {
+        match *self {
+            Self::Process { host, .. }
+            | Self::Thread { host, .. }
+            | Self::User { host, .. }
+            | Self::Container { host, .. }
+            | Self::File { host, .. }
+            | Self::Inode { host, .. } => host,
+        }
+    }
+}
+
+/// `None` for ids that do not follow the `id_*` scheme (unknown kind, empty host or key,
+/// non-numeric pid/uid).
+pub fn parse_node_id(id: &NodeId) -> Option<ParsedId<'_>> {
+    let (host, rest) = id.0.split_once(':')?;
+    if host.is_empty() {
+        return None;
+    }
+    let (kind, key) = rest.split_once(':')?;
+    if key.is_empty() {
+        return None;
+    }
+    match kind {
+        "process" => {
+            let key = key.strip_prefix("pid:")?;
+            let (pid, start) = match key.split_once(':') {
+                Some((pid, start)) => (pid, Some(start.parse().ok()?)),
+                None => (key, None),
+            };
+            Some(ParsedId::Process {
+                host,
+                pid: pid.parse().ok()?,
+                start,
+            })
+        }
+        "thread" => {
+            let (pid, tid) = key.split_once(':')?;
+            Some(ParsedId::Thread {
+                host,
+                pid: pid.parse().ok()?,
+                tid: tid.parse().ok()?,
+            })
+        }
+        "user" => Some(ParsedId::User {
+            host,
+            uid: key.parse().ok()?,
+        }),
+        "container" => Some(ParsedId::Container { host, id: key }),
+        "file" => Some(ParsedId::File { host, path: key }),
+        "inode" => {
+            let (dev, ino) = key.split_once(':')?;
+            Some(ParsedId::Inode {
+                host,
+                dev: dev.parse().ok()?,
+                ino: ino.parse().ok()?,
+            })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ids_built_by_the_id_helpers() {
+        assert_eq!(
+            parse_node_id(&id_process("web-1", 42, Some(8812))),
+            Some(ParsedId::Process {
+                host: "web-1",
+                pid: 42,
+                start: Some(8812)
+            })
+        );
+        // Ids from agents that predate start times.
+        assert_eq!(
+            parse_node_id(&NodeId("web-1:process:pid:42".to_string())),
+            Some(ParsedId::Process {
+                host: "web-1",
+                pid: 42,
+                start: None
+            })
+        );
+        assert_eq!(
+            parse_node_id(&id_thread("web-1", 42, 43)),
+            Some(ParsedId::Thread {
+                host: "web-1",
+                pid: 42,
+                tid: 43
+            })
+        );
+        assert_eq!(
+            parse_node_id(&id_user("web-1", 1000)),
+            Some(ParsedId::User {
+                host: "web-1",
+                uid: 1000
+            })
+        );
+        assert_eq!(
+            parse_node_id(&id_container("db", "abc123")),
+            Some(ParsedId::Container {
+                host: "db",
+                id: "abc123"
+            })
+        );
+        let file = id_file("db", "/srv/data/a:b:c.csv");
+        let parsed = parse_node_id(&file).expect("file id parses");
+        assert_eq!(
+            parsed,
+            ParsedId::File {
+                host: "db",
+                path: "/srv/data/a:b:c.csv"
+            }
+        );
+        assert_eq!(parsed.host(), "db");
+        assert_eq!(
+            parse_node_id(&id_file_by_inode("db", 2049, 131_077)),
+            Some(ParsedId::Inode {
+                host: "db",
+                dev: 2049,
+                ino: 131_077
+            })
+        );
+    }
+
+    #[test]
+    fn reused_pid_gets_a_new_id() {
+        let first = id_process("web-1", 4242, Some(1_000));
+        let second = id_process("web-1", 4242, Some(95_000));
+        assert_ne!(first, second);
+        assert_eq!(first, id_process("web-1", 4242, Some(1_000)));
+        assert_ne!(first, id_process("web-1", 4242, None));
+    }
+
+    #[test]
+    fn malformed_ids_do_not_parse() {
+        for raw in [
+            "",
+            "no-colon",
+            ":file:/x",
+            "web-1:process:42",
+            "web-1:process:pid:abc",
+            "web-1:process:pid:42:later",
+            "web-1:inode:2049",
+            "web-1:inode:x:5",
+            "web-1:thread:42",
+            "web-1:user:-1",
+            "web-1:file:",
+            "web-1:socket:7",
+        ] {
+            assert_eq!(parse_node_id(&NodeId(raw.to_string())), None, "{raw}");
+        }
+    }
+
+    #[test]
+    fn snapshot_chunks_are_bounded_and_complete() {
+        let nodes: Vec<(NodeId, Node)> = (0..5)
+            .map(|uid| {
+                (
+                    id_user("h", uid),
+                    Node::User {
+                        uid,
+                        name: format!("u{uid}"),
+                    },
+                )
+            })
+            .collect();
+        let edges: Vec<Edge> = (1..5)
+            .map(|uid| Edge {
+                from: id_user("h", 0),
+                to: id_user("h", uid),
+                kind: EdgeKind::ParentOf,
+            })
+            .collect();
+
+        let msgs = chunk_snapshot(&nodes, &edges, 4);
+        assert!(matches!(
+            msgs.first(),
+            Some(Msg::SnapshotBegin {
+                total_nodes: 5,
+                total_edges: 4
+            })
+        ));
+        assert!(matches!(msgs.last(), Some(Msg::SnapshotEnd)));
+        let sizes: Vec<(usize, usize)> = msgs
+            .iter()
+            .filter_map(|m| match m {
+                Msg::SnapshotChunk { nodes, edges } => Some((nodes.len(), edges.len())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(sizes, vec![(4, 0), (1, 3), (0, 1)]);
+    }
+
+    #[test]
+    fn empty_snapshot_still_begins_and_ends() {
+        let msgs = chunk_snapshot(&[], &[], SNAPSHOT_CHUNK_ITEMS);
+        assert_eq!(msgs.len(), 2);
+    }
+
+    #[test]
+    fn control_messages_roundtrip() {
+        for cmd in [
+            ControlCmd::SetPollInterval(500),
+            ControlCmd::ReloadPathPolicy,
+            ControlCmd::Pause,
+            ControlCmd::Resume,
+        ] {
+            let json = serde_json::to_string(&Msg::Control { cmd: cmd.clone() }).expect("encode");
+            match serde_json::from_str(&json).expect("decode") {
+                Msg::Control { cmd: decoded } => assert_eq!(decoded, cmd),
+                other => panic!("expected control message, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn legacy_hello_has_no_resume_point() {
+        let json = r#"{"type":"Hello","data":{"version":"0.1.0"}}"#;
+        match serde_json::from_str(json).expect("legacy hello decodes") {
+            Msg::Hello {
+                session,
+                resume_after_batch,
+                ..
+            } => {
+                assert_eq!(session, None);
+                assert_eq!(resume_after_batch, None);
+            }
+            other => panic!("expected hello, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn process_without_usage_fields_defaults_to_zero() {
+        let json = r#"{"type":"Process","data":{"pid":1,"ppid":0,"exe":"/sbin/init","cmdline":"init","uid":0}}"#;
+        let node: Node = serde_json::from_str(json).expect("legacy process decodes");
+        match node {
+            Node::Process {
+                rss_kb,
+                utime_ticks,
+                container,
+                ..
+            } => {
+                assert_eq!(rss_kb, 0);
+                assert_eq!(utime_ticks, 0);
+                assert_eq!(container, None);
+            }
+            _ => panic!("expected process node"),
+        }
+    }
+
+    #[test]
+    fn tag_lists_name_every_variant() {
+        fn tags<T: Serialize>(samples: &[T]) -> Vec<String> {
+            samples
+                .iter()
+                .map(|s| {
+                    serde_json::to_value(s).unwrap()["type"]
+                        .as_str()
+                        .unwrap()
+                        .to_string()
+                })
+                .collect()
+        }
+        let id = || NodeId("n".to_string());
+        let file = Node::File {
+            path: "/etc/hosts".to_string(),
+            inode: 1,
+            kind: FileKind::Regular,
+            category: FileCategory::Config,
+            link_target: None,
+        };
+        let nodes = [
+            Node::Process {
+                pid: 1,
+                ppid: 0,
+                exe: String::new(),
+                cmdline: String::new(),
+                uid: 0,
+                rss_kb: 0,
+                utime_ticks: 0,
+                container: None,
+            },
+            file.clone(),
+            Node::User {
+                uid: 0,
+                name: "root".to_string(),
+            },
+            Node::Container {
+                id: "c".to_string(),
+                name: "c".to_string(),
+            },
+            Node::Thread {
+                tid: 2,
+                pid: 1,
+                name: "t".to_string(),
+            },
+        ];
+        let kinds = [
+            EdgeKind::Opens {
+                fd: 3,
+                mode: "r".to_string(),
+            },
+            EdgeKind::Execs,
+            EdgeKind::RunsAs,
+            EdgeKind::ParentOf,
+            EdgeKind::MemberOf,
+            EdgeKind::PointsTo,
+            EdgeKind::ThreadOf,
+        ];
+        let edge = || Edge {
+            from: id(),
+            to: id(),
+            kind: EdgeKind::Execs,
+        };
+        let deltas = [
+            Delta::BatchBegin { id: 1 },
+            Delta::BatchEnd { id: 1 },
+            Delta::UpsertNode {
+                id: id(),
+                node: file,
+            },
+            Delta::RemoveNode { id: id() },
+            Delta::UpsertEdge { edge: edge() },
+            Delta::RemoveEdge { edge: edge() },
+        ];
+        let msgs = [
+            Msg::Hello {
+                version: String::new(),
+                compression: false,
+                snapshot_chunks: false,
+                token: None,
+                session: None,
+                resume_after_batch: None,
+            },
+            Msg::Identity {
+                ident: NodeIdentity {
+                    node_id: "n".to_string(),
+                    hostname: "h".to_string(),
+                    platform: "linux".to_string(),
+                    arch: "x86_64".to_string(),
+                    protocol: PROTOCOL_VERSION,
+                },
+                caps: Capabilities::default(),
+            },
+            Msg::RequestSnapshot,
+            Msg::Snapshot {
+                nodes: Vec::new(),
+                edges: Vec::new(),
+            },
+            Msg::SnapshotBegin {
+                total_nodes: 0,
+                total_edges: 0,
+            },
+            Msg::SnapshotChunk {
+                nodes: Vec::new(),
+                edges: Vec::new(),
+            },
+            Msg::SnapshotEnd,
+            Msg::Event {
+                delta: Delta::BatchEnd { id: 1 },
+            },
+            Msg::Ping,
+            Msg::Pong,
+            Msg::Control {
+                cmd: ControlCmd::Pause,
+            },
+            Msg::Error {
+                message: String::new(),
+            },
+        ];
+
+        assert_eq!(tags(&nodes), Node::TAGS);
+        assert_eq!(tags(&kinds), EdgeKind::TAGS);
+        assert_eq!(tags(&deltas), Delta::TAGS);
+        assert_eq!(tags(&msgs), Msg::TAGS);
+        assert!(!Msg::TAGS.contains(&"Telemetry"));
+    }
+}
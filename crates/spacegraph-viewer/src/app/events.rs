@@ -1,5 +1,9 @@
 use bevy::prelude::Event;
-use spacegraph_core::NodeId;
+use spacegraph_core::{Edge, NodeId};
 
 #[derive(Event)]
 pub struct Picked(pub NodeId);
+
+/// Edge click in spatial view; `None` when the click hit nothing.
+#[derive(Event)]
+pub struct PickedEdge(pub Option<Edge>);
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+use bevy::winit::{EventLoopProxy, UpdateMode, WakeUp, WinitSettings};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use crate::app::resources::NetRx;
+use crate::graph::layout::LAYOUT_SETTLED_STEP;
+use crate::graph::{GraphState, ViewMode};
+
+/// Quiet period after the last event before the viewer throttles.
+const IDLE_AFTER: Duration = Duration::from_secs(2);
+/// Longest sleep between idle updates; input and network messages wake earlier.
+const IDLE_WAIT: Duration = Duration::from_millis(250);
+
+/// Puts a forwarder between the readers and `NetRx` that wakes the event loop, so a
+/// throttled viewer still applies incoming messages right away.
+pub fn start_net_waker(
+    mut commands: Commands,
+    rx: Res<NetRx>,
+    proxy: NonSend<EventLoopProxy<WakeUp>>,
+) {
+    let upstream = rx.0.clone();
+    let (tx, forwarded) = crossbeam_channel::unbounded();
+    let proxy = proxy.clone();
+    let spawned = std::thread::Builder::new()
+        .name("net-waker".to_string())
+        .spawn(move || {
+            for msg in upstream.iter() {
+                // One wake-up per burst; the frame drains everything queued so far.
+                let wake = tx.is_empty();
+                if tx.send(msg).is_err() {
+                    break;
+                }
+                if wake {
+                    let _ = proxy.send_event(WakeUp);
+                }
+            }
+        });
+    match spawned {
+        Ok(_) => commands.insert_resource(NetRx(forwarded)),
+        Err(err) => warn!("net waker not started, idle throttle may delay messages: {err}"),
+    }
+}
+
+pub fn idle_throttle(st: Res<GraphState>, mut winit: ResMut<WinitSettings>) {
+    let mode = if is_idle(&st, Instant::now()) {
+        UpdateMode::reactive(IDLE_WAIT)
+    } else {
+        UpdateMode::Continuous
+    };
+    if winit.focused_mode != mode {
+        winit.focused_mode = mode;
+        winit.unfocused_mode = mode;
+    }
+}
+
+/// Nothing on screen changes on its own: no recent events, glow, playback or layout motion.
+pub(crate) fn is_idle(st: &GraphState, now: Instant) -> bool {
+    if !st.cfg.limit_fps_when_idle {
+        return false;
+    }
+    let recent_event = st
+        .perf
+        .ev_window
+        .back()
+        .is_some_and(|at| now.saturating_duration_since(*at) < IDLE_AFTER);
+    let animating = !st.spatial.glow_nodes.is_empty()
+        || !st.spatial.glow_edges.is_empty()
+        || st.timeline.playing
+        || (st.ui.view_mode == ViewMode::Timeline && !st.timeline.pause)
+        || (st.ui.view_mode == ViewMode::Spatial && st.spatial.layout_motion > LAYOUT_SETTLED_STEP)
+        || (st.ui.view_mode == ViewMode::Spatial && !st.search_preview().is_empty());
+    let pending = st.needs_redraw.load(Ordering::Relaxed)
+        || st.spatial.dirty_layout
+        || st.ui.jump_to.is_some()
+        || st.ui.fit_to_view
+        || st.ui.screenshot_requested
+        || st.ui.screenshot_hide_ui;
+    let generating = st.cfg.demo_mode || st.replay.is_some();
+    !(recent_event || animating || pending || generating)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::NodeId;
+
+    fn settled() -> GraphState {
+        let mut st = GraphState::default();
+        st.needs_redraw.store(false, Ordering::Relaxed);
+        st.spatial.dirty_layout = false;
+        st
+    }
+
+    #[test]
+    fn settled_graph_is_idle_until_something_moves() {
+        let now = Instant::now();
+        let mut st = settled();
+        assert!(is_idle(&st, now));
+
+        st.spatial.layout_motion = 0.5;
+        assert!(!is_idle(&st, now));
+        st.spatial.layout_motion = 0.0;
+
+        st.spatial
+            .glow_nodes
+            .insert(NodeId("n".to_string()), now + Duration::from_secs(1));
+        assert!(!is_idle(&st, now));
+        st.spatial.glow_nodes.clear();
+
+        st.cfg.limit_fps_when_idle = false;
+        assert!(!is_idle(&st, now));
+    }
+
+    #[test]
+    fn search_preview_pulse_keeps_the_viewer_awake() {
+        let now = Instant::now();
+        let mut st = settled();
+        st.ui.search_hits = vec![NodeId("n".to_string())];
+        assert!(is_idle(&st, now));
+        st.ui.search_open = true;
+        assert!(!is_idle(&st, now));
+    }
+
+    #[test]
+    fn recent_events_keep_the_viewer_awake() {
+        let now = Instant::now();
+        let mut st = settled();
+        st.perf.ev_window.push_back(now);
+        assert!(!is_idle(&st, now + Duration::from_millis(500)));
+        assert!(is_idle(&st, now + IDLE_AFTER));
+    }
+}
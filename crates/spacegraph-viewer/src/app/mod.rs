@@ -1,29 +1,45 @@
 use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::window::PrimaryWindow;
+use std::path::Path;
+use std::time::{Instant, SystemTime};
 
-use crate::app::events::Picked;
+use crate::app::events::{Picked, PickedEdge};
 use crate::app::resources::{NetRx, NetTx};
 use crate::graph::state::{NetCommand, NetStreamStatus};
 use crate::graph::GraphState;
 use crate::net;
 use crate::ui::UiLayout;
+use crate::util::cli::{connect_endpoints, ViewerArgs};
 use crate::util::config;
-use crate::util::config::AgentEndpointKind;
+use crate::util::export;
 
 pub mod events;
+pub mod idle;
 pub mod resources;
 
-pub struct SpaceGraphViewerPlugin;
+pub struct SpaceGraphViewerPlugin {
+    pub args: ViewerArgs,
+}
 
 impl Plugin for SpaceGraphViewerPlugin {
     fn build(&self, app: &mut App) {
         let cfg = config::load_or_default();
         let mut st = GraphState::default();
         st.apply_viewer_config(&cfg);
+        for endpoint in connect_endpoints(&self.args.connect, &st.net.endpoints) {
+            st.net.ensure_stream(&endpoint.name);
+            st.net.endpoints.push(endpoint);
+        }
         app.add_event::<Picked>()
+            .add_event::<PickedEdge>()
             .insert_resource(st)
             .insert_resource(UiLayout::default())
+            .init_resource::<crate::render::NodeMaterials>()
             .add_systems(Startup, auto_connect_agents)
+            .add_systems(Startup, idle::start_net_waker)
             .add_systems(Startup, crate::render::setup_scene)
+            .add_systems(PreUpdate, take_screenshot)
             .add_systems(
                 Update,
                 (
@@ -31,23 +47,73 @@ impl Plugin for SpaceGraphViewerPlugin {
                     pump_network,
                     crate::graph::tick_housekeeping,
                     crate::ui::handle_shortcuts,
-                    crate::ui::ui_panel,
-                    crate::ui::help_overlay,
-                    crate::ui::hud_overlay,
+                    crate::ui::ui_panel.run_if(ui_visible),
+                    crate::ui::help_overlay.run_if(ui_visible),
+                    crate::ui::hud_overlay.run_if(ui_visible),
+                    crate::ui::minimap_overlay.run_if(ui_visible),
                     crate::render::hover_detection_spatial,
                     crate::render::picking_focus,
                     crate::render::apply_picked_focus,
                     crate::render::update_tree_zoom,
                     crate::graph::update_layout_or_timeline,
                     crate::render::draw_scene,
+                    crate::render::node_labels,
+                    crate::render::camera_controls,
                     crate::render::apply_jump_to,
+                    (
+                        crate::render::animate_glow,
+                        crate::render::sync_bloom,
+                        crate::render::sync_theme,
+                        crate::render::sync_ambient,
+                    ),
                 ),
-            );
+            )
+            .add_systems(Last, idle::idle_throttle);
     }
 }
 
+fn ui_visible(st: Res<GraphState>) -> bool {
+    !st.ui.screenshot_hide_ui
+}
+
+/// Captures the primary window to a timestamped PNG. Runs before the UI systems so the
+/// panels can sit out the captured frame.
+fn take_screenshot(
+    mut st: ResMut<GraphState>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    window: Query<Entity, With<PrimaryWindow>>,
+) {
+    st.ui.screenshot_hide_ui = false;
+    if !std::mem::take(&mut st.ui.screenshot_requested) {
+        return;
+    }
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    let dir = Path::new(st.cfg.screenshot_dir.as_deref().unwrap_or_default());
+    let path = export::screenshot_path(dir, SystemTime::now());
+    let result = std::fs::create_dir_all(dir)
+        .map_err(|err| format!("cannot create {}: {err}", dir.display()))
+        .and_then(|()| {
+            screenshots
+                .save_screenshot_to_disk(window, &path)
+                .map_err(|err| err.to_string())
+        });
+    let notice = match result {
+        Ok(()) => {
+            st.ui.screenshot_hide_ui = !st.cfg.screenshot_include_ui;
+            format!("screenshot saved to {}", path.display())
+        }
+        Err(err) => format!("screenshot failed: {err}"),
+    };
+    st.ui.screenshot_notice = Some((notice, Instant::now()));
+}
+
 fn pump_network(mut st: ResMut<GraphState>, rx: Res<NetRx>) {
     for msg in rx.0.try_iter().take(100_000) {
+        if let Some(recorder) = st.net.recorder.as_ref() {
+            recorder.record(&msg);
+        }
         st.apply(msg);
     }
 }
@@ -81,15 +147,45 @@ fn process_net_commands(mut st: ResMut<GraphState>, net_tx: Res<NetTx>) {
                     }
                     continue;
                 };
-                let path = match &endpoint.kind {
-                    AgentEndpointKind::UdsPath(path) => path.clone(),
-                };
                 st.net.ensure_stream(&endpoint.name);
+                let addr = match endpoint.kind.connect_addr() {
+                    Ok(addr) => addr,
+                    Err(err) => {
+                        if let Some(stream) = st.net.streams.get_mut(&endpoint.name) {
+                            stream.status = NetStreamStatus::Disconnected;
+                            stream.last_error = Some(format!("{err:#}"));
+                        }
+                        continue;
+                    }
+                };
+                let token = match endpoint.resolved_token() {
+                    Ok(token) => token,
+                    Err(err) => {
+                        if let Some(stream) = st.net.streams.get_mut(&endpoint.name) {
+                            stream.status = NetStreamStatus::Disconnected;
+                            stream.last_error = Some(format!("{err:#}"));
+                        }
+                        continue;
+                    }
+                };
                 if let Some(stream) = st.net.streams.get_mut(&endpoint.name) {
                     stream.status = NetStreamStatus::Connecting;
                     stream.last_error = None;
                 }
-                let handle = net::spawn_reader(endpoint.name.clone(), path, net_tx.0.clone());
+                let resume = st
+                    .net
+                    .streams
+                    .get(&endpoint.name)
+                    .and_then(|stream| stream.resume_point());
+                let handle = net::spawn_reader(
+                    endpoint.name.clone(),
+                    addr,
+                    endpoint.compression,
+                    token,
+                    resume,
+                    st.cfg.heartbeat_timeout,
+                    net_tx.0.clone(),
+                );
                 st.net.connections.insert(endpoint.name.clone(), handle);
             }
             NetCommand::Disconnect(name) => {
@@ -98,18 +194,32 @@ fn process_net_commands(mut st: ResMut<GraphState>, net_tx: Res<NetTx>) {
                 }
                 if let Some(stream) = st.net.streams.get_mut(&name) {
                     stream.status = NetStreamStatus::Disconnected;
+                    stream.next_reconnect = None;
                 }
             }
             NetCommand::Reconnect(name) => {
                 if let Some(handle) = st.net.connections.remove(&name) {
                     handle.shutdown();
                 }
+                st.reset_reconnects(&name);
                 if let Some(stream) = st.net.streams.get_mut(&name) {
                     stream.status = NetStreamStatus::Connecting;
                     stream.last_error = None;
                 }
                 st.net.commands.push(NetCommand::Connect(name));
             }
+            NetCommand::Control(name, cmd) => {
+                let sent = st
+                    .net
+                    .connections
+                    .get(&name)
+                    .is_some_and(|handle| handle.send_control(cmd));
+                if !sent {
+                    if let Some(stream) = st.net.streams.get_mut(&name) {
+                        stream.last_error = Some("not connected".to_string());
+                    }
+                }
+            }
         }
     }
 }
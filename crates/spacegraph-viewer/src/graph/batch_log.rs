@@ -0,0 +1,103 @@
+use spacegraph_core::{Edge, NodeId};
+use std::collections::{HashSet, VecDeque};
+use std::time::Instant;
+
+/// Completed batches kept for the "last batch" window.
+pub const MAX_RECENT_BATCHES: usize = 5;
+/// Changes listed per batch; the rest are only counted.
+pub const MAX_BATCH_CHANGES: usize = 500;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchChange {
+    NodeUpsert(NodeId),
+    NodeRemove(NodeId),
+    EdgeUpsert(Edge),
+    EdgeRemove(Edge),
+}
+
+/// Frozen record of one batch, taken at `BatchEnd`.
+#[derive(Debug, Clone)]
+pub struct BatchSummary {
+    pub id: u64,
+    pub ended: Instant,
+    pub touched_nodes: Vec<NodeId>,
+    pub touched_edges: Vec<Edge>,
+    pub changes: Vec<BatchChange>,
+    /// Changes beyond `MAX_BATCH_CHANGES` that were not listed.
+    pub omitted: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct BatchLog {
+    pending: Vec<BatchChange>,
+    pending_omitted: usize,
+    pub recent: VecDeque<BatchSummary>,
+}
+
+impl BatchLog {
+    pub fn begin(&mut self) {
+        self.pending.clear();
+        self.pending_omitted = 0;
+    }
+
+    pub fn record(&mut self, change: BatchChange) {
+        if self.pending.len() < MAX_BATCH_CHANGES {
+            self.pending.push(change);
+        } else {
+            self.pending_omitted += 1;
+        }
+    }
+
+    /// Freezes the pending changes together with the batch's touched sets.
+    pub fn finish(
+        &mut self,
+        id: u64,
+        ended: Instant,
+        touched_nodes: &HashSet<NodeId>,
+        touched_edges: &HashSet<Edge>,
+    ) {
+        let mut nodes: Vec<NodeId> = touched_nodes.iter().cloned().collect();
+        nodes.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut edges: Vec<Edge> = touched_edges.iter().cloned().collect();
+        edges.sort_by(|a, b| a.from.0.cmp(&b.from.0).then_with(|| a.to.0.cmp(&b.to.0)));
+        self.recent.push_back(BatchSummary {
+            id,
+            ended,
+            touched_nodes: nodes,
+            touched_edges: edges,
+            changes: std::mem::take(&mut self.pending),
+            omitted: std::mem::take(&mut self.pending_omitted),
+        });
+        while self.recent.len() > MAX_RECENT_BATCHES {
+            self.recent.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.begin();
+        self.recent.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_only_recent_batches_and_caps_changes() {
+        let mut log = BatchLog::default();
+        let now = Instant::now();
+        for id in 0..(MAX_RECENT_BATCHES as u64 + 2) {
+            log.begin();
+            for n in 0..(MAX_BATCH_CHANGES + 3) {
+                log.record(BatchChange::NodeUpsert(NodeId(n.to_string())));
+            }
+            log.finish(id, now, &HashSet::new(), &HashSet::new());
+        }
+        assert_eq!(log.recent.len(), MAX_RECENT_BATCHES);
+        assert_eq!(log.recent.front().map(|b| b.id), Some(2));
+        let last = log.recent.back().unwrap();
+        assert_eq!(last.changes.len(), MAX_BATCH_CHANGES);
+        assert_eq!(last.omitted, 3);
+    }
+}
@@ -0,0 +1,200 @@
+use bevy::prelude::Vec3;
+use spacegraph_core::NodeId;
+use std::collections::{HashMap, HashSet};
+
+use crate::graph::layout::LAYOUT_SETTLED_STEP;
+use crate::graph::model::{AggEdgeKey, EdgeKindClass};
+use crate::graph::state::GraphState;
+
+/// Interior control points per bundled edge.
+const BUNDLE_POINTS: usize = 6;
+const BUNDLE_ITERATIONS: usize = 30;
+const BUNDLE_STEP: f32 = 0.1;
+/// Edges bundle only when roughly parallel (|cos| of their angle at least this).
+const MIN_ANGLE_COS: f32 = 0.8;
+/// Pairwise compatibility is quadratic; the rest of the edges stay straight.
+const MAX_BUNDLED_EDGES: usize = 400;
+
+/// Control points per aggregated edge, computed once the layout has settled.
+#[derive(Debug, Default)]
+pub struct EdgeBundles {
+    pub paths: HashMap<AggEdgeKey, Vec<Vec3>>,
+    ready: bool,
+    strength: f32,
+}
+
+impl EdgeBundles {
+    pub fn invalidate(&mut self) {
+        self.paths.clear();
+        self.ready = false;
+    }
+
+    /// Polyline for `key`, if it was bundled with exactly these endpoints.
+    pub fn path(&self, key: &AggEdgeKey, a: Vec3, b: Vec3) -> Option<&[Vec3]> {
+        let path = self.paths.get(key)?;
+        let (first, last) = (path.first()?, path.last()?);
+        (first.distance_squared(a) < 1e-6 && last.distance_squared(b) < 1e-6)
+            .then_some(path.as_slice())
+    }
+}
+
+/// Endpoints of `edges` plus `BUNDLE_POINTS` control points each, pulled towards the
+/// matching points of compatible edges (same class, similar direction, length and
+/// position) and smoothed by a spring along the edge. `strength` 0 keeps them straight.
+pub fn bundle_paths(edges: &[(Vec3, Vec3, EdgeKindClass)], strength: f32) -> Vec<Vec<Vec3>> {
+    let segments = BUNDLE_POINTS + 1;
+    let mut paths: Vec<Vec<Vec3>> = edges
+        .iter()
+        .map(|(a, b, _)| {
+            (0..=segments)
+                .map(|k| a.lerp(*b, k as f32 / segments as f32))
+                .collect()
+        })
+        .collect();
+    let strength = strength.clamp(0.0, 1.0);
+    if strength <= 0.0 || edges.len() < 2 {
+        return paths;
+    }
+
+    let partners: Vec<Vec<(usize, bool)>> = edges
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            edges
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .filter_map(|(j, b)| compatible(a, b).map(|flipped| (j, flipped)))
+                .collect()
+        })
+        .collect();
+
+    for _ in 0..BUNDLE_ITERATIONS {
+        let prev = paths.clone();
+        for (i, others) in partners.iter().enumerate() {
+            if others.is_empty() {
+                continue;
+            }
+            for k in 1..segments {
+                let p = prev[i][k];
+                let spring = (prev[i][k - 1] + prev[i][k + 1]) * 0.5 - p;
+                let pull = others
+                    .iter()
+                    .map(|&(j, flipped)| prev[j][if flipped { segments - k } else { k }] - p)
+                    .sum::<Vec3>()
+                    / others.len() as f32;
+                paths[i][k] = p + (pull * strength + spring) * BUNDLE_STEP;
+            }
+        }
+    }
+    paths
+}
+
+/// `Some(flipped)` when the edges may bundle; `flipped` means they point in opposite
+/// directions, so control points pair up in reverse order.
+fn compatible(a: &(Vec3, Vec3, EdgeKindClass), b: &(Vec3, Vec3, EdgeKindClass)) -> Option<bool> {
+    if a.2 != b.2 {
+        return None;
+    }
+    let (da, db) = (a.1 - a.0, b.1 - b.0);
+    let (la, lb) = (da.length(), db.length());
+    if la < 1e-3 || lb < 1e-3 || la.min(lb) / la.max(lb) < 0.5 {
+        return None;
+    }
+    let cos = da.dot(db) / (la * lb);
+    if cos.abs() < MIN_ANGLE_COS {
+        return None;
+    }
+    let mid_dist = ((a.0 + a.1) - (b.0 + b.1)).length() * 0.5;
+    (mid_dist <= (la + lb) * 0.5).then_some(cos < 0.0)
+}
+
+impl GraphState {
+    /// Drops bundles while the layout moves and recomputes them once it has settled.
+    pub fn refresh_edge_bundles(&mut self, vis: &HashSet<NodeId>) {
+        let strength = self.cfg.edge_bundling_strength;
+        let settled =
+            !self.spatial.dirty_layout && self.spatial.layout_motion <= LAYOUT_SETTLED_STEP;
+        let bundles = &mut self.spatial.bundles;
+        if !settled || bundles.strength != strength {
+            bundles.invalidate();
+            bundles.strength = strength;
+        }
+        if !settled || bundles.ready {
+            return;
+        }
+
+        let members = &self.spatial.collapsed.members;
+        let positions = &self.spatial.positions;
+        let mut keys: Vec<&AggEdgeKey> = self
+            .model
+            .agg_edges()
+            .map(|edge| &edge.key)
+            .filter(|key| key.from != key.to)
+            .filter(|key| vis.contains(&key.from) && vis.contains(&key.to))
+            .filter(|key| !members.contains_key(&key.from) && !members.contains_key(&key.to))
+            .filter(|key| positions.contains_key(&key.from) && positions.contains_key(&key.to))
+            .collect();
+        keys.sort_by(|a, b| (&a.from.0, &a.to.0).cmp(&(&b.from.0, &b.to.0)));
+        keys.truncate(MAX_BUNDLED_EDGES);
+
+        let edges: Vec<(Vec3, Vec3, EdgeKindClass)> = keys
+            .iter()
+            .map(|key| (positions[&key.from], positions[&key.to], key.class))
+            .collect();
+        let paths = bundle_paths(&edges, strength);
+        let bundles = &mut self.spatial.bundles;
+        bundles.paths = keys.into_iter().cloned().zip(paths).collect();
+        bundles.ready = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mid_gap(paths: &[Vec<Vec3>]) -> f32 {
+        let k = paths[0].len() / 2;
+        paths[0][k].distance(paths[1][k])
+    }
+
+    #[test]
+    fn parallel_edges_of_one_class_pull_together() {
+        let edges = [
+            (Vec3::ZERO, Vec3::new(10.0, 0.0, 0.0), EdgeKindClass::Opens),
+            (
+                Vec3::new(0.0, 0.0, 1.0),
+                Vec3::new(10.0, 0.0, 1.0),
+                EdgeKindClass::Opens,
+            ),
+        ];
+        let paths = bundle_paths(&edges, 1.0);
+
+        assert_eq!(paths[0].len(), BUNDLE_POINTS + 2);
+        assert_eq!(paths[0].first(), Some(&edges[0].0));
+        assert_eq!(paths[1].last(), Some(&edges[1].1));
+        assert!(mid_gap(&paths) < 0.1, "gap {}", mid_gap(&paths));
+
+        // Reversed direction bundles the same way, pairing points in reverse order.
+        let reversed = [edges[0], (edges[1].1, edges[1].0, edges[1].2)];
+        let paths = bundle_paths(&reversed, 1.0);
+        let k = paths[0].len() / 2;
+        assert!(paths[0][k].distance(paths[1][BUNDLE_POINTS + 1 - k]) < 0.1);
+    }
+
+    #[test]
+    fn other_classes_and_zero_strength_stay_straight() {
+        let edges = [
+            (Vec3::ZERO, Vec3::new(10.0, 0.0, 0.0), EdgeKindClass::Opens),
+            (
+                Vec3::new(0.0, 0.0, 1.0),
+                Vec3::new(10.0, 0.0, 1.0),
+                EdgeKindClass::Execs,
+            ),
+        ];
+        assert!((mid_gap(&bundle_paths(&edges, 1.0)) - 1.0).abs() < 1e-5);
+
+        let same_class = [edges[0], (edges[1].0, edges[1].1, EdgeKindClass::Opens)];
+        assert!((mid_gap(&bundle_paths(&same_class, 0.0)) - 1.0).abs() < 1e-5);
+    }
+}
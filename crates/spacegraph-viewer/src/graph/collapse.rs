@@ -0,0 +1,468 @@
+use bevy::prelude::Vec3;
+use spacegraph_core::{parse_node_id, FileCategory, FileKind, Node, NodeId, ParsedId};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::Ordering;
+
+use crate::graph::state::{GraphState, ViewMode};
+use crate::graph::tree::parent_path;
+use crate::util::ids::host_of;
+
+/// Synthetic id of the placeholder standing in for a collapsed directory.
+pub fn placeholder_id(dir: &str) -> NodeId {
+    NodeId(format!("collapsed:dir:{dir}"))
+}
+
+/// Synthetic id of the node standing in for same-path files of several hosts.
+pub fn merged_file_id(path: &str) -> NodeId {
+    NodeId(format!("merged:file:{path}"))
+}
+
+/// Path that file nodes merge under across hosts: the path of a `file:` id, else the
+/// node's own path (inode ids). Pseudo paths like `socket:[..]` are host-local.
+pub fn merge_key<'a>(id: &'a NodeId, node: &'a Node) -> Option<&'a str> {
+    let path = match (parse_node_id(id), node) {
+        (Some(ParsedId::File { path, .. }), Node::File { .. }) => path,
+        (_, Node::File { path, .. }) => path.as_str(),
+        _ => return None,
+    };
+    path.starts_with('/').then_some(path)
+}
+
+/// Outermost collapsed directory that contains `path`, if any.
+pub fn collapsed_dir_for(path: &str, collapsed: &HashSet<String>) -> Option<String> {
+    let mut found = None;
+    let mut cursor = parent_path(path);
+    while let Some(dir) = cursor {
+        cursor = parent_path(&dir);
+        if collapsed.contains(&dir) {
+            found = Some(dir);
+        }
+    }
+    found
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceholderKind {
+    /// Files below a collapsed directory.
+    Dir,
+    /// Same-path files from several hosts ("Merge files by path").
+    MergedFile,
+}
+
+#[derive(Debug, Clone)]
+pub struct Placeholder {
+    /// The collapsed directory, or the shared path of merged files.
+    pub path: String,
+    pub kind: PlaceholderKind,
+    /// Centroid of the collapsed file nodes.
+    pub pos: Vec3,
+    pub count: usize,
+}
+
+impl Placeholder {
+    /// Synthetic file node used for labels; never inserted into the model.
+    pub fn node(&self) -> Node {
+        Node::File {
+            path: self.path.clone(),
+            inode: 0,
+            kind: match self.kind {
+                PlaceholderKind::Dir => FileKind::Dir,
+                PlaceholderKind::MergedFile => FileKind::Regular,
+            },
+            category: FileCategory::Unknown,
+            link_target: None,
+        }
+    }
+}
+
+/// Display-only rewrite of the spatial view; the model is left untouched.
+#[derive(Debug, Default)]
+pub struct CollapsedView {
+    /// Collapsed or merged file node -> its placeholder id.
+    pub members: HashMap<NodeId, NodeId>,
+    pub placeholders: HashMap<NodeId, Placeholder>,
+}
+
+impl CollapsedView {
+    pub fn build(
+        nodes: &HashMap<NodeId, Node>,
+        vis: &HashSet<NodeId>,
+        positions: &HashMap<NodeId, Vec3>,
+        collapsed: &HashSet<String>,
+        merge_files: bool,
+    ) -> Self {
+        let mut view = Self::default();
+        if collapsed.is_empty() && !merge_files {
+            return view;
+        }
+        for id in vis.iter() {
+            let Some(Node::File { path, .. }) = nodes.get(id) else {
+                continue;
+            };
+            let Some(dir) = collapsed_dir_for(path, collapsed) else {
+                continue;
+            };
+            let Some(pos) = positions.get(id) else {
+                continue;
+            };
+            let pid = placeholder_id(&dir);
+            let entry = view
+                .placeholders
+                .entry(pid.clone())
+                .or_insert_with(|| Placeholder {
+                    path: dir,
+                    kind: PlaceholderKind::Dir,
+                    pos: Vec3::ZERO,
+                    count: 0,
+                });
+            entry.pos += *pos;
+            entry.count += 1;
+            view.members.insert(id.clone(), pid);
+        }
+        for p in view.placeholders.values_mut() {
+            p.pos /= p.count as f32;
+        }
+        if merge_files {
+            view.merge_files_by_path(nodes, vis, positions);
+        }
+        view
+    }
+
+    /// Folds visible files sharing a path across hosts into one placeholder each. Only one
+    /// file per host joins (the smallest id); further ids of the same host, e.g. inode ids
+    /// for one path, stay separate.
+    fn merge_files_by_path(
+        &mut self,
+        nodes: &HashMap<NodeId, Node>,
+        vis: &HashSet<NodeId>,
+        positions: &HashMap<NodeId, Vec3>,
+    ) {
+        let mut groups: HashMap<&str, BTreeMap<&str, (&NodeId, Vec3)>> = HashMap::new();
+        for id in vis.iter() {
+            if self.members.contains_key(id) {
+                continue;
+            }
+            let (Some(key), Some(pos)) = (
+                nodes.get(id).and_then(|node| merge_key(id, node)),
+                positions.get(id),
+            ) else {
+                continue;
+            };
+            groups
+                .entry(key)
+                .or_default()
+                .entry(host_of(id))
+                .and_modify(|first| {
+                    if id.0 < first.0 .0 {
+                        *first = (id, *pos);
+                    }
+                })
+                .or_insert((id, *pos));
+        }
+        for (path, per_host) in groups {
+            if per_host.len() < 2 {
+                continue;
+            }
+            let pid = merged_file_id(path);
+            let pos = per_host.values().map(|(_, pos)| *pos).sum::<Vec3>() / per_host.len() as f32;
+            self.placeholders.insert(
+                pid.clone(),
+                Placeholder {
+                    path: path.to_string(),
+                    kind: PlaceholderKind::MergedFile,
+                    pos,
+                    count: per_host.len(),
+                },
+            );
+            for (id, _) in per_host.into_values() {
+                self.members.insert(id.clone(), pid.clone());
+            }
+        }
+    }
+
+    pub fn position(&self, id: &NodeId, positions: &HashMap<NodeId, Vec3>) -> Option<Vec3> {
+        match self.members.get(id) {
+            Some(pid) => self.placeholders.get(pid).map(|p| p.pos),
+            None => positions.get(id).copied(),
+        }
+    }
+}
+
+impl GraphState {
+    /// Rebuilds `spatial.collapsed` for `vis` when a redraw is pending or the model changed.
+    /// Meshes only move on redraws, so in between the last view still matches the scene.
+    pub(crate) fn refresh_collapsed_view(&mut self, vis: &HashSet<NodeId>) {
+        if self.ui.view_mode != ViewMode::Spatial {
+            self.spatial.collapsed = CollapsedView::default();
+            self.spatial.collapsed_rev = None;
+            return;
+        }
+        let rev = self.model.revision();
+        if self.spatial.collapsed_rev == Some(rev) && !self.needs_redraw.load(Ordering::Relaxed) {
+            return;
+        }
+        self.spatial.collapsed = CollapsedView::build(
+            &self.model.nodes,
+            vis,
+            &self.spatial.positions,
+            &self.ui.collapsed_dirs,
+            self.cfg.merge_files_by_path,
+        );
+        self.spatial.collapsed_rev = Some(rev);
+    }
+}
+
+/// Endpoints after collapsing; `None` when both ends fold into the same placeholder.
+pub fn reroute<'a>(
+    from: &'a NodeId,
+    to: &'a NodeId,
+    members: &'a HashMap<NodeId, NodeId>,
+) -> Option<(&'a NodeId, &'a NodeId)> {
+    let from = members.get(from).unwrap_or(from);
+    let to = members.get(to).unwrap_or(to);
+    (from != to).then_some((from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::{Edge, EdgeKind};
+
+    fn id(s: &str) -> NodeId {
+        NodeId(s.to_string())
+    }
+
+    fn opens(from: &str, to: &str, fd: i32) -> Edge {
+        Edge {
+            from: id(from),
+            to: id(to),
+            kind: EdgeKind::Opens {
+                fd,
+                mode: "r".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn outermost_collapsed_ancestor_wins() {
+        let collapsed: HashSet<String> = ["/usr/lib".to_string(), "/usr/lib/x86_64".to_string()]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            collapsed_dir_for("/usr/lib/x86_64/libc.so", &collapsed),
+            Some("/usr/lib".to_string())
+        );
+        assert_eq!(collapsed_dir_for("/usr/bin/ls", &collapsed), None);
+        assert_eq!(collapsed_dir_for("/usr/lib", &collapsed), None);
+    }
+
+    #[test]
+    fn edges_into_collapsed_files_merge_onto_placeholder() {
+        let dir = placeholder_id("/usr/lib");
+        let members: HashMap<NodeId, NodeId> =
+            [(id("libc"), dir.clone()), (id("libm"), dir.clone())]
+                .into_iter()
+                .collect();
+        let edges = [
+            opens("p", "libc", 3),
+            opens("p", "libm", 4),
+            opens("p", "/etc/hosts", 5),
+            opens("libc", "libm", 6),
+        ];
+
+        let routed: HashSet<(&NodeId, &NodeId)> = edges
+            .iter()
+            .filter_map(|e| reroute(&e.from, &e.to, &members))
+            .collect();
+        let hosts = id("/etc/hosts");
+        let p = id("p");
+        let expected: HashSet<(&NodeId, &NodeId)> =
+            [(&p, &dir), (&p, &hosts)].into_iter().collect();
+        assert_eq!(routed, expected);
+        assert_eq!(reroute(&edges[3].from, &edges[3].to, &members), None);
+    }
+
+    #[test]
+    fn placeholder_sits_at_member_centroid() {
+        let file = |path: &str| Node::File {
+            path: path.to_string(),
+            inode: 0,
+            kind: FileKind::Regular,
+            category: FileCategory::Unknown,
+            link_target: None,
+        };
+        let nodes: HashMap<NodeId, Node> = [
+            (id("a"), file("/usr/lib/a.so")),
+            (id("b"), file("/usr/lib/b.so")),
+            (id("c"), file("/etc/c.conf")),
+        ]
+        .into_iter()
+        .collect();
+        let vis: HashSet<NodeId> = nodes.keys().cloned().collect();
+        let positions: HashMap<NodeId, Vec3> = [
+            (id("a"), Vec3::new(0.0, 0.0, 0.0)),
+            (id("b"), Vec3::new(2.0, 4.0, 0.0)),
+            (id("c"), Vec3::new(9.0, 9.0, 9.0)),
+        ]
+        .into_iter()
+        .collect();
+        let collapsed: HashSet<String> = ["/usr/lib".to_string()].into_iter().collect();
+
+        let view = CollapsedView::build(&nodes, &vis, &positions, &collapsed, false);
+        let pid = placeholder_id("/usr/lib");
+        assert_eq!(view.members.len(), 2);
+        assert_eq!(view.placeholders[&pid].count, 2);
+        assert_eq!(
+            view.position(&id("a"), &positions),
+            Some(Vec3::new(1.0, 2.0, 0.0))
+        );
+        assert_eq!(
+            view.position(&id("c"), &positions),
+            Some(Vec3::new(9.0, 9.0, 9.0))
+        );
+    }
+
+    #[test]
+    fn merge_key_is_the_parsed_path_of_real_files() {
+        let file = |path: &str| Node::File {
+            path: path.to_string(),
+            inode: 7,
+            kind: FileKind::Regular,
+            category: FileCategory::Unknown,
+            link_target: None,
+        };
+        let hosts = file("/etc/hosts");
+        assert_eq!(
+            merge_key(&id("a:file:/etc/hosts"), &hosts),
+            Some("/etc/hosts")
+        );
+        assert_eq!(merge_key(&id("b:inode:8:7"), &hosts), Some("/etc/hosts"));
+        assert_eq!(
+            merge_key(&id("a:file:socket:[42]"), &file("socket:[42]")),
+            None
+        );
+        let user = Node::User {
+            uid: 0,
+            name: "root".to_string(),
+        };
+        assert_eq!(merge_key(&id("a:user:0"), &user), None);
+    }
+
+    #[test]
+    fn same_path_files_of_two_hosts_merge_into_one_placeholder() {
+        let file = |path: &str| Node::File {
+            path: path.to_string(),
+            inode: 0,
+            kind: FileKind::Regular,
+            category: FileCategory::Unknown,
+            link_target: None,
+        };
+        let nodes: HashMap<NodeId, Node> = [
+            (id("a:file:/etc/hosts"), file("/etc/hosts")),
+            (id("b:file:/etc/hosts"), file("/etc/hosts")),
+            (id("b:file:/etc/passwd"), file("/etc/passwd")),
+        ]
+        .into_iter()
+        .collect();
+        let vis: HashSet<NodeId> = nodes.keys().cloned().collect();
+        let positions: HashMap<NodeId, Vec3> = [
+            (id("a:file:/etc/hosts"), Vec3::new(-2.0, 0.0, 0.0)),
+            (id("b:file:/etc/hosts"), Vec3::new(2.0, 0.0, 0.0)),
+            (id("b:file:/etc/passwd"), Vec3::new(5.0, 5.0, 5.0)),
+        ]
+        .into_iter()
+        .collect();
+
+        let view = CollapsedView::build(&nodes, &vis, &positions, &HashSet::new(), true);
+        let merged = merged_file_id("/etc/hosts");
+        assert_eq!(view.members.len(), 2);
+        assert_eq!(view.placeholders[&merged].kind, PlaceholderKind::MergedFile);
+        assert_eq!(
+            view.position(&id("b:file:/etc/hosts"), &positions),
+            Some(Vec3::ZERO)
+        );
+        assert!(!view.members.contains_key(&id("b:file:/etc/passwd")));
+        assert!(
+            CollapsedView::build(&nodes, &vis, &positions, &HashSet::new(), false)
+                .members
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn collapsed_view_is_rebuilt_only_on_redraws_and_model_changes() {
+        let mut st = GraphState::default();
+        let now = std::time::Instant::now();
+        for host in ["a", "b"] {
+            let file_id = id(&format!("{host}:file:/etc/hosts"));
+            st.model.upsert_node(
+                file_id.clone(),
+                Node::File {
+                    path: "/etc/hosts".to_string(),
+                    inode: 0,
+                    kind: FileKind::Regular,
+                    category: FileCategory::Unknown,
+                    link_target: None,
+                },
+                now,
+            );
+            st.spatial.positions.insert(file_id, Vec3::ZERO);
+        }
+        st.cfg.merge_files_by_path = true;
+        let vis: HashSet<NodeId> = st.model.nodes.keys().cloned().collect();
+        let merged = merged_file_id("/etc/hosts");
+        st.refresh_collapsed_view(&vis);
+        st.needs_redraw.store(false, Ordering::Relaxed);
+        assert_eq!(st.spatial.collapsed.placeholders[&merged].pos, Vec3::ZERO);
+
+        // nothing pending: the built view is kept
+        st.spatial
+            .positions
+            .insert(id("a:file:/etc/hosts"), Vec3::X * 2.0);
+        st.refresh_collapsed_view(&vis);
+        assert_eq!(st.spatial.collapsed.placeholders[&merged].pos, Vec3::ZERO);
+
+        st.needs_redraw.store(true, Ordering::Relaxed);
+        st.refresh_collapsed_view(&vis);
+        assert_eq!(st.spatial.collapsed.placeholders[&merged].pos, Vec3::X);
+
+        st.needs_redraw.store(false, Ordering::Relaxed);
+        st.model.remove_node(&id("b:file:/etc/hosts"));
+        st.refresh_collapsed_view(&vis);
+        assert!(st.spatial.collapsed.placeholders.is_empty());
+    }
+
+    #[test]
+    fn same_path_ids_of_one_host_do_not_merge() {
+        let file = |inode: u64| Node::File {
+            path: "/etc/hosts".to_string(),
+            inode,
+            kind: FileKind::Regular,
+            category: FileCategory::Unknown,
+            link_target: None,
+        };
+        let nodes: HashMap<NodeId, Node> = [
+            (id("a:inode:8:1"), file(1)),
+            (id("a:inode:8:2"), file(2)),
+            (id("b:inode:8:1"), file(1)),
+        ]
+        .into_iter()
+        .collect();
+        let positions: HashMap<NodeId, Vec3> =
+            nodes.keys().map(|id| (id.clone(), Vec3::ZERO)).collect();
+        let merged = merged_file_id("/etc/hosts");
+
+        // one host alone never merges
+        let one_host: HashSet<NodeId> = [id("a:inode:8:1"), id("a:inode:8:2")].into();
+        let view = CollapsedView::build(&nodes, &one_host, &positions, &HashSet::new(), true);
+        assert!(view.members.is_empty());
+
+        // with a second host, only one id per host joins
+        let all: HashSet<NodeId> = nodes.keys().cloned().collect();
+        let view = CollapsedView::build(&nodes, &all, &positions, &HashSet::new(), true);
+        assert_eq!(view.placeholders[&merged].count, 2);
+        assert_eq!(view.members.get(&id("a:inode:8:1")), Some(&merged));
+        assert_eq!(view.members.get(&id("b:inode:8:1")), Some(&merged));
+        assert!(!view.members.contains_key(&id("a:inode:8:2")));
+    }
+}
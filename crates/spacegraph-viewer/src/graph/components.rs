@@ -0,0 +1,148 @@
+use spacegraph_core::NodeId;
+use std::collections::HashMap;
+
+use crate::graph::model::GraphModel;
+
+/// Connected components of the undirected graph; singletons count as their own component.
+#[derive(Debug, Default)]
+pub struct Components {
+    pub by_node: HashMap<NodeId, usize>,
+    pub count: usize,
+}
+
+impl Components {
+    pub fn component_of(&self, id: &NodeId) -> Option<usize> {
+        self.by_node.get(id).copied()
+    }
+}
+
+struct DisjointSet {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+}
+
+impl DisjointSet {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+        }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra == rb {
+            return;
+        }
+        match self.rank[ra].cmp(&self.rank[rb]) {
+            std::cmp::Ordering::Less => self.parent[ra] = rb,
+            std::cmp::Ordering::Greater => self.parent[rb] = ra,
+            std::cmp::Ordering::Equal => {
+                self.parent[rb] = ra;
+                self.rank[ra] += 1;
+            }
+        }
+    }
+}
+
+/// Labels components with dense ids, numbered in node-id order for stable output.
+/// Edges pointing at unknown nodes are ignored.
+pub fn label_components(model: &GraphModel) -> Components {
+    let mut ids: Vec<&NodeId> = model.nodes.keys().collect();
+    ids.sort_by(|a, b| a.0.cmp(&b.0));
+    let index: HashMap<&NodeId, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+    let mut set = DisjointSet::new(ids.len());
+    for e in model.edges.iter() {
+        if let (Some(&a), Some(&b)) = (index.get(&e.from), index.get(&e.to)) {
+            set.union(a, b);
+        }
+    }
+
+    let mut label_of_root: HashMap<usize, usize> = HashMap::new();
+    let mut by_node = HashMap::with_capacity(ids.len());
+    for (i, id) in ids.iter().enumerate() {
+        let root = set.find(i);
+        let next = label_of_root.len();
+        let label = *label_of_root.entry(root).or_insert(next);
+        by_node.insert((*id).clone(), label);
+    }
+
+    Components {
+        count: label_of_root.len(),
+        by_node,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::{Edge, EdgeKind, Node};
+    use std::time::Instant;
+
+    fn user(model: &mut GraphModel, id: &str) -> NodeId {
+        let id = NodeId(id.to_string());
+        model.upsert_node(
+            id.clone(),
+            Node::User {
+                uid: 0,
+                name: id.0.clone(),
+            },
+            Instant::now(),
+        );
+        id
+    }
+
+    fn link(model: &mut GraphModel, a: &NodeId, b: &NodeId) {
+        model.upsert_edge(
+            Edge {
+                from: a.clone(),
+                to: b.clone(),
+                kind: EdgeKind::RunsAs,
+            },
+            Instant::now(),
+        );
+    }
+
+    #[test]
+    fn two_clusters_and_a_singleton_get_distinct_labels() {
+        let mut model = GraphModel::default();
+        let a = user(&mut model, "a");
+        let b = user(&mut model, "b");
+        let c = user(&mut model, "c");
+        let x = user(&mut model, "x");
+        let y = user(&mut model, "y");
+        let lone = user(&mut model, "z");
+        link(&mut model, &a, &b);
+        link(&mut model, &c, &b);
+        link(&mut model, &y, &x);
+
+        let comps = label_components(&model);
+        assert_eq!(comps.count, 3);
+        assert_eq!(comps.component_of(&a), Some(0));
+        assert_eq!(comps.component_of(&b), Some(0));
+        assert_eq!(comps.component_of(&c), Some(0));
+        assert_eq!(comps.component_of(&x), Some(1));
+        assert_eq!(comps.component_of(&y), Some(1));
+        assert_eq!(comps.component_of(&lone), Some(2));
+    }
+
+    #[test]
+    fn dangling_edges_are_ignored() {
+        let mut model = GraphModel::default();
+        let a = user(&mut model, "a");
+        link(&mut model, &a, &NodeId("gone".to_string()));
+
+        let comps = label_components(&model);
+        assert_eq!(comps.count, 1);
+        assert_eq!(comps.component_of(&NodeId("gone".to_string())), None);
+    }
+}
@@ -0,0 +1,925 @@
+use spacegraph_core::{
+    id_file, id_process, id_user, parse_node_id, Delta, Edge, EdgeKind, FileCategory, FileKind,
+    Msg, Node, NodeId, ParsedId,
+};
+use std::sync::atomic::Ordering;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::graph::state::{GraphState, IMPORT_STREAM};
+use crate::graph::TimelineEvtKind;
+use crate::net::Incoming;
+use crate::util::config::DemoScenario;
+
+/// Host prefix of every synthetic node id.
+pub const DEMO_HOST: &str = "demo";
+
+const USERS: [(u32, &str); 3] = [(0, "root"), (33, "www-data"), (1000, "demo")];
+const BINARIES: [&str; 6] = [
+    "/usr/bin/bash",
+    "/usr/bin/python3",
+    "/usr/sbin/nginx",
+    "/usr/bin/vim",
+    "/usr/lib/postgresql/bin/postgres",
+    "/usr/bin/cargo",
+];
+const FILE_DIRS: [(&str, &str, FileCategory); 4] = [
+    ("/etc/demo", "conf", FileCategory::Config),
+    ("/var/log/demo", "log", FileCategory::Log),
+    ("/home/demo/data", "csv", FileCategory::Data),
+    ("/home/demo/bin", "sh", FileCategory::Script),
+];
+const FD_MODES: [&str; 3] = ["r", "w", "rw"];
+
+/// Larger bursts are split so single batches stay readable in the timeline.
+const MAX_EVENTS_PER_BATCH: usize = 50;
+/// A stalled frame must not turn into one huge burst.
+const MAX_EVENTS_PER_TICK: usize = 1000;
+
+/// SplitMix64; good enough for synthetic churn and stable for a given seed.
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n.max(1) as u64) as usize
+    }
+}
+
+pub fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+/// Synthesizes a process/file/user graph and keeps it changing with batched deltas.
+#[derive(Debug, Clone)]
+pub struct DemoGenerator {
+    rng: Rng,
+    next_pid: i32,
+    next_inode: u64,
+    next_batch: u64,
+    next_fd: i32,
+    processes: Vec<NodeId>,
+    files: Vec<NodeId>,
+    open: Vec<Edge>,
+    pending: f32,
+    script: Option<ScriptPlayer>,
+}
+
+impl DemoGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Rng(seed),
+            next_pid: 1000,
+            next_inode: 1,
+            next_batch: 1,
+            next_fd: 3,
+            processes: Vec::new(),
+            files: Vec::new(),
+            open: Vec::new(),
+            pending: 0.0,
+            script: None,
+        }
+    }
+
+    /// Replays `script` on a loop instead of random churn.
+    pub fn scripted(script: Script) -> Self {
+        Self {
+            script: Some(ScriptPlayer::new(script)),
+            ..Self::new(0)
+        }
+    }
+
+    /// Initial graph with about `entities` processes and files.
+    pub fn snapshot(&mut self, entities: usize) -> (Vec<(NodeId, Node)>, Vec<Edge>) {
+        let mut out = Vec::new();
+        if let Some(player) = self.script.as_ref() {
+            out = player.base.clone();
+        } else {
+            self.random_snapshot(entities, &mut out);
+        }
+
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        for delta in out {
+            match delta {
+                Delta::UpsertNode { id, node } => nodes.push((id, node)),
+                Delta::UpsertEdge { edge } => edges.push(edge),
+                _ => {}
+            }
+        }
+        (nodes, edges)
+    }
+
+    fn random_snapshot(&mut self, entities: usize, out: &mut Vec<Delta>) {
+        for (uid, name) in USERS {
+            out.push(Delta::UpsertNode {
+                id: id_user(DEMO_HOST, uid),
+                node: Node::User {
+                    uid,
+                    name: name.to_string(),
+                },
+            });
+        }
+        for exe in BINARIES {
+            out.push(Delta::UpsertNode {
+                id: id_file(DEMO_HOST, exe),
+                node: self.file_node(exe, FileCategory::Binary),
+            });
+        }
+        let entities = entities.max(2);
+        for _ in 0..entities * 2 / 5 {
+            self.spawn_process(out);
+        }
+        while self.processes.len() + self.files.len() < entities {
+            self.create_file(out);
+        }
+        for _ in 0..entities {
+            self.open_fd(out);
+        }
+    }
+
+    /// Deltas for `dt` seconds at `rate` events per second, wrapped in batches. Churn
+    /// steers the number of processes and files towards `entities`. Scripted scenarios
+    /// keep their own timing and ignore both.
+    pub fn tick(&mut self, dt: f32, rate: f32, entities: usize) -> Vec<Delta> {
+        if let Some(player) = self.script.as_mut() {
+            return player.tick(dt, &mut self.next_batch);
+        }
+        self.pending += rate.max(0.0) * dt.max(0.0);
+        let due = self.pending.floor() as usize;
+        self.pending -= due as f32;
+        let mut remaining = due.min(MAX_EVENTS_PER_TICK);
+
+        let mut out = Vec::new();
+        while remaining > 0 {
+            let n = remaining.min(MAX_EVENTS_PER_BATCH);
+            let id = self.next_batch;
+            self.next_batch += 1;
+            out.push(Delta::BatchBegin { id });
+            for _ in 0..n {
+                self.step(entities, &mut out);
+            }
+            out.push(Delta::BatchEnd { id });
+            remaining -= n;
+        }
+        out
+    }
+
+    fn step(&mut self, entities: usize, out: &mut Vec<Delta>) {
+        let grow = self.processes.len() + self.files.len() < entities;
+        match self.rng.below(100) {
+            0..=39 => self.open_fd(out),
+            40..=69 => self.close_fd(out),
+            70..=84 if grow => self.spawn_process(out),
+            70..=84 => self.exit_process(out),
+            _ if grow => self.create_file(out),
+            _ => self.remove_file(out),
+        }
+    }
+
+    fn file_node(&mut self, path: &str, category: FileCategory) -> Node {
+        let inode = self.next_inode;
+        self.next_inode += 1;
+        Node::File {
+            path: path.to_string(),
+            inode,
+            kind: FileKind::Regular,
+            category,
+            link_target: None,
+        }
+    }
+
+    fn spawn_process(&mut self, out: &mut Vec<Delta>) {
+        let pid = self.next_pid;
+        self.next_pid += 1;
+        let id = id_process(DEMO_HOST, pid, None);
+        let (uid, _) = USERS[self.rng.below(USERS.len())];
+        let exe = BINARIES[self.rng.below(BINARIES.len())];
+        // Every other process forks from an existing one; the rest hang off init.
+        let parent = (!self.processes.is_empty() && self.rng.below(2) == 0)
+            .then(|| self.processes[self.rng.below(self.processes.len())].clone());
+        let ppid = match parent.as_ref().and_then(parse_node_id) {
+            Some(ParsedId::Process { pid, .. }) => pid,
+            _ => 1,
+        };
+
+        out.push(Delta::UpsertNode {
+            id: id.clone(),
+            node: Node::Process {
+                pid,
+                ppid,
+                exe: exe.to_string(),
+                cmdline: format!("{exe} --job {pid}"),
+                uid,
+                rss_kb: 1024 * (1 + self.rng.below(256) as u64),
+                utime_ticks: self.rng.below(1000) as u64,
+                container: None,
+            },
+        });
+        out.push(Delta::UpsertEdge {
+            edge: Edge {
+                from: id.clone(),
+                to: id_user(DEMO_HOST, uid),
+                kind: EdgeKind::RunsAs,
+            },
+        });
+        out.push(Delta::UpsertEdge {
+            edge: Edge {
+                from: id.clone(),
+                to: id_file(DEMO_HOST, exe),
+                kind: EdgeKind::Execs,
+            },
+        });
+        if let Some(parent) = parent {
+            out.push(Delta::UpsertEdge {
+                edge: Edge {
+                    from: parent,
+                    to: id.clone(),
+                    kind: EdgeKind::ParentOf,
+                },
+            });
+        }
+        self.processes.push(id);
+    }
+
+    fn exit_process(&mut self, out: &mut Vec<Delta>) {
+        if self.processes.len() <= 1 {
+            return self.spawn_process(out);
+        }
+        let id = self
+            .processes
+            .swap_remove(self.rng.below(self.processes.len()));
+        // Removing the node drops its edges, as with a real agent.
+        self.open.retain(|edge| edge.from != id);
+        out.push(Delta::RemoveNode { id });
+    }
+
+    fn create_file(&mut self, out: &mut Vec<Delta>) {
+        let (dir, ext, category) = FILE_DIRS[self.rng.below(FILE_DIRS.len())];
+        let path = format!("{dir}/file-{}.{ext}", self.next_inode);
+        let id = id_file(DEMO_HOST, &path);
+        let node = self.file_node(&path, category);
+        out.push(Delta::UpsertNode {
+            id: id.clone(),
+            node,
+        });
+        self.files.push(id);
+    }
+
+    fn remove_file(&mut self, out: &mut Vec<Delta>) {
+        if self.files.len() <= 1 {
+            return self.create_file(out);
+        }
+        let id = self.files.swap_remove(self.rng.below(self.files.len()));
+        self.open.retain(|edge| edge.to != id);
+        out.push(Delta::RemoveNode { id });
+    }
+
+    fn open_fd(&mut self, out: &mut Vec<Delta>) {
+        if self.processes.is_empty() {
+            return self.spawn_process(out);
+        }
+        if self.files.is_empty() {
+            return self.create_file(out);
+        }
+        let fd = self.next_fd;
+        self.next_fd = if fd >= 1023 { 3 } else { fd + 1 };
+        let edge = Edge {
+            from: self.processes[self.rng.below(self.processes.len())].clone(),
+            to: self.files[self.rng.below(self.files.len())].clone(),
+            kind: EdgeKind::Opens {
+                fd,
+                mode: FD_MODES[self.rng.below(FD_MODES.len())].to_string(),
+            },
+        };
+        if self.open.contains(&edge) {
+            return;
+        }
+        self.open.push(edge.clone());
+        out.push(Delta::UpsertEdge { edge });
+    }
+
+    fn close_fd(&mut self, out: &mut Vec<Delta>) {
+        if self.open.is_empty() {
+            return self.open_fd(out);
+        }
+        let edge = self.open.swap_remove(self.rng.below(self.open.len()));
+        out.push(Delta::RemoveEdge { edge });
+    }
+}
+
+/// One step of a scenario: `deltas` go out as one batch `delay` seconds after the
+/// previous step.
+#[derive(Debug, Clone)]
+pub struct ScriptedBatch {
+    pub delay: f32,
+    pub deltas: Vec<Delta>,
+}
+
+/// A canned scenario: the graph it starts from and the batches it loops over. Each loop
+/// removes what it created, so every replay starts from the same graph.
+#[derive(Debug, Clone)]
+pub struct Script {
+    pub base: Vec<Delta>,
+    pub batches: Vec<ScriptedBatch>,
+}
+
+#[derive(Debug, Clone)]
+struct ScriptPlayer {
+    base: Vec<Delta>,
+    batches: Vec<ScriptedBatch>,
+    next: usize,
+    wait: f32,
+}
+
+impl ScriptPlayer {
+    fn new(script: Script) -> Self {
+        let wait = script.batches.first().map_or(0.0, |b| b.delay);
+        Self {
+            base: script.base,
+            batches: script.batches,
+            next: 0,
+            wait,
+        }
+    }
+
+    /// Batches that became due within `dt`; a stalled frame replays at most one loop.
+    fn tick(&mut self, dt: f32, next_batch: &mut u64) -> Vec<Delta> {
+        let mut out = Vec::new();
+        if self.batches.is_empty() {
+            return out;
+        }
+        self.wait -= dt.max(0.0);
+        for _ in 0..self.batches.len() {
+            if self.wait > 0.0 {
+                break;
+            }
+            let id = *next_batch;
+            *next_batch += 1;
+            out.push(Delta::BatchBegin { id });
+            out.extend(self.batches[self.next].deltas.iter().cloned());
+            out.push(Delta::BatchEnd { id });
+            self.next = (self.next + 1) % self.batches.len();
+            self.wait += self.batches[self.next].delay;
+        }
+        self.wait = self.wait.max(0.0);
+        out
+    }
+}
+
+/// The script behind `scenario`; `None` for random churn.
+pub fn scenario_script(scenario: DemoScenario) -> Option<Script> {
+    match scenario {
+        DemoScenario::Random => None,
+        DemoScenario::ForkBomb => Some(fork_bomb()),
+        DemoScenario::LogRotation => Some(log_rotation()),
+        DemoScenario::ManyFiles => Some(many_files()),
+    }
+}
+
+fn base_users() -> Vec<Delta> {
+    USERS
+        .iter()
+        .map(|&(uid, name)| Delta::UpsertNode {
+            id: id_user(DEMO_HOST, uid),
+            node: Node::User {
+                uid,
+                name: name.to_string(),
+            },
+        })
+        .collect()
+}
+
+fn scripted_file(path: &str, inode: u64, category: FileCategory) -> Delta {
+    Delta::UpsertNode {
+        id: id_file(DEMO_HOST, path),
+        node: Node::File {
+            path: path.to_string(),
+            inode,
+            kind: FileKind::Regular,
+            category,
+            link_target: None,
+        },
+    }
+}
+
+/// Process node plus its `runs_as`, `execs` and (with a parent) `parent_of` edges. The
+/// user and the binary must already be part of the graph.
+fn scripted_spawn(pid: i32, parent: Option<i32>, exe: &str, uid: u32, args: &str) -> Vec<Delta> {
+    let id = id_process(DEMO_HOST, pid, None);
+    let mut out = vec![
+        Delta::UpsertNode {
+            id: id.clone(),
+            node: Node::Process {
+                pid,
+                ppid: parent.unwrap_or(1),
+                exe: exe.to_string(),
+                cmdline: format!("{exe} {args}"),
+                uid,
+                rss_kb: 2048,
+                utime_ticks: 0,
+                container: None,
+            },
+        },
+        scripted_edge(&id, &id_user(DEMO_HOST, uid), EdgeKind::RunsAs),
+        scripted_edge(&id, &id_file(DEMO_HOST, exe), EdgeKind::Execs),
+    ];
+    if let Some(ppid) = parent {
+        out.push(scripted_edge(
+            &id_process(DEMO_HOST, ppid, None),
+            &id,
+            EdgeKind::ParentOf,
+        ));
+    }
+    out
+}
+
+fn scripted_edge(from: &NodeId, to: &NodeId, kind: EdgeKind) -> Delta {
+    Delta::UpsertEdge {
+        edge: Edge {
+            from: from.clone(),
+            to: to.clone(),
+            kind,
+        },
+    }
+}
+
+fn opens(pid: i32, path: &str, fd: i32, mode: &str) -> Edge {
+    Edge {
+        from: id_process(DEMO_HOST, pid, None),
+        to: id_file(DEMO_HOST, path),
+        kind: EdgeKind::Opens {
+            fd,
+            mode: mode.to_string(),
+        },
+    }
+}
+
+/// A shell forks in doubling waves (32 processes), then everything is killed in one
+/// batch: a glow burst and a large remove.
+fn fork_bomb() -> Script {
+    const SHELL: i32 = 100;
+    const BASH: &str = "/usr/bin/bash";
+    let mut base = base_users();
+    base.push(scripted_file(BASH, 1, FileCategory::Binary));
+    base.extend(scripted_spawn(SHELL, None, BASH, 1000, "-i"));
+
+    let mut batches = Vec::new();
+    let mut alive = vec![SHELL];
+    let mut next_pid = 2000;
+    for wave in 0..6 {
+        let parents = if wave == 0 {
+            vec![SHELL]
+        } else {
+            alive[1..].to_vec()
+        };
+        let mut deltas = Vec::new();
+        for parent in parents {
+            deltas.extend(scripted_spawn(
+                next_pid,
+                Some(parent),
+                BASH,
+                1000,
+                "-c ':|:&'",
+            ));
+            alive.push(next_pid);
+            next_pid += 1;
+        }
+        batches.push(ScriptedBatch {
+            delay: if wave == 0 { 3.0 } else { 0.4 },
+            deltas,
+        });
+    }
+    batches.push(ScriptedBatch {
+        delay: 1.5,
+        deltas: alive[1..]
+            .iter()
+            .map(|&pid| Delta::RemoveNode {
+                id: id_process(DEMO_HOST, pid, None),
+            })
+            .collect(),
+    });
+    Script { base, batches }
+}
+
+/// logrotate renames, compresses and recreates an nginx access log; the rotated files
+/// are left without edges for GC to pick up until the next round expires them.
+fn log_rotation() -> Script {
+    const NGINX: i32 = 300;
+    const LOGROTATE: i32 = 400;
+    const GZIP: i32 = 401;
+    const NGINX_BIN: &str = "/usr/sbin/nginx";
+    const LOGROTATE_BIN: &str = "/usr/sbin/logrotate";
+    const GZIP_BIN: &str = "/usr/bin/gzip";
+    const LOG: &str = "/var/log/demo/access.log";
+    const LOG_1: &str = "/var/log/demo/access.log.1";
+    const LOG_2: &str = "/var/log/demo/access.log.2.gz";
+
+    let mut base = base_users();
+    for (inode, exe) in [(1, NGINX_BIN), (2, LOGROTATE_BIN), (3, GZIP_BIN)] {
+        base.push(scripted_file(exe, inode, FileCategory::Binary));
+    }
+    base.push(scripted_file(LOG, 10, FileCategory::Log));
+    base.push(scripted_file(LOG_1, 11, FileCategory::Log));
+    base.extend(scripted_spawn(
+        NGINX,
+        None,
+        NGINX_BIN,
+        33,
+        "-g 'daemon off;'",
+    ));
+    base.push(Delta::UpsertEdge {
+        edge: opens(NGINX, LOG, 3, "w"),
+    });
+
+    let write = |delay| ScriptedBatch {
+        delay,
+        deltas: vec![Delta::UpsertEdge {
+            edge: opens(NGINX, LOG, 3, "w"),
+        }],
+    };
+    let mut rotate = scripted_spawn(LOGROTATE, None, LOGROTATE_BIN, 0, "/etc/logrotate.conf");
+    rotate.push(scripted_file(LOG_1, 11, FileCategory::Log));
+    rotate.push(Delta::UpsertEdge {
+        edge: opens(LOGROTATE, LOG_1, 3, "r"),
+    });
+    let mut compress = scripted_spawn(GZIP, Some(LOGROTATE), GZIP_BIN, 0, LOG_1);
+    compress.push(scripted_file(LOG_2, 12, FileCategory::Log));
+    compress.push(Delta::UpsertEdge {
+        edge: opens(GZIP, LOG_2, 3, "w"),
+    });
+    compress.push(Delta::RemoveNode {
+        id: id_file(DEMO_HOST, LOG_1),
+    });
+    let batches = vec![
+        write(1.0),
+        write(1.0),
+        write(1.0),
+        ScriptedBatch {
+            delay: 1.0,
+            deltas: rotate,
+        },
+        ScriptedBatch {
+            delay: 0.5,
+            deltas: compress,
+        },
+        // rename: nginx keeps writing the old file until it reopens
+        ScriptedBatch {
+            delay: 0.5,
+            deltas: vec![
+                Delta::RemoveNode {
+                    id: id_file(DEMO_HOST, LOG),
+                },
+                scripted_file(LOG_1, 10, FileCategory::Log),
+                scripted_file(LOG, 13, FileCategory::Log),
+                Delta::UpsertEdge {
+                    edge: opens(NGINX, LOG_1, 3, "w"),
+                },
+            ],
+        },
+        ScriptedBatch {
+            delay: 0.5,
+            deltas: vec![
+                Delta::RemoveEdge {
+                    edge: opens(NGINX, LOG_1, 3, "w"),
+                },
+                Delta::UpsertEdge {
+                    edge: opens(NGINX, LOG, 3, "w"),
+                },
+                Delta::RemoveNode {
+                    id: id_process(DEMO_HOST, GZIP, None),
+                },
+            ],
+        },
+        ScriptedBatch {
+            delay: 0.5,
+            deltas: vec![Delta::RemoveNode {
+                id: id_process(DEMO_HOST, LOGROTATE, None),
+            }],
+        },
+        write(2.0),
+        ScriptedBatch {
+            delay: 2.0,
+            deltas: vec![Delta::RemoveNode {
+                id: id_file(DEMO_HOST, LOG_2),
+            }],
+        },
+    ];
+    Script { base, batches }
+}
+
+/// rsync opens 240 files in bursts of 20, closes them again and exits; the files are
+/// deleted a little later.
+fn many_files() -> Script {
+    const RSYNC: i32 = 500;
+    const RSYNC_BIN: &str = "/usr/bin/rsync";
+    const BURSTS: usize = 12;
+    const PER_BURST: usize = 20;
+
+    let mut base = base_users();
+    base.push(scripted_file(RSYNC_BIN, 1, FileCategory::Binary));
+
+    let path = |i: usize| format!("/home/demo/data/sync/file-{i:03}.csv");
+    let fd = |i: usize| 3 + i as i32;
+    let mut batches = vec![ScriptedBatch {
+        delay: 2.0,
+        deltas: scripted_spawn(
+            RSYNC,
+            None,
+            RSYNC_BIN,
+            1000,
+            "-a /home/demo/data/sync backup:",
+        ),
+    }];
+    for burst in 0..BURSTS {
+        let mut deltas = Vec::new();
+        for i in burst * PER_BURST..(burst + 1) * PER_BURST {
+            deltas.push(scripted_file(&path(i), 100 + i as u64, FileCategory::Data));
+            deltas.push(Delta::UpsertEdge {
+                edge: opens(RSYNC, &path(i), fd(i), "r"),
+            });
+        }
+        batches.push(ScriptedBatch {
+            delay: 0.25,
+            deltas,
+        });
+    }
+    for chunk in (0..BURSTS * PER_BURST)
+        .collect::<Vec<_>>()
+        .chunks(PER_BURST * 2)
+    {
+        batches.push(ScriptedBatch {
+            delay: 0.3,
+            deltas: chunk
+                .iter()
+                .map(|&i| Delta::RemoveEdge {
+                    edge: opens(RSYNC, &path(i), fd(i), "r"),
+                })
+                .collect(),
+        });
+    }
+    batches.push(ScriptedBatch {
+        delay: 0.5,
+        deltas: vec![Delta::RemoveNode {
+            id: id_process(DEMO_HOST, RSYNC, None),
+        }],
+    });
+    batches.push(ScriptedBatch {
+        delay: 3.0,
+        deltas: (0..BURSTS * PER_BURST)
+            .map(|i| Delta::RemoveNode {
+                id: id_file(DEMO_HOST, &path(i)),
+            })
+            .collect(),
+    });
+    Script { base, batches }
+}
+
+impl GraphState {
+    pub(crate) fn load_demo_graph(&mut self) {
+        self.clear();
+        let now = Instant::now();
+        let mut demo = match scenario_script(self.cfg.demo_scenario) {
+            Some(script) => DemoGenerator::scripted(script),
+            None => DemoGenerator::new(self.cfg.demo_seed.unwrap_or_else(random_seed)),
+        };
+        let (nodes, edges) = demo.snapshot(self.cfg.demo_entities);
+
+        self.model.load_snapshot(nodes, edges, now);
+        let node_ids: Vec<_> = self.model.nodes.keys().cloned().collect();
+        for id in node_ids {
+            self.push_timeline_at(now, TimelineEvtKind::NodeUpsert, Some(id), None, None);
+        }
+        let edges: Vec<_> = self.model.edges.iter().cloned().collect();
+        for edge in edges {
+            self.push_timeline_at(
+                now,
+                TimelineEvtKind::EdgeUpsert,
+                Some(edge.from),
+                Some(edge.to),
+                Some(edge.kind),
+            );
+        }
+
+        self.demo = Some(demo);
+        self.demo_loaded = true;
+        self.spatial.dirty_layout = true;
+        self.needs_redraw.store(true, Ordering::Relaxed);
+    }
+
+    /// Feeds demo churn through the same path as agent events.
+    pub fn tick_demo(&mut self, dt: f32) {
+        if !self.cfg.demo_mode {
+            return;
+        }
+        let (rate, entities) = (self.cfg.demo_rate, self.cfg.demo_entities);
+        let Some(demo) = self.demo.as_mut() else {
+            return;
+        };
+        for delta in demo.tick(dt, rate, entities) {
+            self.apply(Incoming::event(
+                IMPORT_STREAM.to_string(),
+                Msg::Event { delta },
+            ));
+        }
+    }
+
+    /// Rebuilds the demo graph, e.g. after changing the seed.
+    pub fn restart_demo(&mut self) {
+        if self.cfg.demo_mode && self.demo_loaded {
+            self.load_demo_graph();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn ticks_produce_well_formed_batches() {
+        let mut demo = DemoGenerator::new(42);
+        let (nodes, edges) = demo.snapshot(40);
+        let mut live_nodes: HashSet<NodeId> = nodes.into_iter().map(|(id, _)| id).collect();
+        let mut live_edges: HashSet<Edge> = edges.into_iter().collect();
+        assert!(live_edges
+            .iter()
+            .all(|e| live_nodes.contains(&e.from) && live_nodes.contains(&e.to)));
+
+        let mut open_batch = None;
+        let mut last_batch = 0;
+        let mut events = 0;
+        for _ in 0..200 {
+            for delta in demo.tick(0.1, 300.0, 40) {
+                match delta {
+                    Delta::BatchBegin { id } => {
+                        assert!(open_batch.is_none(), "nested batch");
+                        assert!(id > last_batch, "batch ids must increase");
+                        open_batch = Some(id);
+                        last_batch = id;
+                    }
+                    Delta::BatchEnd { id } => {
+                        assert_eq!(open_batch.take(), Some(id));
+                    }
+                    delta => {
+                        assert!(open_batch.is_some(), "delta outside a batch");
+                        events += 1;
+                        match delta {
+                            Delta::UpsertNode { id, .. } => {
+                                assert!(live_nodes.insert(id), "node upserted twice");
+                            }
+                            Delta::RemoveNode { id } => {
+                                assert!(live_nodes.remove(&id), "unknown node removed");
+                                live_edges.retain(|e| e.from != id && e.to != id);
+                            }
+                            Delta::UpsertEdge { edge } => {
+                                assert!(live_nodes.contains(&edge.from));
+                                assert!(live_nodes.contains(&edge.to));
+                                live_edges.insert(edge);
+                            }
+                            Delta::RemoveEdge { edge } => {
+                                assert!(live_edges.remove(&edge), "unknown edge removed");
+                            }
+                            Delta::BatchBegin { .. } | Delta::BatchEnd { .. } => unreachable!(),
+                        }
+                    }
+                }
+            }
+        }
+        assert!(open_batch.is_none());
+        // 200 ticks of 0.1 s at 300/s; spawns add edges on top of their node.
+        assert!(events >= 5900);
+        assert!(last_batch >= 6000 / MAX_EVENTS_PER_BATCH as u64);
+    }
+
+    #[test]
+    fn same_seed_replays_the_same_churn() {
+        let run = |seed| {
+            let mut demo = DemoGenerator::new(seed);
+            let snapshot = demo.snapshot(20);
+            let deltas: Vec<_> = (0..20).flat_map(|_| demo.tick(0.05, 100.0, 20)).collect();
+            serde_json::to_string(&(snapshot, deltas)).expect("serialize")
+        };
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    #[test]
+    fn rate_accumulates_fractional_events() {
+        let mut demo = DemoGenerator::new(1);
+        demo.snapshot(10);
+        assert!(demo.tick(0.01, 10.0, 10).is_empty());
+        let total: usize = (0..100)
+            .map(|_| demo.tick(0.01, 10.0, 10))
+            .filter(|deltas| !deltas.is_empty())
+            .count();
+        // 1 s at 10/s, one batch per event at this rate.
+        assert!((9..=11).contains(&total));
+        assert!(demo.tick(1.0, 0.0, 10).is_empty());
+    }
+
+    #[test]
+    fn scenarios_loop_with_balanced_batches_and_known_nodes() {
+        for scenario in DemoScenario::ALL {
+            let Some(script) = scenario_script(scenario) else {
+                continue;
+            };
+            let loop_secs: f32 = script.batches.iter().map(|b| b.delay).sum();
+            let loop_len = script.batches.len();
+            let mut demo = DemoGenerator::scripted(script);
+            let (nodes, edges) = demo.snapshot(0);
+            let mut live_nodes: HashSet<NodeId> = nodes.into_iter().map(|(id, _)| id).collect();
+            let base_nodes = live_nodes.clone();
+            let mut live_edges: HashSet<Edge> = edges.into_iter().collect();
+            assert!(
+                live_edges
+                    .iter()
+                    .all(|e| live_nodes.contains(&e.from) && live_nodes.contains(&e.to)),
+                "{scenario:?}: base edge to unknown node"
+            );
+
+            let mut open_batch = None;
+            let mut batches = 0;
+            let ticks = (loop_secs * 2.5 / 0.1).ceil() as usize;
+            for _ in 0..ticks {
+                for delta in demo.tick(0.1, 0.0, 0) {
+                    match delta {
+                        Delta::BatchBegin { id } => {
+                            assert!(open_batch.is_none(), "{scenario:?}: nested batch");
+                            open_batch = Some(id);
+                        }
+                        Delta::BatchEnd { id } => {
+                            assert_eq!(open_batch.take(), Some(id), "{scenario:?}");
+                            batches += 1;
+                        }
+                        delta => {
+                            assert!(open_batch.is_some(), "{scenario:?}: delta outside a batch");
+                            match delta {
+                                Delta::UpsertNode { id, .. } => {
+                                    live_nodes.insert(id);
+                                }
+                                Delta::RemoveNode { id } => {
+                                    assert!(live_nodes.remove(&id), "{scenario:?}: removed {id:?}");
+                                    live_edges.retain(|e| e.from != id && e.to != id);
+                                }
+                                Delta::UpsertEdge { edge } => {
+                                    assert!(
+                                        live_nodes.contains(&edge.from)
+                                            && live_nodes.contains(&edge.to),
+                                        "{scenario:?}: dangling {edge:?}"
+                                    );
+                                    live_edges.insert(edge);
+                                }
+                                Delta::RemoveEdge { edge } => {
+                                    assert!(
+                                        live_edges.remove(&edge),
+                                        "{scenario:?}: unknown {edge:?}"
+                                    );
+                                }
+                                Delta::BatchBegin { .. } | Delta::BatchEnd { .. } => {
+                                    unreachable!()
+                                }
+                            }
+                        }
+                    }
+                }
+                if batches % loop_len == 0 {
+                    assert_eq!(live_nodes, base_nodes, "{scenario:?}: loop leaks nodes");
+                }
+            }
+            assert!(open_batch.is_none());
+            assert!(batches >= loop_len * 2, "{scenario:?}: {batches} batches");
+        }
+    }
+
+    #[test]
+    fn demo_mode_applies_churn_to_the_model() {
+        let mut st = GraphState::default();
+        st.cfg.demo_seed = Some(3);
+        st.cfg.demo_entities = 30;
+        st.cfg.demo_rate = 100.0;
+        st.set_demo_mode(true);
+        assert!(st.demo_loaded);
+        assert!(st.model.nodes.len() >= 30);
+
+        let before = st.perf.event_total;
+        st.tick_demo(0.5);
+        assert!(st.perf.event_total > before + 50);
+        assert!(!st.spatial.batch_log.recent.is_empty());
+
+        st.set_demo_mode(false);
+        assert!(st.demo.is_none());
+        assert!(st.model.nodes.is_empty());
+    }
+}
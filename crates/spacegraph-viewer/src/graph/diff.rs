@@ -0,0 +1,415 @@
+use spacegraph_core::{Edge, Msg, Node, NodeId};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use crate::graph::model::GraphModel;
+use crate::graph::state::GraphState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiffStatus {
+    Added,
+    Removed,
+    Changed,
+    Unchanged,
+}
+
+/// What changed from snapshot `a` ("before") to snapshot `b` ("after").
+#[derive(Debug, Default, Clone)]
+pub struct GraphDiff {
+    pub added_nodes: HashSet<NodeId>,
+    pub removed_nodes: HashSet<NodeId>,
+    /// Nodes present in both whose identifying fields differ, with the field names.
+    pub changed_nodes: HashMap<NodeId, Vec<&'static str>>,
+    pub added_edges: HashSet<Edge>,
+    pub removed_edges: HashSet<Edge>,
+}
+
+impl GraphDiff {
+    pub fn node_status(&self, id: &NodeId) -> DiffStatus {
+        if self.added_nodes.contains(id) {
+            DiffStatus::Added
+        } else if self.removed_nodes.contains(id) {
+            DiffStatus::Removed
+        } else if self.changed_nodes.contains_key(id) {
+            DiffStatus::Changed
+        } else {
+            DiffStatus::Unchanged
+        }
+    }
+
+    pub fn edge_status(&self, edge: &Edge) -> DiffStatus {
+        if self.added_edges.contains(edge) {
+            DiffStatus::Added
+        } else if self.removed_edges.contains(edge) {
+            DiffStatus::Removed
+        } else {
+            DiffStatus::Unchanged
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.changed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+}
+
+pub fn diff_snapshots(a: &GraphModel, b: &GraphModel) -> GraphDiff {
+    let mut diff = GraphDiff::default();
+    for (id, before) in &a.nodes {
+        match b.nodes.get(id) {
+            None => {
+                diff.removed_nodes.insert(id.clone());
+            }
+            Some(after) => {
+                let fields = changed_fields(before, after);
+                if !fields.is_empty() {
+                    diff.changed_nodes.insert(id.clone(), fields);
+                }
+            }
+        }
+    }
+    diff.added_nodes = b
+        .nodes
+        .keys()
+        .filter(|id| !a.nodes.contains_key(*id))
+        .cloned()
+        .collect();
+    diff.removed_edges = a.edges.difference(&b.edges).cloned().collect();
+    diff.added_edges = b.edges.difference(&a.edges).cloned().collect();
+    diff
+}
+
+/// Identifying fields that differ. Resource counters (RSS, CPU ticks) move on every
+/// poll and are left out so they don't flag every process.
+pub(crate) fn changed_fields(a: &Node, b: &Node) -> Vec<&'static str> {
+    let mut out = Vec::new();
+    let mut check = |name: &'static str, differs: bool| {
+        if differs {
+            out.push(name);
+        }
+    };
+    match (a, b) {
+        (
+            Node::Process {
+                pid,
+                ppid,
+                exe,
+                cmdline,
+                uid,
+                container,
+                ..
+            },
+            Node::Process {
+                pid: pid_b,
+                ppid: ppid_b,
+                exe: exe_b,
+                cmdline: cmdline_b,
+                uid: uid_b,
+                container: container_b,
+                ..
+            },
+        ) => {
+            check("pid", pid != pid_b);
+            check("ppid", ppid != ppid_b);
+            check("exe", exe != exe_b);
+            check("cmdline", cmdline != cmdline_b);
+            check("uid", uid != uid_b);
+            check("container", container != container_b);
+        }
+        (
+            Node::File {
+                path,
+                inode,
+                kind,
+                category,
+                link_target,
+            },
+            Node::File {
+                path: path_b,
+                inode: inode_b,
+                kind: kind_b,
+                category: category_b,
+                link_target: link_target_b,
+            },
+        ) => {
+            check("path", path != path_b);
+            check("inode", inode != inode_b);
+            check(
+                "kind",
+                std::mem::discriminant(kind) != std::mem::discriminant(kind_b),
+            );
+            check("category", category != category_b);
+            check("link_target", link_target != link_target_b);
+        }
+        (
+            Node::User { uid, name },
+            Node::User {
+                uid: uid_b,
+                name: name_b,
+            },
+        ) => {
+            check("uid", uid != uid_b);
+            check("name", name != name_b);
+        }
+        (
+            Node::Container { id, name },
+            Node::Container {
+                id: id_b,
+                name: name_b,
+            },
+        ) => {
+            check("id", id != id_b);
+            check("name", name != name_b);
+        }
+        (
+            Node::Thread { tid, pid, name },
+            Node::Thread {
+                tid: tid_b,
+                pid: pid_b,
+                name: name_b,
+            },
+        ) => {
+            check("tid", tid != tid_b);
+            check("pid", pid != pid_b);
+            check("name", name != name_b);
+        }
+        _ => check("type", true),
+    }
+    out
+}
+
+impl GraphState {
+    /// Diffs the current graph against a baseline snapshot. Removed nodes and edges are
+    /// merged back in so they can be drawn; refused while agents are connected.
+    pub fn compare_with_snapshot(&mut self, msg: Msg) -> bool {
+        if self.net.active_connection_count() > 0 {
+            return false;
+        }
+        let Msg::Snapshot { nodes, edges } = msg else {
+            return false;
+        };
+        self.clear_snapshot_diff();
+        let now = Instant::now();
+        let mut baseline = GraphModel::default();
+        baseline.load_snapshot(nodes, edges, now);
+        let diff = diff_snapshots(&baseline, &self.model);
+
+        for id in &diff.removed_nodes {
+            if let Some(node) = baseline.nodes.get(id) {
+                self.model.upsert_node(id.clone(), node.clone(), now);
+            }
+        }
+        for edge in &diff.removed_edges {
+            self.model.upsert_edge(edge.clone(), now);
+        }
+        self.diff = Some(diff);
+        self.spatial.dirty_layout = true;
+        self.needs_redraw.store(true, Ordering::Relaxed);
+        true
+    }
+
+    /// Leaves diff mode and drops the baseline-only nodes and edges again.
+    pub fn clear_snapshot_diff(&mut self) {
+        let Some(diff) = self.diff.take() else {
+            return;
+        };
+        for edge in &diff.removed_edges {
+            self.model.remove_edge(edge);
+        }
+        for id in &diff.removed_nodes {
+            self.model.remove_node(id);
+            self.spatial.positions.remove(id);
+            self.spatial.velocities.remove(id);
+            self.ui.expanded.remove_node(id);
+        }
+        self.spatial.dirty_layout = true;
+        self.needs_redraw.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::{EdgeKind, FileCategory, FileKind};
+
+    fn id(s: &str) -> NodeId {
+        NodeId(s.to_string())
+    }
+
+    fn process(pid: i32, cmdline: &str) -> Node {
+        Node::Process {
+            pid,
+            ppid: 1,
+            exe: "/bin/sh".to_string(),
+            cmdline: cmdline.to_string(),
+            uid: 1000,
+            rss_kb: 0,
+            utime_ticks: 0,
+            container: None,
+        }
+    }
+
+    fn file(path: &str, inode: u64) -> Node {
+        Node::File {
+            path: path.to_string(),
+            inode,
+            kind: FileKind::Regular,
+            category: FileCategory::Unknown,
+            link_target: None,
+        }
+    }
+
+    fn edge(from: &str, to: &str, kind: EdgeKind) -> Edge {
+        Edge {
+            from: id(from),
+            to: id(to),
+            kind,
+        }
+    }
+
+    fn model(nodes: Vec<(&str, Node)>, edges: Vec<Edge>) -> GraphModel {
+        let mut m = GraphModel::default();
+        m.load_snapshot(
+            nodes.into_iter().map(|(k, n)| (id(k), n)).collect(),
+            edges,
+            Instant::now(),
+        );
+        m
+    }
+
+    #[test]
+    fn identical_snapshots_have_an_empty_diff() {
+        let a = model(
+            vec![("p", process(1, "sh")), ("f", file("/tmp/a", 1))],
+            vec![edge("p", "f", EdgeKind::Execs)],
+        );
+        let b = model(
+            vec![("p", process(1, "sh")), ("f", file("/tmp/a", 1))],
+            vec![edge("p", "f", EdgeKind::Execs)],
+        );
+        assert!(diff_snapshots(&a, &b).is_empty());
+    }
+
+    #[test]
+    fn node_sets_and_field_changes_are_reported() {
+        let a = model(
+            vec![
+                ("p", process(1, "sh -c old")),
+                ("gone", file("/tmp/gone", 2)),
+                ("f", file("/tmp/a", 3)),
+            ],
+            vec![],
+        );
+        let b = model(
+            vec![
+                ("p", process(1, "sh -c new")),
+                ("f", file("/tmp/a", 4)),
+                ("new", file("/tmp/new", 5)),
+            ],
+            vec![],
+        );
+        let diff = diff_snapshots(&a, &b);
+        assert_eq!(diff.added_nodes, HashSet::from([id("new")]));
+        assert_eq!(diff.removed_nodes, HashSet::from([id("gone")]));
+        assert_eq!(diff.changed_nodes.get(&id("p")), Some(&vec!["cmdline"]));
+        assert_eq!(diff.changed_nodes.get(&id("f")), Some(&vec!["inode"]));
+        assert_eq!(diff.node_status(&id("p")), DiffStatus::Changed);
+        assert_eq!(diff.node_status(&id("new")), DiffStatus::Added);
+        assert_eq!(diff.node_status(&id("gone")), DiffStatus::Removed);
+    }
+
+    #[test]
+    fn resource_counters_and_node_type_changes() {
+        let mut busy = process(1, "sh");
+        if let Node::Process {
+            rss_kb,
+            utime_ticks,
+            ..
+        } = &mut busy
+        {
+            *rss_kb = 4096;
+            *utime_ticks = 99;
+        }
+        assert!(changed_fields(&process(1, "sh"), &busy).is_empty());
+        assert_eq!(
+            changed_fields(&process(1, "sh"), &file("/tmp/a", 1)),
+            vec!["type"]
+        );
+
+        let user = Node::User {
+            uid: 0,
+            name: "root".to_string(),
+        };
+        let renamed = Node::User {
+            uid: 0,
+            name: "toor".to_string(),
+        };
+        assert_eq!(changed_fields(&user, &renamed), vec!["name"]);
+    }
+
+    #[test]
+    fn edge_set_differences_include_kind_changes() {
+        let nodes = || vec![("p", process(1, "sh")), ("f", file("/tmp/a", 1))];
+        let opens_r = edge(
+            "p",
+            "f",
+            EdgeKind::Opens {
+                fd: 3,
+                mode: "r".to_string(),
+            },
+        );
+        let opens_w = edge(
+            "p",
+            "f",
+            EdgeKind::Opens {
+                fd: 3,
+                mode: "w".to_string(),
+            },
+        );
+        let execs = edge("p", "f", EdgeKind::Execs);
+        let a = model(nodes(), vec![opens_r.clone(), execs.clone()]);
+        let b = model(nodes(), vec![opens_w.clone(), execs.clone()]);
+
+        let diff = diff_snapshots(&a, &b);
+        assert_eq!(diff.removed_edges, HashSet::from([opens_r.clone()]));
+        assert_eq!(diff.added_edges, HashSet::from([opens_w.clone()]));
+        assert_eq!(diff.edge_status(&execs), DiffStatus::Unchanged);
+        assert_eq!(diff.edge_status(&opens_r), DiffStatus::Removed);
+        assert!(diff.changed_nodes.is_empty());
+    }
+
+    #[test]
+    fn compare_merges_removed_items_and_clear_drops_them() {
+        let mut st = GraphState {
+            model: model(
+                vec![("p", process(1, "sh")), ("new", file("/tmp/new", 2))],
+                vec![],
+            ),
+            ..Default::default()
+        };
+        let baseline = Msg::Snapshot {
+            nodes: vec![
+                (id("p"), process(1, "sh")),
+                (id("gone"), file("/tmp/gone", 3)),
+            ],
+            edges: vec![edge("p", "gone", EdgeKind::Execs)],
+        };
+
+        assert!(st.compare_with_snapshot(baseline));
+        assert!(st.model.nodes.contains_key(&id("gone")));
+        assert_eq!(st.model.edges.len(), 1);
+        let diff = st.diff.as_ref().expect("diff");
+        assert_eq!(diff.node_status(&id("new")), DiffStatus::Added);
+        assert_eq!(diff.node_status(&id("p")), DiffStatus::Unchanged);
+
+        st.clear_snapshot_diff();
+        assert!(st.diff.is_none());
+        assert!(!st.model.nodes.contains_key(&id("gone")));
+        assert!(st.model.edges.is_empty());
+        assert_eq!(st.model.nodes.len(), 2);
+    }
+}
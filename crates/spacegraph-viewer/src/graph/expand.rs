@@ -0,0 +1,199 @@
+use spacegraph_core::NodeId;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+
+use crate::graph::state::GraphState;
+
+/// Neighbors revealed by "Expand", remembered per source node and refcounted, so
+/// collapsing one source keeps the neighbors another source still reveals.
+#[derive(Debug, Default)]
+pub struct Expansions {
+    by_source: HashMap<NodeId, Vec<NodeId>>,
+    shown: HashMap<NodeId, usize>,
+}
+
+impl Expansions {
+    /// Replaces what `source` reveals with `neighbors`.
+    pub fn expand(&mut self, source: NodeId, neighbors: Vec<NodeId>) {
+        self.collapse(&source);
+        for nb in &neighbors {
+            *self.shown.entry(nb.clone()).or_default() += 1;
+        }
+        self.by_source.insert(source, neighbors);
+    }
+
+    /// Hides what `source` revealed unless another source reveals it too.
+    pub fn collapse(&mut self, source: &NodeId) -> bool {
+        let Some(neighbors) = self.by_source.remove(source) else {
+            return false;
+        };
+        for nb in &neighbors {
+            self.release(nb);
+        }
+        true
+    }
+
+    /// Forgets `id` both as a source and as a revealed node.
+    pub fn remove_node(&mut self, id: &NodeId) {
+        self.collapse(id);
+        if self.shown.remove(id).is_some() {
+            for neighbors in self.by_source.values_mut() {
+                neighbors.retain(|nb| nb != id);
+            }
+        }
+    }
+
+    /// Every revealed node, once.
+    pub fn iter(&self) -> impl Iterator<Item = &NodeId> {
+        self.shown.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.shown.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shown.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.by_source.clear();
+        self.shown.clear();
+    }
+
+    fn release(&mut self, id: &NodeId) {
+        if let Some(count) = self.shown.get_mut(id) {
+            *count -= 1;
+            if *count == 0 {
+                self.shown.remove(id);
+            }
+        }
+    }
+}
+
+impl GraphState {
+    /// Reveals the direct neighbors of `id` on top of the filtered/focused set.
+    pub fn expand_node(&mut self, id: &NodeId) {
+        let neighbors: Vec<NodeId> = self.model.neighbors(id).collect();
+        self.ui.expanded.expand(id.clone(), neighbors);
+        self.spatial.dirty_layout = true;
+        self.needs_redraw.store(true, Ordering::Relaxed);
+    }
+
+    /// Undoes [`Self::expand_node`] for `id`.
+    pub fn collapse_node(&mut self, id: &NodeId) {
+        if self.ui.expanded.collapse(id) {
+            self.spatial.dirty_layout = true;
+            self.needs_redraw.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::{Delta, Edge, EdgeKind, FileCategory, FileKind, Node};
+    use std::time::{Duration, Instant};
+
+    fn id(s: &str) -> NodeId {
+        NodeId(s.to_string())
+    }
+
+    fn revealed(st: &GraphState, name: &str) -> bool {
+        st.ui.expanded.iter().any(|nb| nb.0 == name)
+    }
+
+    fn state_with_edges(edges: &[(&str, &str)]) -> GraphState {
+        let mut st = GraphState::default();
+        let now = Instant::now();
+        for (from, to) in edges {
+            for name in [from, to] {
+                st.model.upsert_node(
+                    id(name),
+                    Node::File {
+                        path: name.to_string(),
+                        inode: 1,
+                        kind: FileKind::Regular,
+                        category: FileCategory::Unknown,
+                        link_target: None,
+                    },
+                    now,
+                );
+            }
+            st.model.upsert_edge(
+                Edge {
+                    from: id(from),
+                    to: id(to),
+                    kind: EdgeKind::Execs,
+                },
+                now,
+            );
+        }
+        st
+    }
+
+    #[test]
+    fn shared_neighbor_stays_until_every_source_collapses() {
+        let mut st = state_with_edges(&[("a", "shared"), ("b", "shared"), ("a", "only_a")]);
+        st.expand_node(&id("a"));
+        st.expand_node(&id("b"));
+        // Expanding again must not count twice.
+        st.expand_node(&id("a"));
+
+        st.collapse_node(&id("a"));
+        assert!(revealed(&st, "shared"));
+        assert!(!revealed(&st, "only_a"));
+
+        st.collapse_node(&id("b"));
+        assert!(st.ui.expanded.is_empty());
+    }
+
+    #[test]
+    fn collapsing_a_node_that_was_not_expanded_keeps_everything() {
+        let mut st = state_with_edges(&[("a", "b"), ("c", "b")]);
+        st.expand_node(&id("a"));
+
+        st.collapse_node(&id("c"));
+
+        assert!(revealed(&st, "b"));
+    }
+
+    #[test]
+    fn removed_nodes_are_not_revealed_again() {
+        let mut st = state_with_edges(&[("a", "b"), ("a", "c")]);
+        st.expand_node(&id("a"));
+
+        st.apply_delta(Delta::RemoveNode { id: id("b") });
+
+        assert!(!revealed(&st, "b"));
+        assert!(revealed(&st, "c"));
+        assert!(!st.visible_set_capped().contains(&id("b")));
+
+        st.apply_delta(Delta::RemoveNode { id: id("a") });
+        assert!(st.ui.expanded.is_empty());
+    }
+
+    #[test]
+    fn gc_prunes_expanded_nodes() {
+        let mut st = state_with_edges(&[("a", "b")]);
+        st.expand_node(&id("a"));
+        st.model.remove_edge(&Edge {
+            from: id("a"),
+            to: id("b"),
+            kind: EdgeKind::Execs,
+        });
+        let now = Instant::now();
+        st.model
+            .last_seen
+            .insert(id("b"), now - Duration::from_secs(10));
+        st.cfg.gc_enabled = true;
+        st.cfg.gc_ttl = Duration::from_secs(5);
+        st.perf.gc_last_run = now - st.cfg.gc_interval - Duration::from_millis(1);
+
+        st.tick_gc();
+
+        assert!(!st.model.nodes.contains_key(&id("b")));
+        assert!(!revealed(&st, "b"));
+        assert!(!st.visible_set_capped().contains(&id("b")));
+    }
+}
@@ -59,6 +59,63 @@ pub fn shortest_path(
     None
 }
 
+/// Union of the shortest paths from `root` to each of `targets`, taken from one BFS
+/// tree so shared prefixes appear once. Unreachable targets contribute nothing.
+pub fn path_tree(
+    model: &GraphModel,
+    root: &NodeId,
+    targets: &[NodeId],
+    max_depth: usize,
+    allowed: &HashSet<NodeId>,
+) -> Vec<PathStep> {
+    if max_depth == 0 || !allowed.contains(root) {
+        return Vec::new();
+    }
+    let mut pending: HashSet<&NodeId> = targets
+        .iter()
+        .filter(|t| *t != root && allowed.contains(*t))
+        .collect();
+    let mut prev: HashMap<NodeId, (NodeId, EdgeKindClass)> = HashMap::new();
+    let mut visited: HashSet<NodeId> = HashSet::from([root.clone()]);
+    let mut q: VecDeque<(NodeId, usize)> = VecDeque::from([(root.clone(), 0)]);
+
+    while let Some((cur, depth)) = q.pop_front() {
+        if pending.is_empty() {
+            break;
+        }
+        if depth >= max_depth {
+            continue;
+        }
+        for edge in model.edges_for_node(&cur) {
+            let next = if edge.from == cur {
+                &edge.to
+            } else {
+                &edge.from
+            };
+            if !allowed.contains(next) || !visited.insert(next.clone()) {
+                continue;
+            }
+            prev.insert(
+                next.clone(),
+                (cur.clone(), EdgeKindClass::from_kind(&edge.kind)),
+            );
+            pending.remove(next);
+            q.push_back((next.clone(), depth + 1));
+        }
+    }
+
+    let mut seen: HashSet<(NodeId, NodeId)> = HashSet::new();
+    let mut steps = Vec::new();
+    for target in targets.iter().filter(|t| prev.contains_key(*t)) {
+        for step in reconstruct_path(&prev, root.clone(), target.clone()) {
+            if seen.insert((step.from.clone(), step.to.clone())) {
+                steps.push(step);
+            }
+        }
+    }
+    steps
+}
+
 fn reconstruct_path(
     prev: &HashMap<NodeId, (NodeId, EdgeKindClass)>,
     start: NodeId,
@@ -195,4 +252,45 @@ mod tests {
         let path = shortest_path(&model, a, c, 4, &allowed);
         assert!(path.is_none());
     }
+
+    #[test]
+    fn path_tree_unions_paths_to_all_targets() {
+        let mut model = GraphModel::default();
+        let now = Instant::now();
+        let id = |s: &str| NodeId(s.to_string());
+        for (uid, name) in (1..).zip(["f", "p", "x", "y", "far", "lone"]) {
+            model.upsert_node(
+                id(name),
+                Node::User {
+                    uid,
+                    name: name.to_string(),
+                },
+                now,
+            );
+        }
+        // f - p - x, p - y, y - far: x and y share the f-p prefix.
+        for (from, to) in [("f", "p"), ("p", "x"), ("p", "y"), ("y", "far")] {
+            model.upsert_edge(
+                Edge {
+                    from: id(from),
+                    to: id(to),
+                    kind: EdgeKind::ParentOf,
+                },
+                now,
+            );
+        }
+        let allowed: HashSet<NodeId> = model.nodes.keys().cloned().collect();
+        let targets = [id("x"), id("y"), id("lone"), id("far")];
+
+        let steps = path_tree(&model, &id("f"), &targets, 2, &allowed);
+        let pairs: Vec<(&str, &str)> = steps
+            .iter()
+            .map(|s| (s.from.0.as_str(), s.to.0.as_str()))
+            .collect();
+        assert_eq!(pairs, vec![("f", "p"), ("p", "x"), ("p", "y")]);
+
+        let deeper = path_tree(&model, &id("f"), &targets, 3, &allowed);
+        assert_eq!(deeper.len(), 4);
+        assert_eq!(deeper.last().map(|s| s.to.clone()), Some(id("far")));
+    }
 }
@@ -0,0 +1,103 @@
+use spacegraph_core::NodeId;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use crate::graph::state::GraphState;
+
+/// Minimum time between two automatic jumps, so a spawn burst settles on its last process.
+pub const FOLLOW_DEBOUNCE: Duration = Duration::from_millis(750);
+
+/// Newest process seen while "follow newest" is on, waiting for the debounce to pass.
+#[derive(Debug, Default)]
+pub struct FollowNewest {
+    pending: Option<NodeId>,
+    last_jump: Option<Instant>,
+}
+
+impl FollowNewest {
+    /// Later spawns replace earlier ones that are still pending.
+    pub fn note(&mut self, id: NodeId) {
+        self.pending = Some(id);
+    }
+
+    /// The process to jump to now, if any and the previous jump is old enough.
+    pub fn take_due(&mut self, now: Instant) -> Option<NodeId> {
+        if self
+            .last_jump
+            .is_some_and(|last| now.duration_since(last) < FOLLOW_DEBOUNCE)
+        {
+            return None;
+        }
+        let id = self.pending.take()?;
+        self.last_jump = Some(now);
+        Some(id)
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl GraphState {
+    pub fn tick_follow(&mut self, now: Instant) {
+        if !self.cfg.follow_newest {
+            return;
+        }
+        let Some(id) = self.follow.take_due(now) else {
+            return;
+        };
+        // The process may have exited while the jump was debounced.
+        if !self.model.nodes.contains_key(&id) {
+            return;
+        }
+        self.ui.focus = Some(id.clone());
+        self.ui.selected = Some(id.clone());
+        self.request_jump(id);
+        self.spatial.dirty_layout = true;
+        self.needs_redraw.store(true, Ordering::Relaxed);
+    }
+
+    /// A manual pick wins over following.
+    pub fn stop_following(&mut self) {
+        self.cfg.follow_newest = false;
+        self.follow.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(s: &str) -> NodeId {
+        NodeId(s.to_string())
+    }
+
+    #[test]
+    fn burst_settles_on_newest_after_debounce() {
+        let mut follow = FollowNewest::default();
+        let t0 = Instant::now();
+
+        follow.note(id("p1"));
+        assert_eq!(follow.take_due(t0), Some(id("p1")));
+
+        follow.note(id("p2"));
+        follow.note(id("p3"));
+        assert_eq!(follow.take_due(t0 + Duration::from_millis(100)), None);
+        assert_eq!(follow.take_due(t0 + FOLLOW_DEBOUNCE), Some(id("p3")));
+        assert_eq!(follow.take_due(t0 + FOLLOW_DEBOUNCE * 3), None);
+    }
+
+    #[test]
+    fn manual_pick_disables_following() {
+        let mut st = GraphState::default();
+        st.cfg.follow_newest = true;
+        st.follow.note(id("p1"));
+
+        st.stop_following();
+        st.tick_follow(Instant::now());
+
+        assert!(!st.cfg.follow_newest);
+        assert!(st.ui.focus.is_none());
+        assert!(st.follow.take_due(Instant::now()).is_none());
+    }
+}
@@ -1,24 +1,30 @@
 use spacegraph_core::{Node, NodeId};
-use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::atomic::Ordering;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
+use crate::graph::history::HistoryChange;
 use crate::graph::state::GraphState;
+use crate::util::config::GcMode;
 
 impl GraphState {
     // ----- Glow maintenance -----
     pub fn tick_glow(&mut self) {
         let now = Instant::now();
         let before_n = self.spatial.glow_nodes.len();
-        let before_e = self.spatial.glow_edges.len();
         self.spatial.glow_nodes.retain(|_, until| *until > now);
         self.spatial.glow_edges.retain(|_, until| *until > now);
-        if self.spatial.glow_nodes.len() != before_n || self.spatial.glow_edges.len() != before_e {
+        if self.cfg.show_only_active && self.spatial.glow_nodes.len() != before_n {
+            self.spatial.dirty_layout = true;
+        }
+        // Fading happens on the glow materials and edges are gizmos drawn every frame;
+        // only a node that stops glowing needs its regular material back.
+        if self.spatial.glow_nodes.len() != before_n {
             self.needs_redraw.store(true, Ordering::Relaxed);
         }
     }
 
-    // ----- GC orphan files -----
+    // ----- GC orphan files and threads -----
     pub fn tick_gc(&mut self) {
         if !self.cfg.gc_enabled || self.cfg.demo_mode {
             return;
@@ -29,37 +35,63 @@ impl GraphState {
         }
         self.perf.gc_last_run = now;
 
-        let mut degree: HashMap<NodeId, u32> = HashMap::new();
-        for e in self.model.edges.iter() {
-            *degree.entry(e.from.clone()).or_insert(0) += 1;
-            *degree.entry(e.to.clone()).or_insert(0) += 1;
+        // Prune fd edges first so files they kept alive become orphans in this pass.
+        if let Some(ttl) = self.cfg.edge_ttl {
+            self.prune_stale_edges(now, ttl);
         }
 
+        let degree = self.model.degree_map();
+        let remove_after = match self.cfg.gc_mode {
+            GcMode::Remove => self.cfg.gc_ttl,
+            GcMode::Dim => self.cfg.gc_hard_ttl.max(self.cfg.gc_ttl),
+        };
+
         let mut to_remove: Vec<NodeId> = Vec::new();
+        let mut stale: HashSet<NodeId> = HashSet::new();
         for (id, node) in self.model.nodes.iter() {
             let is_orphan = degree.get(id).copied().unwrap_or(0) == 0;
             if !is_orphan {
                 continue;
             }
-            if !matches!(node, Node::File { .. }) {
+            if !matches!(node, Node::File { .. } | Node::Thread { .. }) {
                 continue;
             }
             let last = self.model.last_seen.get(id).copied().unwrap_or(now);
-            if now.duration_since(last) >= self.cfg.gc_ttl {
+            let age = now.duration_since(last);
+            if age >= remove_after {
                 to_remove.push(id.clone());
+            } else if age >= self.cfg.gc_ttl {
+                stale.insert(id.clone());
             }
         }
 
+        if stale != self.spatial.stale_nodes {
+            self.spatial.stale_nodes = stale;
+            self.needs_redraw.store(true, Ordering::Relaxed);
+        }
+
         if to_remove.is_empty() {
             return;
         }
 
         for id in to_remove {
-            self.model.nodes.remove(&id);
+            // Through the model, so structure caches see the node go.
+            let node = self.model.nodes.get(&id).cloned();
+            self.model.remove_node(&id);
+            if let Some(node) = node {
+                self.timeline.history.record(
+                    now,
+                    HistoryChange::NodeRemove {
+                        id: id.clone(),
+                        node,
+                        edges: Vec::new(),
+                    },
+                );
+            }
             self.spatial.positions.remove(&id);
             self.spatial.velocities.remove(&id);
             self.spatial.glow_nodes.remove(&id);
-            self.model.last_seen.remove(&id);
+            self.ui.expanded.remove_node(&id);
 
             if self.ui.focus.as_ref() == Some(&id) {
                 self.ui.focus = None;
@@ -74,13 +106,51 @@ impl GraphState {
 
         self.needs_redraw.store(true, Ordering::Relaxed);
     }
+
+    /// Viewer-local: agents are not told, a later upsert brings the edge back.
+    fn prune_stale_edges(&mut self, now: Instant, ttl: Duration) {
+        let stale = self.model.stale_edges(now, ttl);
+        if stale.is_empty() {
+            return;
+        }
+        for edge in stale {
+            if self.model.remove_edge(&edge) {
+                self.timeline
+                    .history
+                    .record(now, HistoryChange::EdgeRemove { edge: edge.clone() });
+            }
+            self.spatial.glow_edges.remove(&edge);
+            if self.ui.selected_edge.as_ref() == Some(&edge) {
+                self.ui.selected_edge = None;
+            }
+        }
+        self.spatial.dirty_layout = true;
+        self.needs_redraw.store(true, Ordering::Relaxed);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use spacegraph_core::{FileKind, Node};
-    use std::time::Duration;
+    use spacegraph_core::{Edge, EdgeKind, FileCategory, FileKind, Node};
+
+    #[test]
+    fn fading_glow_redraws_only_when_a_node_stops_glowing() {
+        let mut st = GraphState::default();
+        let id = NodeId("n".to_string());
+        st.spatial
+            .glow_nodes
+            .insert(id.clone(), Instant::now() + Duration::from_secs(60));
+        st.needs_redraw.store(false, Ordering::Relaxed);
+
+        st.tick_glow();
+        assert!(!st.needs_redraw.load(Ordering::Relaxed));
+
+        st.spatial.glow_nodes.insert(id, Instant::now());
+        st.tick_glow();
+        assert!(st.spatial.glow_nodes.is_empty());
+        assert!(st.needs_redraw.load(Ordering::Relaxed));
+    }
 
     #[test]
     fn gc_removes_orphan_file_after_ttl() {
@@ -92,6 +162,8 @@ mod tests {
                 path: "/tmp/test".to_string(),
                 inode: 1,
                 kind: FileKind::Regular,
+                category: FileCategory::Unknown,
+                link_target: None,
             },
         );
         let now = Instant::now();
@@ -105,4 +177,78 @@ mod tests {
 
         assert!(!st.model.nodes.contains_key(&file_id));
     }
+
+    #[test]
+    fn dim_mode_keeps_stale_orphan_that_remove_mode_deletes() {
+        let file_id = NodeId("file-1".to_string());
+        let now = Instant::now();
+        let run = |mode: GcMode| {
+            let mut st = GraphState::default();
+            st.model.nodes.insert(
+                file_id.clone(),
+                Node::File {
+                    path: "/tmp/test".to_string(),
+                    inode: 1,
+                    kind: FileKind::Regular,
+                    category: FileCategory::Unknown,
+                    link_target: None,
+                },
+            );
+            st.model
+                .last_seen
+                .insert(file_id.clone(), now - Duration::from_secs(10));
+            st.cfg.gc_mode = mode;
+            st.cfg.gc_ttl = Duration::from_secs(5);
+            st.cfg.gc_hard_ttl = Duration::from_secs(60);
+            st.perf.gc_last_run = now - st.cfg.gc_interval - Duration::from_millis(1);
+            st.tick_gc();
+            st
+        };
+
+        let dim = run(GcMode::Dim);
+        assert!(dim.model.nodes.contains_key(&file_id));
+        assert!(dim.spatial.stale_nodes.contains(&file_id));
+
+        let remove = run(GcMode::Remove);
+        assert!(!remove.model.nodes.contains_key(&file_id));
+    }
+
+    #[test]
+    fn edge_ttl_prunes_stale_fd_edges_only() {
+        let mut st = GraphState::default();
+        let now = Instant::now();
+        let edge = |to: &str, kind: EdgeKind| Edge {
+            from: NodeId("proc".to_string()),
+            to: NodeId(to.to_string()),
+            kind,
+        };
+        let opens = |fd| EdgeKind::Opens {
+            fd,
+            mode: "r".to_string(),
+        };
+        let stale = edge("old", opens(3));
+        let fresh = edge("new", opens(4));
+        let parent = edge("child", EdgeKind::ParentOf);
+        st.model
+            .upsert_edge(stale.clone(), now - Duration::from_secs(120));
+        st.model
+            .upsert_edge(parent.clone(), now - Duration::from_secs(120));
+        st.model
+            .upsert_edge(fresh.clone(), now - Duration::from_secs(5));
+        st.cfg.edge_ttl = Some(Duration::from_secs(60));
+        st.perf.gc_last_run = now - st.cfg.gc_interval - Duration::from_millis(1);
+
+        st.tick_gc();
+
+        assert!(!st.model.edges.contains(&stale));
+        assert!(st.model.edges.contains(&fresh));
+        assert!(st.model.edges.contains(&parent));
+        assert!(!st.model.edge_last_seen.contains_key(&stale));
+        assert!(st
+            .model
+            .edges_for_node(&NodeId("old".to_string()))
+            .next()
+            .is_none());
+        assert_eq!(st.model.agg_edge_count(), 2);
+    }
 }
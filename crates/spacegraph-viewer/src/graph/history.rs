@@ -0,0 +1,303 @@
+use spacegraph_core::{Edge, Node, NodeId};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::graph::model::GraphModel;
+
+/// A model change plus whatever is needed to undo it.
+#[derive(Debug, Clone)]
+pub enum HistoryChange {
+    NodeUpsert {
+        id: NodeId,
+        prev: Option<Node>,
+    },
+    NodeRemove {
+        id: NodeId,
+        node: Node,
+        edges: Vec<Edge>,
+    },
+    EdgeUpsert {
+        edge: Edge,
+    },
+    EdgeRemove {
+        edge: Edge,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub ts: Instant,
+    pub change: HistoryChange,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CountSample {
+    pub ts: Instant,
+    pub nodes: usize,
+    pub edges: usize,
+}
+
+/// Undo log of model changes, bounded by age and entry count. Off by default because
+/// every entry keeps a copy of the replaced node.
+#[derive(Debug)]
+pub struct ModelHistory {
+    pub enabled: bool,
+    pub max_entries: usize,
+    pub sample_interval: Duration,
+    entries: VecDeque<HistoryEntry>,
+    samples: VecDeque<CountSample>,
+    /// Timestamp of the newest dropped entry; reconstructions before it are incomplete.
+    horizon: Option<Instant>,
+}
+
+impl Default for ModelHistory {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_entries: 50_000,
+            sample_interval: Duration::from_secs(1),
+            entries: VecDeque::new(),
+            samples: VecDeque::new(),
+            horizon: None,
+        }
+    }
+}
+
+impl ModelHistory {
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if self.enabled != enabled {
+            self.enabled = enabled;
+            self.reset(None);
+        }
+    }
+
+    /// Forgets everything; called when the model is replaced wholesale.
+    pub fn reset(&mut self, now: Option<Instant>) {
+        self.entries.clear();
+        self.samples.clear();
+        self.horizon = now;
+    }
+
+    pub fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn record(&mut self, ts: Instant, change: HistoryChange) {
+        if self.enabled {
+            self.entries.push_back(HistoryEntry { ts, change });
+        }
+    }
+
+    pub fn sample(&mut self, now: Instant, model: &GraphModel) {
+        if !self.enabled {
+            return;
+        }
+        let due = self
+            .samples
+            .back()
+            .map(|s| now.duration_since(s.ts) >= self.sample_interval)
+            .unwrap_or(true);
+        if due {
+            self.samples.push_back(CountSample {
+                ts: now,
+                nodes: model.nodes.len(),
+                edges: model.edges.len(),
+            });
+        }
+    }
+
+    /// Drops entries older than `window` and beyond `max_entries`.
+    pub fn trim(&mut self, now: Instant, window: Duration) {
+        let cutoff = now.checked_sub(window);
+        while let Some(front) = self.entries.front() {
+            let expired = cutoff.map(|c| front.ts < c).unwrap_or(false);
+            if !expired && self.entries.len() <= self.max_entries {
+                break;
+            }
+            self.horizon = Some(front.ts);
+            self.entries.pop_front();
+        }
+        while let Some(front) = self.samples.front() {
+            if cutoff.map(|c| front.ts < c).unwrap_or(false) {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Whether `ts` is recent enough for `reconstruct_at` to be exact.
+    pub fn covers(&self, ts: Instant) -> bool {
+        self.enabled && self.horizon.map(|h| ts >= h).unwrap_or(true)
+    }
+
+    /// Latest count sample taken at or before `ts`.
+    pub fn count_at(&self, ts: Instant) -> Option<CountSample> {
+        self.samples.iter().rev().find(|s| s.ts <= ts).copied()
+    }
+
+    /// Rebuilds the graph as it was at `ts` by undoing newer changes against `model`.
+    pub fn reconstruct_at(
+        &self,
+        model: &GraphModel,
+        ts: Instant,
+    ) -> (HashMap<NodeId, Node>, HashSet<Edge>) {
+        let mut nodes = model.nodes.clone();
+        let mut edges = model.edges.clone();
+        for entry in self.entries.iter().rev() {
+            if entry.ts <= ts {
+                break;
+            }
+            match &entry.change {
+                HistoryChange::NodeUpsert { id, prev } => match prev {
+                    Some(node) => {
+                        nodes.insert(id.clone(), node.clone());
+                    }
+                    None => {
+                        nodes.remove(id);
+                    }
+                },
+                HistoryChange::NodeRemove {
+                    id,
+                    node,
+                    edges: removed,
+                } => {
+                    nodes.insert(id.clone(), node.clone());
+                    edges.extend(removed.iter().cloned());
+                }
+                HistoryChange::EdgeUpsert { edge } => {
+                    edges.remove(edge);
+                }
+                HistoryChange::EdgeRemove { edge } => {
+                    edges.insert(edge.clone());
+                }
+            }
+        }
+        (nodes, edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::EdgeKind;
+
+    fn user(uid: u32) -> Node {
+        Node::User {
+            uid,
+            name: format!("u{uid}"),
+        }
+    }
+
+    fn id(s: &str) -> NodeId {
+        NodeId(s.to_string())
+    }
+
+    fn uid_of(node: &Node) -> Option<u32> {
+        match node {
+            Node::User { uid, .. } => Some(*uid),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn reconstruct_undoes_changes_newer_than_target() {
+        let t0 = Instant::now();
+        let at = |s: u64| t0 + Duration::from_secs(s);
+        let mut model = GraphModel::default();
+        let mut history = ModelHistory {
+            enabled: true,
+            ..ModelHistory::default()
+        };
+        let edge = Edge {
+            from: id("a"),
+            to: id("b"),
+            kind: EdgeKind::RunsAs,
+        };
+
+        // t=1: a and b appear, linked.
+        model.upsert_node(id("a"), user(1), at(1));
+        history.record(
+            at(1),
+            HistoryChange::NodeUpsert {
+                id: id("a"),
+                prev: None,
+            },
+        );
+        model.upsert_node(id("b"), user(2), at(1));
+        history.record(
+            at(1),
+            HistoryChange::NodeUpsert {
+                id: id("b"),
+                prev: None,
+            },
+        );
+        model.upsert_edge(edge.clone(), at(1));
+        history.record(at(1), HistoryChange::EdgeUpsert { edge: edge.clone() });
+
+        // t=2: a is renamed.
+        history.record(
+            at(2),
+            HistoryChange::NodeUpsert {
+                id: id("a"),
+                prev: model.nodes.get(&id("a")).cloned(),
+            },
+        );
+        model.upsert_node(id("a"), user(10), at(2));
+
+        // t=3: b goes away, taking the edge with it.
+        let node = model.nodes[&id("b")].clone();
+        let removed = model.remove_node(&id("b"));
+        history.record(
+            at(3),
+            HistoryChange::NodeRemove {
+                id: id("b"),
+                node,
+                edges: removed,
+            },
+        );
+
+        let (nodes, edges) = history.reconstruct_at(&model, at(3));
+        assert_eq!(nodes.len(), 1);
+        assert!(edges.is_empty());
+
+        let (nodes, edges) = history.reconstruct_at(&model, at(2));
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(uid_of(&nodes[&id("a")]), Some(10));
+        assert!(edges.contains(&edge));
+
+        let (nodes, edges) = history.reconstruct_at(&model, at(1));
+        assert_eq!(uid_of(&nodes[&id("a")]), Some(1));
+        assert_eq!(edges.len(), 1);
+
+        let (nodes, edges) = history.reconstruct_at(&model, t0);
+        assert!(nodes.is_empty());
+        assert!(edges.is_empty());
+    }
+
+    #[test]
+    fn trim_bounds_entries_and_moves_horizon() {
+        let t0 = Instant::now();
+        let mut history = ModelHistory {
+            enabled: true,
+            max_entries: 2,
+            ..ModelHistory::default()
+        };
+        for s in 0..4 {
+            history.record(
+                t0 + Duration::from_secs(s),
+                HistoryChange::EdgeRemove {
+                    edge: Edge {
+                        from: id("a"),
+                        to: id("b"),
+                        kind: EdgeKind::Execs,
+                    },
+                },
+            );
+        }
+        history.trim(t0 + Duration::from_secs(4), Duration::from_secs(60));
+        assert_eq!(history.entry_count(), 2);
+        assert!(!history.covers(t0));
+        assert!(history.covers(t0 + Duration::from_secs(2)));
+    }
+}
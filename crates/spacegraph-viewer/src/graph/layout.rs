@@ -1,10 +1,46 @@
 use bevy::prelude::{Res, ResMut, Time, Vec3};
+use regex::RegexBuilder;
+use spacegraph_core::hash::stable_u32;
 use spacegraph_core::{Edge, FileKind, Node, NodeId};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
 
-use crate::graph::state::{GraphState, ViewMode};
+use crate::graph::components::{label_components, Components};
+use crate::graph::model::EdgeKindClass;
+use crate::graph::state::{FilterRegex, GraphState, ViewMode};
 use crate::graph::tree;
+use crate::graph::user_layout::layout_user_clusters;
+use crate::util::config::{LayoutMode, LayoutProfile};
+
+/// Times per window length the spatial time window is re-applied.
+const SPATIAL_WINDOW_TICKS: u32 = 20;
+
+/// Force layout counts as settled once no node moves further than this per tick.
+pub const LAYOUT_SETTLED_STEP: f32 = 0.002;
+
+fn link_factor(profile: &LayoutProfile, class: EdgeKindClass) -> f32 {
+    match class {
+        EdgeKindClass::Opens => profile.link_opens,
+        EdgeKindClass::Execs => profile.link_execs,
+        EdgeKindClass::RunsAs => profile.link_runs_as,
+        EdgeKindClass::ParentOf => profile.link_parent_of,
+        EdgeKindClass::MemberOf => profile.link_member_of,
+        EdgeKindClass::PointsTo => profile.link_points_to,
+        EdgeKindClass::ThreadOf => profile.link_thread_of,
+    }
+}
+
+fn repel_factor(profile: &LayoutProfile, node: Option<&Node>) -> f32 {
+    match node {
+        Some(Node::Process { .. }) => profile.repel_process,
+        Some(Node::File { .. }) => profile.repel_file,
+        Some(Node::User { .. }) => profile.repel_user,
+        Some(Node::Container { .. }) => profile.repel_container,
+        Some(Node::Thread { .. }) => profile.repel_thread,
+        None => 1.0,
+    }
+}
 
 pub fn update_layout_or_timeline(time: Res<Time>, mut st: ResMut<GraphState>) {
     let vis: HashSet<_> = st.visible_set_capped();
@@ -30,36 +66,136 @@ impl GraphState {
         self.spatial.active_vis_cache.clear();
         self.spatial.progressive_cursor = 0;
         self.explain_cache = None;
+        self.focus_paths_cache = None;
+        self.spatial.components = None;
         self.needs_redraw.store(true, Ordering::Relaxed);
     }
 
+    /// Connected components of the model, relabeled only after nodes or edges changed.
+    pub(crate) fn components(&mut self) -> &Components {
+        let rev = self.model.revision();
+        if matches!(&self.spatial.components, Some((cached, _)) if *cached != rev) {
+            self.spatial.components = None;
+        }
+        let model = &self.model;
+        &self
+            .spatial
+            .components
+            .get_or_insert_with(|| (rev, label_components(model)))
+            .1
+    }
+
+    /// Recompiles the regex filter when the filter text changed; cheap otherwise.
+    pub fn refresh_filter_regex(&mut self) {
+        if !self.cfg.filter_is_regex {
+            self.ui.filter_regex = None;
+            return;
+        }
+        let source = self.ui.filter.trim();
+        if self
+            .ui
+            .filter_regex
+            .as_ref()
+            .is_some_and(|f| f.source == source)
+        {
+            return;
+        }
+        let compiled = RegexBuilder::new(source)
+            .case_insensitive(true)
+            .build()
+            .map_err(|err| err.to_string());
+        self.ui.filter_regex = Some(FilterRegex {
+            source: source.to_string(),
+            compiled,
+        });
+    }
+
     pub fn passes_filter(&self, id: &NodeId, node: &Node) -> bool {
         if self.ui.filter.trim().is_empty() {
             return true;
         }
+        if self.cfg.filter_is_regex {
+            // Invalid or not yet compiled regex matches nothing.
+            let Some(Ok(re)) = self.ui.filter_regex.as_ref().map(|f| &f.compiled) else {
+                return false;
+            };
+            return re.is_match(&id.0)
+                || match node {
+                    Node::File { path, .. } => re.is_match(path),
+                    Node::Process {
+                        cmdline,
+                        exe,
+                        container,
+                        ..
+                    } => {
+                        re.is_match(cmdline)
+                            || re.is_match(exe)
+                            || container.as_deref().is_some_and(|c| re.is_match(c))
+                    }
+                    Node::User { name, .. } => re.is_match(name),
+                    Node::Container { id, name } => re.is_match(id) || re.is_match(name),
+                    Node::Thread { name, .. } => re.is_match(name),
+                };
+        }
         let f = self.ui.filter.to_lowercase();
         let id_ok = id.0.to_lowercase().contains(&f);
         let node_ok = match node {
             Node::File { path, .. } => path.to_lowercase().contains(&f),
-            Node::Process { cmdline, exe, .. } => {
-                cmdline.to_lowercase().contains(&f) || exe.to_lowercase().contains(&f)
+            Node::Process {
+                cmdline,
+                exe,
+                container,
+                ..
+            } => {
+                cmdline.to_lowercase().contains(&f)
+                    || exe.to_lowercase().contains(&f)
+                    || container.as_deref().is_some_and(|c| c.contains(&f))
             }
             Node::User { name, .. } => name.to_lowercase().contains(&f),
+            Node::Container { id, name } => id.contains(&f) || name.to_lowercase().contains(&f),
+            Node::Thread { name, .. } => name.to_lowercase().contains(&f),
         };
         id_ok || node_ok
     }
 
     pub fn visible_set_capped(&mut self) -> HashSet<NodeId> {
-        let mut base: HashSet<NodeId> = self
-            .model
-            .nodes
-            .iter()
-            .filter(|(id, n)| self.passes_filter(id, n))
-            .map(|(id, _)| id.clone())
-            .collect();
+        self.refresh_filter_regex();
+        let mut base: HashSet<NodeId> = if self.cfg.show_only_active {
+            self.active_set()
+        } else {
+            self.model
+                .nodes
+                .iter()
+                .filter(|(id, n)| self.passes_filter(id, n))
+                .map(|(id, _)| id.clone())
+                .collect()
+        };
+
+        if !self.cfg.show_threads {
+            base.retain(|id| !matches!(self.model.nodes.get(id), Some(Node::Thread { .. })));
+        }
+
+        if self.cfg.spatial_window_enabled && self.ui.view_mode == ViewMode::Spatial {
+            if let Some(cutoff) = Instant::now().checked_sub(self.cfg.spatial_window) {
+                base.retain(|id| self.model.last_active(id).is_some_and(|at| at >= cutoff));
+            }
+        }
+
+        if let Some(anchor) = self.ui.isolate.clone() {
+            let comps = self.components();
+            match comps.component_of(&anchor) {
+                Some(c) => base.retain(|id| comps.component_of(id) == Some(c)),
+                None => self.ui.isolate = None,
+            }
+        }
+        if !self.ui.isolate_nodes.is_empty() {
+            base.retain(|id| self.ui.isolate_nodes.contains(id));
+        }
 
         if let Some(focus) = &self.ui.focus {
-            base.insert(focus.clone());
+            if !self.cfg.show_only_active {
+                base.insert(focus.clone());
+            }
             let hops = self.ui.focus_hops.max(1);
 
             let mut vis: HashSet<NodeId> = HashSet::new();
@@ -88,20 +224,84 @@ impl GraphState {
             base = vis.into_iter().filter(|id| base.contains(id)).collect();
         }
 
+        base.extend(self.ui.expanded.iter().cloned());
+
         if self.ui.view_mode == ViewMode::Tree {
             base = self.tree_visible_set(&base);
         }
 
         if base.len() > self.cfg.max_visible_nodes {
-            let mut v: Vec<NodeId> = base.into_iter().collect();
-            v.sort_by(|a, b| a.0.cmp(&b.0));
-            v.truncate(self.cfg.max_visible_nodes);
-            v.into_iter().collect()
+            self.cap_by_rank(base)
         } else {
             base
         }
     }
 
+    /// Seen at or after `cutoff`, either itself or through one of its edges.
+    /// Re-applies the spatial time window a few times per window length (at most once a
+    /// second), so nodes that age past the cutoff leave without waiting for another redraw.
+    pub(crate) fn tick_spatial_window(&mut self, now: Instant) {
+        if !self.cfg.spatial_window_enabled || self.ui.view_mode != ViewMode::Spatial {
+            self.spatial.window_tick = None;
+            return;
+        }
+        let every = (self.cfg.spatial_window / SPATIAL_WINDOW_TICKS).max(Duration::from_secs(1));
+        match self.spatial.window_tick {
+            Some(last) if now.saturating_duration_since(last) < every => {}
+            Some(_) => {
+                self.spatial.window_tick = Some(now);
+                self.spatial.dirty_layout = true;
+                self.needs_redraw.store(true, Ordering::Relaxed);
+            }
+            None => self.spatial.window_tick = Some(now),
+        }
+    }
+
+    /// Keeps the `max_visible_nodes` highest-ranked nodes: focus and selection first, then
+    /// glowing nodes, then by degree (ties by id so the cut is stable).
+    fn cap_by_rank(&self, base: HashSet<NodeId>) -> HashSet<NodeId> {
+        let pinned = [
+            &self.ui.focus,
+            &self.ui.selected,
+            &self.ui.selected_a,
+            &self.ui.selected_b,
+        ];
+        let mut ranked: Vec<(u8, usize, NodeId)> = base
+            .into_iter()
+            .map(|id| {
+                let tier = if pinned.iter().any(|p| p.as_ref() == Some(&id)) {
+                    0
+                } else if self.spatial.glow_nodes.contains_key(&id) {
+                    1
+                } else {
+                    2
+                };
+                let degree = self.model.edges_for_node(&id).count();
+                (tier, degree, id)
+            })
+            .collect();
+        ranked.sort_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then_with(|| b.1.cmp(&a.1))
+                .then_with(|| a.2 .0.cmp(&b.2 .0))
+        });
+        ranked.truncate(self.cfg.max_visible_nodes);
+        ranked.into_iter().map(|(_, _, id)| id).collect()
+    }
+
+    /// Currently glowing nodes plus their direct neighbors.
+    pub(crate) fn active_set(&self) -> HashSet<NodeId> {
+        let mut out = HashSet::new();
+        for id in self.spatial.glow_nodes.keys() {
+            if !self.model.nodes.contains_key(id) {
+                continue;
+            }
+            out.insert(id.clone());
+            out.extend(self.model.neighbors(id));
+        }
+        out
+    }
+
     fn tree_visible_set(&mut self, base: &HashSet<NodeId>) -> HashSet<NodeId> {
         let mut path_by_id: HashMap<NodeId, String> = HashMap::new();
         let mut kind_by_id: HashMap<NodeId, FileKind> = HashMap::new();
@@ -242,12 +442,6 @@ impl GraphState {
 
     // ----- Progressive init / Force layout (spatial) -----
     pub fn progressive_prepare(&mut self, vis: &HashSet<NodeId>) {
-        if self.spatial.active_vis_cache.is_empty() || self.spatial.dirty_layout {
-            self.spatial.active_vis_cache = vis.iter().cloned().collect();
-            self.spatial.active_vis_cache.sort_by(|a, b| a.0.cmp(&b.0));
-            self.spatial.progressive_cursor = 0;
-        }
-
         let radius = if self.cfg.radius <= 0.0 {
             25.0
         } else {
@@ -255,6 +449,16 @@ impl GraphState {
         };
         let y_spread = self.cfg.y_spread;
 
+        if self.spatial.active_vis_cache.is_empty() || self.spatial.dirty_layout {
+            self.spatial.active_vis_cache = vis.iter().cloned().collect();
+            self.spatial.active_vis_cache.sort_by(|a, b| a.0.cmp(&b.0));
+            self.spatial.progressive_cursor = 0;
+            self.spatial.cluster_targets = match self.cfg.layout_mode {
+                LayoutMode::Force | LayoutMode::Seeded => HashMap::new(),
+                LayoutMode::GroupByUser => layout_user_clusters(&self.model, vis, radius, y_spread),
+            };
+        }
+
         let take = self.cfg.progressive_nodes_per_frame.max(1);
         let start = self.spatial.progressive_cursor;
         let end = (start + take).min(self.spatial.active_vis_cache.len());
@@ -267,11 +471,22 @@ impl GraphState {
             if self.spatial.positions.contains_key(id) {
                 continue;
             }
+            if let Some(p) = self.spatial.cluster_targets.get(id) {
+                self.spatial.positions.insert(id.clone(), *p);
+                continue;
+            }
+            if self.cfg.layout_mode == LayoutMode::Seeded {
+                let p = seeded_position(id, radius, y_spread);
+                self.spatial.positions.insert(id.clone(), p);
+                continue;
+            }
             if let Some(n) = self.model.nodes.get(id) {
                 match n {
-                    Node::Process { .. } => proc_ids.push(id.clone()),
+                    // threads stay close to their processes
+                    Node::Process { .. } | Node::Thread { .. } => proc_ids.push(id.clone()),
                     Node::File { .. } => file_ids.push(id.clone()),
-                    Node::User { .. } => user_ids.push(id.clone()),
+                    // containers share the outer ring with users
+                    Node::User { .. } | Node::Container { .. } => user_ids.push(id.clone()),
                 }
             }
         }
@@ -312,13 +527,40 @@ impl GraphState {
 
         self.spatial.progressive_cursor = end;
         if self.spatial.progressive_cursor >= self.spatial.active_vis_cache.len() {
+            if start < end || self.spatial.dirty_layout {
+                self.update_layout_floor(vis);
+            }
             self.spatial.dirty_layout = false;
         }
 
         self.needs_redraw.store(true, Ordering::Relaxed);
     }
 
+    /// Switches the initial placement and lays out all unpinned nodes again.
+    pub fn set_layout_mode(&mut self, mode: LayoutMode) {
+        if self.cfg.layout_mode == mode {
+            return;
+        }
+        self.cfg.layout_mode = mode;
+        let pinned = &self.spatial.pinned;
+        self.spatial.positions.retain(|id, _| pinned.contains(id));
+        self.spatial.velocities.clear();
+        self.mark_dirty_all();
+    }
+
+    /// Once per layout pass rather than per frame, so the grid neither costs a scan every
+    /// frame nor follows every step of the force layout.
+    fn update_layout_floor(&mut self, vis: &HashSet<NodeId>) {
+        self.spatial.layout_floor = vis
+            .iter()
+            .filter_map(|id| self.spatial.positions.get(id))
+            .map(|pos| pos.y)
+            .fold(0.0_f32, f32::min);
+    }
+
     pub fn force_step(&mut self, vis: &HashSet<NodeId>, dt: f32) {
+        let was_moving = self.spatial.layout_motion > LAYOUT_SETTLED_STEP;
+        self.spatial.layout_motion = 0.0;
         if !self.cfg.layout_force {
             return;
         }
@@ -337,6 +579,12 @@ impl GraphState {
         let damping = self.cfg.damping.clamp(0.0, 1.0);
         let max_step = self.cfg.max_step.max(0.001);
 
+        let profile = self.cfg.layout_profile;
+        let repel: Vec<f32> = ids
+            .iter()
+            .map(|id| repel_factor(&profile, self.model.nodes.get(id)).max(0.0))
+            .collect();
+
         let mut forces: HashMap<NodeId, Vec3> = HashMap::new();
         for id in ids.iter() {
             forces.insert(id.clone(), Vec3::ZERO);
@@ -354,7 +602,8 @@ impl GraphState {
                     dir.y = 0.0;
                 }
                 let dist2 = dir.length_squared().max(0.01);
-                let f = (repulsion / dist2) * dir.normalize_or_zero();
+                let strength = repulsion * (repel[i] * repel[j]).sqrt();
+                let f = (strength / dist2) * dir.normalize_or_zero();
 
                 *forces.get_mut(a).unwrap() += f;
                 *forces.get_mut(b).unwrap() -= f;
@@ -388,7 +637,10 @@ impl GraphState {
                 let len = d.length().max(0.001);
                 let dir = d / len;
                 let k = 0.6;
-                let stretch = len - link_dist;
+                let rest = (link_dist
+                    * link_factor(&profile, EdgeKindClass::from_kind(&edge.kind)))
+                .max(0.1);
+                let stretch = len - rest;
                 let f = k * stretch * dir;
 
                 *forces.get_mut(&edge.from).unwrap() += f;
@@ -397,6 +649,10 @@ impl GraphState {
         }
 
         for id in ids.iter() {
+            if self.spatial.pinned.contains(id) {
+                self.spatial.velocities.remove(id);
+                continue;
+            }
             let v = self
                 .spatial
                 .velocities
@@ -410,6 +666,7 @@ impl GraphState {
             if step.length() > max_step {
                 step = step.normalize_or_zero() * max_step;
             }
+            self.spatial.layout_motion = self.spatial.layout_motion.max(step.length());
 
             let p = self
                 .spatial
@@ -421,13 +678,30 @@ impl GraphState {
                 p.y = 0.0;
             }
         }
+        if was_moving && self.spatial.layout_motion <= LAYOUT_SETTLED_STEP {
+            self.update_layout_floor(vis);
+        }
 
         self.needs_redraw.store(true, Ordering::Relaxed);
     }
 
+    pub fn toggle_pin(&mut self, id: &NodeId) {
+        if !self.spatial.pinned.remove(id) {
+            self.spatial.pinned.insert(id.clone());
+        }
+        self.needs_redraw.store(true, Ordering::Relaxed);
+    }
+
     pub fn apply_tree_layout(&mut self, vis: &HashSet<NodeId>) {
-        let positions =
+        let mut positions =
             tree::layout_tree_positions(&self.model.nodes, vis, &self.cfg.path_includes);
+        if !self.ui.show_3d {
+            // 2D keeps everything on the ground plane like the spatial view: depth goes to Z.
+            for pos in positions.values_mut() {
+                pos.z = -pos.y;
+                pos.y = 0.0;
+            }
+        }
         let mut min = Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
         let mut max = Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
         for id in vis {
@@ -453,6 +727,19 @@ impl GraphState {
     }
 }
 
+/// Position on a disk of `radius` picked by the id hash alone, so it does not depend
+/// on which other nodes are visible.
+fn seeded_position(id: &NodeId, radius: f32, y_spread: f32) -> Vec3 {
+    let h = stable_u32(&id.0);
+    let u = (h & 0xffff) as f32 / 65535.0;
+    let v = (h >> 16) as f32 / 65535.0;
+    // Scrambled bits of the same hash, so height does not follow the angle.
+    let w = (h.wrapping_mul(0x9e37_79b9) >> 22) as f32 / 1023.0;
+    let r = radius * u.sqrt();
+    let t = v * std::f32::consts::TAU;
+    Vec3::new(r * t.cos(), w * y_spread, r * t.sin())
+}
+
 fn place_ring(pos: &mut HashMap<NodeId, Vec3>, ids: &[NodeId], r: f32, y_base: f32, y_spread: f32) {
     let n = ids.len().max(1) as f32;
     for (i, id) in ids.iter().enumerate() {
@@ -471,3 +758,403 @@ fn place_ring(pos: &mut HashMap<NodeId, Vec3>, ids: &[NodeId], r: f32, y_base: f
         pos.insert(id.clone(), Vec3::new(x, y, z));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::prelude::Vec2;
+    use spacegraph_core::{EdgeKind, FileCategory};
+
+    #[test]
+    fn seeded_position_is_stable_per_id() {
+        let a = NodeId("h:process:42".to_string());
+        let b = NodeId("h:process:43".to_string());
+        let pa = seeded_position(&a, 25.0, 4.0);
+        assert_eq!(pa, seeded_position(&a, 25.0, 4.0));
+        assert_ne!(pa, seeded_position(&b, 25.0, 4.0));
+        assert!(Vec2::new(pa.x, pa.z).length() <= 25.0 + 1e-3);
+        assert!((0.0..=4.0).contains(&pa.y));
+    }
+
+    #[test]
+    fn pinned_node_does_not_move_during_force_step() {
+        let mut st = GraphState::default();
+        let a = NodeId("a".to_string());
+        let b = NodeId("b".to_string());
+        for (id, pid) in [(&a, 1), (&b, 2)] {
+            st.model.nodes.insert(
+                id.clone(),
+                Node::Process {
+                    pid,
+                    ppid: 0,
+                    exe: "/bin/sh".to_string(),
+                    cmdline: "sh".to_string(),
+                    uid: 0,
+                    rss_kb: 0,
+                    utime_ticks: 0,
+                    container: None,
+                },
+            );
+        }
+        st.model.upsert_edge(
+            Edge {
+                from: a.clone(),
+                to: b.clone(),
+                kind: EdgeKind::ParentOf,
+            },
+            std::time::Instant::now(),
+        );
+        st.spatial
+            .positions
+            .insert(a.clone(), Vec3::new(0.0, 0.0, 0.0));
+        st.spatial
+            .positions
+            .insert(b.clone(), Vec3::new(20.0, 0.0, 0.0));
+        st.toggle_pin(&a);
+
+        let vis: HashSet<NodeId> = [a.clone(), b.clone()].into();
+        st.force_step(&vis, 0.033);
+
+        assert_eq!(st.spatial.positions[&a], Vec3::ZERO);
+        assert_ne!(st.spatial.positions[&b], Vec3::new(20.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn execs_edge_settles_shorter_than_opens_under_profile() {
+        let mut st = GraphState::default();
+        st.cfg.layout_profile.link_execs = 0.5;
+        let now = std::time::Instant::now();
+        let ids: Vec<NodeId> = ["p1", "bin", "p2", "log"]
+            .iter()
+            .map(|s| NodeId(s.to_string()))
+            .collect();
+        for (i, id) in ids.iter().enumerate() {
+            st.model.nodes.insert(id.clone(), file(&id.0));
+            // Two pairs far apart so they do not push each other around.
+            let x = if i < 2 { 0.0 } else { 500.0 };
+            st.spatial
+                .positions
+                .insert(id.clone(), Vec3::new(x + i as f32 * 3.0, 0.0, 0.0));
+        }
+        let execs = EdgeKind::Execs;
+        let opens = EdgeKind::Opens {
+            fd: 3,
+            mode: "w".into(),
+        };
+        for (from, to, kind) in [(0, 1, execs), (2, 3, opens)] {
+            st.model.upsert_edge(
+                Edge {
+                    from: ids[from].clone(),
+                    to: ids[to].clone(),
+                    kind,
+                },
+                now,
+            );
+        }
+
+        let vis: HashSet<NodeId> = ids.iter().cloned().collect();
+        for _ in 0..2000 {
+            st.force_step(&vis, 0.033);
+        }
+
+        let dist = |a: usize, b: usize| {
+            st.spatial.positions[&ids[a]].distance(st.spatial.positions[&ids[b]])
+        };
+        assert!(
+            dist(0, 1) < dist(2, 3),
+            "execs {} vs opens {}",
+            dist(0, 1),
+            dist(2, 3)
+        );
+    }
+
+    fn file(path: &str) -> Node {
+        Node::File {
+            path: path.to_string(),
+            inode: 1,
+            kind: FileKind::Regular,
+            category: FileCategory::Unknown,
+            link_target: None,
+        }
+    }
+
+    #[test]
+    fn substring_filter_is_case_insensitive() {
+        let mut st = GraphState::default();
+        st.ui.filter = "SSH".to_string();
+        st.refresh_filter_regex();
+        let id = NodeId("n:file:/etc/ssh/sshd_config".to_string());
+        assert!(st.passes_filter(&id, &file("/etc/ssh/sshd_config")));
+        assert!(!st.passes_filter(&NodeId("n:file:/tmp/x".to_string()), &file("/tmp/x")));
+    }
+
+    #[test]
+    fn regex_filter_matches_and_rejects_invalid_patterns() {
+        let mut st = GraphState::default();
+        st.cfg.filter_is_regex = true;
+        st.ui.filter = r"^/etc/.*\.conf$".to_string();
+        st.refresh_filter_regex();
+        let id = NodeId("x".to_string());
+        assert!(st.passes_filter(&id, &file("/etc/resolv.CONF")));
+        assert!(!st.passes_filter(&id, &file("/etc/resolv.conf.bak")));
+
+        st.ui.filter = "(".to_string();
+        st.refresh_filter_regex();
+        assert!(matches!(
+            st.ui.filter_regex.as_ref().map(|f| &f.compiled),
+            Some(Err(_))
+        ));
+        assert!(!st.passes_filter(&id, &file("/etc/resolv.conf")));
+    }
+
+    #[test]
+    fn active_set_includes_glowing_nodes_and_neighbors() {
+        let mut st = GraphState::default();
+        let now = std::time::Instant::now();
+        let ids: Vec<NodeId> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|p| NodeId(p.to_string()))
+            .collect();
+        for id in &ids {
+            st.model.nodes.insert(id.clone(), file(&id.0));
+        }
+        for (from, to) in [(0, 1), (2, 3)] {
+            st.model.upsert_edge(
+                Edge {
+                    from: ids[from].clone(),
+                    to: ids[to].clone(),
+                    kind: EdgeKind::Execs,
+                },
+                now,
+            );
+        }
+        st.spatial
+            .glow_nodes
+            .insert(ids[0].clone(), now + std::time::Duration::from_secs(1));
+
+        let active = st.active_set();
+        assert_eq!(active, [ids[0].clone(), ids[1].clone()].into());
+
+        st.cfg.show_only_active = true;
+        st.ui.focus = Some(ids[1].clone());
+        st.ui.focus_hops = 1;
+        assert_eq!(st.visible_set_capped(), active);
+    }
+
+    #[test]
+    fn capping_keeps_hubs_and_pinned_nodes_over_leaves() {
+        let mut st = GraphState::default();
+        let now = std::time::Instant::now();
+        let id = |s: &str| NodeId(s.to_string());
+        // "z-hub" sorts last by id but has the most edges.
+        for name in ["a-leaf", "b", "c", "d", "e-selected", "z-hub"] {
+            st.model.nodes.insert(id(name), file(name));
+        }
+        for leafish in ["b", "c", "d"] {
+            st.model.upsert_edge(
+                Edge {
+                    from: id("z-hub"),
+                    to: id(leafish),
+                    kind: EdgeKind::Execs,
+                },
+                now,
+            );
+        }
+        st.model.upsert_edge(
+            Edge {
+                from: id("b"),
+                to: id("c"),
+                kind: EdgeKind::Execs,
+            },
+            now,
+        );
+        st.ui.selected = Some(id("e-selected"));
+        st.cfg.max_visible_nodes = 3;
+
+        let vis = st.visible_set_capped();
+        assert_eq!(vis, [id("e-selected"), id("z-hub"), id("b")].into());
+        assert!(!vis.contains(&id("a-leaf")));
+    }
+
+    #[test]
+    fn threads_are_hidden_unless_enabled() {
+        let mut st = GraphState::default();
+        let file_id = NodeId("f".to_string());
+        let thread_id = NodeId("t".to_string());
+        st.model.nodes.insert(file_id.clone(), file("f"));
+        st.model.nodes.insert(
+            thread_id.clone(),
+            Node::Thread {
+                tid: 43,
+                pid: 42,
+                name: "worker".to_string(),
+            },
+        );
+
+        assert_eq!(st.visible_set_capped(), [file_id.clone()].into());
+        st.cfg.show_threads = true;
+        assert_eq!(st.visible_set_capped(), [file_id, thread_id].into());
+    }
+
+    #[test]
+    fn time_window_hides_nodes_not_seen_recently() {
+        let mut st = GraphState::default();
+        let now = Instant::now();
+        let old = now
+            .checked_sub(Duration::from_secs(60))
+            .expect("monotonic clock started over a minute ago");
+        let id = |s: &str| NodeId(s.to_string());
+        st.model.upsert_node(id("stale"), file("stale"), old);
+        st.model.upsert_node(id("fresh"), file("fresh"), now);
+        st.model.upsert_node(id("opened"), file("opened"), old);
+        st.model.upsert_edge(
+            Edge {
+                from: id("fresh"),
+                to: id("opened"),
+                kind: EdgeKind::Execs,
+            },
+            now,
+        );
+
+        assert_eq!(st.visible_set_capped().len(), 3);
+        st.cfg.spatial_window_enabled = true;
+        st.cfg.spatial_window = Duration::from_secs(30);
+        assert_eq!(st.visible_set_capped(), [id("fresh"), id("opened")].into());
+    }
+
+    #[test]
+    fn layout_floor_moves_per_layout_pass_not_per_frame() {
+        let mut st = GraphState::default();
+        st.ui.show_3d = true;
+        let now = Instant::now();
+        let id = |s: &str| NodeId(s.to_string());
+        for (name, y) in [("a", -4.0), ("b", 2.0)] {
+            st.model.upsert_node(id(name), file(name), now);
+            st.spatial
+                .positions
+                .insert(id(name), Vec3::new(0.0, y, 0.0));
+        }
+        let vis: HashSet<NodeId> = [id("a"), id("b")].into();
+        st.progressive_prepare(&vis);
+        assert_eq!(st.spatial.layout_floor, -4.0);
+
+        // later frames of a finished pass leave the floor alone
+        st.spatial
+            .positions
+            .insert(id("a"), Vec3::new(0.0, -10.0, 0.0));
+        st.progressive_prepare(&vis);
+        assert_eq!(st.spatial.layout_floor, -4.0);
+
+        st.spatial.dirty_layout = true;
+        st.progressive_prepare(&vis);
+        assert_eq!(st.spatial.layout_floor, -10.0);
+    }
+
+    #[test]
+    fn window_tick_redraws_periodically_only_while_the_window_is_on() {
+        let mut st = GraphState::default();
+        let now = Instant::now();
+        let redraw = |st: &GraphState| st.needs_redraw.swap(false, Ordering::Relaxed);
+        redraw(&st);
+
+        // off: no ticks
+        st.tick_spatial_window(now);
+        st.tick_spatial_window(now + Duration::from_secs(60));
+        assert!(!redraw(&st));
+
+        st.cfg.spatial_window_enabled = true;
+        st.cfg.spatial_window = Duration::from_secs(60);
+        st.tick_spatial_window(now);
+        assert!(!redraw(&st));
+        st.tick_spatial_window(now + Duration::from_secs(2));
+        assert!(!redraw(&st));
+        st.tick_spatial_window(now + Duration::from_secs(3));
+        assert!(redraw(&st) && st.spatial.dirty_layout);
+
+        st.ui.view_mode = ViewMode::Tree;
+        st.tick_spatial_window(now + Duration::from_secs(60));
+        assert!(!redraw(&st));
+    }
+
+    #[test]
+    fn last_activity_follows_node_and_edge_upserts() {
+        let mut st = GraphState::default();
+        let now = Instant::now();
+        let later = now + Duration::from_secs(5);
+        let id = |s: &str| NodeId(s.to_string());
+        st.model.upsert_node(id("p"), file("p"), now);
+        st.model.upsert_node(id("f"), file("f"), now);
+        let edge = Edge {
+            from: id("p"),
+            to: id("f"),
+            kind: EdgeKind::Execs,
+        };
+        st.model.upsert_edge(edge.clone(), later);
+        assert_eq!(st.model.last_active(&id("f")), Some(later));
+        // an older upsert does not move it back
+        st.model.upsert_node(id("f"), file("f"), now);
+        assert_eq!(st.model.last_active(&id("f")), Some(later));
+
+        st.model.remove_node(&id("f"));
+        assert_eq!(st.model.last_active(&id("f")), None);
+        assert_eq!(st.model.last_active(&id("p")), Some(later));
+    }
+
+    #[test]
+    fn expanding_a_node_adds_exactly_its_neighbors() {
+        let mut st = GraphState::default();
+        let now = Instant::now();
+        let id = |s: &str| NodeId(s.to_string());
+        for name in ["a", "b", "c", "d"] {
+            st.model.upsert_node(id(name), file(name), now);
+        }
+        for (from, to) in [("a", "b"), ("c", "a"), ("c", "d")] {
+            st.model.upsert_edge(
+                Edge {
+                    from: id(from),
+                    to: id(to),
+                    kind: EdgeKind::Execs,
+                },
+                now,
+            );
+        }
+        st.ui.isolate_nodes = [id("a")].into();
+        assert_eq!(st.visible_set_capped(), [id("a")].into());
+
+        st.expand_node(&id("a"));
+        assert_eq!(st.visible_set_capped(), [id("a"), id("b"), id("c")].into());
+
+        st.collapse_node(&id("a"));
+        assert_eq!(st.visible_set_capped(), [id("a")].into());
+    }
+
+    #[test]
+    fn isolate_reuses_components_until_the_model_changes() {
+        let mut st = GraphState::default();
+        let now = Instant::now();
+        let id = |s: &str| NodeId(s.to_string());
+        for name in ["a", "b", "c"] {
+            st.model.upsert_node(id(name), file(name), now);
+        }
+        let edge = |from: &str, to: &str| Edge {
+            from: id(from),
+            to: id(to),
+            kind: EdgeKind::Execs,
+        };
+        st.model.upsert_edge(edge("a", "b"), now);
+        st.ui.isolate = Some(id("a"));
+        assert_eq!(st.visible_set_capped(), [id("a"), id("b")].into());
+        let rev = st.model.revision();
+        assert_eq!(st.spatial.components.as_ref().map(|(r, _)| *r), Some(rev));
+
+        // Re-upserting known nodes and edges leaves the structure, and the cache, alone.
+        st.model.upsert_node(id("c"), file("c"), now);
+        st.model.upsert_edge(edge("a", "b"), now);
+        assert_eq!(st.model.revision(), rev);
+        assert_eq!(st.visible_set_capped(), [id("a"), id("b")].into());
+
+        st.model.upsert_edge(edge("b", "c"), now);
+        assert_ne!(st.model.revision(), rev);
+        assert_eq!(st.visible_set_capped(), [id("a"), id("b"), id("c")].into());
+    }
+}
@@ -3,16 +3,24 @@ use std::time::{Duration, Instant};
 
 use crate::graph::state::GraphState;
 
+const COMPONENTS_INTERVAL: Duration = Duration::from_secs(1);
+
 pub fn tick_housekeeping(time: Res<Time>, mut st: ResMut<GraphState>) {
     let dt = time.delta_seconds().max(0.0001);
     st.perf.fps = 1.0 / dt;
 
     st.ensure_demo_graph();
+    st.tick_demo(dt);
+    st.tick_replay(dt);
+    st.tick_follow(Instant::now());
+    st.tick_spatial_window(Instant::now());
 
     st.tick_glow();
     st.tick_metrics(Instant::now());
+    st.tick_reconnects(Instant::now());
     st.tick_gc();
 
+    st.tick_timeline_playback(dt);
     st.tick_timeline();
 }
 
@@ -30,14 +38,12 @@ impl GraphState {
         self.perf.event_rate = (self.perf.ev_window.len() as f32) / window.as_secs_f32();
 
         for stream in self.net.streams.values_mut() {
-            while let Some(front) = stream.msg_window.front() {
-                if now.duration_since(*front) > self.net.msg_window {
-                    stream.msg_window.pop_front();
-                } else {
-                    break;
-                }
-            }
-            stream.msg_rate = stream.msg_window.len() as f32 / self.net.msg_window.as_secs_f32();
+            GraphState::net_prune_stream(stream, now, self.net.msg_window);
+        }
+
+        if now.duration_since(self.perf.components_last_run) >= COMPONENTS_INTERVAL {
+            self.perf.components_last_run = now;
+            self.perf.component_count = self.components().count;
         }
     }
 
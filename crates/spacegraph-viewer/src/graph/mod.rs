@@ -1,13 +1,24 @@
+pub mod batch_log;
+pub mod bundling;
+pub mod collapse;
+pub mod components;
+pub mod demo;
+pub mod diff;
+pub mod expand;
 pub mod explain;
+pub mod follow;
 pub mod gc;
+pub mod history;
 pub mod layout;
 pub mod metrics;
 pub mod model;
 pub mod state;
+pub mod stats;
 pub mod timeline;
 pub mod tree;
+pub mod user_layout;
 
 pub use layout::update_layout_or_timeline;
 pub use metrics::tick_housekeeping;
-pub use state::{GraphState, ViewMode};
+pub use state::{GraphState, NavDir, ViewMode};
 pub use timeline::TimelineEvtKind;
@@ -1,7 +1,7 @@
 use smallvec::SmallVec;
 use spacegraph_core::{Edge, EdgeKind, Node, NodeId};
 use std::collections::{HashMap, HashSet};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 pub type EdgeRef = Edge;
 
@@ -10,6 +10,10 @@ pub enum EdgeKindClass {
     Opens,
     Execs,
     RunsAs,
+    ParentOf,
+    MemberOf,
+    PointsTo,
+    ThreadOf,
 }
 
 impl EdgeKindClass {
@@ -18,6 +22,10 @@ impl EdgeKindClass {
             EdgeKind::Opens { .. } => Self::Opens,
             EdgeKind::Execs => Self::Execs,
             EdgeKind::RunsAs => Self::RunsAs,
+            EdgeKind::ParentOf => Self::ParentOf,
+            EdgeKind::MemberOf => Self::MemberOf,
+            EdgeKind::PointsTo => Self::PointsTo,
+            EdgeKind::ThreadOf => Self::ThreadOf,
         }
     }
 }
@@ -42,7 +50,6 @@ impl AggEdgeKey {
 #[derive(Debug, Clone)]
 pub struct EdgeStats {
     pub count: u64,
-    #[allow(dead_code)]
     pub first_ts: Instant,
     pub last_ts: Instant,
 }
@@ -55,13 +62,28 @@ pub struct AggEdge {
     pub live_count: usize,
 }
 
+/// Widest half-width (world units) an aggregated edge is drawn with.
+pub const AGG_EDGE_MAX_WIDTH: f32 = 0.3;
+
+/// Half-width for an aggregated edge that stands for `count` raw events. Log-scaled so a
+/// busy edge reads as thicker without hiding its endpoints; a single event stays a plain line.
+pub fn agg_edge_width(count: u64) -> f32 {
+    ((count.max(1) as f32).log2() * 0.03).min(AGG_EDGE_MAX_WIDTH)
+}
+
 #[derive(Default)]
 pub struct GraphModel {
     pub nodes: HashMap<NodeId, Node>,
     pub edges: HashSet<Edge>,
     pub last_seen: HashMap<NodeId, Instant>,
+    /// Last upsert per raw edge; basis for the edge TTL.
+    pub edge_last_seen: HashMap<Edge, Instant>,
+    /// Latest upsert of a node or of any edge touching it, for the spatial time window.
+    last_active: HashMap<NodeId, Instant>,
     adj: HashMap<NodeId, SmallVec<[EdgeRef; 8]>>,
     agg: HashMap<AggEdgeKey, AggEdge>,
+    /// Bumped whenever a node or edge appears or disappears.
+    revision: u64,
 }
 
 pub fn edge_kind_name(k: &EdgeKind) -> &'static str {
@@ -69,6 +91,10 @@ pub fn edge_kind_name(k: &EdgeKind) -> &'static str {
         EdgeKind::Opens { .. } => "opens",
         EdgeKind::Execs => "execs",
         EdgeKind::RunsAs => "runs_as",
+        EdgeKind::ParentOf => "parent_of",
+        EdgeKind::MemberOf => "member_of",
+        EdgeKind::PointsTo => "points_to",
+        EdgeKind::ThreadOf => "thread_of",
     }
 }
 
@@ -77,6 +103,10 @@ pub fn edge_class_name(k: EdgeKindClass) -> &'static str {
         EdgeKindClass::Opens => "opens",
         EdgeKindClass::Execs => "execs",
         EdgeKindClass::RunsAs => "runs_as",
+        EdgeKindClass::ParentOf => "parent_of",
+        EdgeKindClass::MemberOf => "member_of",
+        EdgeKindClass::PointsTo => "points_to",
+        EdgeKindClass::ThreadOf => "thread_of",
     }
 }
 
@@ -85,6 +115,10 @@ pub fn edge_explain(k: &EdgeKind) -> String {
         EdgeKind::Opens { fd, mode } => format!("process opened file (fd={fd}, mode={mode})"),
         EdgeKind::Execs => "process execs file (exe)".to_string(),
         EdgeKind::RunsAs => "process runs as user (uid)".to_string(),
+        EdgeKind::ParentOf => "process is parent of process (ppid)".to_string(),
+        EdgeKind::MemberOf => "process runs in container (cgroup)".to_string(),
+        EdgeKind::PointsTo => "symlink points to file".to_string(),
+        EdgeKind::ThreadOf => "thread belongs to process (tid)".to_string(),
     }
 }
 
@@ -93,28 +127,62 @@ impl GraphModel {
         self.nodes.clear();
         self.edges.clear();
         self.last_seen.clear();
+        self.edge_last_seen.clear();
+        self.last_active.clear();
         self.adj.clear();
         self.agg.clear();
+        self.revision += 1;
+    }
+
+    /// Changes with every added or removed node or edge; key for structure caches.
+    pub fn revision(&self) -> u64 {
+        self.revision
     }
 
     pub fn load_snapshot(&mut self, nodes: Vec<(NodeId, Node)>, edges: Vec<Edge>, now: Instant) {
         self.nodes = nodes.into_iter().collect();
         self.edges = edges.into_iter().collect();
         self.last_seen.clear();
+        self.last_active.clear();
         for id in self.nodes.keys() {
             self.last_seen.insert(id.clone(), now);
+            self.last_active.insert(id.clone(), now);
         }
         self.rebuild_indices(now);
+        self.revision += 1;
     }
 
     pub fn upsert_node(&mut self, id: NodeId, node: Node, now: Instant) {
-        self.nodes.insert(id.clone(), node);
-        self.last_seen.insert(id, now);
+        if self.nodes.insert(id.clone(), node).is_none() {
+            self.revision += 1;
+        }
+        self.touch_node(&id, now);
+    }
+
+    /// Marks `id` as seen at `now` without changing the node.
+    pub fn touch_node(&mut self, id: &NodeId, now: Instant) {
+        self.last_seen.insert(id.clone(), now);
+        self.note_activity(id, now);
+    }
+
+    /// When `id` or one of its edges was last upserted.
+    pub fn last_active(&self, id: &NodeId) -> Option<Instant> {
+        self.last_active.get(id).copied()
+    }
+
+    fn note_activity(&mut self, id: &NodeId, now: Instant) {
+        self.last_active
+            .entry(id.clone())
+            .and_modify(|at| *at = (*at).max(now))
+            .or_insert(now);
     }
 
     pub fn remove_node(&mut self, id: &NodeId) -> Vec<Edge> {
-        self.nodes.remove(id);
+        if self.nodes.remove(id).is_some() {
+            self.revision += 1;
+        }
         self.last_seen.remove(id);
+        self.last_active.remove(id);
         let mut removed = Vec::new();
         if let Some(edges) = self.adj.get(id).cloned() {
             for edge in edges {
@@ -129,17 +197,23 @@ impl GraphModel {
 
     pub fn upsert_edge(&mut self, edge: Edge, now: Instant) {
         let inserted = self.edges.insert(edge.clone());
+        self.edge_last_seen.insert(edge.clone(), now);
+        self.note_activity(&edge.from, now);
+        self.note_activity(&edge.to, now);
         if inserted {
             self.insert_adj(&edge);
+            self.revision += 1;
         }
         self.update_agg_on_upsert(&edge, now, inserted);
     }
 
     pub fn remove_edge(&mut self, edge: &Edge) -> bool {
         let removed = self.edges.remove(edge);
+        self.edge_last_seen.remove(edge);
         if removed {
             self.remove_adj(edge);
             self.update_agg_on_remove(edge);
+            self.revision += 1;
         }
         removed
     }
@@ -165,19 +239,69 @@ impl GraphModel {
         })
     }
 
+    /// Number of edge endpoints touching each node.
+    pub fn degree_map(&self) -> HashMap<NodeId, u32> {
+        let mut degree: HashMap<NodeId, u32> = HashMap::new();
+        for e in self.edges.iter() {
+            *degree.entry(e.from.clone()).or_insert(0) += 1;
+            *degree.entry(e.to.clone()).or_insert(0) += 1;
+        }
+        degree
+    }
+
+    /// `Opens` edges per process, i.e. its open file descriptors.
+    pub fn open_file_counts(&self) -> HashMap<NodeId, u32> {
+        let mut counts: HashMap<NodeId, u32> = HashMap::new();
+        for e in self.edges.iter() {
+            if matches!(e.kind, EdgeKind::Opens { .. })
+                && matches!(self.nodes.get(&e.from), Some(Node::Process { .. }))
+            {
+                *counts.entry(e.from.clone()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Distinct neighbors of `id` ordered by id, excluding self-loops.
+    pub fn sorted_neighbors(&self, id: &NodeId) -> Vec<NodeId> {
+        let mut out: Vec<NodeId> = self.neighbors(id).filter(|nb| nb != id).collect();
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        out.dedup();
+        out
+    }
+
     pub fn agg_edges(&self) -> impl Iterator<Item = &AggEdge> + '_ {
         self.agg.values()
     }
 
+    pub fn agg_edge(&self, key: &AggEdgeKey) -> Option<&AggEdge> {
+        self.agg.get(key)
+    }
+
     pub fn agg_edge_count(&self) -> usize {
         self.agg.len()
     }
 
+    /// `Opens` edges not upserted within `ttl`. Agents never re-send other edge kinds
+    /// (parent, user, exec links), so only fd edges can go stale this way.
+    pub fn stale_edges(&self, now: Instant, ttl: Duration) -> Vec<Edge> {
+        self.edge_last_seen
+            .iter()
+            .filter(|(edge, last)| {
+                matches!(edge.kind, EdgeKind::Opens { .. })
+                    && now.saturating_duration_since(**last) >= ttl
+            })
+            .map(|(edge, _)| edge.clone())
+            .collect()
+    }
+
     fn rebuild_indices(&mut self, now: Instant) {
         self.adj.clear();
         self.agg.clear();
+        self.edge_last_seen.clear();
         let edges: Vec<Edge> = self.edges.iter().cloned().collect();
         for edge in edges {
+            self.edge_last_seen.insert(edge.clone(), now);
             self.insert_adj(&edge);
             self.update_agg_on_upsert(&edge, now, true);
         }
@@ -206,6 +330,10 @@ impl GraphModel {
         list.retain(|e| e != edge);
         if list.is_empty() {
             self.adj.remove(id);
+            // an edge endpoint that never arrived as a node has nothing left to track
+            if !self.nodes.contains_key(id) {
+                self.last_active.remove(id);
+            }
         }
     }
 
@@ -246,14 +374,15 @@ impl GraphModel {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use spacegraph_core::FileKind;
-    use std::time::Duration;
+    use spacegraph_core::{FileCategory, FileKind};
 
     fn node_file(path: &str) -> Node {
         Node::File {
             path: path.to_string(),
             inode: 1,
             kind: FileKind::Regular,
+            category: FileCategory::Unknown,
+            link_target: None,
         }
     }
 
@@ -314,4 +443,81 @@ mod tests {
         assert_eq!(agg.live_count, 1);
         assert_eq!(agg.key.class, EdgeKindClass::Opens);
     }
+
+    #[test]
+    fn agg_edge_width_grows_logarithmically_and_caps() {
+        assert_eq!(agg_edge_width(0), 0.0);
+        assert_eq!(agg_edge_width(1), 0.0);
+        let (w2, w4, w8) = (agg_edge_width(2), agg_edge_width(4), agg_edge_width(8));
+        assert!(w2 > 0.0 && w4 > w2 && w8 > w4);
+        assert!(((w4 - w2) - (w8 - w4)).abs() < 1e-6);
+        assert_eq!(agg_edge_width(u64::MAX), AGG_EDGE_MAX_WIDTH);
+    }
+
+    #[test]
+    fn open_file_counts_only_count_opens_from_processes() {
+        let mut model = GraphModel::default();
+        let now = Instant::now();
+        let id = |s: &str| NodeId(s.to_string());
+        for pid in [1, 2] {
+            model.upsert_node(
+                id(&format!("p{pid}")),
+                Node::Process {
+                    pid,
+                    ppid: 0,
+                    exe: "/bin/sh".to_string(),
+                    cmdline: "sh".to_string(),
+                    uid: 0,
+                    rss_kb: 0,
+                    utime_ticks: 0,
+                    container: None,
+                },
+                now,
+            );
+        }
+        model.upsert_node(id("f1"), node_file("/f1"), now);
+        model.upsert_node(id("f2"), node_file("/f2"), now);
+        model.upsert_node(
+            id("u"),
+            Node::User {
+                uid: 0,
+                name: "root".to_string(),
+            },
+            now,
+        );
+
+        let opens = |from: &str, to: &str, fd: i32| Edge {
+            from: id(from),
+            to: id(to),
+            kind: EdgeKind::Opens {
+                fd,
+                mode: "r".to_string(),
+            },
+        };
+        for edge in [
+            opens("p1", "f1", 3),
+            opens("p1", "f1", 4),
+            opens("p1", "f2", 5),
+            opens("p2", "f2", 3),
+            // Not a process: ignored.
+            opens("f1", "f2", 0),
+            Edge {
+                from: id("p1"),
+                to: id("f2"),
+                kind: EdgeKind::Execs,
+            },
+            Edge {
+                from: id("p1"),
+                to: id("u"),
+                kind: EdgeKind::RunsAs,
+            },
+        ] {
+            model.upsert_edge(edge, now);
+        }
+
+        let counts = model.open_file_counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&id("p1")], 3);
+        assert_eq!(counts[&id("p2")], 1);
+    }
 }
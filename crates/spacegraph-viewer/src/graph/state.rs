@@ -1,18 +1,44 @@
 use bevy::prelude::{Resource, Vec3};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use regex::Regex;
 use spacegraph_core::{
-    id_file, id_process, id_user, Delta, Edge, EdgeKind, FileKind, Msg, Node, NodeId,
+    id_file, parse_node_id, Capabilities, ControlCmd, Delta, Edge, EdgeKind, FileKind, Msg, Node,
+    NodeId, ParsedId, PROTOCOL_VERSION,
 };
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
+use crate::graph::batch_log::{BatchChange, BatchLog};
+use crate::graph::bundling::EdgeBundles;
+use crate::graph::collapse::CollapsedView;
+use crate::graph::components::Components;
+use crate::graph::demo::DemoGenerator;
+use crate::graph::diff::GraphDiff;
+use crate::graph::expand::Expansions;
 use crate::graph::explain::{self, PathStep};
+use crate::graph::follow::FollowNewest;
+use crate::graph::history::{HistoryChange, ModelHistory};
 use crate::graph::model::GraphModel;
-use crate::graph::timeline::{BatchSpan, NodeLife, TimelineEvt, TimelineEvtKind};
+use crate::graph::stats::GraphStats;
+use crate::graph::timeline::{
+    BatchSpan, NodeLife, TimelineEvt, TimelineEvtKind, MIN_TIMELINE_EVENTS,
+};
 use crate::graph::tree;
-use crate::net::{Incoming, IncomingKind, ReaderHandle};
-use crate::util::config::{AgentEndpoint, AgentMode, LodEdgesMode, ViewerConfig, ViewerViewMode};
-use crate::util::ids::{node_label_long, node_label_short};
+use crate::net::{Incoming, IncomingKind, MsgCounters, ReaderHandle};
+use crate::util::config::{
+    AgentEndpoint, AgentMode, DemoScenario, DisconnectPolicy, DurationUnits, GcMode, LabelMode,
+    LayoutMode, LayoutProfile, LodEdgesMode, NodeOpacity, Theme, ViewPreset, ViewerConfig,
+    ViewerViewMode,
+};
+use crate::util::ids::{format_duration_in, host_of, node_label_long, node_label_short};
+use crate::util::record::{replay_incoming, RecordLine, Recorder, Replay};
+
+/// Stream name used for snapshots loaded from disk instead of a live agent.
+pub const IMPORT_STREAM: &str = "import";
+/// Synthetic batch id wrapping a chunked snapshot.
+pub const SNAPSHOT_BATCH_ID: u64 = u64::MAX;
 
 #[derive(Default)]
 pub struct SpatialState {
@@ -20,17 +46,46 @@ pub struct SpatialState {
     pub velocities: HashMap<NodeId, Vec3>,
 
     pub in_batch: bool,
+    /// `opens` edges removed in the current batch, keyed by (process, file, fd) with their
+    /// mode, so an fd reopened with another mode shows up as a transition.
+    pub removed_opens: HashMap<(NodeId, NodeId, i32), String>,
     pub touched_nodes: HashSet<NodeId>,
     pub touched_edges: HashSet<Edge>,
     pub glow_nodes: HashMap<NodeId, Instant>,
     pub glow_edges: HashMap<Edge, Instant>,
     pub last_batch_id: Option<u64>,
+    /// Changes of the batch in progress and frozen copies of the last few batches.
+    pub batch_log: BatchLog,
 
     pub active_vis_cache: Vec<NodeId>,
+    /// Start positions from `LayoutMode::GroupByUser`, rebuilt with `active_vis_cache`.
+    pub cluster_targets: HashMap<NodeId, Vec3>,
     pub progressive_cursor: usize,
     pub dirty_layout: bool,
+    /// Largest node step of the last force-layout tick; 0 once settled or disabled.
+    pub layout_motion: f32,
+    /// Lowest visible node height (at most 0) when the last layout pass finished or the
+    /// force layout came to rest; the grid sits below it.
+    pub layout_floor: f32,
+    pub bundles: EdgeBundles,
     pub lod_active: bool,
     pub tree_dir_children: HashSet<NodeId>,
+    /// Nodes held in place by force layout.
+    pub pinned: HashSet<NodeId>,
+    /// Host (node_id prefix) -> palette index, assigned in first-seen order.
+    pub host_colors: BTreeMap<String, usize>,
+    /// Orphan nodes past the GC TTL, kept but drawn dimmed (`GcMode::Dim`).
+    pub stale_nodes: HashSet<NodeId>,
+    /// Built from `UiState::collapsed_dirs` on redraws and model changes.
+    pub collapsed: CollapsedView,
+    /// Model revision `collapsed` was built for; `None` forces a rebuild.
+    pub collapsed_rev: Option<u64>,
+    /// Connected components for isolate and the HUD, tagged with the model revision.
+    pub components: Option<(u64, Components)>,
+    /// Bumped on every spatial redraw; overlays rebuild their caches when it moves.
+    pub redraw_seq: u64,
+    /// Last time the spatial time window was re-applied; `None` while it is off.
+    pub window_tick: Option<Instant>,
 }
 
 #[derive(Default)]
@@ -40,11 +95,23 @@ pub struct TimelineState {
     pub pause: bool,
     pub frozen_now: Option<Instant>,
     pub scrub_seconds: f32,
+    /// Replaying history while paused: `scrub_seconds` counts down towards 0.
+    pub playing: bool,
+    pub playback_speed: f32,
     pub show_connectors: bool,
+    /// One lane per node kind instead of one per node.
+    pub group_by_kind: bool,
+    /// Append with `push_event` so `event_bytes` stays in step.
     pub events: VecDeque<TimelineEvt>,
     pub max_events: usize,
     pub node_life: HashMap<NodeId, NodeLife>,
+    /// Running footprints of `events` and `node_life`, for `buffer_bytes`.
+    pub(crate) event_bytes: usize,
+    pub(crate) life_bytes: usize,
     pub batch_spans: VecDeque<BatchSpan>,
+    pub max_batch_spans: usize,
+    /// Undo log for reconstructing past graph states; see `GraphState::reconstruct_at`.
+    pub history: ModelHistory,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -55,26 +122,65 @@ pub enum ViewMode {
     Timeline,
 }
 
+/// Default sphere radius in the spatial view.
+pub const NODE_RADIUS: f32 = 0.28;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavDir {
+    Next,
+    Prev,
+}
+
+/// Compiled regex filter, keyed by the filter text it was built from.
+pub struct FilterRegex {
+    pub source: String,
+    pub compiled: Result<Regex, String>,
+}
+
 #[derive(Default)]
 pub struct UiState {
     pub filter: String,
+    pub filter_regex: Option<FilterRegex>,
     pub show_3d: bool,
     pub show_edges: bool,
     pub help_open: bool,
+    pub show_event_log: bool,
+    pub event_log_filter: String,
     pub show_path_editor: bool,
     pub path_editor: PathEditorDraft,
     pub show_agent_manager: bool,
+    pub show_stats: bool,
+    pub show_batch_diff: bool,
+    pub show_minimap: bool,
+    pub show_explain: bool,
+    /// Highlight the paths from the focus to each multi-selected node.
+    pub explain_focus_paths: bool,
+    /// Name typed in the view preset editor.
+    pub preset_name: String,
+    /// Computed when the stats window opens; refreshed on demand.
+    pub stats: Option<GraphStats>,
     pub show_agent_editor: bool,
     pub agent_editor: AgentEditorDraft,
     pub agent_command: AgentCommandDraft,
 
     pub focus: Option<NodeId>,
     pub focus_hops: usize,
+    /// Anchor node whose connected component is the only one shown.
+    pub isolate: Option<NodeId>,
+    /// When non-empty, only these nodes are shown ("Isolate selection").
+    pub isolate_nodes: HashSet<NodeId>,
+    /// Neighbors revealed by "Expand"; shown regardless of filter and focus.
+    pub expanded: Expansions,
 
     pub hovered: Option<NodeId>,
     pub selected: Option<NodeId>,
     pub selected_a: Option<NodeId>,
     pub selected_b: Option<NodeId>,
+    pub selected_edge: Option<Edge>,
+    /// Nodes picked by Shift+drag box selection.
+    pub multi_selected: HashSet<NodeId>,
+    /// Node whose neighbors keyboard navigation is cycling through.
+    pub nav_anchor: Option<NodeId>,
 
     pub search_open: bool,
     pub search_query: String,
@@ -84,12 +190,25 @@ pub struct UiState {
 
     pub view_mode: ViewMode,
     pub tree_collapsed: HashSet<NodeId>,
+    /// Directories whose files are drawn as one placeholder in the spatial view.
+    pub collapsed_dirs: HashSet<String>,
     pub tree_expanded: HashSet<NodeId>,
     pub tree_show_files: bool,
     pub tree_zoom: f32,
     pub tree_file_zoom_threshold: f32,
     pub tree_center: Vec3,
     pub tree_default_expand_depth: usize,
+
+    pub export_notice: Option<String>,
+    pub history_notice: Option<String>,
+    pub import_notice: Option<String>,
+
+    /// Set by the Screenshot button or F12; the capture runs at the start of the next frame.
+    pub screenshot_requested: bool,
+    /// The egui overlays skip the frame being captured unless `screenshot_include_ui`.
+    pub screenshot_hide_ui: bool,
+    /// Shown in the HUD for a few seconds after a capture.
+    pub screenshot_notice: Option<(String, Instant)>,
 }
 
 #[derive(Default, Clone)]
@@ -108,6 +227,11 @@ pub struct AgentEditorDraft {
     pub uds_input: String,
     pub auto_connect: bool,
     pub mode_override: Option<AgentMode>,
+    pub compression: bool,
+    pub max_reconnect_attempts: u32,
+    pub on_disconnect: DisconnectPolicy,
+    pub token_file_input: String,
+    pub token_input: String,
     pub notice: Option<String>,
 }
 
@@ -135,10 +259,20 @@ pub struct ExplainCache {
     pub a: NodeId,
     pub b: NodeId,
     pub focus: Option<NodeId>,
+    pub max_depth: usize,
     pub ts: Instant,
     pub result: Option<Vec<PathStep>>,
 }
 
+/// Paths from the focus to every multi-selected node, see `focus_paths_cached`.
+pub struct FocusPathsCache {
+    pub focus: NodeId,
+    pub targets: Vec<NodeId>,
+    pub max_depth: usize,
+    pub ts: Instant,
+    pub result: Vec<PathStep>,
+}
+
 pub struct PerfState {
     pub fps: f32,
     pub event_rate: f32,
@@ -149,6 +283,10 @@ pub struct PerfState {
     pub event_total: u64,
     pub ev_window: VecDeque<Instant>,
     pub gc_last_run: Instant,
+    pub component_count: usize,
+    pub components_last_run: Instant,
+    /// Raw edges left out by `max_rendered_edges` in the last spatial frame.
+    pub edges_capped: usize,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -166,7 +304,43 @@ pub struct NetStreamState {
     pub last_event_at: Option<Instant>,
     pub msg_rate: f32,
     pub msg_window: VecDeque<Instant>,
+    pub bytes_per_sec: f32,
+    pub byte_window: VecDeque<(Instant, usize)>,
+    /// Messages per type, for the breakdown in the agents table.
+    pub msg_counts: MsgCounters,
     pub last_error: Option<String>,
+    /// Session the agent announced in its `Hello`.
+    pub agent_session: Option<u64>,
+    /// Last batch from this stream applied completely; with the session it lets a
+    /// reconnect skip the full snapshot.
+    pub last_batch_done: Option<u64>,
+    /// Protocol version from the agent's `Identity`; 0 for agents that predate it.
+    pub agent_protocol: Option<u32>,
+    /// Capabilities from the agent's `Identity`.
+    pub agent_caps: Option<Capabilities>,
+    /// Host prefix of the agent's node ids, from its `Identity`.
+    pub agent_host: Option<String>,
+    /// Messages or snapshot items skipped because this viewer does not know their variant.
+    pub unknown_variants: u64,
+    pub last_unknown: Option<String>,
+    /// Reconnects since the last message; reset by a message or a manual reconnect.
+    pub reconnect_attempts: u32,
+    pub next_reconnect: Option<Instant>,
+    /// Set once `max_reconnect_attempts` is exceeded; only a manual reconnect clears it.
+    pub gave_up: bool,
+}
+
+const RECONNECT_BACKOFF_BASE: Duration = Duration::from_secs(1);
+const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+/// Delay before reconnect `attempt` (1-based): the first retry is immediate, then
+/// 1s, 2s, 4s, … capped at 30s.
+pub fn reconnect_backoff(attempt: u32) -> Duration {
+    if attempt <= 1 {
+        return Duration::ZERO;
+    }
+    let factor = 1u32 << (attempt - 2).min(5);
+    (RECONNECT_BACKOFF_BASE * factor).min(RECONNECT_BACKOFF_MAX)
 }
 
 pub struct NetState {
@@ -175,12 +349,15 @@ pub struct NetState {
     pub connections: HashMap<String, ReaderHandle>,
     pub msg_window: Duration,
     pub commands: Vec<NetCommand>,
+    /// Active "Record to file" session, if any.
+    pub recorder: Option<Recorder>,
 }
 
 pub enum NetCommand {
     Connect(String),
     Disconnect(String),
     Reconnect(String),
+    Control(String, ControlCmd),
 }
 
 impl NetStreamState {
@@ -193,9 +370,63 @@ impl NetStreamState {
             last_event_at: None,
             msg_rate: 0.0,
             msg_window: VecDeque::new(),
+            bytes_per_sec: 0.0,
+            byte_window: VecDeque::new(),
+            msg_counts: MsgCounters::default(),
             last_error: None,
+            agent_session: None,
+            last_batch_done: None,
+            agent_protocol: None,
+            agent_caps: None,
+            agent_host: None,
+            unknown_variants: 0,
+            last_unknown: None,
+            reconnect_attempts: 0,
+            next_reconnect: None,
+            gave_up: false,
+        }
+    }
+
+    /// Warning for the HUD and agents table when the agent is newer than this viewer.
+    pub fn protocol_warning(&self) -> Option<String> {
+        let newer = self
+            .agent_protocol
+            .filter(|v| *v > PROTOCOL_VERSION)
+            .map(|v| format!("agent protocol v{v} is newer than v{PROTOCOL_VERSION}"));
+        let skipped = self.last_unknown.as_ref().map(|last| {
+            format!(
+                "{} unknown item(s) skipped, last: {last}",
+                self.unknown_variants
+            )
+        });
+        match (newer, skipped) {
+            (Some(a), Some(b)) => Some(format!("{a}; {b}")),
+            (a, b) => a.or(b),
         }
     }
+
+    /// Agent session and batch to resume after, if this stream's data is still intact.
+    pub fn resume_point(&self) -> Option<(u64, u64)> {
+        self.agent_session.zip(self.last_batch_done)
+    }
+}
+
+/// Drops samples older than `span` and returns the average throughput per
+/// second over the remaining window.
+pub(crate) fn rolling_rate(
+    window: &mut VecDeque<(Instant, usize)>,
+    now: Instant,
+    span: Duration,
+) -> f32 {
+    while let Some((at, _)) = window.front() {
+        if now.duration_since(*at) > span {
+            window.pop_front();
+        } else {
+            break;
+        }
+    }
+    let total: usize = window.iter().map(|(_, n)| n).sum();
+    total as f32 / span.as_secs_f32()
 }
 
 impl Default for NetState {
@@ -206,6 +437,7 @@ impl Default for NetState {
             connections: HashMap::new(),
             msg_window: Duration::from_secs(2),
             commands: Vec::new(),
+            recorder: None,
         }
     }
 }
@@ -228,6 +460,60 @@ impl NetState {
     pub fn active_connection_count(&self) -> usize {
         self.connections.len()
     }
+
+    /// Union of the capabilities of all connected agents: a feature is available if any
+    /// agent provides it. `None` until a connected agent has sent its `Identity`.
+    pub fn merged_caps(&self) -> Option<Capabilities> {
+        self.streams
+            .values()
+            .filter(|s| s.status == NetStreamStatus::Connected)
+            .filter_map(|s| s.agent_caps.as_ref())
+            .fold(None, |acc: Option<Capabilities>, c| {
+                let a = acc.unwrap_or_default();
+                Some(Capabilities {
+                    procfs: a.procfs || c.procfs,
+                    fd_edges: a.fd_edges || c.fd_edges,
+                    fs_notify: a.fs_notify || c.fs_notify,
+                    proc_poll: a.proc_poll || c.proc_poll,
+                    ebpf: a.ebpf || c.ebpf,
+                    cloud: a.cloud || c.cloud,
+                    windows: a.windows || c.windows,
+                    compression: a.compression || c.compression,
+                })
+            })
+    }
+}
+
+/// Names of the capabilities an agent reports, for the agents table.
+pub fn capability_names(caps: &Capabilities) -> Vec<&'static str> {
+    [
+        ("procfs", caps.procfs),
+        ("fd", caps.fd_edges),
+        ("fs", caps.fs_notify),
+        ("poll", caps.proc_poll),
+        ("ebpf", caps.ebpf),
+        ("cloud", caps.cloud),
+        ("windows", caps.windows),
+        ("deflate", caps.compression),
+    ]
+    .into_iter()
+    .filter_map(|(name, on)| on.then_some(name))
+    .collect()
+}
+
+/// Short notes on viewer features that lack data because no agent provides it.
+pub fn missing_capability_notes(caps: &Capabilities) -> Vec<&'static str> {
+    let mut notes = Vec::new();
+    if !caps.procfs && !caps.proc_poll {
+        notes.push("no process data");
+    }
+    if !caps.fd_edges {
+        notes.push("no open-file edges");
+    }
+    if !caps.fs_notify {
+        notes.push("no fs events");
+    }
+    notes
 }
 
 impl Default for PerfState {
@@ -242,6 +528,9 @@ impl Default for PerfState {
             event_total: 0,
             ev_window: VecDeque::new(),
             gc_last_run: Instant::now(),
+            component_count: 0,
+            components_last_run: Instant::now(),
+            edges_capped: 0,
         }
     }
 }
@@ -249,8 +538,10 @@ impl Default for PerfState {
 #[derive(Default)]
 pub struct CfgState {
     pub layout_force: bool,
+    pub layout_mode: LayoutMode,
     pub link_distance: f32,
     pub repulsion: f32,
+    pub layout_profile: LayoutProfile,
     pub damping: f32,
     pub max_step: f32,
 
@@ -258,6 +549,25 @@ pub struct CfgState {
     pub y_spread: f32,
 
     pub glow_duration: Duration,
+    /// Emissive multiplier of glowing nodes; values above 1 feed the bloom pass.
+    pub glow_emissive: f32,
+    /// Camera bloom strength; 0 turns bloom (and HDR) off.
+    pub bloom_intensity: f32,
+    pub node_opacity: NodeOpacity,
+    pub edge_opacity: f32,
+    pub grid_enabled: bool,
+    pub grid_spacing: f32,
+    pub grid_extent: f32,
+    pub ambient_brightness: f32,
+    pub theme: Theme,
+    /// Draw just-seen nodes slightly larger while they glow.
+    pub fade_in_new_nodes: bool,
+    /// Drop to a low update rate while nothing moves and no events arrive.
+    pub limit_fps_when_idle: bool,
+    pub label_mode: LabelMode,
+    pub label_max_distance: f32,
+    pub label_max_count: usize,
+    pub duration_units: DurationUnits,
 
     pub max_visible_nodes: usize,
     pub progressive_nodes_per_frame: usize,
@@ -265,25 +575,86 @@ pub struct CfgState {
     pub gc_enabled: bool,
     pub gc_ttl: Duration,
     pub gc_interval: Duration,
+    pub gc_mode: GcMode,
+    pub gc_hard_ttl: Duration,
+    /// Drop fd edges that were not re-observed for this long; `None` keeps them.
+    pub edge_ttl: Option<Duration>,
+
+    pub camera_sensitivity: f32,
+
+    pub size_by_degree: bool,
+    pub color_by_category: bool,
+    /// Tint processes from cool to hot by their number of open files.
+    pub color_by_open_files: bool,
+    pub node_radius_min: f32,
+    pub node_radius_max: f32,
 
     pub show_raw_edges: bool,
     pub show_agg_edges: bool,
+    /// Curve roughly parallel edges of one class together once the layout settles.
+    pub edge_bundling: bool,
+    /// Pull between bundled edges, 0..1.
+    pub edge_bundling_strength: f32,
+    /// Only draw edges touching the focus/selection, independent of LOD.
+    pub edges_selection_only: bool,
     pub explain_max_depth: usize,
 
+    pub filter_is_regex: bool,
+    /// Restricts the visible set to glowing nodes and their neighbors.
+    pub show_only_active: bool,
+    /// Spatial view keeps only nodes seen, directly or via an edge, within `spatial_window`.
+    pub spatial_window_enabled: bool,
+    pub spatial_window: Duration,
+    /// Thread nodes (agents run with `--threads`) are hidden unless set.
+    pub show_threads: bool,
+    /// Spatial view draws same-path files of different hosts as one node; display only.
+    pub merge_files_by_path: bool,
+    /// Focus each newly spawned process (debounced); cleared by a manual pick.
+    pub follow_newest: bool,
+
     pub lod_enabled: bool,
     pub lod_threshold_nodes: usize,
     pub lod_edges_mode: LodEdgesMode,
+    pub max_rendered_edges: usize,
 
     pub demo_mode: bool,
+    /// Synthetic events per second while Demo Mode is on.
+    pub demo_rate: f32,
+    /// Processes and files the demo churn steers towards.
+    pub demo_entities: usize,
+    /// Fixed seed for a reproducible demo; random when unset.
+    pub demo_seed: Option<u64>,
+    /// Scripted scenario replayed on a loop instead of random churn.
+    pub demo_scenario: DemoScenario,
     pub path_includes: Vec<String>,
     pub path_excludes: Vec<String>,
     pub agent_default_mode: AgentMode,
+    /// Readers drop the connection when no frame arrives for this long.
+    pub heartbeat_timeout: Duration,
+    pub view_presets: Vec<ViewPreset>,
+    /// Screenshots are written here; the working directory when unset.
+    pub screenshot_dir: Option<String>,
+    pub screenshot_include_ui: bool,
 }
 
 impl CfgState {
     pub fn lod_active(&self, visible_nodes: usize) -> bool {
         self.lod_enabled && visible_nodes >= self.lod_threshold_nodes
     }
+
+    /// Sphere radius for a node; log-scaled between min and max when sizing by degree.
+    pub fn node_radius(&self, degree: u32, max_degree: u32) -> f32 {
+        if !self.size_by_degree {
+            return NODE_RADIUS;
+        }
+        let min = self.node_radius_min;
+        let max = self.node_radius_max.max(min);
+        if max_degree == 0 {
+            return min;
+        }
+        let t = (degree.min(max_degree) as f32).ln_1p() / (max_degree as f32).ln_1p();
+        min + (max - min) * t
+    }
 }
 
 #[derive(Resource)]
@@ -296,9 +667,16 @@ pub struct GraphState {
     pub net: NetState,
     pub cfg: CfgState,
     pub explain_cache: Option<ExplainCache>,
+    pub focus_paths_cache: Option<FocusPathsCache>,
     pub snapshot_loaded: bool,
     pub live_events_seen: bool,
     pub demo_loaded: bool,
+    pub demo: Option<DemoGenerator>,
+    pub follow: FollowNewest,
+    /// Recording being played back; mutually exclusive with live agents.
+    pub replay: Option<Replay>,
+    /// Set while comparing against a baseline snapshot.
+    pub diff: Option<GraphDiff>,
 
     pub needs_redraw: AtomicBool,
 }
@@ -331,16 +709,30 @@ impl Default for GraphState {
                 positions: HashMap::new(),
                 velocities: HashMap::new(),
                 in_batch: false,
+                removed_opens: HashMap::new(),
                 touched_nodes: HashSet::new(),
                 touched_edges: HashSet::new(),
                 glow_nodes: HashMap::new(),
                 glow_edges: HashMap::new(),
                 last_batch_id: None,
+                batch_log: BatchLog::default(),
                 active_vis_cache: Vec::new(),
+                cluster_targets: HashMap::new(),
                 progressive_cursor: 0,
                 dirty_layout: true,
                 lod_active: false,
                 tree_dir_children: HashSet::new(),
+                pinned: HashSet::new(),
+                layout_motion: 0.0,
+                layout_floor: 0.0,
+                bundles: EdgeBundles::default(),
+                stale_nodes: HashSet::new(),
+                host_colors: BTreeMap::new(),
+                collapsed: CollapsedView::default(),
+                components: None,
+                redraw_seq: 0,
+                window_tick: None,
+                collapsed_rev: None,
             },
             timeline: TimelineState {
                 window: Duration::from_secs(60),
@@ -348,35 +740,59 @@ impl Default for GraphState {
                 pause: false,
                 frozen_now: None,
                 scrub_seconds: 0.0,
+                playing: false,
+                playback_speed: 1.0,
                 show_connectors: true,
+                group_by_kind: false,
                 events: VecDeque::new(),
                 max_events: 20_000,
                 node_life: HashMap::new(),
+                event_bytes: 0,
+                life_bytes: 0,
                 batch_spans: VecDeque::new(),
+                max_batch_spans: 2_000,
+                history: ModelHistory::default(),
             },
             ui: UiState {
                 filter: String::new(),
+                filter_regex: None,
                 show_3d: true,
                 show_edges: true,
                 help_open: false,
+                show_event_log: false,
+                event_log_filter: String::new(),
                 show_path_editor: false,
                 path_editor: PathEditorDraft::default(),
                 show_agent_manager: false,
+                show_stats: false,
+                show_batch_diff: false,
+                show_minimap: false,
+                show_explain: false,
+                explain_focus_paths: false,
+                preset_name: String::new(),
+                stats: None,
                 show_agent_editor: false,
                 agent_editor: AgentEditorDraft::default(),
                 agent_command: AgentCommandDraft::default(),
                 focus: None,
                 focus_hops: 2,
+                isolate: None,
+                isolate_nodes: HashSet::new(),
+                expanded: Expansions::default(),
                 hovered: None,
                 selected: None,
                 selected_a: None,
                 selected_b: None,
+                selected_edge: None,
+                multi_selected: HashSet::new(),
+                nav_anchor: None,
                 search_open: false,
                 search_query: String::new(),
                 search_hits: Vec::new(),
                 jump_to: None,
                 fit_to_view: false,
                 view_mode: ViewMode::Spatial,
+                collapsed_dirs: HashSet::new(),
                 tree_collapsed: HashSet::new(),
                 tree_expanded: HashSet::new(),
                 tree_show_files: false,
@@ -384,6 +800,12 @@ impl Default for GraphState {
                 tree_file_zoom_threshold: 0.05,
                 tree_center: Vec3::ZERO,
                 tree_default_expand_depth: 2,
+                export_notice: None,
+                history_notice: None,
+                import_notice: None,
+                screenshot_requested: false,
+                screenshot_hide_ui: false,
+                screenshot_notice: None,
             },
             perf: PerfState {
                 fps: 0.0,
@@ -392,32 +814,76 @@ impl Default for GraphState {
                 visible_edges: 0,
                 visible_raw_edges: 0,
                 visible_agg_edges: 0,
+                edges_capped: 0,
                 event_total: 0,
                 ev_window: VecDeque::new(),
                 gc_last_run: Instant::now(),
+                component_count: 0,
+                components_last_run: Instant::now(),
             },
             net: NetState::default(),
             cfg: CfgState {
                 layout_force: true,
+                layout_mode: LayoutMode::Force,
                 link_distance: 6.0,
                 repulsion: 22.0,
+                layout_profile: LayoutProfile::default(),
                 damping: 0.92,
                 max_step: 0.35,
                 radius: 25.0,
                 y_spread: 6.0,
                 glow_duration: Duration::from_millis(900),
+                glow_emissive: 4.0,
+                bloom_intensity: 0.2,
+                node_opacity: NodeOpacity::default(),
+                edge_opacity: 1.0,
+                grid_enabled: false,
+                grid_spacing: 5.0,
+                grid_extent: 60.0,
+                ambient_brightness: 80.0,
+                theme: Theme::Dark,
+                fade_in_new_nodes: false,
+                limit_fps_when_idle: true,
+                label_mode: LabelMode::Basename,
+                label_max_distance: 40.0,
+                label_max_count: 30,
+                duration_units: DurationUnits::Human,
                 max_visible_nodes: 1200,
                 progressive_nodes_per_frame: 250,
                 gc_enabled: true,
                 gc_ttl: Duration::from_secs(30),
                 gc_interval: Duration::from_secs(1),
+                gc_mode: GcMode::Remove,
+                gc_hard_ttl: Duration::from_secs(600),
+                edge_ttl: None,
+                camera_sensitivity: 1.0,
+                size_by_degree: false,
+                color_by_category: true,
+                color_by_open_files: false,
+                node_radius_min: 0.2,
+                node_radius_max: 0.8,
                 show_raw_edges: false,
                 show_agg_edges: true,
+                edge_bundling: false,
+                edge_bundling_strength: 0.5,
+                edges_selection_only: false,
                 explain_max_depth: 4,
+                filter_is_regex: false,
+                show_only_active: false,
+                spatial_window_enabled: false,
+                spatial_window: Duration::from_secs(30),
+                show_threads: false,
+                merge_files_by_path: false,
+                follow_newest: false,
                 lod_enabled: true,
                 lod_threshold_nodes: 1500,
                 lod_edges_mode: LodEdgesMode::FocusOnly,
+                max_rendered_edges: 20_000,
                 demo_mode: false,
+                demo_rate: 20.0,
+                demo_entities: 60,
+                demo_seed: None,
+                demo_scenario: DemoScenario::Random,
                 path_includes: vec!["/etc".to_string(), "/home".to_string(), "/var".to_string()],
                 path_excludes: vec![
                     "/proc".to_string(),
@@ -426,12 +892,21 @@ impl Default for GraphState {
                     "/run".to_string(),
                 ],
                 agent_default_mode: AgentMode::User,
+                heartbeat_timeout: Duration::from_secs(10),
+                view_presets: Vec::new(),
+                screenshot_dir: None,
+                screenshot_include_ui: false,
             },
             needs_redraw: AtomicBool::new(true),
             explain_cache: None,
+            focus_paths_cache: None,
             snapshot_loaded: false,
             live_events_seen: false,
             demo_loaded: false,
+            demo: None,
+            follow: FollowNewest::default(),
+            replay: None,
+            diff: None,
         }
     }
 }
@@ -441,11 +916,21 @@ impl GraphState {
         self.model.clear();
         self.spatial.positions.clear();
         self.spatial.velocities.clear();
+        self.spatial.pinned.clear();
+        self.spatial.stale_nodes.clear();
+        self.spatial.host_colors.clear();
         self.ui.focus = None;
+        self.ui.isolate = None;
+        self.ui.isolate_nodes.clear();
+        self.ui.expanded.clear();
+        self.ui.multi_selected.clear();
         self.ui.hovered = None;
         self.ui.selected = None;
         self.ui.selected_a = None;
         self.ui.selected_b = None;
+        self.ui.selected_edge = None;
+        self.ui.nav_anchor = None;
+        self.follow.reset();
 
         self.ui.search_open = false;
         self.ui.search_query.clear();
@@ -456,23 +941,34 @@ impl GraphState {
 
         self.spatial.glow_nodes.clear();
         self.spatial.glow_edges.clear();
+        self.spatial.batch_log.clear();
         self.perf.ev_window.clear();
         self.perf.event_total = 0;
 
         self.timeline.events.clear();
+        self.timeline.event_bytes = 0;
         self.timeline.pause = false;
         self.timeline.frozen_now = None;
         self.timeline.scrub_seconds = 0.0;
+        self.timeline.playing = false;
         self.timeline.node_life.clear();
+        self.timeline.life_bytes = 0;
         self.timeline.batch_spans.clear();
+        self.timeline.history.reset(None);
 
         self.spatial.active_vis_cache.clear();
         self.spatial.progressive_cursor = 0;
         self.spatial.dirty_layout = true;
         self.explain_cache = None;
+        self.focus_paths_cache = None;
         self.snapshot_loaded = false;
         self.live_events_seen = false;
         self.demo_loaded = false;
+        self.demo = None;
+        self.diff = None;
+        for stream in self.net.streams.values_mut() {
+            stream.last_batch_done = None;
+        }
 
         self.needs_redraw.store(true, Ordering::Relaxed);
     }
@@ -528,135 +1024,54 @@ impl GraphState {
         }
     }
 
-    fn load_demo_graph(&mut self) {
+    /// Starts playing a recording from an empty graph. Refused while agents are connected.
+    pub fn start_replay(&mut self, lines: Vec<RecordLine>) -> bool {
+        if self.net.active_connection_count() > 0 {
+            return false;
+        }
+        self.set_demo_mode(false);
         self.clear();
-        let now = Instant::now();
-        let node_id = "demo";
-
-        let user = id_user(node_id, 1000);
-        let proc_a = id_process(node_id, 4242);
-        let proc_b = id_process(node_id, 4243);
-        let file_a = id_file(node_id, "/home/demo/report.txt");
-        let file_b = id_file(node_id, "/var/log/demo.log");
-        let file_c = id_file(node_id, "/usr/bin/demo-app");
-
-        let nodes = vec![
-            (
-                user.clone(),
-                Node::User {
-                    uid: 1000,
-                    name: "demo".to_string(),
-                },
-            ),
-            (
-                proc_a.clone(),
-                Node::Process {
-                    pid: 4242,
-                    ppid: 1,
-                    exe: "/usr/bin/demo-app".to_string(),
-                    cmdline: "/usr/bin/demo-app --demo".to_string(),
-                    uid: 1000,
-                },
-            ),
-            (
-                proc_b.clone(),
-                Node::Process {
-                    pid: 4243,
-                    ppid: 4242,
-                    exe: "/usr/bin/demo-helper".to_string(),
-                    cmdline: "/usr/bin/demo-helper --child".to_string(),
-                    uid: 1000,
-                },
-            ),
-            (
-                file_a.clone(),
-                Node::File {
-                    path: "/home/demo/report.txt".to_string(),
-                    inode: 1001,
-                    kind: FileKind::Regular,
-                },
-            ),
-            (
-                file_b.clone(),
-                Node::File {
-                    path: "/var/log/demo.log".to_string(),
-                    inode: 1002,
-                    kind: FileKind::Regular,
-                },
-            ),
-            (
-                file_c.clone(),
-                Node::File {
-                    path: "/usr/bin/demo-app".to_string(),
-                    inode: 1003,
-                    kind: FileKind::Regular,
-                },
-            ),
-        ];
-
-        let edges = vec![
-            Edge {
-                from: proc_a.clone(),
-                to: file_a.clone(),
-                kind: EdgeKind::Opens {
-                    fd: 3,
-                    mode: "rw".to_string(),
-                },
-            },
-            Edge {
-                from: proc_a.clone(),
-                to: file_b.clone(),
-                kind: EdgeKind::Opens {
-                    fd: 4,
-                    mode: "w".to_string(),
-                },
-            },
-            Edge {
-                from: proc_b.clone(),
-                to: file_b.clone(),
-                kind: EdgeKind::Opens {
-                    fd: 5,
-                    mode: "r".to_string(),
-                },
-            },
-            Edge {
-                from: proc_a.clone(),
-                to: user.clone(),
-                kind: EdgeKind::RunsAs,
-            },
-            Edge {
-                from: proc_b.clone(),
-                to: user.clone(),
-                kind: EdgeKind::RunsAs,
-            },
-        ];
+        self.replay = Some(Replay::new(lines));
+        true
+    }
 
-        self.model.load_snapshot(nodes, edges, now);
-        let node_ids: Vec<_> = self.model.nodes.keys().cloned().collect();
-        for id in node_ids {
-            self.push_timeline_at(now, TimelineEvtKind::NodeUpsert, Some(id), None, None);
+    pub fn seek_replay(&mut self, ms: f64) {
+        let Some(replay) = self.replay.as_mut() else {
+            return;
+        };
+        if replay.seek(ms) {
+            let replay = self.replay.take();
+            self.clear();
+            self.replay = replay;
         }
-        let edges: Vec<_> = self.model.edges.iter().cloned().collect();
-        for edge in edges {
-            self.push_timeline_at(
-                now,
-                TimelineEvtKind::EdgeUpsert,
-                Some(edge.from),
-                Some(edge.to),
-                Some(edge.kind),
-            );
+    }
+
+    pub fn tick_replay(&mut self, dt: f32) {
+        let Some(replay) = self.replay.as_mut() else {
+            return;
+        };
+        replay.advance(dt);
+        for msg in replay.due() {
+            self.apply(replay_incoming(IMPORT_STREAM, msg));
         }
+    }
 
-        self.demo_loaded = true;
-        self.spatial.dirty_layout = true;
-        self.needs_redraw.store(true, Ordering::Relaxed);
+    /// Replaces the graph with a snapshot loaded from disk. Refused while agents are connected.
+    pub fn import_snapshot(&mut self, msg: Msg) -> bool {
+        if self.net.active_connection_count() > 0 {
+            return false;
+        }
+        self.set_demo_mode(false);
+        self.clear();
+        self.apply(Incoming::snapshot(IMPORT_STREAM.to_string(), msg));
+        true
     }
 
     // ----- Apply incoming graph data -----
     pub fn apply(&mut self, inc: Incoming) {
-        if !self.net.is_configured(&inc.stream) {
+        if !self.net.is_configured(&inc.stream) && inc.stream != IMPORT_STREAM {
             match inc.kind {
-                IncomingKind::Disconnected => {
+                IncomingKind::Disconnected | IncomingKind::HeartbeatTimeout => {
                     self.net_on_disconnected(&inc.stream);
                 }
                 IncomingKind::Error(msg) => {
@@ -666,6 +1081,12 @@ impl GraphState {
             }
             return;
         }
+        if let Some(class) = inc.class {
+            let window = self.net.msg_window;
+            if let Some(entry) = self.net.streams.get_mut(&inc.stream) {
+                entry.msg_counts.record(class, Instant::now(), window);
+            }
+        }
         match inc.kind {
             IncomingKind::Connected => {
                 self.net_on_connected(inc.stream);
@@ -676,47 +1097,107 @@ impl GraphState {
             IncomingKind::Error(msg) => {
                 self.net_on_error(&inc.stream, msg);
             }
+            IncomingKind::HeartbeatTimeout => {
+                self.net_on_heartbeat_timeout(&inc.stream);
+            }
             IncomingKind::Snapshot(Msg::Snapshot { nodes, edges }) => {
                 self.on_message();
-                self.net_on_message(&inc.stream);
+                self.net_on_message(&inc.stream, inc.bytes);
                 let now = Instant::now();
                 self.net_on_snapshot(&inc.stream, now);
                 self.model.load_snapshot(nodes, edges, now);
+                self.timeline.history.reset(Some(now));
                 for id in self.model.nodes.keys() {
                     self.timeline.record_node_upsert(id, now);
                 }
                 self.snapshot_loaded = true;
                 self.mark_dirty_all();
             }
+            IncomingKind::Snapshot(Msg::SnapshotBegin { total_nodes, .. }) => {
+                self.on_message();
+                self.net_on_message(&inc.stream, inc.bytes);
+                self.net_on_snapshot(&inc.stream, Instant::now());
+                // Same replace semantics as a single Snapshot frame.
+                self.model.clear();
+                self.model.nodes.reserve(total_nodes);
+                self.timeline.history.reset(Some(Instant::now()));
+                self.apply_delta(Delta::BatchBegin {
+                    id: SNAPSHOT_BATCH_ID,
+                });
+            }
+            IncomingKind::Snapshot(Msg::SnapshotChunk { nodes, edges }) => {
+                self.on_message();
+                self.net_on_message(&inc.stream, inc.bytes);
+                for (id, node) in nodes {
+                    self.apply_delta(Delta::UpsertNode { id, node });
+                }
+                for edge in edges {
+                    self.apply_delta(Delta::UpsertEdge { edge });
+                }
+            }
+            IncomingKind::Snapshot(Msg::SnapshotEnd) => {
+                self.on_message();
+                self.net_on_message(&inc.stream, inc.bytes);
+                self.apply_delta(Delta::BatchEnd {
+                    id: SNAPSHOT_BATCH_ID,
+                });
+                self.snapshot_loaded = true;
+                self.mark_dirty_all();
+            }
 
             IncomingKind::Event(Msg::Event { delta }) => {
                 self.on_message();
-                self.net_on_message(&inc.stream);
-                self.net_on_event(&inc.stream);
+                self.net_on_message(&inc.stream, inc.bytes);
+                self.net_on_event(&inc.stream, &delta);
                 self.apply_delta(delta);
             }
+            IncomingKind::Other(Msg::Pong) => {
+                self.net_on_pong(&inc.stream);
+            }
+            IncomingKind::Other(Msg::Hello { session, .. }) => {
+                self.on_message();
+                self.net_on_message(&inc.stream, inc.bytes);
+                self.net_on_hello(&inc.stream, session);
+            }
+            IncomingKind::Identity(Msg::Identity { ident, caps }) => {
+                self.on_message();
+                self.net_on_message(&inc.stream, inc.bytes);
+                self.net_on_identity(&inc.stream, ident.node_id, ident.protocol, caps);
+            }
+            IncomingKind::Unknown(variants) => {
+                self.net_on_unknown(&inc.stream, variants);
+            }
             IncomingKind::Identity(_) | IncomingKind::Other(_) => {
                 self.on_message();
-                self.net_on_message(&inc.stream);
+                self.net_on_message(&inc.stream, inc.bytes);
             }
 
             _ => {}
         }
     }
 
-    fn apply_delta(&mut self, d: Delta) {
+    pub(crate) fn apply_delta(&mut self, d: Delta) {
         let ts = Instant::now();
         match d {
             Delta::BatchBegin { id } => {
                 self.spatial.in_batch = true;
+                self.spatial.removed_opens.clear();
                 self.spatial.last_batch_id = Some(id);
                 self.spatial.touched_nodes.clear();
                 self.spatial.touched_edges.clear();
+                self.spatial.batch_log.begin();
                 self.push_timeline_at(ts, TimelineEvtKind::BatchBegin(id), None, None, None);
             }
             Delta::BatchEnd { id } => {
                 self.spatial.in_batch = false;
+                self.spatial.removed_opens.clear();
                 let until = ts + self.cfg.glow_duration;
+                self.spatial.batch_log.finish(
+                    id,
+                    ts,
+                    &self.spatial.touched_nodes,
+                    &self.spatial.touched_edges,
+                );
 
                 for idn in self.spatial.touched_nodes.drain() {
                     self.spatial.glow_nodes.insert(idn, until);
@@ -728,8 +1209,29 @@ impl GraphState {
                 self.needs_redraw.store(true, Ordering::Relaxed);
             }
             Delta::UpsertNode { id, node } => {
+                if self.timeline.history.enabled {
+                    let prev = self.model.nodes.get(&id).cloned();
+                    self.timeline.history.record(
+                        ts,
+                        HistoryChange::NodeUpsert {
+                            id: id.clone(),
+                            prev,
+                        },
+                    );
+                }
+                let spawned = self.cfg.follow_newest
+                    && matches!(node, Node::Process { .. })
+                    && !self.model.nodes.contains_key(&id);
                 self.model.upsert_node(id.clone(), node, ts);
                 self.spatial.dirty_layout = true;
+                if spawned {
+                    self.follow.note(id.clone());
+                }
+                if self.spatial.in_batch {
+                    self.spatial
+                        .batch_log
+                        .record(BatchChange::NodeUpsert(id.clone()));
+                }
 
                 self.push_timeline_at(
                     ts,
@@ -751,18 +1253,43 @@ impl GraphState {
                 self.needs_redraw.store(true, Ordering::Relaxed);
             }
             Delta::RemoveNode { id } => {
+                let prev = self
+                    .timeline
+                    .history
+                    .enabled
+                    .then(|| self.model.nodes.get(&id).cloned())
+                    .flatten();
                 let removed_edges = self.model.remove_node(&id);
+                if let Some(node) = prev {
+                    self.timeline.history.record(
+                        ts,
+                        HistoryChange::NodeRemove {
+                            id: id.clone(),
+                            node,
+                            edges: removed_edges.clone(),
+                        },
+                    );
+                }
                 self.spatial.positions.remove(&id);
                 self.spatial.velocities.remove(&id);
                 self.spatial.glow_nodes.remove(&id);
                 for edge in removed_edges {
                     self.spatial.glow_edges.remove(&edge);
                     self.spatial.touched_edges.remove(&edge);
+                    if self.ui.selected_edge.as_ref() == Some(&edge) {
+                        self.ui.selected_edge = None;
+                    }
                 }
 
                 if self.ui.focus.as_ref() == Some(&id) {
                     self.ui.focus = None;
                 }
+                if self.ui.isolate.as_ref() == Some(&id) {
+                    self.ui.isolate = None;
+                }
+                self.ui.isolate_nodes.remove(&id);
+                self.ui.expanded.remove_node(&id);
+                self.ui.multi_selected.remove(&id);
                 if self.ui.selected.as_ref() == Some(&id) {
                     self.ui.selected = None;
                 }
@@ -788,11 +1315,19 @@ impl GraphState {
 
                 self.spatial.dirty_layout = true;
                 if self.spatial.in_batch {
+                    self.spatial
+                        .batch_log
+                        .record(BatchChange::NodeRemove(id.clone()));
                     self.spatial.touched_nodes.insert(id);
                 }
                 self.needs_redraw.store(true, Ordering::Relaxed);
             }
             Delta::UpsertEdge { edge } => {
+                if self.timeline.history.enabled && !self.model.edges.contains(&edge) {
+                    self.timeline
+                        .history
+                        .record(ts, HistoryChange::EdgeUpsert { edge: edge.clone() });
+                }
                 self.model.upsert_edge(edge.clone(), ts);
                 self.touch_node_at(&edge.from, ts);
                 self.touch_node_at(&edge.to, ts);
@@ -800,15 +1335,23 @@ impl GraphState {
                 self.note_path_change(&edge.from, ts);
                 self.note_path_change(&edge.to, ts);
 
+                let kind = if self.take_mode_transition(&edge) {
+                    TimelineEvtKind::EdgeModeChange
+                } else {
+                    TimelineEvtKind::EdgeUpsert
+                };
                 self.push_timeline_at(
                     ts,
-                    TimelineEvtKind::EdgeUpsert,
+                    kind,
                     Some(edge.from.clone()),
                     Some(edge.to.clone()),
                     Some(edge.kind.clone()),
                 );
 
                 if self.spatial.in_batch {
+                    self.spatial
+                        .batch_log
+                        .record(BatchChange::EdgeUpsert(edge.clone()));
                     self.spatial.touched_edges.insert(edge.clone());
                     self.spatial.touched_nodes.insert(edge.from.clone());
                     self.spatial.touched_nodes.insert(edge.to.clone());
@@ -820,8 +1363,25 @@ impl GraphState {
                 self.needs_redraw.store(true, Ordering::Relaxed);
             }
             Delta::RemoveEdge { edge } => {
-                self.model.remove_edge(&edge);
+                if self.model.remove_edge(&edge) {
+                    self.timeline
+                        .history
+                        .record(ts, HistoryChange::EdgeRemove { edge: edge.clone() });
+                }
                 self.spatial.glow_edges.remove(&edge);
+                if self.ui.selected_edge.as_ref() == Some(&edge) {
+                    self.ui.selected_edge = None;
+                }
+                if self.spatial.in_batch {
+                    self.spatial
+                        .batch_log
+                        .record(BatchChange::EdgeRemove(edge.clone()));
+                    if let EdgeKind::Opens { fd, mode } = &edge.kind {
+                        self.spatial
+                            .removed_opens
+                            .insert((edge.from.clone(), edge.to.clone(), *fd), mode.clone());
+                    }
+                }
 
                 self.push_timeline_at(
                     ts,
@@ -837,7 +1397,7 @@ impl GraphState {
     }
 
     fn touch_node_at(&mut self, id: &NodeId, ts: Instant) {
-        self.model.last_seen.insert(id.clone(), ts);
+        self.model.touch_node(id, ts);
     }
 
     fn note_path_change(&mut self, id: &NodeId, ts: Instant) {
@@ -863,12 +1423,12 @@ impl GraphState {
         let Some(Node::File { path, .. }) = self.model.nodes.get(id) else {
             return Vec::new();
         };
-        let Some(prefix) = id.0.split_once(":file:").map(|(prefix, _)| prefix) else {
+        let Some(ParsedId::File { host, .. }) = parse_node_id(id) else {
             return Vec::new();
         };
         let mut out = Vec::new();
         for parent in tree::ancestor_paths(path) {
-            let ancestor_id = NodeId(format!("{prefix}:file:{parent}"));
+            let ancestor_id = id_file(host, &parent);
             if self.model.nodes.contains_key(&ancestor_id) {
                 out.push(ancestor_id);
             }
@@ -878,6 +1438,7 @@ impl GraphState {
 
     fn net_on_connected(&mut self, stream: String) {
         self.set_demo_mode(false);
+        self.replay = None;
         let now = Instant::now();
         let entry = self
             .net
@@ -888,10 +1449,16 @@ impl GraphState {
         entry.last_msg = None;
         entry.msg_window.clear();
         entry.msg_rate = 0.0;
+        entry.byte_window.clear();
+        entry.bytes_per_sec = 0.0;
         entry.last_seen = Some(now);
         entry.last_snapshot_at = None;
         entry.last_event_at = None;
         entry.last_error = None;
+        entry.agent_protocol = None;
+        entry.agent_caps = None;
+        entry.unknown_variants = 0;
+        entry.last_unknown = None;
     }
 
     fn net_on_disconnected(&mut self, stream: &str) {
@@ -899,6 +1466,8 @@ impl GraphState {
             entry.status = NetStreamStatus::Disconnected;
         }
         self.net.connections.remove(stream);
+        self.net_apply_disconnect_policy(stream);
+        self.schedule_reconnect(stream, Instant::now());
     }
 
     fn net_on_error(&mut self, stream: &str, msg: String) {
@@ -909,8 +1478,133 @@ impl GraphState {
         self.net.connections.remove(stream);
     }
 
-    fn net_on_message(&mut self, stream: &str) {
-        self.set_demo_mode(false);
+    fn net_on_heartbeat_timeout(&mut self, stream: &str) {
+        let timeout = self.cfg.heartbeat_timeout.as_secs();
+        self.net_on_error(
+            stream,
+            format!("heartbeat timeout: no frame for {timeout}s"),
+        );
+        self.net_apply_disconnect_policy(stream);
+        self.schedule_reconnect(stream, Instant::now());
+    }
+
+    /// Purges the host of a dropped `RemoveNodes` stream, unless another connected
+    /// agent still reports the same host.
+    fn net_apply_disconnect_policy(&mut self, stream: &str) {
+        let remove = self
+            .net
+            .endpoints
+            .iter()
+            .any(|e| e.name == stream && e.on_disconnect == DisconnectPolicy::RemoveNodes);
+        if !remove {
+            return;
+        }
+        let Some(host) = self
+            .net
+            .streams
+            .get(stream)
+            .and_then(|s| s.agent_host.clone())
+        else {
+            return;
+        };
+        let shared = self.net.streams.iter().any(|(name, s)| {
+            name != stream
+                && s.status == NetStreamStatus::Connected
+                && s.agent_host.as_deref() == Some(host.as_str())
+        });
+        if !shared {
+            self.purge_host(&host);
+        }
+    }
+
+    /// Removes every node whose id carries `host`, with its edges; returns how many went.
+    pub fn purge_host(&mut self, host: &str) -> usize {
+        let ids: Vec<NodeId> = self
+            .model
+            .nodes
+            .keys()
+            .filter(|id| host_of(id) == host)
+            .cloned()
+            .collect();
+        for id in &ids {
+            self.apply_delta(Delta::RemoveNode { id: id.clone() });
+        }
+        if !ids.is_empty() {
+            self.mark_dirty_all();
+        }
+        ids.len()
+    }
+
+    /// Queues the next reconnect of an auto-connect endpoint with backoff, or gives up
+    /// once its `max_reconnect_attempts` is exceeded.
+    fn schedule_reconnect(&mut self, stream: &str, now: Instant) {
+        let Some(max) = self
+            .net
+            .endpoints
+            .iter()
+            .find(|e| e.name == stream && e.auto_connect)
+            .map(|e| e.max_reconnect_attempts)
+        else {
+            return;
+        };
+        let entry = self
+            .net
+            .streams
+            .entry(stream.to_string())
+            .or_insert_with(NetStreamState::new);
+        if entry.gave_up || entry.next_reconnect.is_some() {
+            return;
+        }
+        entry.reconnect_attempts += 1;
+        if max > 0 && entry.reconnect_attempts > max {
+            entry.gave_up = true;
+            let cause = entry
+                .last_error
+                .take()
+                .unwrap_or_else(|| "disconnected".into());
+            entry.last_error = Some(format!("gave up after {max} reconnect attempts: {cause}"));
+            return;
+        }
+        let delay = reconnect_backoff(entry.reconnect_attempts);
+        if delay.is_zero() {
+            self.net
+                .commands
+                .push(NetCommand::Connect(stream.to_string()));
+        } else {
+            entry.next_reconnect = Some(now + delay);
+        }
+    }
+
+    /// Issues the reconnects whose backoff has elapsed.
+    pub fn tick_reconnects(&mut self, now: Instant) {
+        for (name, entry) in self.net.streams.iter_mut() {
+            if entry.next_reconnect.is_some_and(|at| at <= now) {
+                entry.next_reconnect = None;
+                self.net.commands.push(NetCommand::Connect(name.clone()));
+            }
+        }
+    }
+
+    /// Manual reconnect: forget the attempt count and any pending or abandoned retry.
+    pub fn reset_reconnects(&mut self, stream: &str) {
+        if let Some(entry) = self.net.streams.get_mut(stream) {
+            entry.reconnect_attempts = 0;
+            entry.next_reconnect = None;
+            entry.gave_up = false;
+        }
+    }
+
+    fn net_on_pong(&mut self, stream: &str) {
+        if let Some(entry) = self.net.streams.get_mut(stream) {
+            entry.last_seen = Some(Instant::now());
+        }
+    }
+
+    fn net_on_message(&mut self, stream: &str, bytes: usize) {
+        // Demo churn and replays arrive on the import stream; only agent traffic ends demo mode.
+        if stream != IMPORT_STREAM {
+            self.set_demo_mode(false);
+        }
         let now = Instant::now();
         let window = self.net.msg_window;
         let entry = self
@@ -922,11 +1616,17 @@ impl GraphState {
         entry.last_msg = Some(now);
         entry.last_seen = Some(now);
         entry.last_error = None;
+        entry.reconnect_attempts = 0;
         entry.msg_window.push_back(now);
+        entry.byte_window.push_back((now, bytes));
         Self::net_prune_stream(entry, now, window);
     }
 
     fn net_on_snapshot(&mut self, stream: &str, now: Instant) {
+        // A snapshot replaces the whole model, so no stream can resume on top of it.
+        for other in self.net.streams.values_mut() {
+            other.last_batch_done = None;
+        }
         let entry = self
             .net
             .streams
@@ -935,16 +1635,58 @@ impl GraphState {
         entry.last_snapshot_at = Some(now);
     }
 
-    fn net_on_event(&mut self, stream: &str) {
+    fn net_on_hello(&mut self, stream: &str, session: Option<u64>) {
+        let entry = self
+            .net
+            .streams
+            .entry(stream.to_string())
+            .or_insert_with(NetStreamState::new);
+        if entry.agent_session != session {
+            entry.last_batch_done = None;
+        }
+        entry.agent_session = session;
+    }
+
+    fn net_on_identity(&mut self, stream: &str, host: String, protocol: u32, caps: Capabilities) {
+        if protocol > PROTOCOL_VERSION {
+            bevy::log::warn!(
+                "agent {stream} speaks protocol v{protocol}, this viewer v{PROTOCOL_VERSION}; \
+                 unknown messages will be skipped"
+            );
+        }
+        let entry = self
+            .net
+            .streams
+            .entry(stream.to_string())
+            .or_insert_with(NetStreamState::new);
+        entry.agent_protocol = Some(protocol);
+        entry.agent_caps = Some(caps);
+        entry.agent_host = Some(host);
+    }
+
+    fn net_on_unknown(&mut self, stream: &str, variants: Vec<String>) {
+        let entry = self
+            .net
+            .streams
+            .entry(stream.to_string())
+            .or_insert_with(NetStreamState::new);
+        entry.unknown_variants += variants.len() as u64;
+        entry.last_unknown = variants.into_iter().last().or(entry.last_unknown.take());
+    }
+
+    fn net_on_event(&mut self, stream: &str, delta: &Delta) {
         let entry = self
             .net
             .streams
             .entry(stream.to_string())
             .or_insert_with(NetStreamState::new);
         entry.last_event_at = Some(Instant::now());
+        if let Delta::BatchEnd { id } = delta {
+            entry.last_batch_done = Some(*id);
+        }
     }
 
-    fn net_prune_stream(stream: &mut NetStreamState, now: Instant, window: Duration) {
+    pub(crate) fn net_prune_stream(stream: &mut NetStreamState, now: Instant, window: Duration) {
         while let Some(front) = stream.msg_window.front() {
             if now.duration_since(*front) > window {
                 stream.msg_window.pop_front();
@@ -953,6 +1695,7 @@ impl GraphState {
             }
         }
         stream.msg_rate = stream.msg_window.len() as f32 / window.as_secs_f32();
+        stream.bytes_per_sec = rolling_rate(&mut stream.byte_window, now, window);
     }
 
     pub fn node_tooltip_lines(&self, id: &NodeId) -> Vec<String> {
@@ -962,6 +1705,21 @@ impl GraphState {
         let mut out = Vec::new();
         out.push(format!("{} ({})", node_label_short(n), id.0));
         out.extend(node_label_long(n));
+        if let Some(fields) = self.diff.as_ref().and_then(|d| d.changed_nodes.get(id)) {
+            out.push(format!("changed since baseline: {}", fields.join(", ")));
+        }
+        if let Some(life) = self.timeline.node_life.get(id) {
+            let now = Instant::now();
+            let units = self.cfg.duration_units;
+            out.push(format!(
+                "first seen {} ago",
+                format_duration_in(now.saturating_duration_since(life.first_seen), units)
+            ));
+            out.push(format!(
+                "last activity {} ago",
+                format_duration_in(now.saturating_duration_since(life.last_seen), units)
+            ));
+        }
         out
     }
 
@@ -973,39 +1731,163 @@ impl GraphState {
             return;
         }
 
-        let mut hits: Vec<NodeId> = self
+        // `pid:N` matches processes only; a bare number also fuzzy-matches other fields.
+        let explicit_pid = q.strip_prefix("pid:").map(str::trim);
+        let want_pid = explicit_pid.unwrap_or(&q).parse::<i32>().ok();
+        let matcher = SkimMatcherV2::default().ignore_case();
+        let mut scored: Vec<(i64, NodeId)> = self
             .model
             .nodes
             .iter()
-            .filter(|(id, n)| {
-                let id_ok = id.0.to_lowercase().contains(&q);
-                let node_ok = match n {
-                    Node::File { path, .. } => path.to_lowercase().contains(&q),
-                    Node::Process { cmdline, exe, .. } => {
-                        cmdline.to_lowercase().contains(&q) || exe.to_lowercase().contains(&q)
+            .filter_map(|(id, n)| {
+                if let (Some(want), Node::Process { pid, .. }) = (want_pid, n) {
+                    if *pid == want {
+                        return Some((i64::MAX, id.clone()));
                     }
-                    Node::User { name, .. } => name.to_lowercase().contains(&q),
-                };
-                id_ok || node_ok
+                }
+                if explicit_pid.is_some() {
+                    return None;
+                }
+                let label = node_label_short(n);
+                let mut fields: Vec<&str> = vec![id.0.as_str(), label.as_str()];
+                match n {
+                    Node::File { path, .. } => fields.push(path),
+                    Node::Process {
+                        cmdline,
+                        exe,
+                        container,
+                        ..
+                    } => {
+                        fields.push(cmdline);
+                        fields.push(exe);
+                        if let Some(container) = container {
+                            fields.push(container);
+                        }
+                    }
+                    Node::User { name, .. } | Node::Thread { name, .. } => fields.push(name),
+                    Node::Container { id, name } => {
+                        fields.push(id);
+                        fields.push(name);
+                    }
+                }
+                fields
+                    .into_iter()
+                    .filter_map(|field| matcher.fuzzy_match(field, &q))
+                    .max()
+                    .map(|score| (score, id.clone()))
             })
-            .map(|(id, _)| id.clone())
             .collect();
 
-        hits.sort_by(|a, b| a.0.cmp(&b.0));
-        hits.truncate(limit.max(1));
-        self.ui.search_hits = hits;
+        // best score first, ties by id for a stable order
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1 .0.cmp(&b.1 .0)));
+        scored.truncate(limit.max(1));
+        self.ui.search_hits = scored.into_iter().map(|(_, id)| id).collect();
+    }
+
+    /// Hits the spatial view previews; empty while the search overlay is closed.
+    pub fn search_preview(&self) -> &[NodeId] {
+        if self.ui.search_open {
+            &self.ui.search_hits
+        } else {
+            &[]
+        }
+    }
+
+    pub fn assign_host_colors(&mut self, ids: &HashSet<NodeId>) {
+        let mut added = false;
+        for id in ids {
+            let host = host_of(id);
+            if !self.spatial.host_colors.contains_key(host) {
+                let next = self.spatial.host_colors.len();
+                self.spatial.host_colors.insert(host.to_string(), next);
+                added = true;
+            }
+        }
+        if added {
+            self.needs_redraw.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn host_color_index(&self, id: &NodeId) -> Option<usize> {
+        if self.spatial.host_colors.len() < 2 {
+            return None;
+        }
+        self.spatial.host_colors.get(host_of(id)).copied()
     }
 
     pub fn request_jump(&mut self, id: NodeId) {
         self.ui.jump_to = Some(id);
     }
 
+    /// Returns `(anchor, neighbor)` for stepping from `selected`. Cycles through the
+    /// neighbors of `ui.nav_anchor` while `selected` is one of them, otherwise through
+    /// the neighbors of `selected` itself.
+    pub fn next_neighbor(&self, selected: &NodeId, dir: NavDir) -> Option<(NodeId, NodeId)> {
+        let anchor = match &self.ui.nav_anchor {
+            Some(a) if a != selected && self.model.sorted_neighbors(a).contains(selected) => {
+                a.clone()
+            }
+            _ => selected.clone(),
+        };
+        let ring = self.model.sorted_neighbors(&anchor);
+        if ring.is_empty() {
+            return None;
+        }
+        let len = ring.len();
+        let idx = match (ring.iter().position(|id| id == selected), dir) {
+            (Some(i), NavDir::Next) => (i + 1) % len,
+            (Some(i), NavDir::Prev) => (i + len - 1) % len,
+            (None, NavDir::Next) => 0,
+            (None, NavDir::Prev) => len - 1,
+        };
+        Some((anchor, ring[idx].clone()))
+    }
+
+    pub fn select_neighbor(&mut self, dir: NavDir) {
+        let Some(selected) = self.ui.selected.clone() else {
+            return;
+        };
+        let Some((anchor, next)) = self.next_neighbor(&selected, dir) else {
+            return;
+        };
+        self.ui.nav_anchor = Some(anchor);
+        self.ui.selected = Some(next.clone());
+        self.ui.focus = Some(next.clone());
+        self.request_jump(next);
+        self.needs_redraw.store(true, Ordering::Relaxed);
+    }
+
+    /// Step back to the node whose neighbors are being cycled.
+    pub fn select_nav_anchor(&mut self) {
+        let Some(anchor) = self.ui.nav_anchor.take() else {
+            return;
+        };
+        self.ui.selected = Some(anchor.clone());
+        self.ui.focus = Some(anchor.clone());
+        self.request_jump(anchor);
+        self.needs_redraw.store(true, Ordering::Relaxed);
+    }
+
     // ---- Glow checks ----
-    pub fn node_is_glowing(&self, id: &NodeId) -> bool {
-        self.spatial.glow_nodes.contains_key(id)
+    /// Recency of a node change: 1.0 right after it happened, fading to 0.0 at expiry.
+    pub fn glow_intensity(&self, id: &NodeId) -> f32 {
+        self.glow_intensity_at(self.spatial.glow_nodes.get(id).copied(), Instant::now())
     }
-    pub fn edge_is_glowing(&self, e: &Edge) -> bool {
-        self.spatial.glow_edges.contains_key(e)
+
+    pub fn edge_glow_intensity(&self, e: &Edge) -> f32 {
+        self.glow_intensity_at(self.spatial.glow_edges.get(e).copied(), Instant::now())
+    }
+
+    pub(crate) fn glow_intensity_at(&self, until: Option<Instant>, now: Instant) -> f32 {
+        let Some(until) = until else {
+            return 0.0;
+        };
+        let total = self.cfg.glow_duration.as_secs_f32();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        let remaining = until.saturating_duration_since(now).as_secs_f32();
+        (remaining / total).clamp(0.0, 1.0)
     }
 
     pub fn explain_path_cached(
@@ -1016,28 +1898,65 @@ impl GraphState {
     ) -> Option<Vec<PathStep>> {
         let now = Instant::now();
         let focus = self.ui.focus.clone();
+        let max_depth = self.cfg.explain_max_depth.max(1);
         let ttl = Duration::from_millis(200);
         if let Some(cache) = &self.explain_cache {
             if cache.a == *a
                 && cache.b == *b
                 && cache.focus == focus
+                && cache.max_depth == max_depth
                 && now.duration_since(cache.ts) <= ttl
             {
                 return cache.result.clone();
             }
         }
 
-        let result = explain::shortest_path(
-            &self.model,
-            a.clone(),
-            b.clone(),
-            self.cfg.explain_max_depth.max(1),
-            allowed,
-        );
+        let result = explain::shortest_path(&self.model, a.clone(), b.clone(), max_depth, allowed);
         self.explain_cache = Some(ExplainCache {
             a: a.clone(),
             b: b.clone(),
             focus,
+            max_depth,
+            ts: now,
+            result: result.clone(),
+        });
+        result
+    }
+
+    /// Path union from `ui.focus` to the multi-selection; empty while the mode is off or
+    /// either side is missing.
+    pub fn focus_paths_cached(&mut self, allowed: &HashSet<NodeId>) -> Vec<PathStep> {
+        let Some(focus) = self
+            .ui
+            .focus
+            .clone()
+            .filter(|_| self.ui.explain_focus_paths)
+        else {
+            return Vec::new();
+        };
+        let mut targets: Vec<NodeId> = self.ui.multi_selected.iter().cloned().collect();
+        if targets.is_empty() {
+            return Vec::new();
+        }
+        targets.sort_by(|a, b| a.0.cmp(&b.0));
+        let now = Instant::now();
+        let max_depth = self.cfg.explain_max_depth.max(1);
+        let ttl = Duration::from_millis(200);
+        if let Some(cache) = &self.focus_paths_cache {
+            if cache.focus == focus
+                && cache.targets == targets
+                && cache.max_depth == max_depth
+                && now.duration_since(cache.ts) <= ttl
+            {
+                return cache.result.clone();
+            }
+        }
+
+        let result = explain::path_tree(&self.model, &focus, &targets, max_depth, allowed);
+        self.focus_paths_cache = Some(FocusPathsCache {
+            focus,
+            targets,
+            max_depth,
             ts: now,
             result: result.clone(),
         });
@@ -1124,29 +2043,76 @@ impl GraphState {
         self.ui.view_mode = cfg.view_mode.into();
         self.ui.show_3d = cfg.show_3d;
         self.ui.show_edges = cfg.show_edges;
+        self.cfg.filter_is_regex = cfg.filter_is_regex;
+        self.cfg.show_only_active = cfg.show_only_active;
+        self.cfg.spatial_window_enabled = cfg.spatial_window_enabled;
+        self.cfg.spatial_window = Duration::from_secs(cfg.spatial_window_secs.max(1));
+        self.cfg.show_threads = cfg.show_threads;
+        self.cfg.merge_files_by_path = cfg.merge_files_by_path;
+        self.cfg.follow_newest = cfg.follow_newest;
         self.ui.focus_hops = cfg.focus_hops.max(1);
         self.cfg.show_raw_edges = cfg.show_raw_edges;
         self.cfg.show_agg_edges = cfg.show_agg_edges;
+        self.cfg.edge_bundling = cfg.edge_bundling;
+        self.cfg.edge_bundling_strength = cfg.edge_bundling_strength.clamp(0.0, 1.0);
+        self.cfg.edges_selection_only = cfg.edges_selection_only;
         self.cfg.max_visible_nodes = cfg.max_visible_nodes.max(1);
         self.cfg.progressive_nodes_per_frame = cfg.progressive_nodes_per_frame.max(1);
         self.cfg.layout_force = cfg.layout_force;
+        self.cfg.layout_mode = cfg.layout_mode;
         self.cfg.link_distance = cfg.link_distance;
         self.cfg.repulsion = cfg.repulsion;
+        self.cfg.layout_profile = cfg.layout_profile;
         self.cfg.damping = cfg.damping;
         self.cfg.max_step = cfg.max_step;
         self.timeline.window = Duration::from_secs(cfg.timeline_window_secs.max(1));
+        self.timeline.max_events = cfg.timeline_max_events.max(MIN_TIMELINE_EVENTS);
         self.timeline.scale = cfg.timeline_scale.max(0.01);
+        self.timeline.history.set_enabled(cfg.model_history);
         self.cfg.lod_enabled = cfg.lod_enabled;
         self.cfg.lod_threshold_nodes = cfg.lod_threshold_nodes.max(1);
         self.cfg.lod_edges_mode = cfg.lod_edges_mode;
+        self.cfg.max_rendered_edges = cfg.max_rendered_edges;
         self.cfg.glow_duration = Duration::from_millis(cfg.glow_duration_ms.max(1));
+        self.cfg.glow_emissive = cfg.glow_emissive.clamp(0.0, 20.0);
+        self.cfg.bloom_intensity = cfg.bloom_intensity.clamp(0.0, 1.0);
+        self.cfg.node_opacity = cfg.node_opacity.clamped();
+        self.cfg.edge_opacity = cfg.edge_opacity.clamp(0.05, 1.0);
+        self.cfg.grid_enabled = cfg.grid_enabled;
+        self.cfg.grid_spacing = cfg.grid_spacing.clamp(0.5, 100.0);
+        self.cfg.grid_extent = cfg.grid_extent.clamp(self.cfg.grid_spacing, 1000.0);
+        self.cfg.ambient_brightness = cfg.ambient_brightness.clamp(0.0, 2000.0);
+        self.cfg.theme = cfg.theme;
+        self.cfg.fade_in_new_nodes = cfg.fade_in_new_nodes;
+        self.cfg.limit_fps_when_idle = cfg.limit_fps_when_idle;
+        self.cfg.label_mode = cfg.label_mode;
+        self.cfg.label_max_distance = cfg.label_max_distance.clamp(1.0, 500.0);
+        self.cfg.label_max_count = cfg.label_max_count.min(500);
+        self.cfg.duration_units = cfg.duration_units;
         self.cfg.gc_enabled = cfg.gc_enabled;
         self.cfg.gc_ttl = Duration::from_secs(cfg.gc_ttl_secs.max(1));
+        self.cfg.gc_mode = cfg.gc_mode;
+        self.cfg.gc_hard_ttl = Duration::from_secs(cfg.gc_hard_ttl_secs.max(cfg.gc_ttl_secs));
+        self.cfg.edge_ttl = (cfg.edge_ttl_secs > 0).then(|| Duration::from_secs(cfg.edge_ttl_secs));
+        self.cfg.camera_sensitivity = cfg.camera_sensitivity.clamp(0.1, 5.0);
+        self.cfg.size_by_degree = cfg.size_by_degree;
+        self.cfg.color_by_category = cfg.color_by_category;
+        self.cfg.color_by_open_files = cfg.color_by_open_files;
+        self.cfg.node_radius_min = cfg.node_radius_min.clamp(0.05, 3.0);
+        self.cfg.node_radius_max = cfg.node_radius_max.clamp(self.cfg.node_radius_min, 3.0);
+        self.cfg.demo_rate = cfg.demo_rate.clamp(0.0, 1000.0);
+        self.cfg.demo_entities = cfg.demo_entities.clamp(5, 5000);
+        self.cfg.demo_seed = cfg.demo_seed;
+        self.cfg.demo_scenario = cfg.demo_scenario;
         self.set_demo_mode(cfg.demo_mode);
         self.cfg.path_includes = cfg.path_includes.clone();
         self.cfg.path_excludes = cfg.path_excludes.clone();
         self.cfg.agent_default_mode = cfg.default_agent_mode;
+        self.cfg.heartbeat_timeout = Duration::from_secs(cfg.heartbeat_timeout_secs.max(5));
         self.sync_agent_endpoints(cfg.agents.clone());
+        self.cfg.view_presets = cfg.view_presets.clone();
+        self.cfg.screenshot_dir = cfg.screenshot_dir.clone().filter(|dir| !dir.is_empty());
+        self.cfg.screenshot_include_ui = cfg.screenshot_include_ui;
 
         self.needs_redraw.store(true, Ordering::Relaxed);
     }
@@ -1158,35 +2124,134 @@ impl GraphState {
             show_edges: self.ui.show_edges,
             show_raw_edges: self.cfg.show_raw_edges,
             show_agg_edges: self.cfg.show_agg_edges,
+            edge_bundling: self.cfg.edge_bundling,
+            edge_bundling_strength: self.cfg.edge_bundling_strength,
+            edges_selection_only: self.cfg.edges_selection_only,
             demo_mode: self.cfg.demo_mode,
+            demo_rate: self.cfg.demo_rate,
+            demo_entities: self.cfg.demo_entities,
+            demo_seed: self.cfg.demo_seed,
+            demo_scenario: self.cfg.demo_scenario,
             path_includes: self.cfg.path_includes.clone(),
             path_excludes: self.cfg.path_excludes.clone(),
             focus_hops: self.ui.focus_hops,
+            filter_is_regex: self.cfg.filter_is_regex,
+            show_only_active: self.cfg.show_only_active,
+            spatial_window_enabled: self.cfg.spatial_window_enabled,
+            spatial_window_secs: self.cfg.spatial_window.as_secs(),
+            show_threads: self.cfg.show_threads,
+            merge_files_by_path: self.cfg.merge_files_by_path,
+            follow_newest: self.cfg.follow_newest,
             max_visible_nodes: self.cfg.max_visible_nodes,
             progressive_nodes_per_frame: self.cfg.progressive_nodes_per_frame,
             layout_force: self.cfg.layout_force,
+            layout_mode: self.cfg.layout_mode,
             link_distance: self.cfg.link_distance,
             repulsion: self.cfg.repulsion,
+            layout_profile: self.cfg.layout_profile,
             damping: self.cfg.damping,
             max_step: self.cfg.max_step,
             timeline_window_secs: self.timeline.window.as_secs(),
+            timeline_max_events: self.timeline.max_events,
             timeline_scale: self.timeline.scale,
+            model_history: self.timeline.history.enabled,
             lod_enabled: self.cfg.lod_enabled,
             lod_threshold_nodes: self.cfg.lod_threshold_nodes,
             lod_edges_mode: self.cfg.lod_edges_mode,
+            max_rendered_edges: self.cfg.max_rendered_edges,
             glow_duration_ms: self.cfg.glow_duration.as_millis() as u64,
+            glow_emissive: self.cfg.glow_emissive,
+            bloom_intensity: self.cfg.bloom_intensity,
+            node_opacity: self.cfg.node_opacity,
+            edge_opacity: self.cfg.edge_opacity,
+            grid_enabled: self.cfg.grid_enabled,
+            grid_spacing: self.cfg.grid_spacing,
+            grid_extent: self.cfg.grid_extent,
+            ambient_brightness: self.cfg.ambient_brightness,
+            theme: self.cfg.theme,
+            fade_in_new_nodes: self.cfg.fade_in_new_nodes,
+            limit_fps_when_idle: self.cfg.limit_fps_when_idle,
+            label_mode: self.cfg.label_mode,
+            label_max_distance: self.cfg.label_max_distance,
+            label_max_count: self.cfg.label_max_count,
+            duration_units: self.cfg.duration_units,
             gc_enabled: self.cfg.gc_enabled,
             gc_ttl_secs: self.cfg.gc_ttl.as_secs(),
+            gc_mode: self.cfg.gc_mode,
+            gc_hard_ttl_secs: self.cfg.gc_hard_ttl.as_secs(),
+            edge_ttl_secs: self.cfg.edge_ttl.map_or(0, |ttl| ttl.as_secs()),
+            camera_sensitivity: self.cfg.camera_sensitivity,
+            size_by_degree: self.cfg.size_by_degree,
+            color_by_category: self.cfg.color_by_category,
+            color_by_open_files: self.cfg.color_by_open_files,
+            node_radius_min: self.cfg.node_radius_min,
+            node_radius_max: self.cfg.node_radius_max,
             default_agent_mode: self.cfg.agent_default_mode,
-            agents: self.net.endpoints.clone(),
+            heartbeat_timeout_secs: self.cfg.heartbeat_timeout.as_secs(),
+            agents: self
+                .net
+                .endpoints
+                .iter()
+                .filter(|endpoint| !endpoint.temporary)
+                .cloned()
+                .collect(),
+            view_presets: self.cfg.view_presets.clone(),
+            screenshot_dir: self.cfg.screenshot_dir.clone(),
+            screenshot_include_ui: self.cfg.screenshot_include_ui,
+        }
+    }
+
+    pub fn capture_view_preset(&self, name: &str) -> ViewPreset {
+        ViewPreset {
+            name: name.to_string(),
+            filter: self.ui.filter.clone(),
+            filter_is_regex: self.cfg.filter_is_regex,
+            focus: self.ui.focus.as_ref().map(|id| id.0.clone()),
+            focus_hops: self.ui.focus_hops,
+            view_mode: self.ui.view_mode.into(),
+            show_edges: self.ui.show_edges,
+            show_raw_edges: self.cfg.show_raw_edges,
+            show_agg_edges: self.cfg.show_agg_edges,
+            lod_enabled: self.cfg.lod_enabled,
+            lod_threshold_nodes: self.cfg.lod_threshold_nodes,
+            lod_edges_mode: self.cfg.lod_edges_mode,
+        }
+    }
+
+    /// Stores `preset`, replacing one with the same name.
+    pub fn save_view_preset(&mut self, preset: ViewPreset) {
+        match self
+            .cfg
+            .view_presets
+            .iter_mut()
+            .find(|p| p.name == preset.name)
+        {
+            Some(existing) => *existing = preset,
+            None => self.cfg.view_presets.push(preset),
         }
     }
+
+    pub fn apply_view_preset(&mut self, preset: &ViewPreset) {
+        self.ui.filter = preset.filter.clone();
+        self.cfg.filter_is_regex = preset.filter_is_regex;
+        self.ui.focus = preset.focus.clone().map(NodeId);
+        self.ui.focus_hops = preset.focus_hops.max(1);
+        self.ui.view_mode = preset.view_mode.into();
+        self.ui.show_edges = preset.show_edges;
+        self.cfg.show_raw_edges = preset.show_raw_edges;
+        self.cfg.show_agg_edges = preset.show_agg_edges;
+        self.cfg.lod_enabled = preset.lod_enabled;
+        self.cfg.lod_threshold_nodes = preset.lod_threshold_nodes.max(1);
+        self.cfg.lod_edges_mode = preset.lod_edges_mode;
+        self.spatial.dirty_layout = true;
+        self.needs_redraw.store(true, Ordering::Relaxed);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use spacegraph_core::{FileKind, Node};
+    use spacegraph_core::{id_user, FileCategory, FileKind, Node};
 
     #[test]
     fn search_returns_stable_sorted_hits_and_limit() {
@@ -1201,6 +2266,8 @@ mod tests {
                 path: "/var/log/b.log".to_string(),
                 inode: 2,
                 kind: FileKind::Regular,
+                category: FileCategory::Unknown,
+                link_target: None,
             },
         );
         st.model.nodes.insert(
@@ -1209,6 +2276,8 @@ mod tests {
                 path: "/var/log/a.log".to_string(),
                 inode: 1,
                 kind: FileKind::Regular,
+                category: FileCategory::Unknown,
+                link_target: None,
             },
         );
         st.model.nodes.insert(
@@ -1217,6 +2286,8 @@ mod tests {
                 path: "/var/log/c.log".to_string(),
                 inode: 3,
                 kind: FileKind::Regular,
+                category: FileCategory::Unknown,
+                link_target: None,
             },
         );
 
@@ -1228,6 +2299,79 @@ mod tests {
         assert_eq!(st.ui.search_hits[1].0, "b-node");
     }
 
+    #[test]
+    fn search_ranks_closer_fuzzy_match_first() {
+        let mut st = GraphState::default();
+        for (id, path) in [("n1", "/srv/shared/data"), ("n2", "/usr/sbin/sshd")] {
+            st.model.nodes.insert(
+                NodeId(id.to_string()),
+                Node::File {
+                    path: path.to_string(),
+                    inode: 1,
+                    kind: FileKind::Regular,
+                    category: FileCategory::Unknown,
+                    link_target: None,
+                },
+            );
+        }
+
+        st.ui.search_query = "sshd".to_string();
+        st.recompute_search_hits(10);
+
+        assert_eq!(st.ui.search_hits.len(), 2);
+        assert_eq!(st.ui.search_hits[0].0, "n2");
+    }
+
+    #[test]
+    fn search_preview_only_while_overlay_is_open() {
+        let mut st = GraphState::default();
+        st.ui.search_hits = vec![NodeId("n1".to_string())];
+        assert!(st.search_preview().is_empty());
+        st.ui.search_open = true;
+        assert_eq!(st.search_preview(), st.ui.search_hits.as_slice());
+    }
+
+    #[test]
+    fn numeric_query_ranks_exact_pid_matches_first() {
+        let mut st = GraphState::default();
+        let process = |pid: i32, cmdline: &str| Node::Process {
+            pid,
+            ppid: 1,
+            exe: "/usr/bin/app".to_string(),
+            cmdline: cmdline.to_string(),
+            uid: 1000,
+            rss_kb: 0,
+            utime_ticks: 0,
+            container: None,
+        };
+        for (id, node) in [
+            ("h1:process:pid:42", process(42, "app")),
+            ("h2:process:pid:42", process(42, "app --other-host")),
+            ("h1:process:pid:4200", process(4200, "app")),
+            ("h1:process:pid:7", process(7, "sleep 42")),
+        ] {
+            st.model.nodes.insert(NodeId(id.to_string()), node);
+        }
+
+        st.ui.search_query = "42".to_string();
+        st.recompute_search_hits(10);
+        let hits: Vec<&str> = st.ui.search_hits.iter().map(|id| id.0.as_str()).collect();
+        assert_eq!(&hits[..2], ["h1:process:pid:42", "h2:process:pid:42"]);
+        // Fuzzy matches on other fields still follow.
+        assert!(hits.contains(&"h1:process:pid:7"));
+
+        st.ui.search_query = "pid:42".to_string();
+        st.recompute_search_hits(10);
+        assert_eq!(st.ui.search_hits.len(), 2);
+
+        st.ui.search_query = "pid: 7".to_string();
+        st.recompute_search_hits(10);
+        assert_eq!(
+            st.ui.search_hits,
+            vec![NodeId("h1:process:pid:7".to_string())]
+        );
+    }
+
     #[test]
     fn lod_active_when_threshold_reached() {
         let cfg = CfgState {
@@ -1240,6 +2384,45 @@ mod tests {
         assert!(cfg.lod_active(11));
     }
 
+    #[test]
+    fn node_radius_scales_with_degree_when_enabled() {
+        let mut cfg = CfgState {
+            size_by_degree: false,
+            node_radius_min: 0.2,
+            node_radius_max: 0.8,
+            ..Default::default()
+        };
+        assert_eq!(cfg.node_radius(50, 100), NODE_RADIUS);
+
+        cfg.size_by_degree = true;
+        assert_eq!(cfg.node_radius(0, 100), 0.2);
+        assert!((cfg.node_radius(100, 100) - 0.8).abs() < 1e-5);
+        let mid = cfg.node_radius(10, 100);
+        assert!(mid > 0.2 && mid < 0.8);
+        assert_eq!(cfg.node_radius(3, 0), 0.2);
+    }
+
+    #[test]
+    fn glow_intensity_fades_from_one_to_zero() {
+        let mut st = GraphState::default();
+        st.cfg.glow_duration = Duration::from_millis(1000);
+        let id = NodeId("n".to_string());
+        let start = Instant::now();
+        let until = start + st.cfg.glow_duration;
+        st.spatial.glow_nodes.insert(id.clone(), until);
+        let until = Some(until);
+
+        assert_eq!(st.glow_intensity_at(until, start), 1.0);
+        let mid = st.glow_intensity_at(until, start + Duration::from_millis(500));
+        assert!((mid - 0.5).abs() < 1e-3);
+        assert_eq!(
+            st.glow_intensity_at(until, start + Duration::from_millis(1000)),
+            0.0
+        );
+        assert_eq!(st.glow_intensity_at(None, start), 0.0);
+        assert!(st.glow_intensity(&id) > 0.0);
+    }
+
     #[test]
     fn lod_inactive_when_disabled() {
         let cfg = CfgState {
@@ -1264,11 +2447,7 @@ mod tests {
                 status: NetStreamStatus::Connected,
                 last_msg: Some(now),
                 last_seen: Some(now),
-                last_snapshot_at: None,
-                last_event_at: None,
-                msg_rate: 0.0,
-                msg_window: VecDeque::new(),
-                last_error: None,
+                ..NetStreamState::new()
             },
         );
 
@@ -1295,11 +2474,7 @@ mod tests {
                 status: NetStreamStatus::Connected,
                 last_msg: Some(start),
                 last_seen: Some(start),
-                last_snapshot_at: None,
-                last_event_at: None,
-                msg_rate: 0.0,
-                msg_window: VecDeque::new(),
-                last_error: None,
+                ..NetStreamState::new()
             },
         );
 
@@ -1311,4 +2486,584 @@ mod tests {
         let rate = st.net.streams.get(&stream).unwrap().msg_rate;
         assert_eq!(rate, 0.0);
     }
+
+    #[test]
+    fn rolling_rate_averages_bytes_over_window() {
+        let start = Instant::now();
+        let now = start + Duration::from_secs(3);
+        let mut window = VecDeque::new();
+        window.push_back((start, 4096));
+        window.push_back((now - Duration::from_millis(500), 1024));
+        window.push_back((now, 3072));
+
+        let rate = rolling_rate(&mut window, now, Duration::from_secs(2));
+        assert_eq!(window.len(), 2);
+        assert!((rate - 2048.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn chunked_snapshot_accumulates_and_glows_on_end() {
+        let mut st = GraphState::default();
+        let nodes: Vec<(NodeId, Node)> = (0..3)
+            .map(|uid| {
+                (
+                    id_user("h", uid),
+                    Node::User {
+                        uid,
+                        name: format!("u{uid}"),
+                    },
+                )
+            })
+            .collect();
+        let edges = vec![Edge {
+            from: id_user("h", 0),
+            to: id_user("h", 1),
+            kind: EdgeKind::RunsAs,
+        }];
+        for msg in spacegraph_core::chunk_snapshot(&nodes, &edges, 2) {
+            st.apply(Incoming::snapshot(IMPORT_STREAM.to_string(), msg));
+            if !st.snapshot_loaded {
+                assert!(st.spatial.in_batch);
+            }
+        }
+
+        assert!(st.snapshot_loaded);
+        assert!(!st.spatial.in_batch);
+        assert_eq!(st.model.nodes.len(), 3);
+        assert_eq!(st.model.edges.len(), 1);
+        assert!(st.spatial.glow_nodes.contains_key(&id_user("h", 0)));
+    }
+
+    #[test]
+    fn view_preset_apply_restores_captured_view() {
+        let mut st = GraphState::default();
+        st.ui.filter = "ssh".to_string();
+        st.ui.focus = Some(NodeId("n".to_string()));
+        st.ui.focus_hops = 4;
+        st.ui.view_mode = ViewMode::Tree;
+        st.cfg.lod_edges_mode = LodEdgesMode::Off;
+        st.save_view_preset(st.capture_view_preset("work"));
+        st.save_view_preset(st.capture_view_preset("work"));
+        assert_eq!(st.cfg.view_presets.len(), 1);
+
+        st.apply_view_preset(&ViewPreset::default());
+        assert!(st.ui.filter.is_empty());
+        assert!(st.ui.focus.is_none());
+        assert_eq!(st.ui.view_mode, ViewMode::Spatial);
+
+        let preset = st.cfg.view_presets[0].clone();
+        st.apply_view_preset(&preset);
+        assert_eq!(st.ui.filter, "ssh");
+        assert_eq!(st.ui.focus, Some(NodeId("n".to_string())));
+        assert_eq!(st.ui.focus_hops, 4);
+        assert_eq!(st.ui.view_mode, ViewMode::Tree);
+        assert_eq!(st.cfg.lod_edges_mode, LodEdgesMode::Off);
+    }
+
+    #[test]
+    fn batch_end_freezes_touched_sets_and_changes() {
+        let mut st = GraphState::default();
+        let (a, b, c) = (id_user("h", 1), id_user("h", 2), id_user("h", 3));
+        let edge = Edge {
+            from: a.clone(),
+            to: b.clone(),
+            kind: EdgeKind::RunsAs,
+        };
+        for (id, uid) in [(&a, 1), (&b, 2), (&c, 3)] {
+            st.model.upsert_node(
+                id.clone(),
+                Node::User {
+                    uid,
+                    name: format!("u{uid}"),
+                },
+                Instant::now(),
+            );
+        }
+
+        st.apply_delta(Delta::BatchBegin { id: 7 });
+        st.apply_delta(Delta::UpsertEdge { edge: edge.clone() });
+        st.apply_delta(Delta::RemoveNode { id: c.clone() });
+        st.apply_delta(Delta::BatchEnd { id: 7 });
+
+        let batch = st.spatial.batch_log.recent.back().expect("batch recorded");
+        assert_eq!(batch.id, 7);
+        assert_eq!(batch.touched_nodes, vec![a, b, c.clone()]);
+        assert_eq!(batch.touched_edges, vec![edge.clone()]);
+        assert_eq!(
+            batch.changes,
+            vec![BatchChange::EdgeUpsert(edge), BatchChange::NodeRemove(c)]
+        );
+        assert!(st.spatial.touched_nodes.is_empty());
+    }
+
+    #[test]
+    fn fd_reopened_with_another_mode_is_flagged_on_the_timeline() {
+        let mut st = GraphState::default();
+        let opens = |fd: i32, mode: &str| Edge {
+            from: id_user("h", 1),
+            to: id_file("h", "/tmp/a"),
+            kind: EdgeKind::Opens {
+                fd,
+                mode: mode.to_string(),
+            },
+        };
+        st.apply_delta(Delta::UpsertEdge {
+            edge: opens(3, "r"),
+        });
+        st.apply_delta(Delta::BatchBegin { id: 1 });
+        st.apply_delta(Delta::RemoveEdge {
+            edge: opens(3, "r"),
+        });
+        st.apply_delta(Delta::UpsertEdge {
+            edge: opens(3, "rw"),
+        });
+        // same mode again, or a different fd, is a plain upsert
+        st.apply_delta(Delta::RemoveEdge {
+            edge: opens(3, "rw"),
+        });
+        st.apply_delta(Delta::UpsertEdge {
+            edge: opens(3, "rw"),
+        });
+        st.apply_delta(Delta::UpsertEdge {
+            edge: opens(4, "w"),
+        });
+        st.apply_delta(Delta::BatchEnd { id: 1 });
+
+        let upserts: Vec<_> = st
+            .timeline
+            .events
+            .iter()
+            .filter_map(|evt| match evt.kind {
+                TimelineEvtKind::EdgeUpsert => Some(false),
+                TimelineEvtKind::EdgeModeChange => Some(true),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(upserts, vec![false, true, false, false]);
+        assert!(st.spatial.removed_opens.is_empty());
+    }
+
+    #[test]
+    fn heartbeat_timeout_disconnects_and_requests_reconnect() {
+        let mut st = GraphState::default();
+        st.sync_agent_endpoints(vec![AgentEndpoint::default()]);
+        st.apply(Incoming::connected("local".to_string()));
+        st.net.commands.clear();
+
+        st.apply(Incoming::heartbeat_timeout("local".to_string()));
+
+        let stream = &st.net.streams["local"];
+        assert_eq!(stream.status, NetStreamStatus::Disconnected);
+        assert!(stream
+            .last_error
+            .as_deref()
+            .is_some_and(|e| e.starts_with("heartbeat timeout")));
+        assert!(matches!(
+            st.net.commands.as_slice(),
+            [NetCommand::Connect(name)] if name == "local"
+        ));
+    }
+
+    #[test]
+    fn reconnect_backoff_doubles_up_to_cap() {
+        let secs: Vec<u64> = (1..=8).map(|n| reconnect_backoff(n).as_secs()).collect();
+        assert_eq!(secs, vec![0, 1, 2, 4, 8, 16, 30, 30]);
+        assert_eq!(reconnect_backoff(u32::MAX), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn reconnects_give_up_after_max_attempts_until_reset() {
+        let mut st = GraphState::default();
+        st.sync_agent_endpoints(vec![AgentEndpoint {
+            max_reconnect_attempts: 2,
+            ..Default::default()
+        }]);
+        let start = Instant::now();
+
+        st.apply(Incoming::disconnected("local".to_string()));
+        assert!(matches!(
+            st.net.commands.as_slice(),
+            [NetCommand::Connect(_)]
+        ));
+        st.net.commands.clear();
+
+        st.apply(Incoming::disconnected("local".to_string()));
+        assert!(st.net.commands.is_empty());
+        let due = st.net.streams["local"]
+            .next_reconnect
+            .expect("backoff scheduled");
+        st.tick_reconnects(start);
+        assert!(st.net.commands.is_empty());
+        st.tick_reconnects(due);
+        assert_eq!(st.net.commands.len(), 1);
+        st.net.commands.clear();
+
+        st.apply(Incoming::disconnected("local".to_string()));
+        let stream = &st.net.streams["local"];
+        assert!(stream.gave_up);
+        assert_eq!(stream.reconnect_attempts, 3);
+        assert!(stream.next_reconnect.is_none());
+        assert!(stream
+            .last_error
+            .as_deref()
+            .is_some_and(|e| e.starts_with("gave up after 2")));
+        st.apply(Incoming::disconnected("local".to_string()));
+        assert!(st.net.commands.is_empty());
+
+        st.reset_reconnects("local");
+        let stream = &st.net.streams["local"];
+        assert!(!stream.gave_up);
+        assert_eq!(stream.reconnect_attempts, 0);
+    }
+
+    #[test]
+    fn resume_point_tracks_completed_batches_per_session() {
+        let mut st = GraphState::default();
+        st.sync_agent_endpoints(vec![AgentEndpoint::default()]);
+        let hello = |session| {
+            Incoming::other(
+                "local".to_string(),
+                Msg::Hello {
+                    version: "0.1.0".into(),
+                    compression: false,
+                    snapshot_chunks: true,
+                    token: None,
+                    session: Some(session),
+                    resume_after_batch: None,
+                },
+            )
+        };
+        let event = |delta| Incoming::event("local".to_string(), Msg::Event { delta });
+
+        st.apply(Incoming::connected("local".to_string()));
+        st.apply(hello(7));
+        st.apply(Incoming::snapshot(
+            "local".to_string(),
+            Msg::Snapshot {
+                nodes: Vec::new(),
+                edges: Vec::new(),
+            },
+        ));
+        assert_eq!(st.net.streams["local"].resume_point(), None);
+
+        st.apply(event(Delta::BatchBegin { id: 3 }));
+        assert_eq!(st.net.streams["local"].resume_point(), None);
+        st.apply(event(Delta::BatchEnd { id: 3 }));
+        assert_eq!(st.net.streams["local"].resume_point(), Some((7, 3)));
+
+        // Same agent after a reconnect keeps the point; a restarted one drops it.
+        st.apply(Incoming::disconnected("local".to_string()));
+        st.apply(hello(7));
+        assert_eq!(st.net.streams["local"].resume_point(), Some((7, 3)));
+        st.apply(hello(8));
+        assert_eq!(st.net.streams["local"].resume_point(), None);
+
+        st.apply(event(Delta::BatchEnd { id: 4 }));
+        st.clear();
+        assert_eq!(st.net.streams["local"].resume_point(), None);
+    }
+
+    #[test]
+    fn newer_agent_protocol_and_unknown_variants_are_surfaced() {
+        let mut st = GraphState::default();
+        st.sync_agent_endpoints(vec![AgentEndpoint::default()]);
+        st.apply(Incoming::connected("local".to_string()));
+        assert_eq!(st.net.streams["local"].protocol_warning(), None);
+
+        let ident = spacegraph_core::NodeIdentity {
+            node_id: "h1".to_string(),
+            hostname: "h1".to_string(),
+            platform: "linux".to_string(),
+            arch: "x86_64".to_string(),
+            protocol: PROTOCOL_VERSION + 1,
+        };
+        let caps = spacegraph_core::Capabilities {
+            procfs: true,
+            fd_edges: true,
+            fs_notify: false,
+            proc_poll: true,
+            ebpf: false,
+            cloud: false,
+            windows: false,
+            compression: false,
+        };
+        st.apply(Incoming::identity(
+            "local".to_string(),
+            Msg::Identity { ident, caps },
+        ));
+        let warning = st.net.streams["local"].protocol_warning().unwrap();
+        assert!(warning.contains("newer"), "{warning}");
+
+        st.apply(Incoming::unknown(
+            "local".to_string(),
+            vec!["Node::Pod".to_string(), "Delta::Rename".to_string()],
+        ));
+        let stream = &st.net.streams["local"];
+        assert_eq!(stream.unknown_variants, 2);
+        assert!(stream
+            .protocol_warning()
+            .is_some_and(|w| w.contains("last: Delta::Rename")));
+    }
+
+    #[test]
+    fn capabilities_merge_across_connected_agents() {
+        let mut st = GraphState::default();
+        st.sync_agent_endpoints(vec![
+            AgentEndpoint {
+                name: "a".to_string(),
+                ..AgentEndpoint::default()
+            },
+            AgentEndpoint {
+                name: "b".to_string(),
+                ..AgentEndpoint::default()
+            },
+        ]);
+        assert_eq!(st.net.merged_caps(), None);
+
+        let identity = |host: &str, caps: Capabilities| {
+            let ident = spacegraph_core::NodeIdentity {
+                node_id: host.to_string(),
+                hostname: host.to_string(),
+                platform: "linux".to_string(),
+                arch: "x86_64".to_string(),
+                protocol: PROTOCOL_VERSION,
+            };
+            Incoming::identity(host.to_string(), Msg::Identity { ident, caps })
+        };
+        st.apply(Incoming::connected("a".to_string()));
+        st.apply(Incoming::connected("b".to_string()));
+        st.apply(identity(
+            "a",
+            Capabilities {
+                procfs: true,
+                proc_poll: true,
+                ..Capabilities::default()
+            },
+        ));
+        st.apply(identity(
+            "b",
+            Capabilities {
+                fd_edges: true,
+                ..Capabilities::default()
+            },
+        ));
+
+        let merged = st.net.merged_caps().expect("caps");
+        assert!(merged.procfs && merged.proc_poll && merged.fd_edges);
+        assert_eq!(missing_capability_notes(&merged), vec!["no fs events"]);
+
+        st.apply(Incoming::disconnected("b".to_string()));
+        let merged = st.net.merged_caps().expect("caps");
+        assert!(!merged.fd_edges);
+        assert_eq!(
+            missing_capability_notes(&merged),
+            vec!["no open-file edges", "no fs events"]
+        );
+    }
+
+    #[test]
+    fn remove_nodes_policy_purges_only_the_dropped_host() {
+        let mut st = GraphState::default();
+        st.sync_agent_endpoints(vec![
+            AgentEndpoint {
+                name: "a".to_string(),
+                on_disconnect: DisconnectPolicy::RemoveNodes,
+                ..AgentEndpoint::default()
+            },
+            AgentEndpoint {
+                name: "b".to_string(),
+                ..AgentEndpoint::default()
+            },
+        ]);
+        let identity = |stream: &str, host: &str| {
+            let ident = spacegraph_core::NodeIdentity {
+                node_id: host.to_string(),
+                hostname: host.to_string(),
+                platform: "linux".to_string(),
+                arch: "x86_64".to_string(),
+                protocol: PROTOCOL_VERSION,
+            };
+            let caps = Capabilities::default();
+            Incoming::identity(stream.to_string(), Msg::Identity { ident, caps })
+        };
+        let user = |host: &str, uid: u32| {
+            let name = format!("u{uid}");
+            (
+                spacegraph_core::id_user(host, uid),
+                Node::User { uid, name },
+            )
+        };
+        let nodes = vec![user("alpha", 0), user("alpha", 1000), user("beta", 0)];
+        let edges = vec![Edge {
+            from: nodes[1].0.clone(),
+            to: nodes[2].0.clone(),
+            kind: EdgeKind::RunsAs,
+        }];
+        for stream in ["a", "b"] {
+            st.apply(Incoming::connected(stream.to_string()));
+        }
+        st.apply(identity("a", "alpha"));
+        st.apply(identity("b", "beta"));
+        st.apply(Incoming::snapshot(
+            "a".to_string(),
+            Msg::Snapshot { nodes, edges },
+        ));
+        assert_eq!(st.model.nodes.len(), 3);
+
+        // b keeps its nodes when it drops
+        st.apply(Incoming::disconnected("b".to_string()));
+        assert_eq!(st.model.nodes.len(), 3);
+
+        st.apply(Incoming::disconnected("a".to_string()));
+        let left: Vec<&str> = st.model.nodes.keys().map(|id| id.0.as_str()).collect();
+        assert_eq!(left, vec!["beta:user:0"]);
+        assert!(st.model.edges.is_empty());
+    }
+
+    #[test]
+    fn import_snapshot_replaces_graph() {
+        let mut st = GraphState::default();
+        st.model.nodes.insert(
+            NodeId("stale".to_string()),
+            Node::User {
+                uid: 1,
+                name: "stale".to_string(),
+            },
+        );
+        let fresh = NodeId("fresh".to_string());
+        let msg = Msg::Snapshot {
+            nodes: vec![(
+                fresh.clone(),
+                Node::User {
+                    uid: 2,
+                    name: "fresh".to_string(),
+                },
+            )],
+            edges: Vec::new(),
+        };
+
+        assert!(st.import_snapshot(msg));
+
+        assert_eq!(st.model.nodes.len(), 1);
+        assert!(st.model.nodes.contains_key(&fresh));
+        assert!(st.snapshot_loaded);
+    }
+
+    #[test]
+    fn next_neighbor_cycles_sorted_and_wraps() {
+        let mut st = GraphState::default();
+        let now = Instant::now();
+        let ids: Vec<NodeId> = ["hub", "c", "a", "b"]
+            .iter()
+            .map(|s| NodeId(s.to_string()))
+            .collect();
+        for id in &ids {
+            st.model.upsert_node(
+                id.clone(),
+                Node::File {
+                    path: format!("/{}", id.0),
+                    inode: 1,
+                    kind: FileKind::Regular,
+                    category: FileCategory::Unknown,
+                    link_target: None,
+                },
+                now,
+            );
+        }
+        for leaf in &ids[1..] {
+            st.model.upsert_edge(
+                Edge {
+                    from: ids[0].clone(),
+                    to: leaf.clone(),
+                    kind: EdgeKind::Execs,
+                },
+                now,
+            );
+        }
+        let id = |s: &str| NodeId(s.to_string());
+
+        let (anchor, first) = st.next_neighbor(&id("hub"), NavDir::Next).unwrap();
+        assert_eq!(anchor, id("hub"));
+        assert_eq!(first, id("a"));
+        assert_eq!(
+            st.next_neighbor(&id("hub"), NavDir::Prev).unwrap().1,
+            id("c")
+        );
+
+        st.ui.nav_anchor = Some(anchor);
+        assert_eq!(st.next_neighbor(&id("a"), NavDir::Next).unwrap().1, id("b"));
+        assert_eq!(st.next_neighbor(&id("c"), NavDir::Next).unwrap().1, id("a"));
+        assert_eq!(st.next_neighbor(&id("a"), NavDir::Prev).unwrap().1, id("c"));
+    }
+
+    #[test]
+    fn explain_cache_recomputes_when_depth_changes() {
+        let mut st = GraphState::default();
+        let ids: Vec<NodeId> = (1..=3).map(|uid| id_user("h", uid)).collect();
+        for (uid, id) in (1..).zip(&ids) {
+            st.apply_delta(Delta::UpsertNode {
+                id: id.clone(),
+                node: Node::User {
+                    uid,
+                    name: format!("u{uid}"),
+                },
+            });
+        }
+        for pair in ids.windows(2) {
+            st.apply_delta(Delta::UpsertEdge {
+                edge: Edge {
+                    from: pair[0].clone(),
+                    to: pair[1].clone(),
+                    kind: EdgeKind::RunsAs,
+                },
+            });
+        }
+        let allowed: HashSet<NodeId> = ids.iter().cloned().collect();
+
+        st.cfg.explain_max_depth = 1;
+        assert!(st.explain_path_cached(&ids[0], &ids[2], &allowed).is_none());
+        st.cfg.explain_max_depth = 2;
+        let path = st
+            .explain_path_cached(&ids[0], &ids[2], &allowed)
+            .expect("path within depth 2");
+        assert_eq!(path.len(), 2);
+    }
+
+    #[test]
+    fn focus_paths_follow_focus_and_multi_selection() {
+        let mut st = GraphState::default();
+        let ids: Vec<NodeId> = (1..=4).map(|uid| id_user("h", uid)).collect();
+        for (uid, id) in (1..).zip(&ids) {
+            st.apply_delta(Delta::UpsertNode {
+                id: id.clone(),
+                node: Node::User {
+                    uid,
+                    name: format!("u{uid}"),
+                },
+            });
+        }
+        // Star around ids[0].
+        for leaf in &ids[1..] {
+            st.apply_delta(Delta::UpsertEdge {
+                edge: Edge {
+                    from: ids[0].clone(),
+                    to: leaf.clone(),
+                    kind: EdgeKind::RunsAs,
+                },
+            });
+        }
+        let allowed: HashSet<NodeId> = ids.iter().cloned().collect();
+        st.ui.focus = Some(ids[1].clone());
+        st.ui.multi_selected = [ids[2].clone(), ids[3].clone()].into();
+        assert!(st.focus_paths_cached(&allowed).is_empty());
+
+        st.ui.explain_focus_paths = true;
+        let steps = st.focus_paths_cached(&allowed);
+        // ids[1] -> ids[0] is shared by both paths and drawn once.
+        assert_eq!(steps.len(), 3);
+        assert_eq!(steps.iter().filter(|s| s.to == ids[0]).count(), 1);
+
+        st.ui.multi_selected = [ids[2].clone()].into();
+        assert_eq!(st.focus_paths_cached(&allowed).len(), 2);
+    }
 }
@@ -0,0 +1,184 @@
+use spacegraph_core::{Node, NodeId};
+
+use crate::graph::model::{EdgeKindClass, GraphModel};
+
+pub const TOP_DEGREE_COUNT: usize = 10;
+pub const TOP_OPEN_FILES_COUNT: usize = 25;
+
+const EDGE_CLASSES: [EdgeKindClass; 7] = [
+    EdgeKindClass::Opens,
+    EdgeKindClass::Execs,
+    EdgeKindClass::RunsAs,
+    EdgeKindClass::ParentOf,
+    EdgeKindClass::MemberOf,
+    EdgeKindClass::PointsTo,
+    EdgeKindClass::ThreadOf,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphStats {
+    pub processes: usize,
+    pub files: usize,
+    pub users: usize,
+    pub containers: usize,
+    pub threads: usize,
+    pub edges_by_class: [(EdgeKindClass, usize); 7],
+    /// Highest-degree nodes, descending; ties ordered by id.
+    pub top_degree: Vec<(NodeId, u32)>,
+    /// Processes with the most open files, descending; ties ordered by id.
+    pub top_open_files: Vec<(NodeId, u32)>,
+    /// File nodes without any edge (GC candidates).
+    pub orphan_files: usize,
+}
+
+pub fn compute_stats(model: &GraphModel) -> GraphStats {
+    let mut processes = 0;
+    let mut files = 0;
+    let mut users = 0;
+    let mut containers = 0;
+    let mut threads = 0;
+    for node in model.nodes.values() {
+        match node {
+            Node::Process { .. } => processes += 1,
+            Node::File { .. } => files += 1,
+            Node::User { .. } => users += 1,
+            Node::Container { .. } => containers += 1,
+            Node::Thread { .. } => threads += 1,
+        }
+    }
+
+    let mut edges_by_class = EDGE_CLASSES.map(|class| (class, 0));
+    for e in model.edges.iter() {
+        let class = EdgeKindClass::from_kind(&e.kind);
+        if let Some(slot) = edges_by_class.iter_mut().find(|(c, _)| *c == class) {
+            slot.1 += 1;
+        }
+    }
+
+    let degree = model.degree_map();
+    let orphan_files = model
+        .nodes
+        .iter()
+        .filter(|(id, n)| matches!(n, Node::File { .. }) && !degree.contains_key(*id))
+        .count();
+
+    let mut top_degree: Vec<(NodeId, u32)> = degree.into_iter().collect();
+    top_degree.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0 .0.cmp(&b.0 .0)));
+    top_degree.truncate(TOP_DEGREE_COUNT);
+
+    let mut top_open_files: Vec<(NodeId, u32)> = model.open_file_counts().into_iter().collect();
+    top_open_files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0 .0.cmp(&b.0 .0)));
+    top_open_files.truncate(TOP_OPEN_FILES_COUNT);
+
+    GraphStats {
+        processes,
+        files,
+        users,
+        containers,
+        threads,
+        edges_by_class,
+        top_degree,
+        top_open_files,
+        orphan_files,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::{Edge, EdgeKind, FileCategory, FileKind};
+    use std::time::Instant;
+
+    fn file(path: &str) -> Node {
+        Node::File {
+            path: path.to_string(),
+            inode: 1,
+            kind: FileKind::Regular,
+            category: FileCategory::Unknown,
+            link_target: None,
+        }
+    }
+
+    fn sample_model() -> GraphModel {
+        let now = Instant::now();
+        let mut model = GraphModel::default();
+        let p = NodeId("p".to_string());
+        model.upsert_node(
+            p.clone(),
+            Node::Process {
+                pid: 1,
+                ppid: 0,
+                exe: "/bin/init".to_string(),
+                cmdline: "init".to_string(),
+                uid: 0,
+                rss_kb: 0,
+                utime_ticks: 0,
+                container: None,
+            },
+            now,
+        );
+        model.upsert_node(
+            NodeId("u".to_string()),
+            Node::User {
+                uid: 0,
+                name: "root".to_string(),
+            },
+            now,
+        );
+        for path in ["a", "b", "orphan"] {
+            model.upsert_node(NodeId(path.to_string()), file(path), now);
+        }
+        for (to, kind) in [
+            ("a", EdgeKind::Execs),
+            (
+                "b",
+                EdgeKind::Opens {
+                    fd: 3,
+                    mode: "r".to_string(),
+                },
+            ),
+            ("u", EdgeKind::RunsAs),
+        ] {
+            model.upsert_edge(
+                Edge {
+                    from: p.clone(),
+                    to: NodeId(to.to_string()),
+                    kind,
+                },
+                now,
+            );
+        }
+        model
+    }
+
+    #[test]
+    fn counts_nodes_edges_and_orphans() {
+        let stats = compute_stats(&sample_model());
+        assert_eq!((stats.processes, stats.files, stats.users), (1, 3, 1));
+        assert_eq!(
+            stats.edges_by_class,
+            [
+                (EdgeKindClass::Opens, 1),
+                (EdgeKindClass::Execs, 1),
+                (EdgeKindClass::RunsAs, 1),
+                (EdgeKindClass::ParentOf, 0),
+                (EdgeKindClass::MemberOf, 0),
+                (EdgeKindClass::PointsTo, 0),
+                (EdgeKindClass::ThreadOf, 0),
+            ]
+        );
+        assert_eq!(stats.orphan_files, 1);
+    }
+
+    #[test]
+    fn top_degree_is_sorted_descending_with_id_ties() {
+        let stats = compute_stats(&sample_model());
+        let ids: Vec<(&str, u32)> = stats
+            .top_degree
+            .iter()
+            .map(|(id, d)| (id.0.as_str(), *d))
+            .collect();
+        assert_eq!(ids, vec![("p", 3), ("a", 1), ("b", 1), ("u", 1)]);
+        assert_eq!(stats.top_open_files, vec![(NodeId("p".to_string()), 1)]);
+    }
+}
@@ -1,8 +1,14 @@
-use spacegraph_core::{EdgeKind, Node, NodeId};
+use spacegraph_core::{Edge, EdgeKind, Node, NodeId};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 use std::time::{Duration, Instant};
 
 use crate::graph::state::{GraphState, TimelineState};
 
+const NEW_NODE_SCALE_BOOST: f32 = 0.3;
+/// Lower bound for `max_events`, so the timeline never ends up empty by configuration.
+pub const MIN_TIMELINE_EVENTS: usize = 1_000;
+
 #[derive(Debug, Clone)]
 pub struct NodeLife {
     pub first_seen: Instant,
@@ -33,6 +39,8 @@ pub enum TimelineEvtKind {
     NodeRemove,
     EdgeUpsert,
     EdgeRemove,
+    /// Upsert of an `opens` edge whose fd was removed with another mode in the same batch.
+    EdgeModeChange,
     BatchBegin(u64),
     BatchEnd(u64),
 }
@@ -47,16 +55,60 @@ impl TimelineState {
         base - Duration::from_secs_f32(self.scrub_seconds.max(0.0))
     }
 
+    /// Moves the scrub position forward in time by `dt * playback_speed` seconds.
+    /// Returns true while playback is still running.
+    pub fn advance_playback(&mut self, dt: f32) -> bool {
+        if !self.playing || !self.pause {
+            self.playing = false;
+            return false;
+        }
+        self.scrub_seconds = (self.scrub_seconds - dt * self.playback_speed.max(0.0)).max(0.0);
+        if self.scrub_seconds <= 0.0 {
+            self.playing = false;
+        }
+        true
+    }
+
     pub fn window_start(&self, now: Instant) -> Instant {
         now - self.window
     }
 
+    /// Heap footprint of one buffered event.
+    fn event_size(evt: &TimelineEvt) -> usize {
+        let ids = |id: &Option<NodeId>| id.as_ref().map_or(0, |id| id.0.capacity());
+        size_of::<TimelineEvt>() + ids(&evt.a) + ids(&evt.b)
+    }
+
+    pub fn push_event(&mut self, evt: TimelineEvt) {
+        self.event_bytes += Self::event_size(&evt);
+        self.events.push_back(evt);
+    }
+
+    fn pop_event(&mut self) {
+        if let Some(evt) = self.events.pop_front() {
+            self.event_bytes = self.event_bytes.saturating_sub(Self::event_size(&evt));
+        }
+    }
+
+    fn life_entry(&mut self, id: &NodeId, life: NodeLife) -> &mut NodeLife {
+        match self.node_life.entry(id.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                self.life_bytes += size_of::<(NodeId, NodeLife)>() + entry.key().0.capacity();
+                entry.insert(life)
+            }
+        }
+    }
+
     pub fn record_node_upsert(&mut self, id: &NodeId, ts: Instant) {
-        let entry = self.node_life.entry(id.clone()).or_insert(NodeLife {
-            first_seen: ts,
-            last_seen: ts,
-            removed_at: None,
-        });
+        let entry = self.life_entry(
+            id,
+            NodeLife {
+                first_seen: ts,
+                last_seen: ts,
+                removed_at: None,
+            },
+        );
         if ts < entry.first_seen {
             entry.first_seen = ts;
         }
@@ -73,11 +125,14 @@ impl TimelineState {
     }
 
     pub fn record_node_remove(&mut self, id: &NodeId, ts: Instant) {
-        let entry = self.node_life.entry(id.clone()).or_insert(NodeLife {
-            first_seen: ts,
-            last_seen: ts,
-            removed_at: Some(ts),
-        });
+        let entry = self.life_entry(
+            id,
+            NodeLife {
+                first_seen: ts,
+                last_seen: ts,
+                removed_at: Some(ts),
+            },
+        );
         if ts < entry.first_seen {
             entry.first_seen = ts;
         }
@@ -106,20 +161,28 @@ impl TimelineState {
         }
     }
 
+    /// Rough heap footprint of the event buffer, batch spans and node lifetimes.
+    pub fn buffer_bytes(&self) -> usize {
+        self.event_bytes + self.life_bytes + self.batch_spans.len() * size_of::<BatchSpan>()
+    }
+
     pub fn trim(&mut self, now: Instant) {
         while self.events.len() > self.max_events {
-            self.events.pop_front();
+            self.pop_event();
         }
 
         let window_start = self.window_start(now);
         while let Some(front) = self.events.front() {
             if front.ts < window_start {
-                self.events.pop_front();
+                self.pop_event();
             } else {
                 break;
             }
         }
 
+        while self.batch_spans.len() > self.max_batch_spans {
+            self.batch_spans.pop_front();
+        }
         while let Some(front) = self.batch_spans.front() {
             match front.end {
                 Some(end) if end < window_start => {
@@ -136,7 +199,20 @@ pub fn timeline_lane_key(node: &Node) -> String {
         Node::Process { pid, .. } => format!("pid:{pid}"),
         Node::File { path, .. } => format!("path:{path}"),
         Node::User { uid, .. } => format!("uid:{uid}"),
+        Node::Container { id, .. } => format!("container:{id}"),
+        Node::Thread { tid, .. } => format!("tid:{tid}"),
+    }
+}
+
+pub fn node_kind_lane(node: &Node) -> String {
+    match node {
+        Node::Process { .. } => "kind:process",
+        Node::File { .. } => "kind:file",
+        Node::User { .. } => "kind:user",
+        Node::Container { .. } => "kind:container",
+        Node::Thread { .. } => "kind:thread",
     }
+    .to_string()
 }
 
 impl GraphState {
@@ -145,10 +221,51 @@ impl GraphState {
         self.timeline.effective_now()
     }
 
+    pub fn tick_timeline_playback(&mut self, dt: f32) {
+        if self.timeline.advance_playback(dt) {
+            self.needs_redraw
+                .store(true, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
     pub fn tick_timeline(&mut self) {
         // cap + window trimming
         let now = self.timeline_now();
         self.timeline.trim(now);
+
+        let real_now = Instant::now();
+        let window = self.timeline.window;
+        self.timeline.history.trim(real_now, window);
+        self.timeline.history.sample(real_now, &self.model);
+    }
+
+    /// Nodes and edges as they were at `ts`; `None` while model history is off.
+    pub fn reconstruct_at(&self, ts: Instant) -> Option<(HashMap<NodeId, Node>, HashSet<Edge>)> {
+        self.timeline
+            .history
+            .enabled
+            .then(|| self.timeline.history.reconstruct_at(&self.model, ts))
+    }
+
+    pub fn node_age(&self, id: &NodeId, now: Instant) -> Option<Duration> {
+        self.timeline
+            .node_life
+            .get(id)
+            .map(|life| now.saturating_duration_since(life.first_seen))
+    }
+
+    /// Radius multiplier for freshly seen nodes; shrinks back to 1 over the glow duration.
+    pub fn new_node_scale(&self, id: &NodeId, now: Instant) -> f32 {
+        if !self.cfg.fade_in_new_nodes || self.cfg.glow_duration.is_zero() {
+            return 1.0;
+        }
+        match self.node_age(id, now) {
+            Some(age) if age < self.cfg.glow_duration => {
+                let t = age.as_secs_f32() / self.cfg.glow_duration.as_secs_f32();
+                1.0 + NEW_NODE_SCALE_BOOST * (1.0 - t)
+            }
+            _ => 1.0,
+        }
     }
 
     pub fn set_timeline_pause(&mut self, pause: bool) {
@@ -161,11 +278,24 @@ impl GraphState {
         } else {
             self.timeline.frozen_now = None;
             self.timeline.scrub_seconds = 0.0;
+            self.timeline.playing = false;
         }
         self.needs_redraw
             .store(true, std::sync::atomic::Ordering::Relaxed);
     }
 
+    /// True when `edge` reopens an fd that this batch removed with a different mode.
+    pub(crate) fn take_mode_transition(&mut self, edge: &Edge) -> bool {
+        let EdgeKind::Opens { fd, mode } = &edge.kind else {
+            return false;
+        };
+        let key = (edge.from.clone(), edge.to.clone(), *fd);
+        self.spatial
+            .removed_opens
+            .remove(&key)
+            .is_some_and(|old| &old != mode)
+    }
+
     pub(crate) fn push_timeline_at(
         &mut self,
         ts: Instant,
@@ -200,7 +330,7 @@ impl GraphState {
             }
             _ => {}
         }
-        self.timeline.events.push_back(evt);
+        self.timeline.push_event(evt);
     }
 }
 
@@ -208,7 +338,7 @@ impl GraphState {
 mod tests {
     use super::*;
     use crate::graph::state::GraphState;
-    use spacegraph_core::FileKind;
+    use spacegraph_core::{FileCategory, FileKind};
     use std::time::Duration;
 
     #[test]
@@ -219,14 +349,14 @@ mod tests {
         st.timeline.frozen_now = Some(now);
         st.timeline.window = Duration::from_secs(10);
 
-        st.timeline.events.push_back(TimelineEvt {
+        st.timeline.push_event(TimelineEvt {
             ts: now - Duration::from_secs(20),
             kind: TimelineEvtKind::NodeUpsert,
             a: None,
             b: None,
             edge_kind: None,
         });
-        st.timeline.events.push_back(TimelineEvt {
+        st.timeline.push_event(TimelineEvt {
             ts: now - Duration::from_secs(5),
             kind: TimelineEvtKind::NodeUpsert,
             a: None,
@@ -248,7 +378,7 @@ mod tests {
         st.timeline.window = Duration::from_secs(60);
 
         for i in 0..5 {
-            st.timeline.events.push_back(TimelineEvt {
+            st.timeline.push_event(TimelineEvt {
                 ts: now - Duration::from_secs(i),
                 kind: TimelineEvtKind::NodeUpsert,
                 a: None,
@@ -261,6 +391,37 @@ mod tests {
         assert_eq!(st.timeline.events.len(), 3);
     }
 
+    #[test]
+    fn lowering_caps_trims_on_next_tick() {
+        let mut st = GraphState::default();
+        let now = Instant::now();
+        st.timeline.pause = true;
+        st.timeline.frozen_now = Some(now);
+        st.timeline.window = Duration::from_secs(60);
+        for i in 0..50u64 {
+            let ts = now - Duration::from_millis(i * 10);
+            st.timeline.push_event(TimelineEvt {
+                ts,
+                kind: TimelineEvtKind::NodeUpsert,
+                a: Some(NodeId(format!("n{i}"))),
+                b: None,
+                edge_kind: None,
+            });
+            st.timeline.record_batch_begin(i, ts);
+            st.timeline.record_batch_end(i, ts);
+        }
+        st.tick_timeline();
+        assert_eq!(st.timeline.events.len(), 50);
+        let before = st.timeline.buffer_bytes();
+
+        st.timeline.max_events = 10;
+        st.timeline.max_batch_spans = 5;
+        st.tick_timeline();
+        assert_eq!(st.timeline.events.len(), 10);
+        assert_eq!(st.timeline.batch_spans.len(), 5);
+        assert!(st.timeline.buffer_bytes() < before);
+    }
+
     #[test]
     fn pause_freezes_now_and_scrub_moves_back() {
         let mut timeline = TimelineState::default();
@@ -286,6 +447,27 @@ mod tests {
         assert_eq!(life.removed_at, Some(base + Duration::from_secs(12)));
     }
 
+    #[test]
+    fn node_age_counts_from_first_seen() {
+        let mut st = GraphState::default();
+        let base = Instant::now();
+        let id = NodeId("node-1".to_string());
+        assert_eq!(st.node_age(&id, base), None);
+
+        st.timeline.record_node_upsert(&id, base);
+        st.timeline
+            .record_node_upsert(&id, base + Duration::from_secs(3));
+        assert_eq!(
+            st.node_age(&id, base + Duration::from_secs(5)),
+            Some(Duration::from_secs(5))
+        );
+        // Clock skew never yields a negative age.
+        assert_eq!(
+            st.node_age(&id, base - Duration::from_secs(1)),
+            Some(Duration::ZERO)
+        );
+    }
+
     #[test]
     fn batch_spans_open_close_and_trim() {
         let mut timeline = TimelineState::default();
@@ -313,22 +495,131 @@ mod tests {
             exe: "/bin/bash".to_string(),
             cmdline: "bash".to_string(),
             uid: 1000,
+            rss_kb: 0,
+            utime_ticks: 0,
+            container: None,
         };
         assert_eq!(timeline_lane_key(&node), "pid:4242");
     }
 
+    #[test]
+    fn node_kind_lane_collapses_nodes_of_same_kind() {
+        let node_a = Node::File {
+            path: "/tmp/a".to_string(),
+            inode: 1,
+            kind: FileKind::Regular,
+            category: FileCategory::Unknown,
+            link_target: None,
+        };
+        let node_b = Node::File {
+            path: "/tmp/b".to_string(),
+            inode: 2,
+            kind: FileKind::Dir,
+            category: FileCategory::Unknown,
+            link_target: None,
+        };
+        let user = Node::User {
+            uid: 0,
+            name: "root".to_string(),
+        };
+        assert_eq!(node_kind_lane(&node_a), node_kind_lane(&node_b));
+        assert_ne!(node_kind_lane(&node_a), node_kind_lane(&user));
+    }
+
     #[test]
     fn timeline_lane_key_distinguishes_file_paths() {
         let node_a = Node::File {
             path: "/tmp/a".to_string(),
             inode: 1,
             kind: FileKind::Regular,
+            category: FileCategory::Unknown,
+            link_target: None,
         };
         let node_b = Node::File {
             path: "/tmp/b".to_string(),
             inode: 1,
             kind: FileKind::Regular,
+            category: FileCategory::Unknown,
+            link_target: None,
         };
         assert_ne!(timeline_lane_key(&node_a), timeline_lane_key(&node_b));
     }
+
+    #[test]
+    fn playback_advances_scrub_and_stops_at_zero() {
+        let mut st = GraphState::default();
+        st.set_timeline_pause(true);
+        st.timeline.scrub_seconds = 3.0;
+        st.timeline.playback_speed = 2.0;
+        st.timeline.playing = true;
+        let frozen = st.timeline.frozen_now.unwrap();
+
+        st.tick_timeline_playback(0.5);
+        assert!((st.timeline.scrub_seconds - 2.0).abs() < 1e-5);
+        assert_eq!(
+            st.timeline.effective_now_from(frozen),
+            frozen - Duration::from_secs(2)
+        );
+
+        st.tick_timeline_playback(5.0);
+        assert_eq!(st.timeline.scrub_seconds, 0.0);
+        assert!(!st.timeline.playing);
+    }
+
+    #[test]
+    fn unpause_stops_playback() {
+        let mut st = GraphState::default();
+        st.set_timeline_pause(true);
+        st.timeline.scrub_seconds = 3.0;
+        st.timeline.playing = true;
+        st.set_timeline_pause(false);
+        assert!(!st.timeline.playing);
+    }
+
+    #[test]
+    fn buffer_bytes_tracks_pushes_trims_and_resets() {
+        let mut st = GraphState::default();
+        let now = Instant::now();
+        st.timeline.pause = true;
+        st.timeline.frozen_now = Some(now);
+        st.timeline.window = Duration::from_secs(60);
+        let scan = |t: &TimelineState| -> usize {
+            let ids = |id: &Option<NodeId>| id.as_ref().map_or(0, |id| id.0.capacity());
+            let events: usize = t
+                .events
+                .iter()
+                .map(|evt| size_of::<TimelineEvt>() + ids(&evt.a) + ids(&evt.b))
+                .sum();
+            let lives: usize = t
+                .node_life
+                .keys()
+                .map(|id| size_of::<(NodeId, NodeLife)>() + id.0.capacity())
+                .sum();
+            events + lives + t.batch_spans.len() * size_of::<BatchSpan>()
+        };
+
+        for i in 0..20u64 {
+            let id = NodeId(format!("node-{i}"));
+            st.timeline.record_node_upsert(&id, now);
+            st.timeline.record_node_upsert(&id, now);
+            st.timeline.push_event(TimelineEvt {
+                ts: now - Duration::from_secs((19 - i) * 5),
+                kind: TimelineEvtKind::NodeUpsert,
+                a: Some(id),
+                b: None,
+                edge_kind: None,
+            });
+        }
+        assert_eq!(st.timeline.buffer_bytes(), scan(&st.timeline));
+
+        // Events older than 60s age out of the window; the counter follows the eviction.
+        let before = st.timeline.buffer_bytes();
+        st.tick_timeline();
+        assert_eq!(st.timeline.events.len(), 13);
+        assert!(st.timeline.buffer_bytes() < before);
+        assert_eq!(st.timeline.buffer_bytes(), scan(&st.timeline));
+
+        st.clear();
+        assert_eq!(st.timeline.buffer_bytes(), 0);
+    }
 }
@@ -213,7 +213,7 @@ fn layout_subtree(
     let x_units = start_units + width / 2.0;
     positions.insert(
         id.clone(),
-        Vec3::new(x_units * COL_SPACING, depth as f32 * ROW_SPACING, 0.0),
+        Vec3::new(x_units * COL_SPACING, -(depth as f32) * ROW_SPACING, 0.0),
     );
     start_units + width
 }
@@ -221,6 +221,7 @@ fn layout_subtree(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use spacegraph_core::{FileCategory, FileKind};
 
     #[test]
     fn parent_path_derivation() {
@@ -253,6 +254,50 @@ mod tests {
         assert_eq!(ancestor_paths("/"), Vec::<String>::new());
     }
 
+    #[test]
+    fn layout_places_parents_above_children() {
+        let dir = NodeId("dir".to_string());
+        let sub = NodeId("sub".to_string());
+        let file = NodeId("file".to_string());
+        let mut nodes = HashMap::new();
+        nodes.insert(
+            dir.clone(),
+            Node::File {
+                path: "/home".to_string(),
+                inode: 1,
+                kind: FileKind::Dir,
+                category: FileCategory::Unknown,
+                link_target: None,
+            },
+        );
+        nodes.insert(
+            sub.clone(),
+            Node::File {
+                path: "/home/user".to_string(),
+                inode: 2,
+                kind: FileKind::Dir,
+                category: FileCategory::Unknown,
+                link_target: None,
+            },
+        );
+        nodes.insert(
+            file.clone(),
+            Node::File {
+                path: "/home/user/report.txt".to_string(),
+                inode: 3,
+                kind: FileKind::Regular,
+                category: FileCategory::Unknown,
+                link_target: None,
+            },
+        );
+        let visible: HashSet<NodeId> = nodes.keys().cloned().collect();
+
+        let positions = layout_tree_positions(&nodes, &visible, &["/home".to_string()]);
+
+        assert!(positions[&dir].y > positions[&sub].y);
+        assert!(positions[&sub].y > positions[&file].y);
+    }
+
     #[test]
     fn children_sorted_lexicographically() {
         let a = NodeId("a".to_string());
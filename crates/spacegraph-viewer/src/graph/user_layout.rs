@@ -0,0 +1,178 @@
+use bevy::prelude::Vec3;
+use spacegraph_core::{EdgeKind, Node, NodeId};
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::TAU;
+
+use crate::graph::model::GraphModel;
+
+const PROC_SPACING: f32 = 1.6;
+const LEAF_RADIUS: f32 = 1.2;
+
+/// Visible processes grouped under the visible user they `RunsAs`, both sorted by id.
+pub fn user_clusters(model: &GraphModel, visible: &HashSet<NodeId>) -> Vec<(NodeId, Vec<NodeId>)> {
+    let mut clusters: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+    for id in visible {
+        if matches!(model.nodes.get(id), Some(Node::User { .. })) {
+            clusters.entry(id.clone()).or_default();
+        }
+    }
+    for edge in &model.edges {
+        if edge.kind != EdgeKind::RunsAs || !visible.contains(&edge.from) {
+            continue;
+        }
+        if let Some(procs) = clusters.get_mut(&edge.to) {
+            procs.push(edge.from.clone());
+        }
+    }
+    let mut out: Vec<(NodeId, Vec<NodeId>)> = clusters.into_iter().collect();
+    for (_, procs) in out.iter_mut() {
+        procs.sort_by(|a, b| a.0.cmp(&b.0));
+        procs.dedup();
+    }
+    out.sort_by(|a, b| a.0 .0.cmp(&b.0 .0));
+    out
+}
+
+/// Start positions for "group by user": users on a ring, their processes on a sub-ring
+/// around each user, and the files and threads of a process close around it. Nodes
+/// outside any cluster get no position here.
+pub fn layout_user_clusters(
+    model: &GraphModel,
+    visible: &HashSet<NodeId>,
+    radius: f32,
+    y_spread: f32,
+) -> HashMap<NodeId, Vec3> {
+    let clusters = user_clusters(model, visible);
+    let mut pos = HashMap::new();
+    let users = clusters.len().max(1) as f32;
+    for (u, (user, procs)) in clusters.iter().enumerate() {
+        let t = u as f32 / users * TAU;
+        let center = Vec3::new(radius * t.cos(), 0.0, radius * t.sin());
+        pos.insert(user.clone(), center);
+
+        let sub_r = (procs.len() as f32 * PROC_SPACING / TAU).max(2.0);
+        let n = procs.len().max(1) as f32;
+        for (i, proc_id) in procs.iter().enumerate() {
+            if pos.contains_key(proc_id) {
+                continue;
+            }
+            let a = i as f32 / n * TAU;
+            let y = spread_y(i, y_spread);
+            let p = center + Vec3::new(sub_r * a.cos(), y, sub_r * a.sin());
+            pos.insert(proc_id.clone(), p);
+
+            let leaves: Vec<NodeId> = model
+                .sorted_neighbors(proc_id)
+                .into_iter()
+                .filter(|id| visible.contains(id) && !pos.contains_key(id))
+                .filter(|id| {
+                    matches!(
+                        model.nodes.get(id),
+                        Some(Node::File { .. } | Node::Thread { .. })
+                    )
+                })
+                .collect();
+            let m = leaves.len().max(1) as f32;
+            for (j, leaf) in leaves.into_iter().enumerate() {
+                let b = j as f32 / m * TAU;
+                let offset = Vec3::new(LEAF_RADIUS * b.cos(), 0.0, LEAF_RADIUS * b.sin());
+                pos.insert(leaf, p + offset);
+            }
+        }
+    }
+    pos
+}
+
+fn spread_y(i: usize, y_spread: f32) -> f32 {
+    if y_spread > 0.0 {
+        ((i as f32) % 7.0) / 7.0 * y_spread
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::{Edge, FileCategory, FileKind};
+    use std::time::Instant;
+
+    fn process(uid: u32) -> Node {
+        Node::Process {
+            pid: 1,
+            ppid: 0,
+            exe: "/bin/sh".to_string(),
+            cmdline: "sh".to_string(),
+            uid,
+            rss_kb: 0,
+            utime_ticks: 0,
+            container: None,
+        }
+    }
+
+    #[test]
+    fn processes_join_the_cluster_of_their_user() {
+        let mut model = GraphModel::default();
+        let now = Instant::now();
+        let id = |s: &str| NodeId(s.to_string());
+        for (user, uid) in [("u:0", 0), ("u:1000", 1000)] {
+            model.upsert_node(
+                id(user),
+                Node::User {
+                    uid,
+                    name: user.to_string(),
+                },
+                now,
+            );
+        }
+        for (proc_id, user) in [("p:2", "u:1000"), ("p:1", "u:0"), ("p:3", "u:1000")] {
+            model.upsert_node(id(proc_id), process(0), now);
+            model.upsert_edge(
+                Edge {
+                    from: id(proc_id),
+                    to: id(user),
+                    kind: EdgeKind::RunsAs,
+                },
+                now,
+            );
+        }
+        model.upsert_node(id("p:orphan"), process(0), now);
+        model.upsert_node(
+            id("f:log"),
+            Node::File {
+                path: "/var/log/syslog".to_string(),
+                inode: 1,
+                kind: FileKind::Regular,
+                category: FileCategory::Unknown,
+                link_target: None,
+            },
+            now,
+        );
+        model.upsert_edge(
+            Edge {
+                from: id("p:2"),
+                to: id("f:log"),
+                kind: EdgeKind::Opens {
+                    fd: 3,
+                    mode: "w".to_string(),
+                },
+            },
+            now,
+        );
+        let visible: HashSet<NodeId> = model.nodes.keys().cloned().collect();
+
+        assert_eq!(
+            user_clusters(&model, &visible),
+            vec![
+                (id("u:0"), vec![id("p:1")]),
+                (id("u:1000"), vec![id("p:2"), id("p:3")]),
+            ]
+        );
+
+        let pos = layout_user_clusters(&model, &visible, 25.0, 0.0);
+        let d = |a: &str, b: &str| pos[&id(a)].distance(pos[&id(b)]);
+        assert!(d("p:2", "u:1000") < d("p:2", "u:0"));
+        assert!(d("f:log", "p:2") < d("f:log", "u:0"));
+        assert!(!pos.contains_key(&id("p:orphan")));
+    }
+}
@@ -11,6 +11,14 @@ use bevy_egui::EguiPlugin;
 use crate::app::resources::{NetRx, NetTx};
 
 fn main() {
+    let args = match util::cli::parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("spacegraph-viewer: {err:#}");
+            eprintln!("usage: spacegraph-viewer [--connect <socket-path|tcp://host:port>]...");
+            std::process::exit(2);
+        }
+    };
     let (tx, rx) = crossbeam_channel::unbounded();
 
     App::new()
@@ -24,6 +32,6 @@ fn main() {
         .add_plugins(EguiPlugin)
         .insert_resource(NetRx(rx))
         .insert_resource(NetTx(tx))
-        .add_plugins(app::SpaceGraphViewerPlugin)
+        .add_plugins(app::SpaceGraphViewerPlugin { args })
         .run();
 }
@@ -2,4 +2,4 @@ pub mod protocol;
 pub mod uds;
 
 pub use protocol::{Incoming, IncomingKind};
-pub use uds::{spawn_reader, ReaderHandle};
+pub use uds::{spawn_reader, AgentAddr, MsgClass, MsgCounters, ReaderHandle};
@@ -1,9 +1,15 @@
 use spacegraph_core::Msg;
 
+use crate::net::MsgClass;
+
 #[derive(Debug, Clone)]
 pub struct Incoming {
     pub stream: String,
     pub kind: IncomingKind,
+    /// Wire size of the frame this message was decoded from.
+    pub bytes: usize,
+    /// Message type, set by the reader for frames off the wire.
+    pub class: Option<MsgClass>,
 }
 
 #[derive(Debug, Clone)]
@@ -16,12 +22,18 @@ pub enum IncomingKind {
     Event(Msg),
     Other(Msg),
     Error(String),
+    /// Variants from a newer protocol that were skipped while decoding a frame.
+    Unknown(Vec<String>),
+    /// No frame arrived within the heartbeat timeout; the reader has stopped.
+    HeartbeatTimeout,
 }
 
 impl Incoming {
     pub fn connected(stream: String) -> Self {
         Self {
             stream,
+            bytes: 0,
+            class: None,
             kind: IncomingKind::Connected,
         }
     }
@@ -29,6 +41,8 @@ impl Incoming {
     pub fn disconnected(stream: String) -> Self {
         Self {
             stream,
+            bytes: 0,
+            class: None,
             kind: IncomingKind::Disconnected,
         }
     }
@@ -36,6 +50,8 @@ impl Incoming {
     pub fn identity(stream: String, msg: Msg) -> Self {
         Self {
             stream,
+            bytes: 0,
+            class: None,
             kind: IncomingKind::Identity(msg),
         }
     }
@@ -43,6 +59,8 @@ impl Incoming {
     pub fn snapshot(stream: String, msg: Msg) -> Self {
         Self {
             stream,
+            bytes: 0,
+            class: None,
             kind: IncomingKind::Snapshot(msg),
         }
     }
@@ -50,6 +68,8 @@ impl Incoming {
     pub fn event(stream: String, msg: Msg) -> Self {
         Self {
             stream,
+            bytes: 0,
+            class: None,
             kind: IncomingKind::Event(msg),
         }
     }
@@ -57,6 +77,8 @@ impl Incoming {
     pub fn other(stream: String, msg: Msg) -> Self {
         Self {
             stream,
+            bytes: 0,
+            class: None,
             kind: IncomingKind::Other(msg),
         }
     }
@@ -64,7 +86,37 @@ impl Incoming {
     pub fn error(stream: String, msg: String) -> Self {
         Self {
             stream,
+            bytes: 0,
+            class: None,
             kind: IncomingKind::Error(msg),
         }
     }
+
+    pub fn unknown(stream: String, variants: Vec<String>) -> Self {
+        Self {
+            stream,
+            bytes: 0,
+            class: None,
+            kind: IncomingKind::Unknown(variants),
+        }
+    }
+
+    pub fn heartbeat_timeout(stream: String) -> Self {
+        Self {
+            stream,
+            bytes: 0,
+            class: None,
+            kind: IncomingKind::HeartbeatTimeout,
+        }
+    }
+
+    pub fn with_bytes(mut self, bytes: usize) -> Self {
+        self.bytes = bytes;
+        self
+    }
+
+    pub fn with_class(mut self, class: MsgClass) -> Self {
+        self.class = Some(class);
+        self
+    }
 }
@@ -1,68 +1,269 @@
 use crate::net::Incoming;
 use crossbeam_channel::Sender;
 use futures_util::{SinkExt, StreamExt};
-use spacegraph_core::Msg;
-use tokio::net::UnixStream;
-use tokio::sync::watch;
-use tokio_util::codec::{Framed, LengthDelimitedCodec};
+use spacegraph_core::frame::{decode_frame_lenient, encode_frame, MAX_FRAME_LENGTH};
+use spacegraph_core::{ControlCmd, Msg};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpStream, UnixStream};
+use tokio::sync::{mpsc, watch};
+use tokio_util::codec::{Framed, LengthDelimitedCodec, LengthDelimitedCodecError};
+
+/// How often the reader pings the agent to prove the connection is alive.
+pub const PING_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Coarse message type, counted per stream for the agents table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MsgClass {
+    Event,
+    /// Whole snapshots and every part of a chunked one.
+    Snapshot,
+    Identity,
+    /// Ping and pong.
+    Ping,
+    Other,
+}
+
+impl MsgClass {
+    pub const ALL: [Self; 5] = [
+        Self::Event,
+        Self::Snapshot,
+        Self::Identity,
+        Self::Ping,
+        Self::Other,
+    ];
+
+    pub fn of(msg: &Msg) -> Self {
+        match msg {
+            Msg::Event { .. } => Self::Event,
+            Msg::Snapshot { .. }
+            | Msg::SnapshotBegin { .. }
+            | Msg::SnapshotChunk { .. }
+            | Msg::SnapshotEnd => Self::Snapshot,
+            Msg::Identity { .. } => Self::Identity,
+            Msg::Ping | Msg::Pong => Self::Ping,
+            _ => Self::Other,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Event => "events",
+            Self::Snapshot => "snapshots",
+            Self::Identity => "identity",
+            Self::Ping => "pings",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Width of one [`MsgCounters`] rate bucket.
+const MSG_BUCKET: Duration = Duration::from_millis(250);
+
+/// Totals per [`MsgClass`] plus per-class counts in short buckets for rolling rates.
+#[derive(Debug, Clone, Default)]
+pub struct MsgCounters {
+    totals: [u64; MsgClass::ALL.len()],
+    /// Bucket start and per-class counts, oldest first.
+    buckets: VecDeque<(Instant, [u32; MsgClass::ALL.len()])>,
+}
+
+impl MsgCounters {
+    /// Counts one message and forgets buckets older than `window`.
+    pub fn record(&mut self, class: MsgClass, now: Instant, window: Duration) {
+        self.totals[class as usize] += 1;
+        match self.buckets.back_mut() {
+            Some((start, counts)) if now.duration_since(*start) < MSG_BUCKET => {
+                counts[class as usize] += 1;
+            }
+            _ => {
+                let mut counts = [0; MsgClass::ALL.len()];
+                counts[class as usize] = 1;
+                self.buckets.push_back((now, counts));
+            }
+        }
+        while let Some((start, _)) = self.buckets.front() {
+            if now.duration_since(*start) > window {
+                self.buckets.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Whether no message has been counted yet.
+    pub fn is_empty(&self) -> bool {
+        self.totals.iter().all(|total| *total == 0)
+    }
+
+    pub fn total(&self, class: MsgClass) -> u64 {
+        self.totals[class as usize]
+    }
+
+    /// Messages of `class` per second over the last `window`.
+    pub fn rate(&self, class: MsgClass, now: Instant, window: Duration) -> f32 {
+        let count: u32 = self
+            .buckets
+            .iter()
+            .filter(|(start, _)| now.duration_since(*start) <= window)
+            .map(|(_, counts)| counts[class as usize])
+            .sum();
+        count as f32 / window.as_secs_f32()
+    }
+
+    /// One `name: rate/s (total)` line per class that has been seen.
+    pub fn breakdown(&self, now: Instant, window: Duration) -> Vec<String> {
+        MsgClass::ALL
+            .into_iter()
+            .filter(|class| self.total(*class) > 0)
+            .map(|class| {
+                format!(
+                    "{}: {:.1}/s ({} total)",
+                    class.as_str(),
+                    self.rate(class, now, window),
+                    self.total(class)
+                )
+            })
+            .collect()
+    }
+}
+
+/// Where a reader connects to its agent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgentAddr {
+    /// Socket path for `UnixStream::connect`; a leading NUL is a Linux abstract address.
+    Uds(String),
+    /// `host:port`.
+    Tcp(String),
+}
+
+impl fmt::Display for AgentAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Uds(path) => match path.strip_prefix('\0') {
+                Some(name) => write!(f, "UDS @{name}"),
+                None => write!(f, "UDS {path}"),
+            },
+            Self::Tcp(addr) => write!(f, "TCP {addr}"),
+        }
+    }
+}
+
+/// Byte stream to an agent, whichever transport it came from.
+trait AgentStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> AgentStream for T {}
+
+async fn connect(addr: &AgentAddr) -> io::Result<Box<dyn AgentStream>> {
+    match addr {
+        AgentAddr::Uds(path) => Ok(Box::new(UnixStream::connect(path).await?)),
+        AgentAddr::Tcp(addr) => {
+            let stream = TcpStream::connect(addr).await?;
+            stream.set_nodelay(true)?;
+            Ok(Box::new(stream))
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct ReaderHandle {
     shutdown: watch::Sender<bool>,
+    control: mpsc::UnboundedSender<ControlCmd>,
 }
 
 impl ReaderHandle {
     pub fn shutdown(&self) {
         let _ = self.shutdown.send(true);
     }
+
+    /// Queues `cmd` for the agent; false once the connection is gone.
+    pub fn send_control(&self, cmd: ControlCmd) -> bool {
+        self.control.send(cmd).is_ok()
+    }
 }
 
-pub fn spawn_reader(stream_name: String, sock_path: String, tx: Sender<Incoming>) -> ReaderHandle {
+pub fn spawn_reader(
+    stream_name: String,
+    addr: AgentAddr,
+    compression: bool,
+    token: Option<String>,
+    resume: Option<(u64, u64)>,
+    heartbeat_timeout: Duration,
+    tx: Sender<Incoming>,
+) -> ReaderHandle {
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let (control_tx, control_rx) = mpsc::unbounded_channel();
     std::thread::spawn(move || {
         let rt = tokio::runtime::Runtime::new().expect("tokio runtime");
         rt.block_on(async move {
-            run(stream_name, sock_path, tx.clone(), shutdown_rx).await;
+            run(
+                stream_name,
+                addr,
+                compression,
+                token,
+                resume,
+                heartbeat_timeout,
+                tx.clone(),
+                shutdown_rx,
+                control_rx,
+            )
+            .await;
         });
     });
 
     ReaderHandle {
         shutdown: shutdown_tx,
+        control: control_tx,
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run(
     stream_name: String,
-    sock_path: String,
+    addr: AgentAddr,
+    compression: bool,
+    token: Option<String>,
+    resume: Option<(u64, u64)>,
+    heartbeat_timeout: Duration,
     tx: Sender<Incoming>,
     mut shutdown: watch::Receiver<bool>,
+    mut control: mpsc::UnboundedReceiver<ControlCmd>,
 ) {
     let stream = match tokio::select! {
         _ = shutdown.changed() => {
             return;
         }
-        res = UnixStream::connect(&sock_path) => res,
+        res = connect(&addr) => res,
     } {
         Ok(stream) => stream,
         Err(err) => {
             let _ = tx.send(Incoming::error(
                 stream_name.clone(),
-                format!("connect UDS {sock_path}: {err}"),
+                format!("connect {addr}: {err}"),
             ));
             let _ = tx.send(Incoming::disconnected(stream_name.clone()));
             return;
         }
     };
 
-    let mut framed = Framed::new(stream, LengthDelimitedCodec::new());
+    let mut framed = Framed::new(stream, frame_codec());
 
     let _ = tx.send(Incoming::connected(stream_name.clone()));
 
-    // Send hello (agent tolerates anything)
+    // Send hello (required when the agent was started with a token). A resume point lets
+    // the agent send only the events missed since the last connection.
     let hello = Msg::Hello {
         version: "0.1.0".into(),
+        compression,
+        snapshot_chunks: true,
+        token,
+        session: resume.map(|(session, _)| session),
+        resume_after_batch: resume.map(|(_, batch)| batch),
     };
-    let hello_bytes = match serde_json::to_vec(&hello) {
+    let hello_bytes = match encode_frame(&hello, false) {
         Ok(bytes) => bytes,
         Err(err) => {
             let _ = tx.send(Incoming::error(
@@ -82,23 +283,62 @@ async fn run(
         return;
     }
 
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    let mut last_frame = Instant::now();
+    let mut timed_out = false;
     loop {
         tokio::select! {
+            // A requested shutdown is not a drop: no event, so nothing schedules a reconnect.
             _ = shutdown.changed() => {
-                break;
+                return;
+            }
+            _ = ping.tick() => {
+                if last_frame.elapsed() > heartbeat_timeout {
+                    timed_out = true;
+                    break;
+                }
+                let Ok(bytes) = encode_frame(&Msg::Ping, false) else {
+                    continue;
+                };
+                if framed.send(bytes.into()).await.is_err() {
+                    break;
+                }
+            }
+            Some(cmd) = control.recv() => {
+                let Ok(bytes) = encode_frame(&Msg::Control { cmd }, false) else {
+                    continue;
+                };
+                if framed.send(bytes.into()).await.is_err() {
+                    break;
+                }
             }
             frame = framed.next() => {
                 match frame {
                     Some(Ok(bytes)) => {
-                        match serde_json::from_slice::<Msg>(&bytes) {
-                            Ok(m) => {
+                        last_frame = Instant::now();
+                        match decode_frame_lenient(&bytes) {
+                            Ok(decoded) => {
+                                if !decoded.unknown.is_empty() {
+                                    let _ = tx.send(Incoming::unknown(stream_name.clone(), decoded.unknown));
+                                }
+                                let Some(m) = decoded.msg else {
+                                    continue;
+                                };
+                                let class = MsgClass::of(&m);
                                 let inc = match &m {
                                     Msg::Identity { .. } => Incoming::identity(stream_name.clone(), m),
-                                    Msg::Snapshot { .. } => Incoming::snapshot(stream_name.clone(), m),
+                                    Msg::Snapshot { .. }
+                                    | Msg::SnapshotBegin { .. }
+                                    | Msg::SnapshotChunk { .. }
+                                    | Msg::SnapshotEnd => Incoming::snapshot(stream_name.clone(), m),
                                     Msg::Event { .. } => Incoming::event(stream_name.clone(), m),
+                                    Msg::Error { message } => Incoming::error(
+                                        stream_name.clone(),
+                                        format!("agent refused connection: {message}"),
+                                    ),
                                     _ => Incoming::other(stream_name.clone(), m),
                                 };
-                                let _ = tx.send(inc);
+                                let _ = tx.send(inc.with_bytes(bytes.len()).with_class(class));
                             }
                             Err(e) => {
                                 let _ = tx.send(Incoming::error(
@@ -111,7 +351,7 @@ async fn run(
                     Some(Err(err)) => {
                         let _ = tx.send(Incoming::error(
                             stream_name.clone(),
-                            format!("stream error: {err}"),
+                            stream_error_message(&err),
                         ));
                         break;
                     }
@@ -123,5 +363,192 @@ async fn run(
         }
     }
 
-    let _ = tx.send(Incoming::disconnected(stream_name.clone()));
+    // A timeout implies disconnection; sending both would race a reconnect.
+    if timed_out {
+        let _ = tx.send(Incoming::heartbeat_timeout(stream_name.clone()));
+    } else {
+        let _ = tx.send(Incoming::disconnected(stream_name.clone()));
+    }
+}
+
+fn frame_codec() -> LengthDelimitedCodec {
+    LengthDelimitedCodec::builder()
+        .max_frame_length(MAX_FRAME_LENGTH)
+        .new_codec()
+}
+
+fn stream_error_message(err: &io::Error) -> String {
+    let oversized = err
+        .get_ref()
+        .is_some_and(|inner| inner.is::<LengthDelimitedCodecError>());
+    if oversized {
+        format!("frame exceeds max length ({MAX_FRAME_LENGTH} bytes)")
+    } else {
+        format!("stream error: {err}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_util::bytes::BytesMut;
+    use tokio_util::codec::Decoder;
+
+    #[test]
+    fn msg_counters_split_a_mixed_stream_by_type() {
+        let window = Duration::from_secs(2);
+        let start = Instant::now();
+        let later = start + Duration::from_secs(3);
+        let event = Msg::Event {
+            delta: spacegraph_core::Delta::BatchBegin { id: 1 },
+        };
+        let mut counters = MsgCounters::default();
+        for msg in [
+            &Msg::SnapshotBegin {
+                total_nodes: 0,
+                total_edges: 0,
+            },
+            &Msg::SnapshotEnd,
+            &Msg::Pong,
+        ] {
+            counters.record(MsgClass::of(msg), start, window);
+        }
+        for _ in 0..4 {
+            counters.record(MsgClass::of(&event), later, window);
+        }
+        counters.record(MsgClass::of(&Msg::Pong), later, window);
+
+        assert_eq!(counters.total(MsgClass::Snapshot), 2);
+        assert_eq!(counters.total(MsgClass::Event), 4);
+        assert_eq!(counters.total(MsgClass::Ping), 2);
+        assert_eq!(counters.total(MsgClass::Identity), 0);
+        // the early snapshot and pong dropped out of the window
+        assert_eq!(counters.rate(MsgClass::Snapshot, later, window), 0.0);
+        assert_eq!(counters.rate(MsgClass::Event, later, window), 2.0);
+        assert_eq!(counters.rate(MsgClass::Ping, later, window), 0.5);
+        assert_eq!(
+            counters.breakdown(later, window),
+            vec![
+                "events: 2.0/s (4 total)",
+                "snapshots: 0.0/s (2 total)",
+                "pings: 0.5/s (2 total)"
+            ]
+        );
+    }
+
+    #[test]
+    fn msg_counters_keep_a_bounded_number_of_buckets() {
+        let window = Duration::from_secs(2);
+        let start = Instant::now();
+        let mut counters = MsgCounters::default();
+        for i in 0..10_000u64 {
+            let now = start + Duration::from_millis(i);
+            counters.record(MsgClass::Event, now, window);
+        }
+
+        let buckets = (window.as_millis() / MSG_BUCKET.as_millis()) as usize + 1;
+        assert!(counters.buckets.len() <= buckets);
+        assert_eq!(counters.total(MsgClass::Event), 10_000);
+        let end = start + Duration::from_millis(9_999);
+        assert!((counters.rate(MsgClass::Event, end, window) - 1000.0).abs() < 150.0);
+        assert_eq!(counters.rate(MsgClass::Ping, end, window), 0.0);
+    }
+
+    #[test]
+    fn msg_counters_start_empty() {
+        let mut counters = MsgCounters::default();
+        assert!(counters.is_empty());
+        assert!(counters
+            .breakdown(Instant::now(), Duration::from_secs(2))
+            .is_empty());
+
+        counters.record(MsgClass::Ping, Instant::now(), Duration::from_secs(2));
+        assert!(!counters.is_empty());
+    }
+
+    fn spawn_test_reader(addr: AgentAddr) -> (ReaderHandle, crossbeam_channel::Receiver<Incoming>) {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let handle = spawn_reader(
+            "agent".to_string(),
+            addr,
+            false,
+            None,
+            None,
+            Duration::from_secs(30),
+            tx,
+        );
+        (handle, rx)
+    }
+
+    #[test]
+    fn tcp_reader_connects_and_sends_hello() {
+        use std::io::Read;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = AgentAddr::Tcp(listener.local_addr().unwrap().to_string());
+        let (handle, rx) = spawn_test_reader(addr);
+
+        let (mut conn, _) = listener.accept().expect("accept");
+        let mut len = [0u8; 4];
+        conn.read_exact(&mut len).expect("frame length");
+        let mut frame = vec![0u8; u32::from_be_bytes(len) as usize];
+        conn.read_exact(&mut frame).expect("frame body");
+        let hello = decode_frame_lenient(&frame).expect("decode").msg;
+        assert!(matches!(hello, Some(Msg::Hello { .. })), "{hello:?}");
+
+        let first = rx.recv_timeout(Duration::from_secs(5)).expect("incoming");
+        assert!(matches!(first.kind, crate::net::IncomingKind::Connected));
+        handle.shutdown();
+    }
+
+    #[test]
+    fn tcp_reader_reports_a_refused_connection() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().unwrap().to_string();
+        drop(listener);
+        let (_handle, rx) = spawn_test_reader(AgentAddr::Tcp(addr.clone()));
+
+        let first = rx.recv_timeout(Duration::from_secs(5)).expect("incoming");
+        match first.kind {
+            crate::net::IncomingKind::Error(message) => {
+                assert!(
+                    message.starts_with(&format!("connect TCP {addr}:")),
+                    "{message}"
+                );
+            }
+            other => panic!("expected a connect error, got {other:?}"),
+        }
+        let second = rx.recv_timeout(Duration::from_secs(5)).expect("incoming");
+        assert!(matches!(
+            second.kind,
+            crate::net::IncomingKind::Disconnected
+        ));
+    }
+
+    #[test]
+    fn abstract_addresses_display_with_an_at_sign() {
+        assert_eq!(AgentAddr::Uds("\0sg".to_string()).to_string(), "UDS @sg");
+        assert_eq!(
+            AgentAddr::Uds("/tmp/a.sock".to_string()).to_string(),
+            "UDS /tmp/a.sock"
+        );
+    }
+
+    #[test]
+    fn oversized_length_prefix_reports_max_length_error() {
+        let mut codec = frame_codec();
+        let prefix = (MAX_FRAME_LENGTH as u32 + 1).to_be_bytes();
+        let mut buf = BytesMut::from(&prefix[..]);
+        let err = codec.decode(&mut buf).expect_err("oversized frame");
+        assert_eq!(
+            stream_error_message(&err),
+            format!("frame exceeds max length ({MAX_FRAME_LENGTH} bytes)")
+        );
+    }
+
+    #[test]
+    fn other_io_errors_stay_generic() {
+        let err = io::Error::from(io::ErrorKind::BrokenPipe);
+        assert!(stream_error_message(&err).starts_with("stream error:"));
+    }
 }
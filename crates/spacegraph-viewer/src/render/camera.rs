@@ -1,8 +1,74 @@
+use bevy::core_pipeline::bloom::BloomSettings;
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
 use bevy::prelude::*;
+use bevy_egui::EguiContexts;
 use std::sync::atomic::Ordering;
 
 use crate::graph::{GraphState, ViewMode};
 
+const ORBIT_RADIANS_PER_PIXEL: f32 = 0.005;
+const PAN_UNITS_PER_PIXEL: f32 = 0.0015;
+const DOLLY_PER_LINE: f32 = 0.1;
+const PITCH_LIMIT: f32 = 1.54;
+const MIN_DISTANCE: f32 = 1.0;
+const MAX_DISTANCE: f32 = 2000.0;
+
+/// Orbit camera state; the camera transform is derived from it every frame.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CameraRig {
+    pub target: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+}
+
+impl CameraRig {
+    pub fn from_eye(eye: Vec3, target: Vec3) -> Self {
+        let offset = eye - target;
+        let distance = offset.length().clamp(MIN_DISTANCE, MAX_DISTANCE);
+        let yaw = offset.x.atan2(offset.z);
+        let pitch = (offset.y / offset.length().max(f32::EPSILON))
+            .clamp(-1.0, 1.0)
+            .asin()
+            .clamp(-PITCH_LIMIT, PITCH_LIMIT);
+        Self {
+            target,
+            yaw,
+            pitch,
+            distance,
+        }
+    }
+
+    pub fn eye(&self) -> Vec3 {
+        let (sy, cy) = self.yaw.sin_cos();
+        let (sp, cp) = self.pitch.sin_cos();
+        self.target + Vec3::new(sy * cp, sp, cy * cp) * self.distance
+    }
+
+    pub fn orbit(&mut self, delta: Vec2) {
+        self.yaw -= delta.x * ORBIT_RADIANS_PER_PIXEL;
+        self.pitch =
+            (self.pitch + delta.y * ORBIT_RADIANS_PER_PIXEL).clamp(-PITCH_LIMIT, PITCH_LIMIT);
+    }
+
+    pub fn pan(&mut self, delta: Vec2) {
+        let forward = (self.target - self.eye()).normalize_or_zero();
+        let right = forward.cross(Vec3::Y).normalize_or_zero();
+        let up = right.cross(forward).normalize_or_zero();
+        let scale = self.distance * PAN_UNITS_PER_PIXEL;
+        self.target += (-right * delta.x + up * delta.y) * scale;
+    }
+
+    pub fn dolly(&mut self, lines: f32) {
+        let factor = (1.0 - lines * DOLLY_PER_LINE).clamp(0.5, 1.5);
+        self.distance = (self.distance * factor).clamp(MIN_DISTANCE, MAX_DISTANCE);
+    }
+
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(self.eye()).looking_at(self.target, Vec3::Y)
+    }
+}
+
 pub fn setup_scene(mut commands: Commands) {
     commands.spawn(PointLightBundle {
         point_light: PointLight {
@@ -14,13 +80,105 @@ pub fn setup_scene(mut commands: Commands) {
         ..default()
     });
 
-    commands.spawn(Camera3dBundle {
-        transform: Transform::from_xyz(0.0, 18.0, 28.0).looking_at(Vec3::ZERO, Vec3::Y),
-        ..default()
-    });
+    // Bloom needs an HDR target; `sync_bloom` keeps both in line with the config.
+    let rig = CameraRig::from_eye(Vec3::new(0.0, 18.0, 28.0), Vec3::ZERO);
+    commands.spawn((
+        Camera3dBundle {
+            camera: Camera {
+                hdr: true,
+                ..default()
+            },
+            transform: rig.transform(),
+            ..default()
+        },
+        BloomSettings::NATURAL,
+        rig,
+    ));
+}
+
+/// Bloom settings for a configured intensity; `None` disables the pass.
+pub(crate) fn bloom_settings(intensity: f32) -> Option<BloomSettings> {
+    (intensity.is_finite() && intensity > 0.0).then(|| BloomSettings {
+        intensity: intensity.min(1.0),
+        ..BloomSettings::NATURAL
+    })
+}
+
+/// Applies `bloom_intensity` to the camera. With bloom off the camera drops back to an
+/// LDR target, so setups without working HDR still render normally.
+pub fn sync_bloom(
+    mut commands: Commands,
+    st: Res<GraphState>,
+    mut cam_q: Query<(Entity, &mut Camera, Option<&mut BloomSettings>), With<CameraRig>>,
+) {
+    let wanted = bloom_settings(st.cfg.bloom_intensity);
+    for (entity, mut camera, bloom) in cam_q.iter_mut() {
+        if camera.hdr != wanted.is_some() {
+            camera.hdr = wanted.is_some();
+        }
+        match (wanted.as_ref(), bloom) {
+            (Some(w), Some(mut b)) if b.intensity != w.intensity => b.intensity = w.intensity,
+            (Some(_), Some(_)) => {}
+            (Some(w), None) => {
+                commands.entity(entity).insert(w.clone());
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<BloomSettings>();
+            }
+            (None, None) => {}
+        }
+    }
 }
 
-pub fn apply_jump_to(mut st: ResMut<GraphState>, mut cam_q: Query<&mut Transform, With<Camera>>) {
+// Right drag = orbit, middle drag = pan, wheel = dolly.
+pub fn camera_controls(
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut wheel: EventReader<MouseWheel>,
+    mut contexts: EguiContexts,
+    st: Res<GraphState>,
+    mut cam_q: Query<(&mut Transform, &mut CameraRig), With<Camera>>,
+) {
+    let drag: Vec2 = motion.read().map(|ev| ev.delta).sum();
+    let scroll: f32 = wheel
+        .read()
+        .map(|ev| match ev.unit {
+            MouseScrollUnit::Line => ev.y,
+            MouseScrollUnit::Pixel => ev.y * 0.01,
+        })
+        .sum();
+
+    if contexts.ctx_mut().wants_pointer_input() {
+        return;
+    }
+    let Ok((mut cam_tf, mut rig)) = cam_q.get_single_mut() else {
+        return;
+    };
+
+    let sensitivity = st.cfg.camera_sensitivity.max(0.01);
+    let mut changed = false;
+    if drag != Vec2::ZERO {
+        if buttons.pressed(MouseButton::Right) {
+            rig.orbit(drag * sensitivity);
+            changed = true;
+        } else if buttons.pressed(MouseButton::Middle) {
+            rig.pan(drag * sensitivity);
+            changed = true;
+        }
+    }
+    if scroll != 0.0 {
+        rig.dolly(scroll * sensitivity);
+        changed = true;
+    }
+    if changed {
+        *cam_tf = rig.transform();
+    }
+}
+
+pub fn apply_jump_to(
+    mut st: ResMut<GraphState>,
+    mut cam_q: Query<(&mut Transform, &mut CameraRig), With<Camera>>,
+) {
     if st.ui.fit_to_view {
         st.ui.fit_to_view = false;
         if st.ui.view_mode == ViewMode::Tree {
@@ -40,15 +198,15 @@ pub fn apply_jump_to(mut st: ResMut<GraphState>, mut cam_q: Query<&mut Transform
             }
 
             if min.x.is_finite() {
-                let Ok(mut cam_tf) = cam_q.get_single_mut() else {
+                let Ok((mut cam_tf, mut rig)) = cam_q.get_single_mut() else {
                     return;
                 };
                 let center = (min + max) * 0.5;
                 let extent = (max.x - min.x).max(max.y - min.y).max(1.0);
                 let dist = extent.max(6.0);
                 let offset = Vec3::new(dist * 0.6, dist * 0.5, dist * 0.9);
-                cam_tf.translation = center + offset;
-                cam_tf.look_at(center, Vec3::Y);
+                *rig = CameraRig::from_eye(center + offset, center);
+                *cam_tf = rig.transform();
             }
         }
     }
@@ -72,16 +230,14 @@ pub fn apply_jump_to(mut st: ResMut<GraphState>, mut cam_q: Query<&mut Transform
     st.ui.selected = Some(id);
     st.needs_redraw.store(true, Ordering::Relaxed);
 
-    let Ok(mut cam_tf) = cam_q.get_single_mut() else {
+    let Ok((mut cam_tf, mut rig)) = cam_q.get_single_mut() else {
         return;
     };
 
-    let current = cam_tf.translation;
-    let dist = (current - target).length().max(6.0);
-    let offset = Vec3::new(dist * 0.6, dist * 0.5, dist * 0.9);
-
-    cam_tf.translation = target + offset;
-    cam_tf.look_at(target, Vec3::Y);
+    // Keep the current viewing angle and distance; only re-center the orbit target.
+    rig.target = target;
+    rig.distance = rig.distance.max(6.0);
+    *cam_tf = rig.transform();
 }
 
 pub fn update_tree_zoom(cam_q: Query<&Transform, With<Camera>>, mut st: ResMut<GraphState>) {
@@ -94,3 +250,41 @@ pub fn update_tree_zoom(cam_q: Query<&Transform, With<Camera>>, mut st: ResMut<G
     let dist = cam_tf.translation.distance(st.ui.tree_center).max(1.0);
     st.ui.tree_zoom = 1.0 / dist;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rig_roundtrips_eye_position() {
+        let eye = Vec3::new(0.0, 18.0, 28.0);
+        let rig = CameraRig::from_eye(eye, Vec3::ZERO);
+        assert!(rig.eye().distance(eye) < 1e-3);
+    }
+
+    #[test]
+    fn orbit_clamps_pitch() {
+        let mut rig = CameraRig::from_eye(Vec3::new(0.0, 5.0, 10.0), Vec3::ZERO);
+        rig.orbit(Vec2::new(0.0, 100_000.0));
+        assert!(rig.pitch <= PITCH_LIMIT);
+        rig.orbit(Vec2::new(0.0, -200_000.0));
+        assert!(rig.pitch >= -PITCH_LIMIT);
+    }
+
+    #[test]
+    fn dolly_stays_within_bounds() {
+        let mut rig = CameraRig::from_eye(Vec3::new(0.0, 0.0, 2.0), Vec3::ZERO);
+        for _ in 0..100 {
+            rig.dolly(10.0);
+        }
+        assert!(rig.distance >= MIN_DISTANCE);
+    }
+
+    #[test]
+    fn bloom_is_disabled_at_zero_and_clamped() {
+        assert!(bloom_settings(0.0).is_none());
+        assert!(bloom_settings(f32::NAN).is_none());
+        assert_eq!(bloom_settings(0.3).map(|b| b.intensity), Some(0.3));
+        assert_eq!(bloom_settings(5.0).map(|b| b.intensity), Some(1.0));
+    }
+}
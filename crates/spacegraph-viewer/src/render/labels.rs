@@ -0,0 +1,181 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::graph::{GraphState, ViewMode};
+use crate::util::config::LabelMode;
+use crate::util::ids::node_label;
+
+/// Longer labels are cut so a deep path doesn't cover half the scene.
+const LABEL_MAX_CHARS: usize = 48;
+
+/// What the nearest-node pick depends on besides the graph itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LabelKey {
+    redraw_seq: u64,
+    eye: Vec3,
+    mode: LabelMode,
+    max_count: usize,
+    max_distance: f32,
+}
+
+/// Labels of the nodes closest to the camera, picked again only after a redraw or a
+/// camera move; painting them each frame just projects the cached positions.
+#[derive(Default)]
+pub(crate) struct LabelCache {
+    key: Option<LabelKey>,
+    labels: Vec<(Vec3, String)>,
+}
+
+impl LabelCache {
+    /// Returns true when the labels were picked again.
+    pub(crate) fn refresh(&mut self, st: &mut GraphState, eye: Vec3) -> bool {
+        let key = LabelKey {
+            redraw_seq: st.spatial.redraw_seq,
+            eye,
+            mode: st.cfg.label_mode,
+            max_count: st.cfg.label_max_count,
+            max_distance: st.cfg.label_max_distance,
+        };
+        if self.key == Some(key) {
+            return false;
+        }
+        self.key = Some(key);
+
+        let max_dist_sq = key.max_distance * key.max_distance;
+        let vis = st.visible_set_capped();
+        let mut near: Vec<_> = vis
+            .iter()
+            .filter(|id| !st.spatial.collapsed.members.contains_key(*id))
+            .filter_map(|id| {
+                let pos = *st.spatial.positions.get(id)?;
+                let d = pos.distance_squared(eye);
+                (d <= max_dist_sq).then_some((d, id, pos))
+            })
+            .collect();
+        near.sort_by(|a, b| a.0.total_cmp(&b.0));
+        near.truncate(key.max_count);
+
+        self.labels.clear();
+        for (_, id, pos) in near {
+            let Some(text) = st
+                .model
+                .nodes
+                .get(id)
+                .and_then(|node| node_label(node, key.mode))
+            else {
+                continue;
+            };
+            let text = if text.chars().count() > LABEL_MAX_CHARS {
+                let cut: String = text.chars().take(LABEL_MAX_CHARS - 1).collect();
+                format!("{cut}…")
+            } else {
+                text
+            };
+            self.labels.push((pos, text));
+        }
+        true
+    }
+}
+
+/// Text labels for the nodes closest to the camera, painted behind the UI panels.
+pub fn node_labels(
+    mut contexts: EguiContexts,
+    mut st: ResMut<GraphState>,
+    cam_q: Query<(&Camera, &GlobalTransform)>,
+    mut cache: Local<LabelCache>,
+) {
+    if st.cfg.label_mode == LabelMode::Hidden
+        || st.cfg.label_max_count == 0
+        || st.ui.view_mode == ViewMode::Timeline
+    {
+        return;
+    }
+    let Ok((camera, cam_tf)) = cam_q.get_single() else {
+        return;
+    };
+    cache.refresh(&mut st, cam_tf.translation());
+
+    let ctx = contexts.ctx_mut();
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("node_labels"),
+    ));
+    let color = ctx.style().visuals.text_color();
+    for (pos, text) in &cache.labels {
+        let Some(screen) = camera.world_to_viewport(cam_tf, *pos) else {
+            continue;
+        };
+        painter.text(
+            egui::pos2(screen.x + 10.0, screen.y - 10.0),
+            egui::Align2::LEFT_BOTTOM,
+            text,
+            egui::FontId::proportional(12.0),
+            color,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::{FileCategory, FileKind, Node, NodeId};
+    use std::time::Instant;
+
+    fn file(path: &str) -> Node {
+        Node::File {
+            path: path.to_string(),
+            inode: 0,
+            kind: FileKind::Regular,
+            category: FileCategory::Log,
+            link_target: None,
+        }
+    }
+
+    fn state() -> GraphState {
+        let mut st = GraphState::default();
+        let now = Instant::now();
+        for (path, x) in [("/srv/near.log", 1.0), ("/srv/far.log", 30.0)] {
+            let id = NodeId(path.to_string());
+            st.model.upsert_node(id.clone(), file(path), now);
+            st.spatial.positions.insert(id, Vec3::new(x, 0.0, 0.0));
+        }
+        st.cfg.label_max_count = 1;
+        st
+    }
+
+    #[test]
+    fn labels_are_picked_again_after_a_redraw_or_camera_move() {
+        let mut st = state();
+        let mut cache = LabelCache::default();
+        assert!(cache.refresh(&mut st, Vec3::ZERO));
+        assert_eq!(
+            cache.labels,
+            [(Vec3::new(1.0, 0.0, 0.0), "near.log".to_string())]
+        );
+
+        assert!(cache.refresh(&mut st, Vec3::new(30.0, 0.0, 0.0)));
+        assert_eq!(cache.labels[0].1, "far.log");
+
+        st.spatial.positions.insert(
+            NodeId("/srv/far.log".to_string()),
+            Vec3::new(90.0, 0.0, 0.0),
+        );
+        st.spatial.redraw_seq += 1;
+        assert!(cache.refresh(&mut st, Vec3::new(30.0, 0.0, 0.0)));
+        assert_eq!(cache.labels[0].1, "near.log");
+    }
+
+    #[test]
+    fn labels_are_reused_while_nothing_changed() {
+        let mut st = state();
+        let mut cache = LabelCache::default();
+        assert!(cache.refresh(&mut st, Vec3::ZERO));
+        // Without a redraw the moved node keeps its cached label position.
+        st.spatial.positions.insert(
+            NodeId("/srv/near.log".to_string()),
+            Vec3::new(5.0, 0.0, 0.0),
+        );
+        assert!(!cache.refresh(&mut st, Vec3::ZERO));
+        assert_eq!(cache.labels[0].0, Vec3::new(1.0, 0.0, 0.0));
+    }
+}
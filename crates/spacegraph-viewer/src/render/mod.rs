@@ -1,5 +1,8 @@
 pub mod camera;
+pub mod labels;
+pub mod scene;
 pub mod spatial;
+pub mod theme;
 pub mod timeline;
 
 use bevy::prelude::*;
@@ -8,16 +11,24 @@ use bevy_egui::EguiContexts;
 use crate::graph::{GraphState, ViewMode};
 use crate::ui::UiLayout;
 
-pub use camera::{apply_jump_to, setup_scene, update_tree_zoom};
-pub use spatial::{apply_picked_focus, draw_spatial, hover_detection_spatial, picking_focus};
+pub use camera::{apply_jump_to, camera_controls, setup_scene, sync_bloom, update_tree_zoom};
+pub use labels::node_labels;
+pub use scene::sync_ambient;
+pub use spatial::{
+    animate_glow, apply_picked_focus, draw_spatial, hover_detection_spatial, picking_focus,
+    NodeMaterials,
+};
+pub use theme::sync_theme;
 pub use timeline::draw_timeline;
 
 #[allow(clippy::too_many_arguments)]
 pub fn draw_scene(
+    time: Res<Time>,
     commands: Commands,
     st: ResMut<GraphState>,
     meshes: ResMut<Assets<Mesh>>,
     mats: ResMut<Assets<StandardMaterial>>,
+    materials: ResMut<spatial::NodeMaterials>,
     query: Query<(Entity, &spatial::NodeMarker)>,
     gizmos: Gizmos,
     contexts: EguiContexts,
@@ -27,9 +38,9 @@ pub fn draw_scene(
     cam_q: Query<(&Camera, &GlobalTransform)>,
 ) {
     match st.ui.view_mode {
-        ViewMode::Spatial | ViewMode::Tree => {
-            draw_spatial(commands, st, meshes, mats, query, gizmos, contexts)
-        }
+        ViewMode::Spatial | ViewMode::Tree => draw_spatial(
+            time, commands, st, meshes, mats, materials, query, gizmos, contexts,
+        ),
         ViewMode::Timeline => draw_timeline(st, gizmos, contexts, layout, windows, buttons, cam_q),
     }
 }
@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+
+use crate::graph::GraphState;
+use crate::render::theme::Palette;
+
+/// Grid lines per axis at most; coarser spacing is used beyond that.
+const GRID_MAX_LINES: usize = 201;
+const GRID_ALPHA: f32 = 0.25;
+
+/// Line segments of a square grid in the XZ plane at height `y`, centered on the origin
+/// and snapped to `spacing`. Both directions get the same number of lines.
+pub(crate) fn grid_lines(spacing: f32, extent: f32, y: f32) -> Vec<(Vec3, Vec3)> {
+    if !(spacing > 0.0 && extent >= 0.0 && spacing.is_finite() && extent.is_finite()) {
+        return Vec::new();
+    }
+    let half = ((extent / spacing).floor() as usize).min(GRID_MAX_LINES / 2);
+    let edge = half as f32 * spacing;
+    let mut lines = Vec::with_capacity((2 * half + 1) * 2);
+    for i in 0..=2 * half {
+        let offset = i as f32 * spacing - edge;
+        lines.push((Vec3::new(offset, y, -edge), Vec3::new(offset, y, edge)));
+        lines.push((Vec3::new(-edge, y, offset), Vec3::new(edge, y, offset)));
+    }
+    lines
+}
+
+/// Draws the reference grid one spacing below the layout floor. Gizmos are never picked,
+/// so the grid stays out of hover and click handling.
+pub(crate) fn draw_grid(gizmos: &mut Gizmos, st: &GraphState, palette: &Palette) {
+    if !st.cfg.grid_enabled {
+        return;
+    }
+    let [r, g, b] = palette.lane_guide;
+    let color = Color::srgba(r, g, b, GRID_ALPHA);
+    for (a, b) in grid_lines(
+        st.cfg.grid_spacing,
+        st.cfg.grid_extent,
+        st.spatial.layout_floor - st.cfg.grid_spacing,
+    ) {
+        gizmos.line(a, b, color);
+    }
+}
+
+/// Keeps the ambient light in line with `ambient_brightness`.
+pub fn sync_ambient(st: Res<GraphState>, mut ambient: ResMut<AmbientLight>) {
+    if ambient.brightness != st.cfg.ambient_brightness {
+        ambient.brightness = st.cfg.ambient_brightness;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_lines_cover_the_extent_in_both_directions() {
+        let lines = grid_lines(5.0, 12.0, -3.0);
+        // 12 / 5 -> two lines either side of the center line, per direction
+        assert_eq!(lines.len(), 10);
+        for (a, b) in &lines {
+            assert_eq!(a.y, -3.0);
+            assert_eq!(b.y, -3.0);
+            assert_eq!(a.distance(*b), 20.0);
+            assert!(a.x.abs() <= 10.0 && a.z.abs() <= 10.0);
+        }
+        assert!(lines.contains(&(Vec3::new(-10.0, -3.0, -10.0), Vec3::new(-10.0, -3.0, 10.0))));
+        assert!(lines.contains(&(Vec3::new(-10.0, -3.0, 10.0), Vec3::new(10.0, -3.0, 10.0))));
+    }
+
+    #[test]
+    fn grid_lines_are_capped_and_reject_bad_spacing() {
+        assert_eq!(grid_lines(0.01, 1000.0, 0.0).len(), GRID_MAX_LINES * 2);
+        assert!(grid_lines(0.0, 10.0, 0.0).is_empty());
+        assert!(grid_lines(f32::NAN, 10.0, 0.0).is_empty());
+        assert_eq!(grid_lines(5.0, 0.0, 0.0).len(), 2);
+    }
+}
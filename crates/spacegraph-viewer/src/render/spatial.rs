@@ -1,17 +1,308 @@
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
-use std::collections::HashSet;
+use spacegraph_core::{Edge, FileCategory, FileKind, Node, NodeId};
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
 
-use crate::app::events::Picked;
-use crate::graph::model::{edge_class_name, AggEdgeKey};
+use crate::app::events::{Picked, PickedEdge};
+use crate::graph::collapse::{reroute, CollapsedView, PlaceholderKind};
+use crate::graph::diff::DiffStatus;
+use crate::graph::explain::PathStep;
+use crate::graph::model::{agg_edge_width, edge_class_name, AggEdgeKey};
+use crate::graph::state::NODE_RADIUS;
 use crate::graph::{GraphState, ViewMode};
+use crate::render::scene::draw_grid;
+use crate::render::theme::{self, rgb};
 use crate::ui::tooltips::render_tooltip;
 use crate::util::config::LodEdgesMode;
+use crate::util::ids::{category_color, host_color, node_label_long, HOST_PALETTE};
 
 #[derive(Component)]
 pub struct NodeMarker;
 
+/// Steps the glow emissive fades through; materials are only touched on a step change.
+const GLOW_LEVELS: usize = 8;
+
+/// Node materials kept across redraws.
+#[derive(Resource, Default)]
+pub struct NodeMaterials {
+    /// Own material per glowing node with its current glow step; [`animate_glow`] fades
+    /// the emissive in place, so the glow needs no redraw until it ends.
+    glow: HashMap<NodeId, (Handle<StandardMaterial>, usize)>,
+    /// Cool-to-hot steps for "color processes by open files", created on first use.
+    open_files: Vec<Handle<StandardMaterial>>,
+    /// Translucent copies per base color and opacity; a redraw keeps the ones it uses.
+    translucent: HashMap<TranslucentKey, Handle<StandardMaterial>>,
+}
+
+/// Base color and opacity of a translucent node material, as bits.
+type TranslucentKey = ([u32; 4], u32);
+
+impl NodeMaterials {
+    fn open_files(&mut self, mats: &mut Assets<StandardMaterial>) -> &[Handle<StandardMaterial>] {
+        if self.open_files.is_empty() {
+            self.open_files = (0..OPEN_FILE_LEVELS)
+                .map(|level| {
+                    let t = level as f32 / (OPEN_FILE_LEVELS - 1) as f32;
+                    let [r, g, b] = [0, 1, 2]
+                        .map(|i| OPEN_FILES_LOW[i] + (OPEN_FILES_HIGH[i] - OPEN_FILES_LOW[i]) * t);
+                    mats.add(StandardMaterial {
+                        base_color: Color::srgb(r, g, b),
+                        ..default()
+                    })
+                })
+                .collect();
+        }
+        &self.open_files
+    }
+}
+
+/// Color steps for "color processes by open files".
+const OPEN_FILE_LEVELS: usize = 6;
+const OPEN_FILES_LOW: [f32; 3] = [0.35, 0.55, 0.9];
+const OPEN_FILES_HIGH: [f32; 3] = [1.0, 0.25, 0.15];
+
+const EXPLAIN_PATH_COLOR: Color = Color::srgb(1.0, 0.9, 0.1);
+const SELECTED_EDGE_COLOR: Color = Color::srgb(0.2, 0.95, 1.0);
+const FOCUS_PATHS_COLOR: Color = Color::srgb(1.0, 0.6, 0.15);
+const COLLAPSED_DIR_COLOR: Color = Color::srgb(0.55, 0.45, 0.3);
+const HOVER_NEIGHBOR_COLOR: Color = Color::srgb(0.95, 0.6, 1.0);
+const MULTI_SELECT_COLOR: Color = Color::srgb(0.3, 0.8, 1.0);
+const SEARCH_HIT_COLOR: Color = Color::srgb(1.0, 0.45, 0.85);
+
+/// Two clicks on the same placeholder within this window expand it.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Max screen distance (px) between cursor and edge segment for an edge click.
+const EDGE_PICK_PX: f32 = 6.0;
+
+/// Shorter Shift+drags count as a Shift+click.
+const BOX_SELECT_MIN_PX: f32 = 6.0;
+
+/// Gizmo lines are one pixel wide; draw a few offset copies so the line reads as thicker.
+fn draw_thick_line(gizmos: &mut Gizmos, a: Vec3, b: Vec3, color: Color) {
+    draw_wide_line(gizmos, a, b, color, 0.05);
+}
+
+fn draw_wide_line(gizmos: &mut Gizmos, a: Vec3, b: Vec3, color: Color, half_width: f32) {
+    if half_width <= 0.0 {
+        gizmos.line(a, b, color);
+        return;
+    }
+    let dir = (b - a).normalize_or_zero();
+    let side = dir.any_orthogonal_vector() * half_width;
+    let up = dir.cross(side);
+    for offset in [Vec3::ZERO, side, -side, up, -up] {
+        gizmos.line(a + offset, b + offset, color);
+    }
+}
+
+/// Screen-space distance from `p` to the segment `a`-`b`.
+pub(crate) fn point_segment_distance(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let len_sq = ab.length_squared();
+    if len_sq <= f32::EPSILON {
+        return p.distance(a);
+    }
+    let t = ((p - a).dot(ab) / len_sq).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
+
+/// Whether `p` lies in the rectangle spanned by the drag corners `a` and `b`.
+pub(crate) fn rect_contains(a: Vec2, b: Vec2, p: Vec2) -> bool {
+    let (min, max) = (a.min(b), a.max(b));
+    p.cmpge(min).all() && p.cmple(max).all()
+}
+
+fn draw_selection_box(ctx: &egui::Context, a: Vec2, b: Vec2) {
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("box_select"),
+    ));
+    let rect = egui::Rect::from_two_pos(egui::pos2(a.x, a.y), egui::pos2(b.x, b.y));
+    let stroke = egui::Color32::from_rgb(77, 204, 255);
+    painter.rect_filled(rect, 0.0, stroke.gamma_multiply(0.12));
+    painter.rect_stroke(rect, 0.0, egui::Stroke::new(1.0, stroke));
+}
+
+/// Node positions as drawn: collapsed files are replaced by their directory placeholder.
+fn pickable_positions(st: &GraphState) -> impl Iterator<Item = (&NodeId, &Vec3)> {
+    let view = &st.spatial.collapsed;
+    st.spatial
+        .positions
+        .iter()
+        .filter(|(id, _)| !view.members.contains_key(*id))
+        .chain(view.placeholders.iter().map(|(id, p)| (id, &p.pos)))
+}
+
+/// Display endpoints of an edge after collapsing. Rerouted edges are drawn once;
+/// edges folded entirely into one placeholder are dropped.
+fn display_endpoints(
+    view: &CollapsedView,
+    positions: &HashMap<NodeId, Vec3>,
+    from: &NodeId,
+    to: &NodeId,
+    drawn: &mut HashSet<(NodeId, NodeId)>,
+) -> Option<(Vec3, Vec3)> {
+    if view.members.is_empty() {
+        return Some((*positions.get(from)?, *positions.get(to)?));
+    }
+    let (a, b) = reroute(from, to, &view.members)?;
+    if (a != from || b != to) && !drawn.insert((a.clone(), b.clone())) {
+        return None;
+    }
+    Some((
+        view.position(from, positions)?,
+        view.position(to, positions)?,
+    ))
+}
+
+/// Visible edges of the hovered node as drawn segments, each with its neighbor's end.
+fn hover_neighbor_segments(
+    st: &GraphState,
+    hid: &NodeId,
+    vis: &HashSet<NodeId>,
+) -> Vec<(Vec3, Vec3, Vec3)> {
+    if !vis.contains(hid) {
+        return Vec::new();
+    }
+    let view = &st.spatial.collapsed;
+    let positions = &st.spatial.positions;
+    let mut drawn = HashSet::new();
+    st.model
+        .edges_for_node(hid)
+        .filter(|edge| st.edge_visible(edge, vis))
+        .filter_map(|edge| {
+            let (a, b) = display_endpoints(view, positions, &edge.from, &edge.to, &mut drawn)?;
+            Some((a, b, if &edge.from == hid { b } else { a }))
+        })
+        .collect()
+}
+
+/// Line segments for the steps of `path` whose endpoints are both placed.
+fn path_segments(path: &[PathStep], positions: &HashMap<NodeId, Vec3>) -> Vec<(Vec3, Vec3)> {
+    path.iter()
+        .filter_map(|step| Some((*positions.get(&step.from)?, *positions.get(&step.to)?)))
+        .collect()
+}
+
+/// Outline radius for search hits; pulses about twice a second.
+fn search_pulse_radius(t: f32) -> f32 {
+    0.6 + 0.15 * (t * std::f32::consts::TAU * 2.0).sin()
+}
+
+/// Emissive for glow level `v` (0..1); `strength` above 1 pushes it into bloom range.
+fn glow_emissive_color(v: f32, strength: f32) -> LinearRgba {
+    let c = Color::srgb(v, v, v).to_linear();
+    LinearRgba::rgb(c.red * strength, c.green * strength, c.blue * strength)
+}
+
+fn glow_level(glow: f32) -> usize {
+    ((glow * GLOW_LEVELS as f32).ceil() as usize).min(GLOW_LEVELS)
+}
+
+/// Color step for `count` open files, log-scaled against the visible maximum.
+fn open_files_level(count: u32, max: u32) -> Option<usize> {
+    if count == 0 || max == 0 {
+        return None;
+    }
+    let t = (count.min(max) as f32).ln_1p() / (max as f32).ln_1p();
+    Some(((t * (OPEN_FILE_LEVELS - 1) as f32).round() as usize).min(OPEN_FILE_LEVELS - 1))
+}
+
+/// Blend `base` towards white by glow intensity `t` (0..1).
+fn glow_color(base: [f32; 3], t: f32) -> Color {
+    let [r, g, b] = base.map(|c| c + (1.0 - c) * t);
+    Color::srgb(r, g, b)
+}
+
+fn diff_color(status: DiffStatus) -> Color {
+    match status {
+        DiffStatus::Added => Color::srgb(0.2, 0.85, 0.3),
+        DiffStatus::Removed => Color::srgb(0.9, 0.2, 0.2),
+        DiffStatus::Changed => Color::srgb(1.0, 0.6, 0.1),
+        DiffStatus::Unchanged => Color::srgb(0.45, 0.45, 0.45),
+    }
+}
+
+/// Draw order when capping raw edges: the selected edge, edges touching the focus or
+/// selection, glowing edges, then the rest.
+fn edge_render_rank(selected: bool, touches_focus: bool, glowing: bool) -> u8 {
+    match (selected, touches_focus, glowing) {
+        (true, _, _) => 0,
+        (_, true, _) => 1,
+        (_, _, true) => 2,
+        _ => 3,
+    }
+}
+
+/// Keeps the `cap` best-ranked items (unordered) and returns how many were dropped.
+fn cap_by_rank<T>(items: &mut Vec<T>, cap: usize, mut rank: impl FnMut(&T) -> u8) -> usize {
+    if cap == 0 || items.len() <= cap {
+        return 0;
+    }
+    items.select_nth_unstable_by_key(cap, |item| rank(item));
+    let dropped = items.len() - cap;
+    items.truncate(cap);
+    dropped
+}
+
+/// Raw edges follow the diff colors while comparing snapshots, the glow otherwise.
+fn raw_edge_color(st: &GraphState, edge: &Edge, base: [f32; 3]) -> Color {
+    let color = match st.diff.as_ref() {
+        Some(diff) => diff_color(diff.edge_status(edge)),
+        None => glow_color(base, st.edge_glow_intensity(edge)),
+    };
+    color.with_alpha(color.alpha() * st.cfg.edge_opacity)
+}
+
+/// Blended copy of `base` with its alpha scaled by `alpha`, shared per color and alpha.
+/// Copies made by the previous redraw (`prev`) are reused instead of added again.
+fn translucent_material(
+    mats: &mut Assets<StandardMaterial>,
+    prev: &mut HashMap<TranslucentKey, Handle<StandardMaterial>>,
+    cache: &mut HashMap<TranslucentKey, Handle<StandardMaterial>>,
+    base: &Handle<StandardMaterial>,
+    alpha: f32,
+) -> Handle<StandardMaterial> {
+    let base_color = mats.get(base).map_or(Color::WHITE, |mat| mat.base_color);
+    let key = (
+        base_color.to_linear().to_f32_array().map(f32::to_bits),
+        alpha.to_bits(),
+    );
+    cache
+        .entry(key)
+        .or_insert_with(|| {
+            prev.remove(&key).unwrap_or_else(|| {
+                let mut mat = mats.get(base).cloned().unwrap_or_default();
+                mat.base_color = base_color.with_alpha(base_color.alpha() * alpha);
+                mat.alpha_mode = AlphaMode::Blend;
+                mats.add(mat)
+            })
+        })
+        .clone()
+}
+
+/// Steps the emissive of glowing nodes down as their glow fades, without a redraw.
+pub fn animate_glow(
+    st: Res<GraphState>,
+    mut materials: ResMut<NodeMaterials>,
+    mut mats: ResMut<Assets<StandardMaterial>>,
+) {
+    for (id, (handle, shown)) in materials.glow.iter_mut() {
+        let level = glow_level(st.glow_intensity(id));
+        if level == *shown {
+            continue;
+        }
+        *shown = level;
+        if let Some(mat) = mats.get_mut(handle.id()) {
+            let v = level as f32 / GLOW_LEVELS as f32;
+            mat.emissive = glow_emissive_color(v, st.cfg.glow_emissive);
+        }
+    }
+}
+
 // Spatial hover only (timeline has its own hover picking based on events)
 pub fn hover_detection_spatial(
     windows: Query<&Window>,
@@ -40,7 +331,7 @@ pub fn hover_detection_spatial(
     }
 
     let mut best: Option<(f32, spacegraph_core::NodeId)> = None;
-    for (id, pos) in st.spatial.positions.iter() {
+    for (id, pos) in pickable_positions(&st) {
         let Some(screen) = camera.world_to_viewport(cam_tf, *pos) else {
             continue;
         };
@@ -52,21 +343,21 @@ pub fn hover_detection_spatial(
     st.ui.hovered = best.map(|(_, id)| id);
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn picking_focus(
     buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
     windows: Query<&Window>,
     cam_q: Query<(&Camera, &GlobalTransform)>,
     mut contexts: EguiContexts,
-    st: Res<GraphState>,
+    mut st: ResMut<GraphState>,
     mut out: EventWriter<Picked>,
+    mut out_edge: EventWriter<PickedEdge>,
+    mut last_click: Local<Option<(Instant, NodeId)>>,
+    mut box_start: Local<Option<Vec2>>,
 ) {
     if st.ui.view_mode == ViewMode::Timeline {
-        return;
-    }
-    if !buttons.just_pressed(MouseButton::Left) {
-        return;
-    }
-    if contexts.ctx_mut().wants_pointer_input() {
+        *box_start = None;
         return;
     }
 
@@ -80,8 +371,43 @@ pub fn picking_focus(
         return;
     };
 
+    if let Some(start) = *box_start {
+        if buttons.pressed(MouseButton::Left) {
+            draw_selection_box(contexts.ctx_mut(), start, cursor);
+            return;
+        }
+        *box_start = None;
+        if start.distance(cursor) >= BOX_SELECT_MIN_PX {
+            let vis = st.visible_set_capped();
+            let hits: HashSet<NodeId> = pickable_positions(&st)
+                .filter(|(id, _)| vis.contains(*id))
+                .filter(|(_, pos)| {
+                    camera
+                        .world_to_viewport(cam_tf, **pos)
+                        .is_some_and(|screen| rect_contains(start, cursor, screen))
+                })
+                .map(|(id, _)| id.clone())
+                .collect();
+            st.ui.multi_selected = hits;
+            st.needs_redraw.store(true, Ordering::Relaxed);
+            return;
+        }
+        // Too short for a box: handle the release like a Shift+click.
+    } else {
+        if !buttons.just_pressed(MouseButton::Left) {
+            return;
+        }
+        if contexts.ctx_mut().wants_pointer_input() {
+            return;
+        }
+        if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            *box_start = Some(cursor);
+            return;
+        }
+    }
+
     let mut best: Option<(f32, spacegraph_core::NodeId)> = None;
-    for (id, pos) in st.spatial.positions.iter() {
+    for (id, pos) in pickable_positions(&st) {
         let Some(screen) = camera.world_to_viewport(cam_tf, *pos) else {
             continue;
         };
@@ -91,15 +417,95 @@ pub fn picking_focus(
         }
     }
     if let Some((_, picked)) = best {
-        out.send(Picked(picked));
+        let view = &st.spatial.collapsed;
+        let dir = match view.placeholders.get(&picked) {
+            Some(p) if p.kind == PlaceholderKind::Dir => Some(p.path.clone()),
+            _ => None,
+        };
+        let Some(dir) = dir else {
+            // A merged file selects one of its per-host nodes.
+            let picked = view
+                .members
+                .iter()
+                .filter(|(_, pid)| **pid == picked)
+                .map(|(id, _)| id)
+                .min_by(|a, b| a.0.cmp(&b.0))
+                .cloned()
+                .unwrap_or(picked);
+            out.send(Picked(picked));
+            return;
+        };
+        let now = Instant::now();
+        match last_click.take() {
+            Some((at, id)) if id == picked && now.duration_since(at) <= DOUBLE_CLICK => {
+                st.ui.collapsed_dirs.remove(&dir);
+                st.needs_redraw.store(true, Ordering::Relaxed);
+            }
+            _ => *last_click = Some((now, picked)),
+        }
+        return;
     }
+
+    if st.ui.view_mode != ViewMode::Spatial || !st.ui.show_edges {
+        return;
+    }
+    let vis = st.visible_set_capped();
+    let mut best_edge: Option<(f32, &Edge)> = None;
+    for edge in st.model.edges.iter() {
+        if !st.edge_visible(edge, &vis) {
+            continue;
+        }
+        // rerouted edges belong to a placeholder, not to a single file
+        let members = &st.spatial.collapsed.members;
+        if members.contains_key(&edge.from) || members.contains_key(&edge.to) {
+            continue;
+        }
+        let (Some(a), Some(b)) = (
+            st.spatial.positions.get(&edge.from),
+            st.spatial.positions.get(&edge.to),
+        ) else {
+            continue;
+        };
+        let (Some(sa), Some(sb)) = (
+            camera.world_to_viewport(cam_tf, *a),
+            camera.world_to_viewport(cam_tf, *b),
+        ) else {
+            continue;
+        };
+        let d = point_segment_distance(cursor, sa, sb);
+        if d < EDGE_PICK_PX && best_edge.map(|(bd, _)| d < bd).unwrap_or(true) {
+            best_edge = Some((d, edge));
+        }
+    }
+    out_edge.send(PickedEdge(best_edge.map(|(_, e)| e.clone())));
 }
 
-pub fn apply_picked_focus(mut st: ResMut<GraphState>, mut ev: EventReader<Picked>) {
+pub fn apply_picked_focus(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut st: ResMut<GraphState>,
+    mut ev: EventReader<Picked>,
+    mut ev_edge: EventReader<PickedEdge>,
+) {
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    for PickedEdge(edge) in ev_edge.read() {
+        if st.ui.selected_edge != *edge {
+            st.ui.selected_edge = edge.clone();
+            st.needs_redraw.store(true, Ordering::Relaxed);
+        }
+    }
     for Picked(id) in ev.read() {
+        st.ui.selected_edge = None;
+        // Shift+click sets the second Explain endpoint and leaves focus alone.
+        if shift && st.ui.selected_a.as_ref().is_some_and(|a| a != id) {
+            st.ui.selected_b = Some(id.clone());
+            st.needs_redraw.store(true, Ordering::Relaxed);
+            continue;
+        }
         if st.ui.view_mode == ViewMode::Tree {
             st.toggle_tree_dir(id);
         }
+        st.stop_following();
+        st.ui.multi_selected.clear();
         st.ui.focus = Some(id.clone());
         st.ui.selected = Some(id.clone());
         st.ui.selected_a = Some(id.clone());
@@ -108,33 +514,72 @@ pub fn apply_picked_focus(mut st: ResMut<GraphState>, mut ev: EventReader<Picked
     }
 }
 
+/// Edge mode for this redraw; "selection only" narrows to focus edges unless LOD hides edges.
+fn edges_mode(lod_active: bool, selection_only: bool, lod_mode: LodEdgesMode) -> LodEdgesMode {
+    match (lod_active, selection_only) {
+        (true, true) if lod_mode == LodEdgesMode::Off => LodEdgesMode::Off,
+        (_, true) => LodEdgesMode::FocusOnly,
+        (true, false) => lod_mode,
+        (false, false) => LodEdgesMode::All,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn draw_spatial(
+    time: Res<Time>,
     mut commands: Commands,
     mut st: ResMut<GraphState>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut mats: ResMut<Assets<StandardMaterial>>,
+    mut materials: ResMut<NodeMaterials>,
     mut query: Query<(Entity, &NodeMarker)>,
     mut gizmos: Gizmos,
     mut contexts: EguiContexts,
 ) {
     let vis: HashSet<_> = st.visible_set_capped();
+    st.refresh_collapsed_view(&vis);
     let lod_active = st.cfg.lod_active(vis.len());
     if st.spatial.lod_active != lod_active {
         st.spatial.lod_active = lod_active;
         st.needs_redraw.store(true, Ordering::Relaxed);
     }
+    if st.cfg.edge_bundling && st.ui.view_mode == ViewMode::Spatial {
+        st.refresh_edge_bundles(&vis);
+    } else {
+        st.spatial.bundles.invalidate();
+    }
 
     // Tooltip
     let hovered = st.ui.hovered.clone();
     let selected = st.ui.selected.clone();
+    let mut explain_path = Vec::new();
     if let Some(hid) = hovered.as_ref() {
         let pos = contexts
             .ctx_mut()
             .input(|i| i.pointer.hover_pos().unwrap_or(egui::pos2(0.0, 0.0)))
             + egui::vec2(14.0, 14.0);
 
-        let mut lines = st.node_tooltip_lines(hid);
-        if let Some(selected) = selected.as_ref() {
+        let placeholder = st.spatial.collapsed.placeholders.get(hid);
+        let mut lines = match placeholder {
+            Some(p) => {
+                let mut lines = node_label_long(&p.node());
+                lines.push(match p.kind {
+                    PlaceholderKind::Dir => {
+                        format!("{} files collapsed; double-click to expand", p.count)
+                    }
+                    PlaceholderKind::MergedFile => format!("same path on {} hosts", p.count),
+                });
+                lines
+            }
+            None => {
+                let mut lines = st.node_tooltip_lines(hid);
+                lines.push(
+                    "Ctrl+C copy path · Ctrl+Shift+C copy id · N expand neighbors".to_string(),
+                );
+                lines
+            }
+        };
+        if let Some(selected) = selected.as_ref().filter(|_| placeholder.is_none()) {
             if selected != hid {
                 lines.push("why connected:".to_string());
                 match st.explain_path_cached(selected, hid, &vis) {
@@ -142,7 +587,7 @@ pub fn draw_spatial(
                         lines.push("same node".to_string());
                     }
                     Some(path) => {
-                        for step in path {
+                        for step in &path {
                             let from = st.node_label_with_id(&step.from);
                             let to = st.node_label_with_id(&step.to);
                             lines.push(format!(
@@ -152,6 +597,7 @@ pub fn draw_spatial(
                                 to
                             ));
                         }
+                        explain_path = path;
                     }
                     None => lines.push("no path within depth cap".to_string()),
                 }
@@ -160,21 +606,66 @@ pub fn draw_spatial(
         render_tooltip(contexts.ctx_mut(), "tooltip_spatial", pos, lines);
     }
 
+    st.assign_host_colors(&vis);
+
     if st.needs_redraw.swap(false, Ordering::Relaxed) {
+        st.spatial.redraw_seq += 1;
         for (e, _) in query.iter_mut() {
             commands.entity(e).despawn_recursive();
         }
+        let mut prev_glow = std::mem::take(&mut materials.glow);
 
         if !lod_active {
-            let sphere = meshes.add(Sphere::new(0.28));
+            // Unit-radius mesh shared by all nodes; per-node size comes from Transform scale.
+            let sphere = meshes.add(Sphere::new(1.0));
+            // Symlinks get a cube so they stand apart from regular files at any color.
+            let link_cube = meshes.add(Cuboid::new(1.6, 1.6, 1.6));
+            let degree = if st.cfg.size_by_degree {
+                st.model.degree_map()
+            } else {
+                HashMap::new()
+            };
+            let max_degree = vis
+                .iter()
+                .filter_map(|id| degree.get(id).copied())
+                .max()
+                .unwrap_or(0);
+            let open_files = if st.cfg.color_by_open_files {
+                st.model.open_file_counts()
+            } else {
+                HashMap::new()
+            };
+            let max_open_files = vis
+                .iter()
+                .filter_map(|id| open_files.get(id).copied())
+                .max()
+                .unwrap_or(0);
+            let mat_open_files = materials.open_files(&mut mats).to_vec();
             let mat_norm = mats.add(StandardMaterial::default());
-            let mat_glow = mats.add(StandardMaterial {
-                emissive: Color::srgb(1.0, 1.0, 1.0).into(),
+            let mat_stale = mats.add(StandardMaterial {
+                base_color: Color::srgba(0.5, 0.5, 0.5, 0.35),
+                alpha_mode: AlphaMode::Blend,
                 ..default()
             });
+            let mat_hosts: Vec<Handle<StandardMaterial>> = (0..HOST_PALETTE.len())
+                .map(|idx| {
+                    let [r, g, b] = host_color(idx);
+                    mats.add(StandardMaterial {
+                        base_color: Color::srgb(r, g, b),
+                        ..default()
+                    })
+                })
+                .collect();
+            let mut mat_categories: HashMap<FileCategory, Handle<StandardMaterial>> =
+                HashMap::new();
+            let mut mat_diff: HashMap<DiffStatus, Handle<StandardMaterial>> = HashMap::new();
+            let glow_emissive = st.cfg.glow_emissive;
+            let mut prev_translucent = std::mem::take(&mut materials.translucent);
+            let mut mat_translucent = HashMap::new();
 
+            let now = Instant::now();
             for (id, node) in st.model.nodes.iter() {
-                if !vis.contains(id) {
+                if !vis.contains(id) || st.spatial.collapsed.members.contains_key(id) {
                     continue;
                 }
                 if !st.passes_filter(id, node) {
@@ -183,17 +674,124 @@ pub fn draw_spatial(
                 let Some(pos) = st.spatial.positions.get(id).cloned() else {
                     continue;
                 };
-                let use_glow = st.node_is_glowing(id);
+                let level = glow_level(st.glow_intensity(id));
+                let radius = st
+                    .cfg
+                    .node_radius(degree.get(id).copied().unwrap_or(0), max_degree)
+                    * st.new_node_scale(id, now);
+                let category = match node {
+                    Node::File { category, .. } if st.cfg.color_by_category => {
+                        category_color(*category).map(|rgb| (*category, rgb))
+                    }
+                    _ => None,
+                };
+
+                let open_files_step = open_files
+                    .get(id)
+                    .and_then(|count| open_files_level(*count, max_open_files));
+
+                let diff_status = st.diff.as_ref().map(|diff| diff.node_status(id));
+
+                let is_link = matches!(
+                    node,
+                    Node::File {
+                        kind: FileKind::Symlink,
+                        ..
+                    }
+                );
+
+                let opacity = st.cfg.node_opacity.for_node(node);
+                let material = if level > 0 {
+                    let (handle, _) = prev_glow
+                        .remove(id)
+                        .unwrap_or_else(|| (mats.add(StandardMaterial::default()), level));
+                    if let Some(mat) = mats.get_mut(handle.id()) {
+                        mat.emissive =
+                            glow_emissive_color(level as f32 / GLOW_LEVELS as f32, glow_emissive);
+                        mat.base_color = Color::WHITE.with_alpha(opacity);
+                        mat.alpha_mode = if opacity < 1.0 {
+                            AlphaMode::Blend
+                        } else {
+                            AlphaMode::Opaque
+                        };
+                    }
+                    materials.glow.insert(id.clone(), (handle.clone(), level));
+                    handle
+                } else {
+                    let material = if let Some(status) = diff_status {
+                        mat_diff
+                            .entry(status)
+                            .or_insert_with(|| {
+                                mats.add(StandardMaterial {
+                                    base_color: diff_color(status),
+                                    ..default()
+                                })
+                            })
+                            .clone()
+                    } else if st.spatial.stale_nodes.contains(id) {
+                        mat_stale.clone()
+                    } else if let Some((category, [r, g, b])) = category {
+                        mat_categories
+                            .entry(category)
+                            .or_insert_with(|| {
+                                mats.add(StandardMaterial {
+                                    base_color: Color::srgb(r, g, b),
+                                    ..default()
+                                })
+                            })
+                            .clone()
+                    } else if let Some(step) = open_files_step {
+                        mat_open_files[step].clone()
+                    } else {
+                        match st.host_color_index(id) {
+                            Some(idx) => mat_hosts[idx % mat_hosts.len()].clone(),
+                            None => mat_norm.clone(),
+                        }
+                    };
+                    if opacity < 1.0 {
+                        translucent_material(
+                            &mut mats,
+                            &mut prev_translucent,
+                            &mut mat_translucent,
+                            &material,
+                            opacity,
+                        )
+                    } else {
+                        material
+                    }
+                };
 
                 commands.spawn((
                     PbrBundle {
-                        mesh: sphere.clone(),
-                        material: if use_glow {
-                            mat_glow.clone()
+                        mesh: if is_link {
+                            link_cube.clone()
                         } else {
-                            mat_norm.clone()
+                            sphere.clone()
                         },
-                        transform: Transform::from_translation(pos),
+                        material,
+                        transform: Transform::from_translation(pos).with_scale(Vec3::splat(radius)),
+                        ..default()
+                    },
+                    NodeMarker,
+                ));
+            }
+            materials.translucent = mat_translucent;
+
+            let mat_collapsed = mats.add(StandardMaterial {
+                base_color: COLLAPSED_DIR_COLOR,
+                ..default()
+            });
+            for p in st.spatial.collapsed.placeholders.values() {
+                let (material, scale) = match p.kind {
+                    PlaceholderKind::Dir => (mat_collapsed.clone(), 2.0),
+                    PlaceholderKind::MergedFile => (mat_norm.clone(), 1.5),
+                };
+                commands.spawn((
+                    PbrBundle {
+                        mesh: sphere.clone(),
+                        material,
+                        transform: Transform::from_translation(p.pos)
+                            .with_scale(Vec3::splat(NODE_RADIUS * scale)),
                         ..default()
                     },
                     NodeMarker,
@@ -202,17 +800,24 @@ pub fn draw_spatial(
         }
     }
 
+    let palette = theme::palette(st.cfg.theme);
+
+    if st.ui.view_mode == ViewMode::Spatial {
+        draw_grid(&mut gizmos, &st, palette);
+    }
+
     if lod_active {
         let marker = 0.35;
         for id in vis.iter() {
             let Some(pos) = st.spatial.positions.get(id).cloned() else {
                 continue;
             };
-            let color = if st.node_is_glowing(id) {
-                Color::WHITE
+            let base = if st.spatial.stale_nodes.contains(id) {
+                [0.35, 0.35, 0.4]
             } else {
-                Color::srgb(0.7, 0.7, 0.95)
+                palette.agg_edge
             };
+            let color = glow_color(base, st.glow_intensity(id));
             gizmos.line(
                 pos + Vec3::new(-marker, 0.0, 0.0),
                 pos + Vec3::new(marker, 0.0, 0.0),
@@ -231,8 +836,43 @@ pub fn draw_spatial(
         }
     }
 
+    if st.ui.view_mode == ViewMode::Spatial {
+        let pin_color = Color::srgb(1.0, 0.75, 0.2);
+        for id in st.spatial.pinned.iter() {
+            if !vis.contains(id) {
+                continue;
+            }
+            let Some(pos) = st.spatial.positions.get(id).cloned() else {
+                continue;
+            };
+            gizmos.circle(pos, Dir3::Y, 0.45, pin_color);
+            gizmos.line(pos, pos + Vec3::new(0.0, 0.8, 0.0), pin_color);
+        }
+        for id in st.ui.multi_selected.iter() {
+            if !vis.contains(id) {
+                continue;
+            }
+            let Some(pos) = st.spatial.positions.get(id).cloned() else {
+                continue;
+            };
+            gizmos.circle(pos, Dir3::Y, 0.6, MULTI_SELECT_COLOR);
+            gizmos.circle(pos, Dir3::Z, 0.6, MULTI_SELECT_COLOR);
+        }
+        let radius = search_pulse_radius(time.elapsed_seconds());
+        for id in st.search_preview() {
+            if !vis.contains(id) {
+                continue;
+            }
+            let Some(pos) = st.spatial.positions.get(id).cloned() else {
+                continue;
+            };
+            gizmos.circle(pos, Dir3::Y, radius, SEARCH_HIT_COLOR);
+            gizmos.circle(pos, Dir3::X, radius, SEARCH_HIT_COLOR);
+        }
+    }
+
     if st.ui.view_mode == ViewMode::Tree {
-        let indicator_color = Color::srgb(0.9, 0.9, 0.9);
+        let indicator_color = rgb(palette.indicator);
         let size = 0.35;
         let offset = Vec3::new(-0.6, 0.0, 0.0);
         for id in st.spatial.tree_dir_children.iter() {
@@ -261,12 +901,13 @@ pub fn draw_spatial(
         }
     }
 
+    let mut edges_capped = 0;
     if st.ui.show_edges {
-        let edges_mode = if lod_active {
-            st.cfg.lod_edges_mode
-        } else {
-            LodEdgesMode::All
-        };
+        let edges_mode = edges_mode(
+            lod_active,
+            st.cfg.edges_selection_only,
+            st.cfg.lod_edges_mode,
+        );
 
         let mut focus_nodes = HashSet::new();
         if let Some(id) = st.ui.focus.clone() {
@@ -290,6 +931,9 @@ pub fn draw_spatial(
             }
         }
 
+        let view = &st.spatial.collapsed;
+        let positions = &st.spatial.positions;
+        let mut drawn = HashSet::new();
         match edges_mode {
             LodEdgesMode::Off => {}
             LodEdgesMode::FocusOnly => {
@@ -304,13 +948,19 @@ pub fn draw_spatial(
                         }
                     }
                     for key in agg_keys {
-                        let (Some(a), Some(b)) = (
-                            st.spatial.positions.get(&key.from),
-                            st.spatial.positions.get(&key.to),
-                        ) else {
+                        let Some((a, b)) =
+                            display_endpoints(view, positions, &key.from, &key.to, &mut drawn)
+                        else {
                             continue;
                         };
-                        gizmos.line(*a, *b, Color::srgb(0.8, 0.8, 1.0));
+                        let count = st.model.agg_edge(&key).map_or(1, |e| e.stats.count);
+                        draw_wide_line(
+                            &mut gizmos,
+                            a,
+                            b,
+                            rgb(palette.agg_edge).with_alpha(st.cfg.edge_opacity),
+                            agg_edge_width(count),
+                        );
                     }
                 }
                 if st.cfg.show_raw_edges && !focus_nodes.is_empty() {
@@ -323,17 +973,15 @@ pub fn draw_spatial(
                             raw_edges.insert(edge.clone());
                         }
                     }
+                    drawn.clear();
                     for edge in raw_edges {
-                        let (Some(a), Some(b)) = (
-                            st.spatial.positions.get(&edge.from),
-                            st.spatial.positions.get(&edge.to),
-                        ) else {
+                        let Some((a, b)) =
+                            display_endpoints(view, positions, &edge.from, &edge.to, &mut drawn)
+                        else {
                             continue;
                         };
-                        if st.edge_is_glowing(&edge) {
-                            gizmos.line(*a, *b, Color::srgb(1.0, 1.0, 1.0));
-                        }
-                        gizmos.line(*a, *b, Color::WHITE);
+                        let color = raw_edge_color(&st, &edge, palette.raw_edge);
+                        gizmos.line(a, b, color);
                     }
                 }
             }
@@ -343,38 +991,277 @@ pub fn draw_spatial(
                         if !vis.contains(&edge.key.from) || !vis.contains(&edge.key.to) {
                             continue;
                         }
-                        let (Some(a), Some(b)) = (
-                            st.spatial.positions.get(&edge.key.from),
-                            st.spatial.positions.get(&edge.key.to),
+                        let Some((a, b)) = display_endpoints(
+                            view,
+                            positions,
+                            &edge.key.from,
+                            &edge.key.to,
+                            &mut drawn,
                         ) else {
                             continue;
                         };
-                        gizmos.line(*a, *b, Color::srgb(0.8, 0.8, 1.0));
+                        let color = rgb(palette.agg_edge).with_alpha(st.cfg.edge_opacity);
+                        let width = agg_edge_width(edge.stats.count);
+                        match st.spatial.bundles.path(&edge.key, a, b) {
+                            Some(path) => {
+                                for seg in path.windows(2) {
+                                    draw_wide_line(&mut gizmos, seg[0], seg[1], color, width);
+                                }
+                            }
+                            None => draw_wide_line(&mut gizmos, a, b, color, width),
+                        }
                     }
                 }
                 if st.cfg.show_raw_edges {
-                    for id in vis.iter() {
-                        for edge in st.model.edges_for_node(id) {
-                            if &edge.from != id {
-                                continue;
-                            }
-                            if !st.edge_visible(edge, &vis) {
-                                continue;
-                            }
-                            let (Some(a), Some(b)) = (
-                                st.spatial.positions.get(&edge.from),
-                                st.spatial.positions.get(&edge.to),
-                            ) else {
-                                continue;
-                            };
-                            if st.edge_is_glowing(edge) {
-                                gizmos.line(*a, *b, Color::srgb(1.0, 1.0, 1.0));
-                            }
-                            gizmos.line(*a, *b, Color::WHITE);
+                    drawn.clear();
+                    let mut raw_edges: Vec<&Edge> = vis
+                        .iter()
+                        .flat_map(|id| {
+                            st.model
+                                .edges_for_node(id)
+                                .filter(move |edge| &edge.from == id)
+                        })
+                        .filter(|edge| st.edge_visible(edge, &vis))
+                        .collect();
+                    edges_capped = cap_by_rank(&mut raw_edges, st.cfg.max_rendered_edges, |edge| {
+                        edge_render_rank(
+                            st.ui.selected_edge.as_ref() == Some(*edge),
+                            focus_nodes.contains(&edge.from) || focus_nodes.contains(&edge.to),
+                            st.edge_glow_intensity(edge) > 0.0,
+                        )
+                    });
+                    for edge in raw_edges {
+                        let Some((a, b)) =
+                            display_endpoints(view, positions, &edge.from, &edge.to, &mut drawn)
+                        else {
+                            continue;
+                        };
+                        let color = raw_edge_color(&st, edge, palette.raw_edge);
+                        let bundled = st
+                            .cfg
+                            .edge_bundling
+                            .then(|| st.spatial.bundles.path(&AggEdgeKey::new(edge), a, b))
+                            .flatten();
+                        match bundled {
+                            Some(path) => gizmos.linestrip(path.iter().copied(), color),
+                            None => gizmos.line(a, b, color),
                         }
                     }
                 }
             }
         }
     }
+
+    st.perf.edges_capped = edges_capped;
+
+    // Ghost-highlight the hovered node's edges and neighbors, regardless of edge toggles/LOD.
+    if let Some(hid) = hovered.as_ref() {
+        for (a, b, neighbor) in hover_neighbor_segments(&st, hid, &vis) {
+            gizmos.line(a, b, HOVER_NEIGHBOR_COLOR);
+            gizmos.sphere(
+                neighbor,
+                Quat::IDENTITY,
+                NODE_RADIUS * 1.6,
+                HOVER_NEIGHBOR_COLOR,
+            );
+        }
+    }
+
+    // Paths from the focus to each multi-selected node ("explain to focus").
+    for step in st.focus_paths_cached(&vis).iter() {
+        let (Some(a), Some(b)) = (
+            st.spatial.positions.get(&step.from),
+            st.spatial.positions.get(&step.to),
+        ) else {
+            continue;
+        };
+        draw_thick_line(&mut gizmos, *a, *b, FOCUS_PATHS_COLOR);
+    }
+
+    // Highlight the explain route between the selection and the hovered node.
+    for (a, b) in path_segments(&explain_path, &st.spatial.positions) {
+        draw_thick_line(&mut gizmos, a, b, EXPLAIN_PATH_COLOR);
+    }
+
+    if let Some(edge) = st.ui.selected_edge.as_ref() {
+        if let (Some(a), Some(b)) = (
+            st.spatial.positions.get(&edge.from),
+            st.spatial.positions.get(&edge.to),
+        ) {
+            draw_thick_line(&mut gizmos, *a, *b, SELECTED_EDGE_COLOR);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_segment_distance_clamps_to_endpoints() {
+        let a = Vec2::new(0.0, 0.0);
+        let b = Vec2::new(10.0, 0.0);
+        assert!((point_segment_distance(Vec2::new(5.0, 3.0), a, b) - 3.0).abs() < 1e-5);
+        assert!((point_segment_distance(Vec2::new(-4.0, 3.0), a, b) - 5.0).abs() < 1e-5);
+        assert!((point_segment_distance(Vec2::new(13.0, 4.0), a, b) - 5.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn explain_path_segments_follow_placed_steps() {
+        let id = |s: &str| NodeId(s.to_string());
+        let step = |from: &str, to: &str| PathStep {
+            from: id(from),
+            to: id(to),
+            class: crate::graph::model::EdgeKindClass::Opens,
+        };
+        let positions = HashMap::from([
+            (id("a"), Vec3::ZERO),
+            (id("b"), Vec3::X),
+            (id("c"), Vec3::Y),
+        ]);
+        assert_eq!(
+            path_segments(&[step("a", "b"), step("b", "c")], &positions),
+            vec![(Vec3::ZERO, Vec3::X), (Vec3::X, Vec3::Y)]
+        );
+        // unplaced endpoints and empty paths draw nothing
+        assert!(path_segments(&[step("a", "gone")], &positions).is_empty());
+        assert!(path_segments(&[], &positions).is_empty());
+    }
+
+    #[test]
+    fn hover_highlights_only_visible_neighbors() {
+        let id = |s: &str| NodeId(s.to_string());
+        let mut st = GraphState::default();
+        let now = Instant::now();
+        for (name, pos) in [("p", Vec3::ZERO), ("u", Vec3::X), ("hidden", Vec3::Y)] {
+            let node = Node::User {
+                uid: 0,
+                name: name.to_string(),
+            };
+            st.model.upsert_node(id(name), node, now);
+            st.spatial.positions.insert(id(name), pos);
+        }
+        for to in ["u", "hidden"] {
+            let edge = Edge {
+                from: id("p"),
+                to: id(to),
+                kind: spacegraph_core::EdgeKind::RunsAs,
+            };
+            st.model.upsert_edge(edge, now);
+        }
+        let vis = HashSet::from([id("p"), id("u")]);
+
+        assert_eq!(
+            hover_neighbor_segments(&st, &id("p"), &vis),
+            vec![(Vec3::ZERO, Vec3::X, Vec3::X)]
+        );
+        // hovering a node outside the visible set highlights nothing
+        assert!(hover_neighbor_segments(&st, &id("hidden"), &vis).is_empty());
+    }
+
+    #[test]
+    fn point_segment_distance_handles_degenerate_segment() {
+        let a = Vec2::new(2.0, 2.0);
+        assert!((point_segment_distance(Vec2::new(5.0, 6.0), a, a) - 5.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn glow_emissive_reaches_bloom_range_only_above_unit_strength() {
+        assert!(glow_emissive_color(1.0, 4.0).red > 1.0);
+        assert!(glow_emissive_color(1.0, 1.0).red <= 1.0);
+        assert_eq!(glow_emissive_color(0.0, 4.0), LinearRgba::BLACK);
+    }
+
+    #[test]
+    fn translucent_materials_carry_over_to_the_next_redraw() {
+        let mut mats = Assets::<StandardMaterial>::default();
+        let red = || StandardMaterial {
+            base_color: Color::srgb(1.0, 0.0, 0.0),
+            ..default()
+        };
+        let base = mats.add(red());
+        let mut first = HashMap::new();
+        let shown = translucent_material(&mut mats, &mut HashMap::new(), &mut first, &base, 0.5);
+        assert_eq!(mats.get(&shown).unwrap().base_color.alpha(), 0.5);
+
+        // the next redraw adds its base materials again; the copy is still reused
+        let base = mats.add(red());
+        let before = mats.len();
+        let mut second = HashMap::new();
+        let again = translucent_material(&mut mats, &mut first, &mut second, &base, 0.5);
+        assert_eq!(again, shown);
+        assert_eq!(mats.len(), before);
+
+        // another opacity is another material
+        let other = translucent_material(&mut mats, &mut first, &mut second, &base, 0.25);
+        assert_ne!(other, shown);
+        assert_eq!(mats.len(), before + 1);
+    }
+
+    #[test]
+    fn open_files_materials_are_created_once() {
+        let mut mats = Assets::<StandardMaterial>::default();
+        let mut materials = NodeMaterials::default();
+        let first = materials.open_files(&mut mats).to_vec();
+        assert_eq!(first.len(), OPEN_FILE_LEVELS);
+        assert_eq!(mats.len(), OPEN_FILE_LEVELS);
+        // a later redraw reuses the same handles instead of adding new materials
+        assert_eq!(materials.open_files(&mut mats), first.as_slice());
+        assert_eq!(mats.len(), OPEN_FILE_LEVELS);
+    }
+
+    #[test]
+    fn open_files_level_spans_cool_to_hot() {
+        assert_eq!(open_files_level(0, 40), None);
+        assert_eq!(open_files_level(40, 40), Some(OPEN_FILE_LEVELS - 1));
+        assert_eq!(open_files_level(1, 10_000), Some(0));
+        assert!(open_files_level(10, 40) < open_files_level(30, 40));
+    }
+
+    #[test]
+    fn edge_cap_keeps_selected_then_focus_then_glowing() {
+        let ranked = [
+            ("plain", edge_render_rank(false, false, false)),
+            ("glow", edge_render_rank(false, false, true)),
+            ("focus", edge_render_rank(false, true, false)),
+            ("plain2", edge_render_rank(false, false, false)),
+            ("selected", edge_render_rank(true, false, true)),
+        ];
+        let mut items = ranked.to_vec();
+        assert_eq!(cap_by_rank(&mut items, 3, |(_, rank)| *rank), 2);
+        let mut kept: Vec<&str> = items.iter().map(|(name, _)| *name).collect();
+        kept.sort_unstable();
+        assert_eq!(kept, vec!["focus", "glow", "selected"]);
+
+        let mut all = ranked.to_vec();
+        assert_eq!(cap_by_rank(&mut all, 0, |(_, rank)| *rank), 0);
+        assert_eq!(all.len(), 5);
+    }
+
+    #[test]
+    fn rect_contains_works_for_any_drag_direction() {
+        let (a, b) = (Vec2::new(10.0, 40.0), Vec2::new(50.0, 20.0));
+        assert!(rect_contains(a, b, Vec2::new(30.0, 30.0)));
+        assert!(rect_contains(b, a, Vec2::new(30.0, 30.0)));
+        assert!(rect_contains(a, b, Vec2::new(10.0, 20.0)));
+        assert!(!rect_contains(a, b, Vec2::new(9.0, 30.0)));
+        assert!(!rect_contains(a, b, Vec2::new(30.0, 41.0)));
+    }
+
+    #[test]
+    fn selection_only_narrows_edges_to_focus() {
+        let all = LodEdgesMode::All;
+        assert_eq!(edges_mode(false, true, all), LodEdgesMode::FocusOnly);
+        assert_eq!(edges_mode(true, true, all), LodEdgesMode::FocusOnly);
+        // Off: the toggle does not bring edges back that LOD hides.
+        assert_eq!(edges_mode(true, true, LodEdgesMode::Off), LodEdgesMode::Off);
+        assert_eq!(
+            edges_mode(false, false, LodEdgesMode::Off),
+            LodEdgesMode::All
+        );
+        assert_eq!(
+            edges_mode(true, false, LodEdgesMode::FocusOnly),
+            LodEdgesMode::FocusOnly
+        );
+    }
 }
@@ -0,0 +1,166 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::graph::{GraphState, TimelineEvtKind};
+use crate::util::config::Theme;
+
+/// Scene colors for one [`Theme`], as sRGB triples.
+pub struct Palette {
+    pub clear: [f32; 3],
+    pub agg_edge: [f32; 3],
+    pub raw_edge: [f32; 3],
+    pub axis: [f32; 3],
+    pub lane_guide: [f32; 3],
+    /// Tree expand/collapse markers.
+    pub indicator: [f32; 3],
+    /// Node upsert, node remove, edge upsert, edge remove, batch bounds, fd mode change.
+    pub events: [[f32; 3]; 6],
+}
+
+const DARK: Palette = Palette {
+    clear: [0.17, 0.17, 0.18],
+    agg_edge: [0.8, 0.8, 1.0],
+    raw_edge: [0.65, 0.65, 0.8],
+    axis: [1.0, 1.0, 1.0],
+    lane_guide: [0.7, 0.7, 0.7],
+    indicator: [0.9, 0.9, 0.9],
+    events: [
+        [0.2, 0.85, 0.3],
+        [0.9, 0.2, 0.2],
+        [0.2, 0.55, 0.9],
+        [0.9, 0.55, 0.2],
+        [0.75, 0.75, 0.75],
+        [0.8, 0.3, 0.9],
+    ],
+};
+
+const LIGHT: Palette = Palette {
+    clear: [0.95, 0.95, 0.95],
+    agg_edge: [0.25, 0.3, 0.6],
+    raw_edge: [0.4, 0.4, 0.55],
+    axis: [0.1, 0.1, 0.1],
+    lane_guide: [0.6, 0.6, 0.6],
+    indicator: [0.2, 0.2, 0.2],
+    events: [
+        [0.1, 0.6, 0.2],
+        [0.8, 0.1, 0.1],
+        [0.1, 0.35, 0.8],
+        [0.85, 0.45, 0.0],
+        [0.45, 0.45, 0.45],
+        [0.6, 0.1, 0.7],
+    ],
+};
+
+const HIGH_CONTRAST: Palette = Palette {
+    clear: [0.0, 0.0, 0.0],
+    agg_edge: [0.6, 0.8, 1.0],
+    raw_edge: [0.7, 0.7, 0.9],
+    axis: [1.0, 1.0, 1.0],
+    lane_guide: [0.8, 0.8, 0.8],
+    indicator: [1.0, 1.0, 1.0],
+    events: [
+        [0.0, 1.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [0.3, 0.6, 1.0],
+        [1.0, 0.8, 0.0],
+        [1.0, 1.0, 1.0],
+        [1.0, 0.0, 1.0],
+    ],
+};
+
+pub fn palette(theme: Theme) -> &'static Palette {
+    match theme {
+        Theme::Dark => &DARK,
+        Theme::Light => &LIGHT,
+        Theme::HighContrast => &HIGH_CONTRAST,
+    }
+}
+
+pub fn rgb([r, g, b]: [f32; 3]) -> Color {
+    Color::srgb(r, g, b)
+}
+
+impl Palette {
+    pub fn event(&self, kind: &TimelineEvtKind) -> [f32; 3] {
+        match kind {
+            TimelineEvtKind::NodeUpsert => self.events[0],
+            TimelineEvtKind::NodeRemove => self.events[1],
+            TimelineEvtKind::EdgeUpsert => self.events[2],
+            TimelineEvtKind::EdgeRemove => self.events[3],
+            TimelineEvtKind::BatchBegin(_) | TimelineEvtKind::BatchEnd(_) => self.events[4],
+            TimelineEvtKind::EdgeModeChange => self.events[5],
+        }
+    }
+
+    pub fn event_color(&self, kind: &TimelineEvtKind) -> Color {
+        rgb(self.event(kind))
+    }
+
+    pub fn event_color_with_alpha(&self, kind: &TimelineEvtKind, alpha: f32) -> Color {
+        let [r, g, b] = self.event(kind);
+        Color::srgba(r, g, b, alpha)
+    }
+}
+
+pub fn egui_visuals(theme: Theme) -> egui::Visuals {
+    match theme {
+        Theme::Dark => egui::Visuals::dark(),
+        Theme::Light => egui::Visuals::light(),
+        Theme::HighContrast => {
+            let mut visuals = egui::Visuals::dark();
+            visuals.override_text_color = Some(egui::Color32::WHITE);
+            visuals.panel_fill = egui::Color32::BLACK;
+            visuals.window_fill = egui::Color32::BLACK;
+            visuals.extreme_bg_color = egui::Color32::BLACK;
+            visuals.window_stroke = egui::Stroke::new(1.0, egui::Color32::WHITE);
+            visuals.widgets.noninteractive.bg_stroke = egui::Stroke::new(1.0, egui::Color32::WHITE);
+            visuals.selection.bg_fill = egui::Color32::from_rgb(0, 90, 200);
+            visuals
+        }
+    }
+}
+
+/// Applies the configured theme to the clear color and egui whenever it changes.
+pub fn sync_theme(
+    st: Res<GraphState>,
+    mut clear: ResMut<ClearColor>,
+    mut contexts: EguiContexts,
+    mut applied: Local<Option<Theme>>,
+) {
+    let theme = st.cfg.theme;
+    if *applied == Some(theme) {
+        return;
+    }
+    clear.0 = rgb(palette(theme).clear);
+    contexts.ctx_mut().set_visuals(egui_visuals(theme));
+    *applied = Some(theme);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+        a.iter()
+            .zip(b.iter())
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum::<f32>()
+            .sqrt()
+    }
+
+    #[test]
+    fn event_colors_stay_distinguishable_in_every_theme() {
+        for theme in [Theme::Dark, Theme::Light, Theme::HighContrast] {
+            let p = palette(theme);
+            for (i, a) in p.events.iter().enumerate() {
+                assert!(distance(*a, p.clear) > 0.3, "{theme:?} event {i} vs clear");
+                for (j, b) in p.events.iter().enumerate().skip(i + 1) {
+                    assert!(distance(*a, *b) > 0.3, "{theme:?} events {i} vs {j}");
+                }
+            }
+            for line in [p.agg_edge, p.raw_edge, p.axis, p.lane_guide] {
+                assert!(distance(line, p.clear) > 0.3, "{theme:?} line vs clear");
+            }
+        }
+    }
+}
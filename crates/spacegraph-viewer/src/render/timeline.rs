@@ -2,14 +2,15 @@ use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::atomic::Ordering;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use crate::graph::model::{edge_explain, edge_kind_name};
-use crate::graph::timeline::timeline_lane_key;
+use crate::graph::timeline::{node_kind_lane, timeline_lane_key};
 use crate::graph::{GraphState, TimelineEvtKind};
+use crate::render::theme::{self, rgb, Palette};
 use crate::ui::tooltips::render_tooltip;
 use crate::ui::UiLayout;
-use crate::util::ids::{node_label_long, node_label_short};
+use crate::util::ids::{format_duration_in, node_label_long, node_label_short};
 
 #[derive(Clone)]
 enum TimelinePick {
@@ -44,36 +45,12 @@ fn draw_segment(gizmos: &mut Gizmos, a: Vec3, b: Vec3, color: Color) {
     }
 }
 
-fn event_color(kind: &TimelineEvtKind) -> Color {
-    match kind {
-        TimelineEvtKind::NodeUpsert => Color::srgb(0.2, 0.85, 0.3),
-        TimelineEvtKind::NodeRemove => Color::srgb(0.9, 0.2, 0.2),
-        TimelineEvtKind::EdgeUpsert => Color::srgb(0.2, 0.55, 0.9),
-        TimelineEvtKind::EdgeRemove => Color::srgb(0.9, 0.55, 0.2),
-        TimelineEvtKind::BatchBegin(_) | TimelineEvtKind::BatchEnd(_) => {
-            Color::srgb(0.75, 0.75, 0.75)
-        }
-    }
-}
-
-fn event_color_with_alpha(kind: &TimelineEvtKind, alpha: f32) -> Color {
-    match kind {
-        TimelineEvtKind::NodeUpsert => Color::srgba(0.2, 0.85, 0.3, alpha),
-        TimelineEvtKind::NodeRemove => Color::srgba(0.9, 0.2, 0.2, alpha),
-        TimelineEvtKind::EdgeUpsert => Color::srgba(0.2, 0.55, 0.9, alpha),
-        TimelineEvtKind::EdgeRemove => Color::srgba(0.9, 0.55, 0.2, alpha),
-        TimelineEvtKind::BatchBegin(_) | TimelineEvtKind::BatchEnd(_) => {
-            Color::srgba(0.75, 0.75, 0.75, alpha)
-        }
-    }
-}
-
-fn draw_event_marker(gizmos: &mut Gizmos, pos: Vec3, kind: &TimelineEvtKind) {
+fn draw_event_marker(gizmos: &mut Gizmos, pos: Vec3, kind: &TimelineEvtKind, palette: &Palette) {
     if !pos.is_finite() {
         return;
     }
     let s = 0.25;
-    let color = event_color(kind);
+    let color = palette.event_color(kind);
     match kind {
         TimelineEvtKind::NodeUpsert => {
             draw_segment(
@@ -122,6 +99,19 @@ fn draw_event_marker(gizmos: &mut Gizmos, pos: Vec3, kind: &TimelineEvtKind) {
                 );
             }
         }
+        TimelineEvtKind::EdgeModeChange => {
+            // diamond, so a reopened fd stands apart from plain upserts
+            let corners = [
+                Vec3::new(0.0, s, 0.0),
+                Vec3::new(s, 0.0, 0.0),
+                Vec3::new(0.0, -s, 0.0),
+                Vec3::new(-s, 0.0, 0.0),
+            ];
+            for i in 0..corners.len() {
+                let next = corners[(i + 1) % corners.len()];
+                draw_segment(gizmos, pos + corners[i], pos + next, color);
+            }
+        }
         TimelineEvtKind::BatchBegin(_) | TimelineEvtKind::BatchEnd(_) => {}
     }
 }
@@ -154,6 +144,8 @@ pub fn draw_timeline(
     };
 
     let now = st.timeline_now();
+    let units = st.cfg.duration_units;
+    let age_label = |age: f32| format_duration_in(Duration::from_secs_f32(age.max(0.0)), units);
     let window_dur = st.timeline.window;
     let scale = st.timeline.scale.max(0.001);
     let x_min = -window_dur.as_secs_f32() * scale;
@@ -172,12 +164,12 @@ pub fn draw_timeline(
         return;
     }
 
-    let lane_key_for_node = |id: &spacegraph_core::NodeId| {
-        st.model
-            .nodes
-            .get(id)
-            .map(timeline_lane_key)
-            .unwrap_or_else(|| format!("id:{}", id.0))
+    let group_by_kind = st.timeline.group_by_kind;
+    let lane_key_for_node = |id: &spacegraph_core::NodeId| match st.model.nodes.get(id) {
+        Some(node) if group_by_kind => node_kind_lane(node),
+        Some(node) => timeline_lane_key(node),
+        None if group_by_kind => "kind:unknown".to_string(),
+        None => format!("id:{}", id.0),
     };
     let mut lane_keys: BTreeSet<String> = BTreeSet::new();
     let mut event_entries: Vec<EventEntry> = Vec::new();
@@ -239,7 +231,9 @@ pub fn draw_timeline(
                     edge_kind: None,
                 });
             }
-            TimelineEvtKind::EdgeUpsert | TimelineEvtKind::EdgeRemove => {
+            TimelineEvtKind::EdgeUpsert
+            | TimelineEvtKind::EdgeRemove
+            | TimelineEvtKind::EdgeModeChange => {
                 let (Some(aid), Some(bid)) = (&ev.a, &ev.b) else {
                     continue;
                 };
@@ -294,12 +288,14 @@ pub fn draw_timeline(
         (-2.0, 2.0)
     };
 
+    let palette = theme::palette(st.cfg.theme);
+
     // axis line (now)
     draw_segment(
         &mut gizmos,
         Vec3::new(0.0, lane_y_min, 0.0),
         Vec3::new(0.0, lane_y_max, 0.0),
-        Color::WHITE,
+        rgb(palette.axis),
     );
 
     // lane guides
@@ -308,7 +304,7 @@ pub fn draw_timeline(
             &mut gizmos,
             Vec3::new(x_min, *y, 0.0),
             Vec3::new(x_max, *y, 0.0),
-            Color::srgb(0.7, 0.7, 0.7),
+            rgb(palette.lane_guide),
         );
     }
 
@@ -318,7 +314,7 @@ pub fn draw_timeline(
         let end_age = now.duration_since(*end).as_secs_f32();
         let x_start = -start_age * scale;
         let x_end = -end_age * scale;
-        let color = event_color(&TimelineEvtKind::BatchBegin(*id));
+        let color = palette.event_color(&TimelineEvtKind::BatchBegin(*id));
 
         draw_segment(
             &mut gizmos,
@@ -350,12 +346,12 @@ pub fn draw_timeline(
                     &mut gizmos,
                     *prev,
                     pos,
-                    event_color_with_alpha(&entry.kind, 0.35),
+                    palette.event_color_with_alpha(&entry.kind, 0.35),
                 );
             }
         }
         last_in_lane.insert(entry.lane_key.clone(), pos);
-        draw_event_marker(&mut gizmos, pos, &entry.kind);
+        draw_event_marker(&mut gizmos, pos, &entry.kind, palette);
 
         if allow_pick {
             if let (Some(cur), Some(screen)) = (cursor, camera.world_to_viewport(cam_tf, pos)) {
@@ -372,11 +368,13 @@ pub fn draw_timeline(
                                 if let Some(node) = st.model.nodes.get(aid) {
                                     lines.extend(node_label_long(node));
                                 }
-                                lines.push(format!("age: {:.2}s", entry.age));
+                                lines.push(format!("age: {}", age_label(entry.age)));
                                 lines.join("\n")
                             })
                         }
-                        TimelineEvtKind::EdgeUpsert | TimelineEvtKind::EdgeRemove => {
+                        TimelineEvtKind::EdgeUpsert
+                        | TimelineEvtKind::EdgeRemove
+                        | TimelineEvtKind::EdgeModeChange => {
                             if let (Some(aid), Some(bid)) = (entry.a.as_ref(), entry.b.as_ref()) {
                                 let ek = entry.edge_kind.as_ref();
                                 let kind_line = ek
@@ -389,14 +387,14 @@ pub fn draw_timeline(
                                     })
                                     .unwrap_or_else(|| "edge_kind: (none)".to_string());
                                 Some(format!(
-                                    "{:?}\nfrom: {} ({})\nto: {} ({})\n{}\nage: {:.2}s",
+                                    "{:?}\nfrom: {} ({})\nto: {} ({})\n{}\nage: {}",
                                     entry.kind,
                                     label_for_node(aid),
                                     aid.0,
                                     label_for_node(bid),
                                     bid.0,
                                     kind_line,
-                                    entry.age
+                                    age_label(entry.age)
                                 ))
                             } else {
                                 None
@@ -431,9 +429,12 @@ pub fn draw_timeline(
             if let (Some(cur), Some(screen)) = (cursor, camera.world_to_viewport(cam_tf, mid)) {
                 let d = screen.distance(cur);
                 if d < 14.0 {
-                    let duration = end.duration_since(*start).as_secs_f32();
-                    let label =
-                        format!("Batch {}\nspan: {:.2}s\nage: {:.2}s", id, duration, mid_age);
+                    let label = format!(
+                        "Batch {}\nspan: {}\nage: {}",
+                        id,
+                        format_duration_in(end.duration_since(*start), units),
+                        age_label(mid_age)
+                    );
                     if hover_best
                         .as_ref()
                         .map(|best| d < best.dist)
@@ -0,0 +1,96 @@
+use bevy_egui::egui;
+use spacegraph_core::Edge;
+
+use crate::graph::batch_log::BatchChange;
+use crate::graph::model::edge_kind_name;
+use crate::graph::{GraphState, ViewMode};
+use crate::ui::UiLayout;
+use crate::util::ids::format_duration_in;
+
+pub fn batch_diff_window(ctx: &egui::Context, st: &mut GraphState, layout: &UiLayout) {
+    if !st.ui.show_batch_diff {
+        return;
+    }
+
+    let mut content_rect = layout.content_rect;
+    if content_rect == egui::Rect::NOTHING {
+        content_rect = ctx.screen_rect();
+    }
+
+    let mut open = st.ui.show_batch_diff;
+    let mut jump = None;
+    egui::Window::new("Last Batches")
+        .collapsible(true)
+        .resizable(true)
+        .constrain_to(content_rect)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            if st.spatial.batch_log.recent.is_empty() {
+                ui.label("No completed batches yet");
+                return;
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for batch in st.spatial.batch_log.recent.iter().rev() {
+                    let title = format!(
+                        "Batch {} — {} nodes / {} edges touched, {} ago",
+                        batch.id,
+                        batch.touched_nodes.len(),
+                        batch.touched_edges.len(),
+                        format_duration_in(batch.ended.elapsed(), st.cfg.duration_units)
+                    );
+                    egui::CollapsingHeader::new(title)
+                        .id_source(("batch_diff", batch.id))
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            egui::Grid::new(("batch_diff_changes", batch.id))
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for change in batch.changes.iter() {
+                                        let (op, target, id) = match change {
+                                            BatchChange::NodeUpsert(id) => {
+                                                ("upsert", st.node_label_with_id(id), id)
+                                            }
+                                            BatchChange::NodeRemove(id) => {
+                                                ("remove", st.node_label_with_id(id), id)
+                                            }
+                                            BatchChange::EdgeUpsert(edge) => {
+                                                ("upsert", edge_label(st, edge), &edge.from)
+                                            }
+                                            BatchChange::EdgeRemove(edge) => {
+                                                ("remove", edge_label(st, edge), &edge.from)
+                                            }
+                                        };
+                                        ui.label(op);
+                                        ui.label(target);
+                                        // Removed nodes are gone from the model; nothing to jump to.
+                                        if st.model.nodes.contains_key(id)
+                                            && ui.small_button("Jump").clicked()
+                                        {
+                                            jump = Some(id.clone());
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                            if batch.omitted > 0 {
+                                ui.label(format!("… {} more changes not listed", batch.omitted));
+                            }
+                        });
+                }
+            });
+        });
+
+    st.ui.show_batch_diff = open;
+    if let Some(id) = jump {
+        st.ui.view_mode = ViewMode::Spatial;
+        st.request_jump(id);
+    }
+}
+
+fn edge_label(st: &GraphState, edge: &Edge) -> String {
+    format!(
+        "{} --[{}]--> {}",
+        st.node_label_with_id(&edge.from),
+        edge_kind_name(&edge.kind),
+        st.node_label_with_id(&edge.to)
+    )
+}
@@ -0,0 +1,168 @@
+use bevy_egui::egui;
+use spacegraph_core::{EdgeKind, NodeId};
+use std::sync::atomic::Ordering;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::graph::model::edge_kind_name;
+use crate::graph::timeline::{TimelineEvt, TimelineEvtKind};
+use crate::graph::GraphState;
+use crate::util::ids::node_label_short;
+
+/// Rows rendered per frame; older events stay in the timeline buffer.
+pub const EVENT_LOG_MAX_ROWS: usize = 300;
+/// Newest events considered when a filter is active.
+const EVENT_LOG_SCAN_LIMIT: usize = 5_000;
+
+/// Bottom panel listing recent timeline events, newest first.
+/// Returns the panel rect so the caller can shrink the content area.
+pub fn event_log_panel(ctx: &egui::Context, st: &mut GraphState) -> Option<egui::Rect> {
+    if !st.ui.show_event_log {
+        return None;
+    }
+
+    let now = Instant::now();
+    let wall = SystemTime::now();
+    let filter = st.ui.event_log_filter.to_lowercase();
+    let rows: Vec<(String, Option<NodeId>, Option<NodeId>)> = st
+        .timeline
+        .events
+        .iter()
+        .rev()
+        .take(EVENT_LOG_SCAN_LIMIT)
+        .map(|evt| {
+            (
+                event_log_line(st, evt, now, wall),
+                evt.a.clone(),
+                evt.b.clone(),
+            )
+        })
+        .filter(|(line, _, _)| filter.is_empty() || line.to_lowercase().contains(&filter))
+        .take(EVENT_LOG_MAX_ROWS)
+        .collect();
+
+    let resp = egui::TopBottomPanel::bottom("event_log")
+        .resizable(true)
+        .default_height(180.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Event Log").strong());
+                ui.add(
+                    egui::TextEdit::singleline(&mut st.ui.event_log_filter)
+                        .hint_text("filter")
+                        .desired_width(200.0),
+                );
+                ui.label(egui::RichText::new(format!("{} rows", rows.len())).weak());
+            });
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    for (line, a, b) in rows {
+                        let selected = a.is_some() && st.ui.selected_a == a;
+                        let label = egui::RichText::new(line).monospace();
+                        if ui.selectable_label(selected, label).clicked() && a.is_some() {
+                            st.ui.selected_a = a;
+                            st.ui.selected_b = b;
+                            st.needs_redraw.store(true, Ordering::Relaxed);
+                        }
+                    }
+                });
+        });
+    Some(resp.response.rect)
+}
+
+fn event_log_line(st: &GraphState, evt: &TimelineEvt, now: Instant, wall: SystemTime) -> String {
+    let at = wall
+        .checked_sub(now.saturating_duration_since(evt.ts))
+        .unwrap_or(wall);
+    let clock = format_clock(at);
+    let label = |id: &Option<NodeId>| match id {
+        Some(id) => st
+            .model
+            .nodes
+            .get(id)
+            .map(node_label_short)
+            .unwrap_or_else(|| id.0.clone()),
+        None => "?".to_string(),
+    };
+    let edge = |verb: &str| {
+        let kind = evt.edge_kind.as_ref().map(edge_kind_name).unwrap_or("edge");
+        format!(
+            "{clock} {verb} {kind} {} → {}",
+            label(&evt.a),
+            label(&evt.b)
+        )
+    };
+    match &evt.kind {
+        TimelineEvtKind::NodeUpsert => format!("{clock} UpsertNode {}", label(&evt.a)),
+        TimelineEvtKind::NodeRemove => format!("{clock} RemoveNode {}", label(&evt.a)),
+        TimelineEvtKind::EdgeUpsert => edge("UpsertEdge"),
+        TimelineEvtKind::EdgeRemove => edge("RemoveEdge"),
+        TimelineEvtKind::EdgeModeChange => match &evt.edge_kind {
+            Some(EdgeKind::Opens { fd, mode }) => {
+                format!("{} (fd {fd} now {mode})", edge("ModeChange"))
+            }
+            _ => edge("ModeChange"),
+        },
+        TimelineEvtKind::BatchBegin(id) => format!("{clock} BatchBegin #{id}"),
+        TimelineEvtKind::BatchEnd(id) => format!("{clock} BatchEnd #{id}"),
+    }
+}
+
+/// `HH:MM:SS` (UTC) of a wall-clock time.
+fn format_clock(t: SystemTime) -> String {
+    let secs = t
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+        % 86_400;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::{EdgeKind, Node};
+    use std::time::Duration;
+
+    #[test]
+    fn clock_wraps_to_time_of_day() {
+        let t = UNIX_EPOCH + Duration::from_secs(3 * 86_400 + 12 * 3600 + 3 * 60 + 1);
+        assert_eq!(format_clock(t), "12:03:01");
+    }
+
+    #[test]
+    fn edge_line_uses_short_labels_and_kind() {
+        let mut st = GraphState::default();
+        let proc_id = NodeId("h:process:pid:123".to_string());
+        st.model.nodes.insert(
+            proc_id.clone(),
+            Node::Process {
+                pid: 123,
+                ppid: 1,
+                exe: "/usr/bin/curl".to_string(),
+                cmdline: "curl".to_string(),
+                uid: 0,
+                rss_kb: 0,
+                utime_ticks: 0,
+                container: None,
+            },
+        );
+        let now = Instant::now();
+        let wall = UNIX_EPOCH + Duration::from_secs(12 * 3600 + 3 * 60 + 1);
+        let evt = TimelineEvt {
+            ts: now,
+            kind: TimelineEvtKind::EdgeUpsert,
+            a: Some(proc_id),
+            b: Some(NodeId("h:file:/etc/hosts".to_string())),
+            edge_kind: Some(EdgeKind::Opens {
+                fd: 3,
+                mode: "r".to_string(),
+            }),
+        };
+        assert_eq!(
+            event_log_line(&st, &evt, now, wall),
+            "12:03:01 UpsertEdge opens curl → h:file:/etc/hosts"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use bevy_egui::egui;
+
+use crate::graph::model::edge_class_name;
+use crate::graph::{GraphState, ViewMode};
+use crate::ui::UiLayout;
+
+const EXPLAIN_DEPTH_MAX: usize = 12;
+
+/// Persistent "why connected" view for the A/B selection, in every view mode.
+pub fn explain_window(ctx: &egui::Context, st: &mut GraphState, layout: &UiLayout) {
+    if !st.ui.show_explain {
+        return;
+    }
+
+    let mut content_rect = layout.content_rect;
+    if content_rect == egui::Rect::NOTHING {
+        content_rect = ctx.screen_rect();
+    }
+
+    let a = st.ui.selected_a.clone();
+    let b = st.ui.selected_b.clone();
+    let result = match (a.as_ref(), b.as_ref()) {
+        (Some(a), Some(b)) => {
+            let vis = st.visible_set_capped();
+            if vis.contains(a) && vis.contains(b) {
+                Some(st.explain_path_cached(a, b, &vis))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+
+    let mut open = st.ui.show_explain;
+    let mut jump = None;
+    let mut swap = false;
+    let mut depth = st.cfg.explain_max_depth;
+    let mut focus_paths = st.ui.explain_focus_paths;
+    egui::Window::new("Explain")
+        .collapsible(true)
+        .resizable(true)
+        .default_width(360.0)
+        .constrain_to(content_rect)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            egui::Grid::new("explain_endpoints").show(ui, |ui| {
+                for (name, id) in [("A", a.as_ref()), ("B", b.as_ref())] {
+                    ui.label(egui::RichText::new(name).strong());
+                    match id {
+                        Some(id) => {
+                            ui.label(st.node_label_with_id(id));
+                            if ui.small_button("Jump").clicked() {
+                                jump = Some(id.clone());
+                            }
+                        }
+                        None => {
+                            ui.label(egui::RichText::new("(none)").weak());
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(a.is_some() && b.is_some(), egui::Button::new("Swap"))
+                    .clicked()
+                {
+                    swap = true;
+                }
+                ui.add(egui::Slider::new(&mut depth, 1..=EXPLAIN_DEPTH_MAX).text("max depth"));
+            });
+            ui.checkbox(&mut focus_paths, "Paths from focus to selection")
+                .on_hover_text(
+                    "Highlights the shortest path from the focus node to each \
+                     Shift+drag selected node in the spatial view",
+                );
+            ui.separator();
+
+            match (&a, &b, &result) {
+                (None, _, _) | (_, None, _) => {
+                    ui.label(
+                        "Select A, then Shift+click B in the spatial view, \
+                         or click an edge in the timeline.",
+                    );
+                }
+                (Some(_), Some(_), None) => {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        "A or B is hidden by the current filter or focus.",
+                    );
+                }
+                (Some(_), Some(_), Some(None)) => {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!("No path within depth cap ({depth})."),
+                    );
+                }
+                (Some(_), Some(_), Some(Some(path))) if path.is_empty() => {
+                    ui.label("A and B are the same node.");
+                }
+                (Some(_), Some(_), Some(Some(path))) => {
+                    ui.label(format!("{} hops", path.len()));
+                    egui::ScrollArea::vertical()
+                        .max_height(320.0)
+                        .show(ui, |ui| {
+                            egui::Grid::new("explain_steps")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for (i, step) in path.iter().enumerate() {
+                                        ui.label(format!("{}.", i + 1));
+                                        ui.label(st.node_label_with_id(&step.from));
+                                        ui.label(format!("--[{}]-->", edge_class_name(step.class)));
+                                        ui.label(st.node_label_with_id(&step.to));
+                                        if ui.small_button("Jump").clicked() {
+                                            jump = Some(step.to.clone());
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                }
+            }
+        });
+
+    st.ui.show_explain = open;
+    st.cfg.explain_max_depth = depth;
+    st.ui.explain_focus_paths = focus_paths;
+    if swap {
+        std::mem::swap(&mut st.ui.selected_a, &mut st.ui.selected_b);
+    }
+    if let Some(id) = jump {
+        st.ui.view_mode = ViewMode::Spatial;
+        st.request_jump(id);
+    }
+}
@@ -15,8 +15,21 @@ pub fn help_overlay(mut contexts: EguiContexts, st: ResMut<GraphState>) {
             ui.label("Ctrl+P — Search");
             ui.label("Esc — Clear selection/focus, close overlays");
             ui.label("F — Focus selected");
+            ui.label("Shift+F — Toggle force layout");
+            ui.label("[ / ] — Fewer/more focus hops (1–10)");
+            ui.label("E — Toggle edges");
+            ui.label("P — Pin/unpin hovered node");
+            ui.label("N / Shift+N — Expand/collapse neighbors of hovered or selected node");
+            ui.label("Tab/→, Shift+Tab/← — Next/previous neighbor of selection");
+            ui.label("↓ / ↑ — Walk into selection / back to previous node");
             ui.label("Space — Pause timeline");
+            ui.label("L — Toggle event log");
+            ui.label("M — Toggle minimap");
+            ui.label("X — Toggle explain window (Shift+click sets node B)");
+            ui.label("Ctrl+C / Ctrl+Shift+C — Copy path / id of hovered or selected node");
+            ui.label("Shift+drag — Box-select nodes");
             ui.label("T — Toggle view (Spatial/Tree/Timeline)");
+            ui.label("F12 — Save screenshot (PNG)");
             ui.label("? — Toggle help");
         });
 }
@@ -1,11 +1,16 @@
-use bevy::prelude::Res;
+use bevy::prelude::{Res, ResMut};
 use bevy_egui::{egui, EguiContexts};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
+use crate::graph::model::{edge_explain, edge_kind_name, AggEdgeKey};
 use crate::graph::{GraphState, ViewMode};
 use crate::ui::{UiLayout, HUD_EDGE_PADDING, HUD_FALLBACK_Y_OFFSET, HUD_MIN_CONTENT_W};
+use crate::util::ids::{format_duration_in, host_color};
 
-pub fn hud_overlay(mut contexts: EguiContexts, st: Res<GraphState>, layout: Res<UiLayout>) {
+/// How long the HUD shows the outcome of a screenshot.
+const SCREENSHOT_NOTICE: Duration = Duration::from_secs(3);
+
+pub fn hud_overlay(mut contexts: EguiContexts, mut st: ResMut<GraphState>, layout: Res<UiLayout>) {
     let ctx = contexts.ctx_mut();
     let screen = ctx.screen_rect();
     let content_rect = if layout.content_rect.width() > 0.0 && layout.content_rect.height() > 0.0 {
@@ -20,12 +25,14 @@ pub fn hud_overlay(mut contexts: EguiContexts, st: Res<GraphState>, layout: Res<
         y = screen.min.y + HUD_EDGE_PADDING + HUD_FALLBACK_Y_OFFSET;
     }
 
+    let mut open_batch_diff = false;
     egui::Area::new("hud".into())
         .order(egui::Order::Foreground)
         .fixed_pos(egui::pos2(x, y))
         .show(ctx, |ui| {
             ui.group(|ui| {
                 let now = Instant::now();
+                let units = st.cfg.duration_units;
                 let mut snapshot_seen = false;
                 let mut live_seen = false;
                 let mut last_activity: Option<Instant> = None;
@@ -44,7 +51,7 @@ pub fn hud_overlay(mut contexts: EguiContexts, st: Res<GraphState>, layout: Res<
                     }
                 }
                 let last_label = last_activity
-                    .map(|ts| format!("{:.1}s ago", now.duration_since(ts).as_secs_f32()))
+                    .map(|ts| format!("{} ago", format_duration_in(now.duration_since(ts), units)))
                     .unwrap_or_else(|| "—".to_string());
                 ui.label(format!("FPS: {:.0}", st.perf.fps));
                 ui.label(format!(
@@ -55,10 +62,32 @@ pub fn hud_overlay(mut contexts: EguiContexts, st: Res<GraphState>, layout: Res<
                     "Edges (raw/agg): {} / {}",
                     st.perf.visible_raw_edges, st.perf.visible_agg_edges
                 ));
+                if st.perf.edges_capped > 0 {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "edges capped at {} ({} hidden)",
+                            st.cfg.max_rendered_edges, st.perf.edges_capped
+                        ))
+                        .color(egui::Color32::YELLOW),
+                    );
+                }
+                ui.label(format!("Components: {}", st.perf.component_count));
                 ui.label(format!("Event rate: {:.1}/s", st.perf.event_rate));
                 ui.label(format!("Total msgs: {}", st.perf.event_total));
+                ui.label(format!(
+                    "Timeline buffer: {} / {} (~{:.1} MiB)",
+                    st.timeline.events.len(),
+                    st.timeline.max_events,
+                    st.timeline.buffer_bytes() as f64 / (1024.0 * 1024.0)
+                ));
                 if let Some(id) = st.spatial.last_batch_id {
-                    ui.label(format!("Last batch: {}", id));
+                    if ui
+                        .link(format!("Last batch: {}", id))
+                        .on_hover_text("Show what the recent batches changed")
+                        .clicked()
+                    {
+                        open_batch_diff = true;
+                    }
                 }
                 ui.label(format!(
                     "Data flow: snapshot: {} | live: {} | last: {}",
@@ -66,6 +95,19 @@ pub fn hud_overlay(mut contexts: EguiContexts, st: Res<GraphState>, layout: Res<
                     if live_seen { "yes" } else { "no" },
                     last_label
                 ));
+                let mut warnings: Vec<(&String, String)> = st
+                    .net
+                    .streams
+                    .iter()
+                    .filter_map(|(name, s)| Some((name, s.protocol_warning()?)))
+                    .collect();
+                warnings.sort();
+                for (name, warning) in warnings {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {name}: {warning}"))
+                            .color(egui::Color32::YELLOW),
+                    );
+                }
                 ui.label(format!(
                     "Mode: {}",
                     match st.ui.view_mode {
@@ -74,6 +116,43 @@ pub fn hud_overlay(mut contexts: EguiContexts, st: Res<GraphState>, layout: Res<
                         ViewMode::Timeline => "Timeline",
                     }
                 ));
+                if st.spatial.host_colors.len() > 1 {
+                    ui.label("Hosts:");
+                    for (host, idx) in st.spatial.host_colors.iter() {
+                        let [r, g, b] = host_color(*idx);
+                        let color = egui::Color32::from_rgb(
+                            (r * 255.0) as u8,
+                            (g * 255.0) as u8,
+                            (b * 255.0) as u8,
+                        );
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new("●").color(color));
+                            ui.label(host);
+                        });
+                    }
+                }
+                if let Some(edge) = st.ui.selected_edge.as_ref() {
+                    ui.label(format!(
+                        "Edge: {} --[{}]--> {}",
+                        st.node_label_with_id(&edge.from),
+                        edge_kind_name(&edge.kind),
+                        st.node_label_with_id(&edge.to)
+                    ));
+                    ui.label(edge_explain(&edge.kind));
+                    if let Some(agg) = st.model.agg_edge(&AggEdgeKey::new(edge)) {
+                        ui.label(format!(
+                            "Seen {}× | first: {} ago | last: {} ago",
+                            agg.stats.count,
+                            format_duration_in(now.duration_since(agg.stats.first_ts), units),
+                            format_duration_in(now.duration_since(agg.stats.last_ts), units)
+                        ));
+                    }
+                }
+                if let Some((notice, at)) = st.ui.screenshot_notice.as_ref() {
+                    if now.duration_since(*at) < SCREENSHOT_NOTICE {
+                        ui.label(notice);
+                    }
+                }
                 if st.snapshot_loaded
                     && !st.live_events_seen
                     && !st.model.nodes.is_empty()
@@ -83,4 +162,7 @@ pub fn hud_overlay(mut contexts: EguiContexts, st: Res<GraphState>, layout: Res<
                 }
             });
         });
+    if open_batch_diff {
+        st.ui.show_batch_diff = true;
+    }
 }
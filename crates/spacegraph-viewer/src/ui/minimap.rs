@@ -0,0 +1,266 @@
+use bevy::prelude::{Local, Query, Res, ResMut, Vec2, Vec3};
+use bevy_egui::{egui, EguiContexts};
+use spacegraph_core::NodeId;
+
+use crate::graph::{GraphState, ViewMode};
+use crate::render::camera::CameraRig;
+use crate::ui::{UiLayout, HUD_EDGE_PADDING};
+
+const MINIMAP_SIZE: f32 = 180.0;
+/// Above this many visible nodes only an evenly spaced sample is plotted.
+const MINIMAP_MAX_POINTS: usize = 2000;
+/// Matches the default vertical field of view of the 3D camera.
+const CAMERA_HALF_FOV: f32 = std::f32::consts::FRAC_PI_8;
+
+/// Top-down (x/z) mapping between world positions and the inset rect.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct MinimapProjection {
+    min: Vec2,
+    max: Vec2,
+}
+
+impl MinimapProjection {
+    /// Square bounds around `points`, so the inset keeps the aspect ratio.
+    pub(crate) fn fit(points: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+        let mut min = Vec2::splat(f32::INFINITY);
+        let mut max = Vec2::splat(f32::NEG_INFINITY);
+        for p in points {
+            let xz = Vec2::new(p.x, p.z);
+            min = min.min(xz);
+            max = max.max(xz);
+        }
+        if !min.x.is_finite() {
+            return None;
+        }
+        let center = (min + max) * 0.5;
+        // A little margin, and room around a single node.
+        let half = ((max - min).max_element() * 0.55).max(1.0);
+        Some(Self {
+            min: center - Vec2::splat(half),
+            max: center + Vec2::splat(half),
+        })
+    }
+
+    pub(crate) fn to_map(self, pos: Vec3, rect: egui::Rect) -> egui::Pos2 {
+        let t = (Vec2::new(pos.x, pos.z) - self.min) / (self.max - self.min);
+        egui::pos2(
+            rect.min.x + t.x * rect.width(),
+            rect.min.y + t.y * rect.height(),
+        )
+    }
+
+    /// World x/z under an inset point.
+    pub(crate) fn to_world(self, p: egui::Pos2, rect: egui::Rect) -> Vec2 {
+        let t = Vec2::new(
+            (p.x - rect.min.x) / rect.width(),
+            (p.y - rect.min.y) / rect.height(),
+        );
+        self.min + t * (self.max - self.min)
+    }
+
+    fn scale(&self, rect: egui::Rect) -> f32 {
+        rect.width() / (self.max.x - self.min.x)
+    }
+}
+
+pub(crate) fn sample_stride(count: usize, max_points: usize) -> usize {
+    count.div_ceil(max_points.max(1)).max(1)
+}
+
+/// Placed visible nodes and their bounds, rebuilt only after the scene was redrawn.
+#[derive(Default)]
+pub(crate) struct MinimapCache {
+    redraw_seq: Option<u64>,
+    points: Vec<(NodeId, Vec3)>,
+    proj: Option<MinimapProjection>,
+}
+
+impl MinimapCache {
+    pub(crate) fn refresh(&mut self, st: &mut GraphState) {
+        if self.redraw_seq == Some(st.spatial.redraw_seq) {
+            return;
+        }
+        self.redraw_seq = Some(st.spatial.redraw_seq);
+        let vis = st.visible_set_capped();
+        self.points.clear();
+        self.points.extend(
+            vis.into_iter()
+                .filter_map(|id| st.spatial.positions.get(&id).map(|p| (id.clone(), *p))),
+        );
+        // Stable sampling, otherwise the plotted subset flickers between rebuilds.
+        self.points.sort_by(|a, b| a.0 .0.cmp(&b.0 .0));
+        self.proj = MinimapProjection::fit(self.points.iter().map(|(_, p)| *p));
+    }
+}
+
+pub fn minimap_overlay(
+    mut contexts: EguiContexts,
+    mut st: ResMut<GraphState>,
+    layout: Res<UiLayout>,
+    cam_q: Query<&CameraRig>,
+    mut cache: Local<MinimapCache>,
+) {
+    if !st.ui.show_minimap || st.ui.view_mode != ViewMode::Spatial {
+        return;
+    }
+    cache.refresh(&mut st);
+    let Some(proj) = cache.proj else {
+        return;
+    };
+    let points = &cache.points;
+
+    let ctx = contexts.ctx_mut();
+    let content_rect = if layout.content_rect.width() > 0.0 && layout.content_rect.height() > 0.0 {
+        layout.content_rect
+    } else {
+        ctx.screen_rect()
+    };
+    let origin = content_rect.right_bottom()
+        - egui::vec2(
+            MINIMAP_SIZE + HUD_EDGE_PADDING,
+            MINIMAP_SIZE + HUD_EDGE_PADDING,
+        );
+
+    let mut clicked_at = None;
+    egui::Area::new("minimap".into())
+        .order(egui::Order::Foreground)
+        .fixed_pos(origin)
+        .show(ctx, |ui| {
+            let (rect, response) = ui
+                .allocate_exact_size(egui::vec2(MINIMAP_SIZE, MINIMAP_SIZE), egui::Sense::click());
+            let painter = ui.painter_at(rect);
+            let visuals = ui.visuals();
+            painter.rect_filled(rect, 4.0, visuals.extreme_bg_color.gamma_multiply(0.85));
+            painter.rect_stroke(rect, 4.0, visuals.window_stroke);
+
+            let dot = visuals.text_color().gamma_multiply(0.7);
+            let stride = sample_stride(points.len(), MINIMAP_MAX_POINTS);
+            for (_, pos) in points.iter().step_by(stride) {
+                painter.circle_filled(proj.to_map(*pos, rect), 1.5, dot);
+            }
+            if let Some(pos) = st
+                .ui
+                .selected
+                .as_ref()
+                .and_then(|id| st.spatial.positions.get(id))
+            {
+                painter.circle_stroke(
+                    proj.to_map(*pos, rect),
+                    4.0,
+                    egui::Stroke::new(1.5, visuals.selection.bg_fill),
+                );
+            }
+
+            if let Ok(rig) = cam_q.get_single() {
+                let view = egui::Stroke::new(1.5, visuals.warn_fg_color);
+                let center = proj.to_map(rig.target, rect);
+                let reach = rig.distance * CAMERA_HALF_FOV.tan() * proj.scale(rect);
+                painter.circle_stroke(center, reach.max(3.0), view);
+                painter.line_segment([proj.to_map(rig.eye(), rect), center], view);
+                painter.circle_filled(center, 2.5, visuals.warn_fg_color);
+            }
+
+            if response.clicked() {
+                clicked_at = response
+                    .interact_pointer_pos()
+                    .map(|p| proj.to_world(p, rect));
+            }
+            response.on_hover_text("Click to jump to the nearest node");
+        });
+
+    if let Some(target) = clicked_at {
+        let nearest = points
+            .iter()
+            .min_by(|a, b| {
+                let da = Vec2::new(a.1.x, a.1.z).distance_squared(target);
+                let db = Vec2::new(b.1.x, b.1.z).distance_squared(target);
+                da.total_cmp(&db)
+            })
+            .map(|(id, _)| id.clone());
+        if let Some(id) = nearest {
+            st.request_jump(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect() -> egui::Rect {
+        egui::Rect::from_min_size(egui::pos2(100.0, 50.0), egui::vec2(200.0, 200.0))
+    }
+
+    #[test]
+    fn projection_is_square_and_roundtrips() {
+        let proj = MinimapProjection::fit([Vec3::new(-10.0, 3.0, 0.0), Vec3::new(10.0, -7.0, 4.0)])
+            .expect("bounds");
+        // The wide x range decides both axes; y is ignored.
+        let size = proj.max - proj.min;
+        assert_eq!(size.x, size.y);
+        assert!((size.x - 22.0).abs() < 1e-4);
+
+        let p = Vec3::new(2.5, 0.0, 1.0);
+        let back = proj.to_world(proj.to_map(p, rect()), rect());
+        assert!(back.distance(Vec2::new(2.5, 1.0)) < 1e-4);
+
+        let center = proj.to_map(Vec3::new(0.0, 0.0, 2.0), rect());
+        assert!(center.distance(rect().center()) < 1e-4);
+    }
+
+    #[test]
+    fn projection_handles_empty_and_single_points() {
+        assert!(MinimapProjection::fit(std::iter::empty()).is_none());
+        let proj = MinimapProjection::fit([Vec3::new(5.0, 0.0, 5.0)]).expect("bounds");
+        assert!(proj.max.x > proj.min.x);
+        assert!(
+            proj.to_map(Vec3::new(5.0, 0.0, 5.0), rect())
+                .distance(rect().center())
+                < 1e-4
+        );
+    }
+
+    #[test]
+    fn sampling_stride_caps_plotted_points() {
+        assert_eq!(sample_stride(0, 2000), 1);
+        assert_eq!(sample_stride(1500, 2000), 1);
+        assert_eq!(sample_stride(4001, 2000), 3);
+        assert!(10_000usize.div_ceil(sample_stride(10_000, 2000)) <= 2000);
+    }
+
+    #[test]
+    fn cached_points_are_rebuilt_only_after_a_redraw() {
+        let mut st = GraphState::default();
+        let now = std::time::Instant::now();
+        let id = |s: &str| NodeId(s.to_string());
+        for (name, x) in [("b", 10.0), ("a", -10.0)] {
+            st.model.upsert_node(
+                id(name),
+                spacegraph_core::Node::User {
+                    uid: 0,
+                    name: name.to_string(),
+                },
+                now,
+            );
+            st.spatial
+                .positions
+                .insert(id(name), Vec3::new(x, 0.0, 0.0));
+        }
+        let mut cache = MinimapCache::default();
+        cache.refresh(&mut st);
+        let ids: Vec<_> = cache.points.iter().map(|(id, _)| id.0.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+
+        // Moves between redraws are picked up with the next one, not every frame.
+        st.spatial
+            .positions
+            .insert(id("b"), Vec3::new(30.0, 0.0, 0.0));
+        cache.refresh(&mut st);
+        assert_eq!(cache.points[1].1.x, 10.0);
+
+        st.spatial.redraw_seq += 1;
+        cache.refresh(&mut st);
+        assert_eq!(cache.points[1].1.x, 30.0);
+        assert!(cache.proj.expect("bounds").max.x > 30.0);
+    }
+}
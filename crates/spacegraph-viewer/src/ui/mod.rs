@@ -1,11 +1,16 @@
+pub mod batch_diff;
+pub mod event_log;
+pub mod explain;
 pub mod help;
 pub mod hud;
 pub mod layout;
+pub mod minimap;
 pub mod panel;
 pub mod search;
 pub mod settings_agents;
 pub mod settings_paths;
 pub mod shortcuts;
+pub mod stats;
 pub mod tooltips;
 
 pub const HUD_EDGE_PADDING: f32 = 10.0;
@@ -15,5 +20,6 @@ pub const HUD_FALLBACK_Y_OFFSET: f32 = 220.0;
 pub use help::help_overlay;
 pub use hud::hud_overlay;
 pub use layout::UiLayout;
+pub use minimap::minimap_overlay;
 pub use panel::ui_panel;
 pub use shortcuts::handle_shortcuts;
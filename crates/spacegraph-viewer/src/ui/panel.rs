@@ -1,9 +1,21 @@
+use crate::graph::state::{missing_capability_notes, NetStreamStatus};
+use crate::graph::timeline::MIN_TIMELINE_EVENTS;
+use crate::graph::tree;
 use crate::graph::{GraphState, ViewMode};
-use crate::ui::{settings_agents, settings_paths, UiLayout};
-use crate::util::config::{self, LodEdgesMode, ViewerConfig};
+use crate::ui::tooltips::copy_buttons;
+use crate::ui::{batch_diff, event_log, explain, settings_agents, settings_paths, stats, UiLayout};
+use crate::util::config::{
+    self, DemoScenario, DurationUnits, GcMode, LabelMode, LayoutMode, LayoutProfile, LodEdgesMode,
+    Theme, ViewerConfig,
+};
+use crate::util::export;
+use crate::util::record::{self, Recorder};
 use bevy::prelude::ResMut;
 use bevy_egui::{egui, EguiContexts};
+use spacegraph_core::Node;
+use std::path::Path;
 use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
 
 pub fn ui_panel(
     mut contexts: EguiContexts,
@@ -35,6 +47,52 @@ pub fn ui_panel(
                 } else {
                     ui.label(format!("{active} Agents connected"));
                 }
+                if active > 0 {
+                    let mut names: Vec<&String> = st
+                        .net
+                        .streams
+                        .iter()
+                        .filter(|(_, s)| s.status == NetStreamStatus::Connected)
+                        .map(|(name, _)| name)
+                        .collect();
+                    names.sort();
+                    egui::Grid::new("panel_agents_rate")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label(egui::RichText::new("Agent").strong());
+                            ui.label(egui::RichText::new("Msgs/s").strong());
+                            ui.label(egui::RichText::new("KB/s").strong());
+                            ui.end_row();
+                            for name in names {
+                                let stream = &st.net.streams[name];
+                                ui.label(name.as_str());
+                                let rate = ui.label(format!("{:.1}", stream.msg_rate));
+                                if !stream.msg_counts.is_empty() {
+                                    rate.on_hover_ui(|ui| {
+                                        let lines = stream
+                                            .msg_counts
+                                            .breakdown(Instant::now(), st.net.msg_window);
+                                        ui.label(lines.join("\n"));
+                                    });
+                                }
+                                ui.label(format!("{:.1}", stream.bytes_per_sec / 1024.0));
+                                ui.end_row();
+                            }
+                        });
+                }
+                let notes = st
+                    .net
+                    .merged_caps()
+                    .map(|caps| missing_capability_notes(&caps))
+                    .unwrap_or_default();
+                if !notes.is_empty() {
+                    ui.label(
+                        egui::RichText::new(notes.join(" · "))
+                            .small()
+                            .color(egui::Color32::YELLOW),
+                    )
+                    .on_hover_text("No connected agent provides this data");
+                }
                 if ui.button("Manage Agents…").clicked() {
                     st.ui.show_agent_manager = true;
                 }
@@ -77,6 +135,85 @@ pub fn ui_panel(
                         st.ui.tree_file_zoom_threshold
                     ));
                 }
+                if ui
+                    .checkbox(&mut st.cfg.show_only_active, "Only active")
+                    .on_hover_text("Show glowing nodes and their neighbors only")
+                    .changed()
+                {
+                    st.spatial.dirty_layout = true;
+                    st.needs_redraw.store(true, Ordering::Relaxed);
+                }
+                if ui
+                    .checkbox(&mut st.cfg.spatial_window_enabled, "Active within window")
+                    .on_hover_text(
+                        "Spatial view: only nodes seen (or whose edges were seen) recently",
+                    )
+                    .changed()
+                {
+                    st.spatial.dirty_layout = true;
+                    st.needs_redraw.store(true, Ordering::Relaxed);
+                }
+                if st.cfg.spatial_window_enabled {
+                    let mut secs = st.cfg.spatial_window.as_secs();
+                    if ui
+                        .add(
+                            egui::Slider::new(&mut secs, 1..=3600)
+                                .text("window (s)")
+                                .logarithmic(true),
+                        )
+                        .changed()
+                    {
+                        st.cfg.spatial_window = Duration::from_secs(secs);
+                        st.spatial.dirty_layout = true;
+                        st.needs_redraw.store(true, Ordering::Relaxed);
+                    }
+                }
+                if ui
+                    .checkbox(&mut st.cfg.show_threads, "Show threads")
+                    .on_hover_text("Thread nodes from agents started with --threads")
+                    .changed()
+                {
+                    st.spatial.dirty_layout = true;
+                    st.needs_redraw.store(true, Ordering::Relaxed);
+                }
+                if ui
+                    .checkbox(&mut st.cfg.merge_files_by_path, "Merge files by path")
+                    .on_hover_text("Draw the same path on several hosts as one node")
+                    .changed()
+                {
+                    st.needs_redraw.store(true, Ordering::Relaxed);
+                }
+                if ui
+                    .checkbox(&mut st.cfg.follow_newest, "Follow newest process")
+                    .on_hover_text("Focus each new process; clicking a node turns this off")
+                    .changed()
+                {
+                    st.follow.reset();
+                }
+                ui.checkbox(&mut st.cfg.limit_fps_when_idle, "Limit FPS when idle")
+                    .on_hover_text("Update only on input or new events while nothing moves");
+                ui.checkbox(&mut st.ui.show_event_log, "Event log (L)");
+                ui.checkbox(&mut st.ui.show_minimap, "Minimap (M)");
+                if ui
+                    .checkbox(&mut st.cfg.color_by_category, "Color files by category")
+                    .changed()
+                {
+                    st.needs_redraw.store(true, Ordering::Relaxed);
+                }
+                let fd_edges = st.net.merged_caps().is_none_or(|caps| caps.fd_edges);
+                if ui
+                    .add_enabled(
+                        fd_edges,
+                        egui::Checkbox::new(
+                            &mut st.cfg.color_by_open_files,
+                            "Color processes by open files",
+                        ),
+                    )
+                    .on_disabled_hover_text("no open-file edges from the connected agents")
+                    .changed()
+                {
+                    st.needs_redraw.store(true, Ordering::Relaxed);
+                }
                 let demo_allowed = st.net.active_connection_count() == 0
                     && (st.model.nodes.is_empty() || st.demo_loaded);
                 let mut demo_mode = st.cfg.demo_mode;
@@ -92,6 +229,136 @@ pub fn ui_panel(
                 if !demo_allowed && !demo_mode {
                     ui.label("Demo mode requires no active agents and an empty graph.");
                 }
+                if st.cfg.demo_mode {
+                    ui.horizontal(|ui| {
+                        ui.label("Scenario");
+                        let before = st.cfg.demo_scenario;
+                        egui::ComboBox::from_id_source("demo_scenario")
+                            .selected_text(st.cfg.demo_scenario.as_str())
+                            .show_ui(ui, |ui| {
+                                for scenario in DemoScenario::ALL {
+                                    ui.selectable_value(
+                                        &mut st.cfg.demo_scenario,
+                                        scenario,
+                                        scenario.as_str(),
+                                    );
+                                }
+                            });
+                        if st.cfg.demo_scenario != before {
+                            st.restart_demo();
+                        }
+                    });
+                    let random = st.cfg.demo_scenario == DemoScenario::Random;
+                    ui.add_enabled_ui(random, |ui| {
+                        ui.add(
+                            egui::Slider::new(&mut st.cfg.demo_rate, 0.0..=1000.0)
+                                .logarithmic(true)
+                                .text("Demo events/s"),
+                        );
+                        ui.add(
+                            egui::Slider::new(&mut st.cfg.demo_entities, 5..=5000)
+                                .logarithmic(true)
+                                .text("Demo entities"),
+                        )
+                        .on_hover_text("Processes and files the churn steers towards");
+                    });
+                    ui.horizontal(|ui| {
+                        let mut fixed = st.cfg.demo_seed.is_some();
+                        if ui.checkbox(&mut fixed, "Fixed seed").changed() {
+                            st.cfg.demo_seed = fixed.then_some(0);
+                        }
+                        if let Some(seed) = st.cfg.demo_seed.as_mut() {
+                            ui.add(egui::DragValue::new(seed));
+                        }
+                        if ui.button("Restart").clicked() {
+                            st.restart_demo();
+                        }
+                    });
+                }
+                let import_allowed = st.net.active_connection_count() == 0;
+                if ui
+                    .add_enabled(import_allowed, egui::Button::new("Load Snapshot…"))
+                    .on_hover_text(format!(
+                        "Loads {} from the working directory",
+                        export::JSON_EXPORT_FILE
+                    ))
+                    .clicked()
+                {
+                    let path = Path::new(export::JSON_EXPORT_FILE);
+                    st.ui.import_notice = Some(match export::read_snapshot(path) {
+                        Ok(msg) => {
+                            if st.import_snapshot(msg) {
+                                format!("loaded {}", export::JSON_EXPORT_FILE)
+                            } else {
+                                "disconnect agents before loading a snapshot".to_string()
+                            }
+                        }
+                        Err(err) => format!("load failed: {err}"),
+                    });
+                }
+                if st.replay.is_none() {
+                    if ui
+                        .add_enabled(import_allowed, egui::Button::new("Replay Recording…"))
+                        .on_hover_text(format!(
+                            "Plays {} from the working directory",
+                            record::RECORD_FILE
+                        ))
+                        .clicked()
+                    {
+                        let path = Path::new(record::RECORD_FILE);
+                        st.ui.import_notice = Some(match record::read_recording(path) {
+                            Ok(lines) => {
+                                if st.start_replay(lines) {
+                                    format!("replaying {}", record::RECORD_FILE)
+                                } else {
+                                    "disconnect agents before replaying".to_string()
+                                }
+                            }
+                            Err(err) => format!("replay failed: {err:#}"),
+                        });
+                    }
+                } else {
+                    replay_controls(ui, st.as_mut());
+                }
+                if let Some(diff) = st.diff.as_ref() {
+                    if diff.is_empty() {
+                        ui.label("Diff: no changes against the baseline");
+                    } else {
+                        ui.label(format!(
+                            "Diff: +{} -{} ~{} nodes, +{} -{} edges",
+                            diff.added_nodes.len(),
+                            diff.removed_nodes.len(),
+                            diff.changed_nodes.len(),
+                            diff.added_edges.len(),
+                            diff.removed_edges.len()
+                        ));
+                    }
+                    if ui.button("Clear Diff").clicked() {
+                        st.clear_snapshot_diff();
+                    }
+                } else if ui
+                    .add_enabled(import_allowed, egui::Button::new("Compare Snapshot…"))
+                    .on_hover_text(format!(
+                        "Diffs the graph against {} from the working directory",
+                        export::DIFF_BASELINE_FILE
+                    ))
+                    .clicked()
+                {
+                    let path = Path::new(export::DIFF_BASELINE_FILE);
+                    st.ui.import_notice = Some(match export::read_snapshot(path) {
+                        Ok(msg) => {
+                            if st.compare_with_snapshot(msg) {
+                                format!("comparing with {}", export::DIFF_BASELINE_FILE)
+                            } else {
+                                "disconnect agents before comparing snapshots".to_string()
+                            }
+                        }
+                        Err(err) => format!("compare failed: {err}"),
+                    });
+                }
+                if let Some(msg) = st.ui.import_notice.as_ref() {
+                    ui.label(egui::RichText::new(msg).weak());
+                }
 
                 if st.ui.view_mode == ViewMode::Timeline {
                     ui.add_space(6.0);
@@ -109,6 +376,21 @@ pub fn ui_panel(
                     });
                     st.timeline.window = std::time::Duration::from_secs(w as u64);
 
+                    let mut max_events = st.timeline.max_events;
+                    ui.horizontal(|ui| {
+                        ui.label("Event buffer");
+                        ui.add(
+                            egui::Slider::new(&mut max_events, MIN_TIMELINE_EVENTS..=200_000)
+                                .logarithmic(true),
+                        );
+                    });
+                    if max_events != st.timeline.max_events {
+                        st.timeline.max_events = max_events;
+                        let now = st.timeline_now();
+                        st.timeline.trim(now);
+                        st.needs_redraw.store(true, Ordering::Relaxed);
+                    }
+
                     ui.horizontal(|ui| {
                         ui.label("X scale");
                         ui.add(egui::Slider::new(&mut st.timeline.scale, 0.05..=1.5));
@@ -130,14 +412,83 @@ pub fn ui_panel(
                                 0.0..=window_secs,
                             ));
                         });
+                        ui.horizontal(|ui| {
+                            let label = if st.timeline.playing { "Stop" } else { "Play" };
+                            if ui
+                                .add_enabled(
+                                    st.timeline.playing || st.timeline.scrub_seconds > 0.0,
+                                    egui::Button::new(label),
+                                )
+                                .clicked()
+                            {
+                                st.timeline.playing = !st.timeline.playing;
+                            }
+                            ui.add(
+                                egui::Slider::new(&mut st.timeline.playback_speed, 0.1..=10.0)
+                                    .logarithmic(true)
+                                    .text("speed"),
+                            );
+                        });
                         if ui.button("Reset scrub").clicked() {
                             st.timeline.scrub_seconds = 0.0;
+                            st.timeline.playing = false;
                         }
                         st.timeline.scrub_seconds =
                             st.timeline.scrub_seconds.clamp(0.0, window_secs);
                     }
                     ui.label(format!("events buffered: {}", st.timeline.events.len()));
-                    ui.label("Lanes: grouped by entity (pid/path).");
+                    let mut history = st.timeline.history.enabled;
+                    if ui
+                        .checkbox(&mut history, "Model history (memory heavy)")
+                        .changed()
+                    {
+                        st.timeline.history.set_enabled(history);
+                        st.ui.history_notice = None;
+                    }
+                    if st.timeline.history.enabled {
+                        ui.label(format!(
+                            "history entries: {}",
+                            st.timeline.history.entry_count()
+                        ));
+                        let at = st.timeline_now();
+                        if let Some(sample) = st.timeline.history.count_at(at) {
+                            ui.label(format!(
+                                "sampled: {} nodes, {} edges",
+                                sample.nodes, sample.edges
+                            ));
+                        }
+                        if paused && ui.button("Reconstruct at scrub").clicked() {
+                            st.ui.history_notice = st.reconstruct_at(at).map(|(nodes, edges)| {
+                                let exact = if st.timeline.history.covers(at) {
+                                    ""
+                                } else {
+                                    " (partial: older history dropped)"
+                                };
+                                format!(
+                                    "-{:.1}s: {} nodes, {} edges{exact}",
+                                    st.timeline.scrub_seconds,
+                                    nodes.len(),
+                                    edges.len()
+                                )
+                            });
+                        }
+                        if let Some(msg) = st.ui.history_notice.as_ref() {
+                            ui.label(egui::RichText::new(msg).weak());
+                        }
+                    }
+                    let mut group_by_kind = st.timeline.group_by_kind;
+                    if ui
+                        .checkbox(&mut group_by_kind, "Group lanes by kind")
+                        .changed()
+                    {
+                        st.timeline.group_by_kind = group_by_kind;
+                        st.needs_redraw.store(true, Ordering::Relaxed);
+                    }
+                    if st.timeline.group_by_kind {
+                        ui.label("Lanes: grouped by kind (process/file/user).");
+                    } else {
+                        ui.label("Lanes: grouped by entity (pid/path).");
+                    }
                     ui.label("Hover an event point → tooltip.");
 
                     ui.add_space(6.0);
@@ -163,17 +514,23 @@ pub fn ui_panel(
                             egui::RichText::new("Batch span")
                                 .color(egui::Color32::from_rgb(191, 191, 191)),
                         );
+                        ui.label(
+                            egui::RichText::new("fd mode change")
+                                .color(egui::Color32::from_rgb(204, 77, 230)),
+                        );
                     });
 
                     ui.add_space(6.0);
                     ui.label(egui::RichText::new("Selection").strong());
                     if let Some(id) = st.ui.selected_a.as_ref() {
                         ui.label(format!("A: {}", st.node_label_with_id(id)));
+                        ui.horizontal(|ui| copy_buttons(ui, id, st.model.nodes.get(id)));
                     } else {
                         ui.label("A: (none)");
                     }
                     if let Some(id) = st.ui.selected_b.as_ref() {
                         ui.label(format!("B: {}", st.node_label_with_id(id)));
+                        ui.horizontal(|ui| copy_buttons(ui, id, st.model.nodes.get(id)));
                     } else {
                         ui.label("B: (none)");
                     }
@@ -193,18 +550,47 @@ pub fn ui_panel(
                 ui.horizontal(|ui| {
                     ui.checkbox(&mut st.ui.show_3d, "3D");
                     ui.checkbox(&mut st.ui.show_edges, "Edges");
+                    ui.checkbox(&mut st.cfg.edges_selection_only, "Selection only")
+                        .on_hover_text("Only draw edges touching the focus or selection");
                 });
                 ui.horizontal(|ui| {
                     ui.checkbox(&mut st.cfg.show_agg_edges, "Agg edges");
                     ui.checkbox(&mut st.cfg.show_raw_edges, "Raw edges");
                 });
+                ui.checkbox(&mut st.cfg.edge_bundling, "Bundle edges")
+                    .on_hover_text(
+                        "Curve parallel edges of one kind together once the layout settles",
+                    );
+                ui.add_enabled_ui(st.cfg.edge_bundling, |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut st.cfg.edge_bundling_strength, 0.0..=1.0)
+                            .text("bundle strength"),
+                    );
+                });
             });
 
             ui.separator();
             ui.vertical(|ui| {
                 section_header(ui, "Filtering");
-                ui.label("Filter (substring):");
-                ui.text_edit_singleline(&mut st.ui.filter);
+                ui.horizontal(|ui| {
+                    ui.label(if st.cfg.filter_is_regex {
+                        "Filter (regex):"
+                    } else {
+                        "Filter (substring):"
+                    });
+                    if ui.checkbox(&mut st.cfg.filter_is_regex, "Regex").changed() {
+                        st.needs_redraw.store(true, Ordering::Relaxed);
+                    }
+                });
+                if ui.text_edit_singleline(&mut st.ui.filter).changed() {
+                    st.needs_redraw.store(true, Ordering::Relaxed);
+                }
+                if let Some(Err(err)) = st.ui.filter_regex.as_ref().map(|f| &f.compiled) {
+                    ui.label(
+                        egui::RichText::new(format!("Invalid regex: {err}"))
+                            .color(egui::Color32::from_rgb(230, 51, 51)),
+                    );
+                }
 
                 ui.add_space(6.0);
                 ui.horizontal(|ui| {
@@ -221,6 +607,129 @@ pub fn ui_panel(
                 } else {
                     ui.label("Focus: (none) — click a node");
                 }
+
+                let selected = st.ui.selected.clone().or_else(|| st.ui.selected_a.clone());
+                if let Some(id) = selected.as_ref() {
+                    ui.horizontal(|ui| {
+                        ui.label("Selected:");
+                        copy_buttons(ui, id, st.model.nodes.get(id));
+                    });
+                }
+                if let Some(anchor) = st.ui.isolate.clone() {
+                    ui.label(format!("Isolated: component of {}", anchor.0));
+                    if ui.button("Clear isolation").clicked() {
+                        st.ui.isolate = None;
+                        st.spatial.dirty_layout = true;
+                        st.needs_redraw.store(true, Ordering::Relaxed);
+                    }
+                } else if ui
+                    .add_enabled(selected.is_some(), egui::Button::new("Isolate component"))
+                    .clicked()
+                {
+                    st.ui.isolate = selected.clone();
+                    st.spatial.dirty_layout = true;
+                    st.needs_redraw.store(true, Ordering::Relaxed);
+                }
+
+                if !st.ui.isolate_nodes.is_empty() {
+                    ui.label(format!(
+                        "Isolated: {} selected nodes",
+                        st.ui.isolate_nodes.len()
+                    ));
+                    if ui.button("Clear selection isolation").clicked() {
+                        st.ui.isolate_nodes.clear();
+                        st.spatial.dirty_layout = true;
+                        st.needs_redraw.store(true, Ordering::Relaxed);
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(selected.is_some(), egui::Button::new("Expand"))
+                        .on_hover_text("Show the selected node's neighbors (N)")
+                        .clicked()
+                    {
+                        if let Some(id) = selected.as_ref() {
+                            st.expand_node(id);
+                        }
+                    }
+                    if ui
+                        .add_enabled(selected.is_some(), egui::Button::new("Collapse"))
+                        .on_hover_text("Hide the neighbors expanded from it (Shift+N)")
+                        .clicked()
+                    {
+                        if let Some(id) = selected.as_ref() {
+                            st.collapse_node(id);
+                        }
+                    }
+                });
+                if !st.ui.expanded.is_empty() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Expanded: {} nodes", st.ui.expanded.len()));
+                        if ui.button("Clear").clicked() {
+                            st.ui.expanded.clear();
+                            st.spatial.dirty_layout = true;
+                            st.needs_redraw.store(true, Ordering::Relaxed);
+                        }
+                    });
+                }
+                if st.ui.multi_selected.is_empty() {
+                    ui.label("Selection: Shift+drag to box-select");
+                } else {
+                    ui.label(format!("Selection: {} nodes", st.ui.multi_selected.len()));
+                    ui.horizontal(|ui| {
+                        if ui.button("Isolate selection").clicked() {
+                            st.ui.isolate_nodes = st.ui.multi_selected.clone();
+                            st.spatial.dirty_layout = true;
+                            st.needs_redraw.store(true, Ordering::Relaxed);
+                        }
+                        if ui.button("Pin selection").clicked() {
+                            let ids = st.ui.multi_selected.clone();
+                            st.spatial.pinned.extend(ids);
+                            st.needs_redraw.store(true, Ordering::Relaxed);
+                        }
+                        if ui.button("Clear").clicked() {
+                            st.ui.multi_selected.clear();
+                            st.needs_redraw.store(true, Ordering::Relaxed);
+                        }
+                    });
+                }
+
+                let selected_dir = st
+                    .ui
+                    .selected
+                    .as_ref()
+                    .and_then(|id| st.model.nodes.get(id))
+                    .and_then(|node| match node {
+                        Node::File { path, .. } => tree::parent_path(path),
+                        _ => None,
+                    });
+                if let Some(dir) = selected_dir {
+                    if ui.button(format!("Collapse {dir}")).clicked() {
+                        st.ui.collapsed_dirs.insert(dir);
+                        st.needs_redraw.store(true, Ordering::Relaxed);
+                    }
+                }
+                let mut expand = None;
+                let mut collapsed: Vec<&String> = st.ui.collapsed_dirs.iter().collect();
+                collapsed.sort();
+                for dir in collapsed {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Collapsed: {dir}"));
+                        if ui.small_button("Expand").clicked() {
+                            expand = Some(dir.clone());
+                        }
+                    });
+                }
+                if let Some(dir) = expand {
+                    st.ui.collapsed_dirs.remove(&dir);
+                    st.needs_redraw.store(true, Ordering::Relaxed);
+                }
+            });
+
+            ui.separator();
+            ui.vertical(|ui| {
+                section_header(ui, "View Presets");
+                view_presets(ui, st.as_mut());
             });
 
             ui.separator();
@@ -259,16 +768,147 @@ pub fn ui_panel(
                         );
                         ui.selectable_value(&mut st.cfg.lod_edges_mode, LodEdgesMode::All, "All");
                     });
+                ui.add(
+                    egui::Slider::new(&mut st.cfg.max_rendered_edges, 0..=100_000)
+                        .text("max raw edges (0 = ∞)"),
+                )
+                .on_hover_text("Per frame; selected and glowing edges are drawn first");
             });
 
             ui.separator();
             ui.vertical(|ui| {
                 section_header(ui, "Layout (Spatial)");
                 ui.checkbox(&mut st.cfg.layout_force, "Force layout");
+                ui.horizontal(|ui| {
+                    ui.label("Placement");
+                    let mut mode = st.cfg.layout_mode;
+                    egui::ComboBox::from_id_source("layout_mode")
+                        .selected_text(mode.as_str())
+                        .show_ui(ui, |ui| {
+                            for m in [
+                                LayoutMode::Force,
+                                LayoutMode::GroupByUser,
+                                LayoutMode::Seeded,
+                            ] {
+                                ui.selectable_value(&mut mode, m, m.as_str());
+                            }
+                        });
+                    st.set_layout_mode(mode);
+                });
                 ui.add(egui::Slider::new(&mut st.cfg.link_distance, 1.0..=20.0).text("link dist"));
                 ui.add(egui::Slider::new(&mut st.cfg.repulsion, 0.0..=120.0).text("repulsion"));
                 ui.add(egui::Slider::new(&mut st.cfg.damping, 0.80..=0.999).text("damping"));
                 ui.add(egui::Slider::new(&mut st.cfg.max_step, 0.05..=2.0).text("max step"));
+                ui.collapsing("Per-kind profile", |ui| {
+                    let profile = &mut st.cfg.layout_profile;
+                    ui.label("Link distance ×");
+                    for (value, label) in [
+                        (&mut profile.link_opens, "opens"),
+                        (&mut profile.link_execs, "execs"),
+                        (&mut profile.link_runs_as, "runs as"),
+                        (&mut profile.link_parent_of, "parent of"),
+                        (&mut profile.link_member_of, "member of"),
+                        (&mut profile.link_points_to, "points to"),
+                        (&mut profile.link_thread_of, "thread of"),
+                    ] {
+                        ui.add(egui::Slider::new(value, 0.2..=3.0).text(label));
+                    }
+                    ui.label("Repulsion ×");
+                    for (value, label) in [
+                        (&mut profile.repel_process, "processes"),
+                        (&mut profile.repel_file, "files"),
+                        (&mut profile.repel_user, "users"),
+                        (&mut profile.repel_container, "containers"),
+                        (&mut profile.repel_thread, "threads"),
+                    ] {
+                        ui.add(egui::Slider::new(value, 0.0..=4.0).text(label));
+                    }
+                    if ui.button("Reset profile").clicked() {
+                        *profile = LayoutProfile::default();
+                    }
+                });
+            });
+
+            ui.separator();
+            ui.vertical(|ui| {
+                section_header(ui, "Node Size");
+                let mut changed = ui
+                    .checkbox(&mut st.cfg.size_by_degree, "Size by degree")
+                    .changed();
+                ui.add_enabled_ui(st.cfg.size_by_degree, |ui| {
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut st.cfg.node_radius_min, 0.05..=3.0)
+                                .text("min radius"),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut st.cfg.node_radius_max, 0.05..=3.0)
+                                .text("max radius"),
+                        )
+                        .changed();
+                });
+                if st.cfg.node_radius_max < st.cfg.node_radius_min {
+                    st.cfg.node_radius_max = st.cfg.node_radius_min;
+                }
+                if changed {
+                    st.needs_redraw.store(true, Ordering::Relaxed);
+                }
+            });
+
+            ui.separator();
+            ui.vertical(|ui| {
+                section_header(ui, "Camera");
+                ui.label("Right drag: orbit · Middle drag: pan · Wheel: zoom");
+                ui.add(
+                    egui::Slider::new(&mut st.cfg.camera_sensitivity, 0.1..=5.0)
+                        .text("mouse sensitivity"),
+                );
+            });
+
+            ui.separator();
+            ui.vertical(|ui| {
+                section_header(ui, "Scene");
+                ui.checkbox(&mut st.cfg.grid_enabled, "Reference grid");
+                ui.add_enabled_ui(st.cfg.grid_enabled, |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut st.cfg.grid_spacing, 0.5..=100.0)
+                            .logarithmic(true)
+                            .text("grid spacing"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut st.cfg.grid_extent, 10.0..=1000.0)
+                            .logarithmic(true)
+                            .text("grid extent"),
+                    );
+                });
+                ui.add(
+                    egui::Slider::new(&mut st.cfg.ambient_brightness, 0.0..=2000.0)
+                        .logarithmic(true)
+                        .text("ambient light"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut st.cfg.edge_opacity, 0.05..=1.0).text("edge opacity"),
+                );
+                ui.collapsing("Node opacity", |ui| {
+                    let opacity = &mut st.cfg.node_opacity;
+                    let mut changed = false;
+                    for (value, label) in [
+                        (&mut opacity.process, "processes"),
+                        (&mut opacity.file, "files"),
+                        (&mut opacity.user, "users"),
+                        (&mut opacity.container, "containers"),
+                        (&mut opacity.thread, "threads"),
+                    ] {
+                        changed |= ui
+                            .add(egui::Slider::new(value, 0.05..=1.0).text(label))
+                            .changed();
+                    }
+                    if changed {
+                        st.needs_redraw.store(true, Ordering::Relaxed);
+                    }
+                });
             });
 
             ui.separator();
@@ -277,6 +917,70 @@ pub fn ui_panel(
                 let mut ms = st.cfg.glow_duration.as_millis() as i32;
                 ui.add(egui::Slider::new(&mut ms, 100..=3000).text("glow ms"));
                 st.cfg.glow_duration = std::time::Duration::from_millis(ms as u64);
+                if ui
+                    .add(
+                        egui::Slider::new(&mut st.cfg.glow_emissive, 0.0..=20.0)
+                            .text("glow strength"),
+                    )
+                    .changed()
+                {
+                    st.needs_redraw.store(true, Ordering::Relaxed);
+                }
+                ui.add(
+                    egui::Slider::new(&mut st.cfg.bloom_intensity, 0.0..=1.0)
+                        .text("Bloom intensity"),
+                );
+                ui.checkbox(&mut st.cfg.fade_in_new_nodes, "Enlarge new nodes")
+                    .on_hover_text(
+                        "Just-seen nodes start slightly larger and shrink while they glow",
+                    );
+                ui.horizontal(|ui| {
+                    ui.label("Theme");
+                    egui::ComboBox::from_id_source("viewer_theme")
+                        .selected_text(st.cfg.theme.as_str())
+                        .show_ui(ui, |ui| {
+                            for theme in [Theme::Dark, Theme::Light, Theme::HighContrast] {
+                                ui.selectable_value(&mut st.cfg.theme, theme, theme.as_str());
+                            }
+                        });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Labels");
+                    egui::ComboBox::from_id_source("node_label_mode")
+                        .selected_text(st.cfg.label_mode.as_str())
+                        .show_ui(ui, |ui| {
+                            for mode in
+                                [LabelMode::FullPath, LabelMode::Basename, LabelMode::Hidden]
+                            {
+                                ui.selectable_value(&mut st.cfg.label_mode, mode, mode.as_str());
+                            }
+                        });
+                });
+                ui.add_enabled_ui(st.cfg.label_mode != LabelMode::Hidden, |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut st.cfg.label_max_distance, 1.0..=500.0)
+                            .logarithmic(true)
+                            .text("Label distance"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut st.cfg.label_max_count, 0..=200).text("Max labels"),
+                    )
+                    .on_hover_text("Only the closest nodes within the distance are labelled");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Durations");
+                    egui::ComboBox::from_id_source("duration_units")
+                        .selected_text(st.cfg.duration_units.as_str())
+                        .show_ui(ui, |ui| {
+                            for units in [DurationUnits::Human, DurationUnits::Seconds] {
+                                ui.selectable_value(
+                                    &mut st.cfg.duration_units,
+                                    units,
+                                    units.as_str(),
+                                );
+                            }
+                        });
+                });
             });
 
             ui.separator();
@@ -286,6 +990,26 @@ pub fn ui_panel(
                 let mut ttl = st.cfg.gc_ttl.as_secs() as i32;
                 ui.add(egui::Slider::new(&mut ttl, 1..=600).text("orphan TTL (s)"));
                 st.cfg.gc_ttl = std::time::Duration::from_secs(ttl as u64);
+                ui.horizontal(|ui| {
+                    ui.label("orphans:");
+                    ui.selectable_value(&mut st.cfg.gc_mode, GcMode::Remove, "Remove");
+                    ui.selectable_value(&mut st.cfg.gc_mode, GcMode::Dim, "Dim");
+                });
+                if st.cfg.gc_mode == GcMode::Dim {
+                    let mut hard = st.cfg.gc_hard_ttl.as_secs() as i32;
+                    ui.add(egui::Slider::new(&mut hard, ttl..=3600).text("remove after (s)"));
+                    st.cfg.gc_hard_ttl = std::time::Duration::from_secs(hard.max(ttl) as u64);
+                }
+                let mut edge_ttl = st.cfg.edge_ttl.map_or(0, |ttl| ttl.as_secs() as i32);
+                let fd_edges = st.net.merged_caps().is_none_or(|caps| caps.fd_edges);
+                ui.add_enabled(
+                    fd_edges,
+                    egui::Slider::new(&mut edge_ttl, 0..=3600).text("fd edge TTL (s, 0 = off)"),
+                )
+                .on_hover_text("Drops open-file edges the agent has not re-sent for this long")
+                .on_disabled_hover_text("no open-file edges from the connected agents");
+                st.cfg.edge_ttl =
+                    (edge_ttl > 0).then(|| std::time::Duration::from_secs(edge_ttl as u64));
             });
 
             ui.separator();
@@ -315,9 +1039,80 @@ pub fn ui_panel(
                 }
             });
 
+            ui.separator();
+            ui.vertical(|ui| {
+                section_header(ui, "Export");
+                ui.horizontal(|ui| {
+                    if ui.button("Export JSON").clicked() {
+                        let result = export::graph_to_json(&st.model).and_then(|json| {
+                            export::write_export(Path::new(export::JSON_EXPORT_FILE), &json)
+                        });
+                        st.ui.export_notice = Some(match result {
+                            Ok(()) => format!("wrote {}", export::JSON_EXPORT_FILE),
+                            Err(err) => format!("export failed: {err}"),
+                        });
+                    }
+                    if ui.button("Export DOT").clicked() {
+                        let dot = export::graph_to_dot(&st.model);
+                        let result = export::write_export(Path::new(export::DOT_EXPORT_FILE), &dot);
+                        st.ui.export_notice = Some(match result {
+                            Ok(()) => format!("wrote {}", export::DOT_EXPORT_FILE),
+                            Err(err) => format!("export failed: {err}"),
+                        });
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Screenshot (F12)")
+                        .on_hover_text("Saves the window as a timestamped PNG")
+                        .clicked()
+                    {
+                        st.ui.screenshot_requested = true;
+                    }
+                    ui.checkbox(&mut st.cfg.screenshot_include_ui, "Include UI");
+                });
+                let mut recording = st.net.recorder.is_some();
+                if ui
+                    .checkbox(&mut recording, "Record to file…")
+                    .on_hover_text(format!(
+                        "Appends incoming messages to {} as JSON lines",
+                        record::RECORD_FILE
+                    ))
+                    .changed()
+                {
+                    st.ui.export_notice = Some(if recording {
+                        let snapshot = export::snapshot_msg(&st.model);
+                        match Recorder::start(Path::new(record::RECORD_FILE), snapshot) {
+                            Ok(recorder) => {
+                                st.net.recorder = Some(recorder);
+                                format!("recording to {}", record::RECORD_FILE)
+                            }
+                            Err(err) => format!("recording failed: {err:#}"),
+                        }
+                    } else {
+                        match st.net.recorder.take().map(Recorder::stop) {
+                            Some(Ok(lines)) => {
+                                format!("wrote {lines} lines to {}", record::RECORD_FILE)
+                            }
+                            Some(Err(err)) => format!("recording failed: {err:#}"),
+                            None => String::new(),
+                        }
+                    });
+                }
+                if let Some(msg) = st.ui.export_notice.as_ref() {
+                    ui.label(egui::RichText::new(msg).weak());
+                }
+            });
+
             ui.separator();
             ui.vertical(|ui| {
                 section_header(ui, "Actions");
+                if ui.button("Stats…").clicked() {
+                    st.ui.show_stats = true;
+                }
+                if ui.button("Explain… (X)").clicked() {
+                    st.ui.show_explain = true;
+                }
                 if ui.button("Clear graph").clicked() {
                     st.clear();
                 }
@@ -325,11 +1120,16 @@ pub fn ui_panel(
         });
     });
 
+    let log_rect = event_log::event_log_panel(ctx, st.as_mut());
+
     let panel_rect = resp.response.rect;
     let screen = ctx.screen_rect();
     let content_rect = egui::Rect::from_min_max(
         egui::pos2(panel_rect.max.x, screen.min.y),
-        egui::pos2(screen.max.x, screen.max.y),
+        egui::pos2(
+            screen.max.x,
+            log_rect.map_or(screen.max.y, |rect| rect.min.y),
+        ),
     );
     layout.panel_rect = panel_rect;
     layout.content_rect = content_rect;
@@ -338,10 +1138,93 @@ pub fn ui_panel(
     settings_agents::agent_manager_window(ctx, st.as_mut(), &layout);
     settings_agents::agent_editor_window(ctx, st.as_mut(), &layout);
     settings_agents::agent_command_window(ctx, st.as_mut(), &layout);
+    stats::stats_window(ctx, st.as_mut(), &layout);
+    explain::explain_window(ctx, st.as_mut(), &layout);
+    batch_diff::batch_diff_window(ctx, st.as_mut(), &layout);
     super::search::search_overlay(contexts, st);
 }
 
+fn view_presets(ui: &mut egui::Ui, st: &mut GraphState) {
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(&mut st.ui.preset_name);
+        let name = st.ui.preset_name.trim().to_string();
+        if ui
+            .add_enabled(!name.is_empty(), egui::Button::new("Save"))
+            .on_hover_text("Save filter, focus, view mode, edges and LOD under this name")
+            .clicked()
+        {
+            st.save_view_preset(st.capture_view_preset(&name));
+            save_presets(st);
+        }
+    });
+    if st.cfg.view_presets.is_empty() {
+        ui.label("No presets saved");
+        return;
+    }
+    let mut load = None;
+    let mut delete = None;
+    for (idx, preset) in st.cfg.view_presets.iter().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(&preset.name);
+            if ui.small_button("Load").clicked() {
+                load = Some(idx);
+            }
+            if ui.small_button("Delete").clicked() {
+                delete = Some(idx);
+            }
+        });
+    }
+    if let Some(idx) = load {
+        let preset = st.cfg.view_presets[idx].clone();
+        st.ui.preset_name = preset.name.clone();
+        st.apply_view_preset(&preset);
+    }
+    if let Some(idx) = delete {
+        st.cfg.view_presets.remove(idx);
+        save_presets(st);
+    }
+}
+
+/// Presets are written straight away so they survive without "Save Settings".
+fn save_presets(st: &GraphState) {
+    let mut cfg = config::load_or_default();
+    cfg.view_presets = st.cfg.view_presets.clone();
+    if let Err(err) = config::save(&cfg) {
+        eprintln!("failed to save view presets: {err}");
+    }
+}
+
 fn section_header(ui: &mut egui::Ui, title: &str) {
     ui.add_space(6.0);
     ui.label(egui::RichText::new(title).strong());
 }
+
+fn replay_controls(ui: &mut egui::Ui, st: &mut GraphState) {
+    let Some(replay) = st.replay.as_mut() else {
+        return;
+    };
+    let duration_secs = replay.duration_ms() as f64 / 1000.0;
+    let mut pos_secs = replay.position_ms / 1000.0;
+    ui.horizontal(|ui| {
+        let label = if replay.playing { "Pause" } else { "Play" };
+        if ui.button(label).clicked() {
+            replay.playing = !replay.playing;
+        }
+        ui.add(
+            egui::Slider::new(&mut replay.speed, 0.1..=10.0)
+                .logarithmic(true)
+                .text("speed"),
+        );
+    });
+    let seek = ui
+        .add(egui::Slider::new(&mut pos_secs, 0.0..=duration_secs.max(0.001)).text("s"))
+        .changed();
+    let stop = ui.button("Stop replay").clicked();
+    if seek {
+        st.seek_replay(pos_secs * 1000.0);
+    }
+    if stop {
+        st.replay = None;
+        st.ui.import_notice = None;
+    }
+}
@@ -2,6 +2,7 @@ use bevy::prelude::ResMut;
 use bevy_egui::{egui, EguiContexts};
 
 use crate::graph::GraphState;
+use crate::ui::tooltips::copy_buttons;
 
 // Ctrl+P search overlay
 pub fn search_overlay(mut contexts: EguiContexts, mut st: ResMut<GraphState>) {
@@ -17,7 +18,9 @@ pub fn search_overlay(mut contexts: EguiContexts, mut st: ResMut<GraphState>) {
         .show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.label("Query:");
-                let resp = ui.text_edit_singleline(&mut st.ui.search_query);
+                let resp = ui
+                    .text_edit_singleline(&mut st.ui.search_query)
+                    .on_hover_text("pid:1234 or a bare number finds processes by PID");
                 if resp.changed() {
                     st.recompute_search_hits(30);
                 }
@@ -46,13 +49,22 @@ pub fn search_overlay(mut contexts: EguiContexts, mut st: ResMut<GraphState>) {
                                 spacegraph_core::Node::User { name, uid } => {
                                     format!("user: {name} uid={uid} ({})", id.0)
                                 }
+                                spacegraph_core::Node::Container { name, .. } => {
+                                    format!("container: {name} ({})", id.0)
+                                }
+                                spacegraph_core::Node::Thread { name, tid, .. } => {
+                                    format!("thread: tid={tid} {name} ({})", id.0)
+                                }
                             }
                         } else {
                             id.0.clone()
                         };
-                        if ui.selectable_label(false, label).clicked() {
-                            picked = Some(id.clone());
-                        }
+                        ui.horizontal(|ui| {
+                            copy_buttons(ui, id, st.model.nodes.get(id));
+                            if ui.selectable_label(false, label).clicked() {
+                                picked = Some(id.clone());
+                            }
+                        });
                     }
                 });
 
@@ -1,11 +1,17 @@
 use bevy_egui::egui;
+use spacegraph_core::ControlCmd;
 use std::time::Instant;
 
-use crate::graph::state::{NetCommand, NetStreamStatus};
+use crate::graph::state::{
+    capability_names, missing_capability_notes, NetCommand, NetStreamStatus,
+};
 use crate::graph::GraphState;
 use crate::ui::UiLayout;
 use crate::util::agent_command::build_agent_command;
-use crate::util::config::{AgentEndpoint, AgentEndpointKind, AgentMode, PathPolicyConfig};
+use crate::util::config::{
+    AgentEndpoint, AgentEndpointKind, AgentMode, DisconnectPolicy, PathPolicyConfig,
+};
+use crate::util::ids::format_duration_in;
 
 pub fn agent_manager_window(ctx: &egui::Context, st: &mut GraphState, layout: &UiLayout) {
     if !st.ui.show_agent_manager {
@@ -40,6 +46,7 @@ pub fn agent_manager_window(ctx: &egui::Context, st: &mut GraphState, layout: &U
         .open(&mut open)
         .show(ctx, |ui| {
             let now = Instant::now();
+            let units = st.cfg.duration_units;
             let mut rows: Vec<usize> = (0..st.net.endpoints.len()).collect();
             rows.sort_by(|a, b| st.net.endpoints[*a].name.cmp(&st.net.endpoints[*b].name));
 
@@ -49,9 +56,12 @@ pub fn agent_manager_window(ctx: &egui::Context, st: &mut GraphState, layout: &U
                 .show(ui, |ui| {
                     ui.label(egui::RichText::new("Name").strong());
                     ui.label(egui::RichText::new("Status").strong());
+                    ui.label(egui::RichText::new("Caps").strong());
                     ui.label(egui::RichText::new("Msgs/s").strong());
+                    ui.label(egui::RichText::new("KB/s").strong());
                     ui.label(egui::RichText::new("Data flow").strong());
                     ui.label(egui::RichText::new("Mode").strong());
+                    ui.label(egui::RichText::new("On disconnect").strong());
                     ui.label(egui::RichText::new("Actions").strong());
                     ui.end_row();
 
@@ -60,14 +70,13 @@ pub fn agent_manager_window(ctx: &egui::Context, st: &mut GraphState, layout: &U
                             let endpoint = &st.net.endpoints[idx];
                             (endpoint.name.clone(), endpoint.mode_override)
                         };
-                        let endpoint_path = match &st.net.endpoints[idx].kind {
-                            AgentEndpointKind::UdsPath(path) => path.as_str(),
-                        };
+                        let endpoint_path = st.net.endpoints[idx].kind.address();
                         let stream = st.net.streams.get(&endpoint_name);
                         let status = stream
                             .map(|s| s.status)
                             .unwrap_or(NetStreamStatus::Disconnected);
                         let msg_rate = stream.map(|s| s.msg_rate).unwrap_or(0.0);
+                        let kb_rate = stream.map(|s| s.bytes_per_sec).unwrap_or(0.0) / 1024.0;
                         let last_seen = stream
                             .and_then(|s| s.last_seen)
                             .map(|ts| now.duration_since(ts));
@@ -78,6 +87,14 @@ pub fn agent_manager_window(ctx: &egui::Context, st: &mut GraphState, layout: &U
                             .and_then(|s| s.last_event_at)
                             .map(|ts| now.duration_since(ts));
                         let last_error = stream.and_then(|s| s.last_error.as_ref());
+                        let protocol_warning = stream.and_then(|s| s.protocol_warning());
+                        let caps = stream.and_then(|s| s.agent_caps.clone());
+                        let gave_up = stream.is_some_and(|s| s.gave_up);
+                        let attempts = stream.map(|s| s.reconnect_attempts).unwrap_or(0);
+                        let attempts_text = match st.net.endpoints[idx].max_reconnect_attempts {
+                            0 => format!("reconnect attempts: {attempts}/∞"),
+                            max => format!("reconnect attempts: {attempts}/{max}"),
+                        };
 
                         ui.vertical(|ui| {
                             ui.label(&endpoint_name);
@@ -94,29 +111,62 @@ pub fn agent_manager_window(ctx: &egui::Context, st: &mut GraphState, layout: &U
                         }
                         ui.vertical(|ui| {
                             let status_resp = ui.label(status_label);
+                            status_resp.on_hover_text(match last_error {
+                                Some(err) => format!("{err}\n{attempts_text}"),
+                                None => attempts_text,
+                            });
                             if let Some(err) = last_error {
-                                status_resp.on_hover_text(err);
                                 ui.label(
                                     egui::RichText::new(err)
                                         .small()
                                         .color(egui::Color32::LIGHT_RED),
                                 );
                             }
+                            if let Some(warning) = &protocol_warning {
+                                ui.label(
+                                    egui::RichText::new(warning)
+                                        .small()
+                                        .color(egui::Color32::YELLOW),
+                                );
+                            }
                         });
-                        ui.label(format!("{msg_rate:.1}"));
+                        match &caps {
+                            Some(caps) => {
+                                let names = capability_names(caps);
+                                let notes = missing_capability_notes(caps);
+                                let text = if names.is_empty() {
+                                    "none".to_string()
+                                } else {
+                                    names.join(" ")
+                                };
+                                let resp = ui.label(egui::RichText::new(text).small());
+                                if !notes.is_empty() {
+                                    resp.on_hover_text(notes.join("\n"));
+                                }
+                            }
+                            None => {
+                                ui.label("—");
+                            }
+                        }
+                        let rate = ui.label(format!("{msg_rate:.1}"));
+                        if let Some(counts) = stream
+                            .map(|s| &s.msg_counts)
+                            .filter(|counts| !counts.is_empty())
+                        {
+                            rate.on_hover_ui(|ui| {
+                                let lines = counts.breakdown(now, st.net.msg_window);
+                                ui.label(lines.join("\n"));
+                            });
+                        }
+                        ui.label(format!("{kb_rate:.1}"));
                         ui.vertical(|ui| {
-                            let last_seen_label = match last_seen {
-                                Some(delta) => format!("{:.1}s", delta.as_secs_f32()),
-                                None => "—".to_string(),
-                            };
-                            let last_snapshot_label = match last_snapshot {
-                                Some(delta) => format!("{:.1}s", delta.as_secs_f32()),
-                                None => "—".to_string(),
-                            };
-                            let last_event_label = match last_event {
-                                Some(delta) => format!("{:.1}s", delta.as_secs_f32()),
+                            let age = |delta: Option<std::time::Duration>| match delta {
+                                Some(delta) => format_duration_in(delta, units),
                                 None => "—".to_string(),
                             };
+                            let last_seen_label = age(last_seen);
+                            let last_snapshot_label = age(last_snapshot);
+                            let last_event_label = age(last_event);
                             ui.label(format!("seen: {last_seen_label}"));
                             ui.label(
                                 egui::RichText::new(format!("snap: {last_snapshot_label}")).small(),
@@ -147,6 +197,21 @@ pub fn agent_manager_window(ctx: &egui::Context, st: &mut GraphState, layout: &U
                         if mode_override != endpoint_mode_override {
                             st.net.endpoints[idx].mode_override = mode_override;
                         }
+                        egui::ComboBox::from_id_source(format!(
+                            "agent_on_disconnect_{endpoint_name}"
+                        ))
+                        .selected_text(st.net.endpoints[idx].on_disconnect.as_str())
+                        .show_ui(ui, |ui| {
+                            for policy in DisconnectPolicy::ALL {
+                                ui.selectable_value(
+                                    &mut st.net.endpoints[idx].on_disconnect,
+                                    policy,
+                                    policy.as_str(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text("What happens to this agent's nodes when it drops");
 
                         ui.horizontal(|ui| {
                             let has_connection = st.net.connections.contains_key(&endpoint_name);
@@ -167,7 +232,10 @@ pub fn agent_manager_window(ctx: &egui::Context, st: &mut GraphState, layout: &U
                                     .push(NetCommand::Disconnect(endpoint_name.clone()));
                             }
                             if ui
-                                .add_enabled(has_connection, egui::Button::new("Reconnect"))
+                                .add_enabled(
+                                    has_connection || gave_up,
+                                    egui::Button::new("Reconnect"),
+                                )
                                 .clicked()
                             {
                                 st.net
@@ -208,11 +276,14 @@ pub fn agent_manager_window(ctx: &egui::Context, st: &mut GraphState, layout: &U
             if ui.button("Add Agent…").clicked() {
                 let default_endpoint = AgentEndpoint::default();
                 st.ui.agent_editor.name_input.clear();
-                st.ui.agent_editor.uds_input = match default_endpoint.kind {
-                    AgentEndpointKind::UdsPath(path) => path,
-                };
+                st.ui.agent_editor.uds_input = default_endpoint.kind.address();
                 st.ui.agent_editor.auto_connect = default_endpoint.auto_connect;
                 st.ui.agent_editor.mode_override = None;
+                st.ui.agent_editor.compression = default_endpoint.compression;
+                st.ui.agent_editor.max_reconnect_attempts = default_endpoint.max_reconnect_attempts;
+                st.ui.agent_editor.on_disconnect = default_endpoint.on_disconnect;
+                st.ui.agent_editor.token_file_input.clear();
+                st.ui.agent_editor.token_input.clear();
                 st.ui.agent_editor.notice = None;
                 st.ui.show_agent_editor = true;
             }
@@ -262,7 +333,7 @@ pub fn agent_editor_window(ctx: &egui::Context, st: &mut GraphState, layout: &Ui
         .constrain_to(content_rect)
         .open(&mut open)
         .show(ctx, |ui| {
-            ui.label("Register a new agent endpoint (UDS only for now).");
+            ui.label("Register a new agent endpoint.");
             ui.add_space(6.0);
 
             ui.horizontal(|ui| {
@@ -272,10 +343,13 @@ pub fn agent_editor_window(ctx: &egui::Context, st: &mut GraphState, layout: &Ui
                 ));
             });
             ui.horizontal(|ui| {
-                ui.label("UDS path");
+                ui.label("Address");
                 ui.add(
                     egui::TextEdit::singleline(&mut st.ui.agent_editor.uds_input)
                         .desired_width(260.0),
+                )
+                .on_hover_text(
+                    "Socket path, @name for a Linux abstract socket, or tcp://host:port",
                 );
             });
             ui.horizontal(|ui| {
@@ -286,6 +360,48 @@ pub fn agent_editor_window(ctx: &egui::Context, st: &mut GraphState, layout: &Ui
                 &mut st.ui.agent_editor.auto_connect,
                 "Auto-connect on startup",
             );
+            ui.checkbox(
+                &mut st.ui.agent_editor.compression,
+                "Request compressed frames",
+            );
+            ui.horizontal(|ui| {
+                ui.label("Max reconnect attempts (0 = ∞)");
+                ui.add(
+                    egui::DragValue::new(&mut st.ui.agent_editor.max_reconnect_attempts)
+                        .range(0..=1000),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("On disconnect");
+                egui::ComboBox::from_id_source("agent_editor_on_disconnect")
+                    .selected_text(st.ui.agent_editor.on_disconnect.as_str())
+                    .show_ui(ui, |ui| {
+                        for policy in DisconnectPolicy::ALL {
+                            ui.selectable_value(
+                                &mut st.ui.agent_editor.on_disconnect,
+                                policy,
+                                policy.as_str(),
+                            );
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                ui.label("Token file");
+                ui.add(
+                    egui::TextEdit::singleline(&mut st.ui.agent_editor.token_file_input)
+                        .hint_text("optional")
+                        .desired_width(260.0),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Token");
+                ui.add(
+                    egui::TextEdit::singleline(&mut st.ui.agent_editor.token_input)
+                        .password(true)
+                        .hint_text("session only, not saved")
+                        .desired_width(200.0),
+                );
+            });
 
             if let Some(msg) = st.ui.agent_editor.notice.as_ref() {
                 ui.add_space(4.0);
@@ -303,17 +419,27 @@ pub fn agent_editor_window(ctx: &egui::Context, st: &mut GraphState, layout: &Ui
                     if name.is_empty() {
                         st.ui.agent_editor.notice =
                             Some("Please enter a name for this agent.".to_string());
-                    } else if uds.is_empty() {
-                        st.ui.agent_editor.notice = Some("Please enter a UDS path.".to_string());
+                    } else if matches!(uds, "" | "@" | "tcp://") {
+                        st.ui.agent_editor.notice =
+                            Some("Please enter a socket path or address.".to_string());
                     } else if st.net.endpoints.iter().any(|e| e.name == name) {
                         st.ui.agent_editor.notice =
                             Some("An agent with this name already exists.".to_string());
                     } else {
                         let endpoint = AgentEndpoint {
                             name: name.to_string(),
-                            kind: AgentEndpointKind::UdsPath(uds.to_string()),
+                            kind: AgentEndpointKind::parse(uds),
                             auto_connect: st.ui.agent_editor.auto_connect,
                             mode_override: st.ui.agent_editor.mode_override,
+                            compression: st.ui.agent_editor.compression,
+                            max_reconnect_attempts: st.ui.agent_editor.max_reconnect_attempts,
+                            token_file: Some(st.ui.agent_editor.token_file_input.trim())
+                                .filter(|p| !p.is_empty())
+                                .map(str::to_string),
+                            on_disconnect: st.ui.agent_editor.on_disconnect,
+                            token: Some(std::mem::take(&mut st.ui.agent_editor.token_input))
+                                .filter(|t| !t.is_empty()),
+                            temporary: false,
                         };
                         st.net.endpoints.push(endpoint);
                         st.net.ensure_stream(name);
@@ -372,6 +498,8 @@ pub fn agent_command_window(ctx: &egui::Context, st: &mut GraphState, layout: &U
     let mode = endpoint.mode_override.unwrap_or(st.cfg.agent_default_mode);
     let command = build_agent_command(endpoint, &policy, mode);
 
+    let connected = st.net.connections.contains_key(&target);
+    let mut control = None;
     let mut close_requested = false;
     egui::Window::new(format!("Agent Command: {}", endpoint.name))
         .collapsible(false)
@@ -387,6 +515,31 @@ pub fn agent_command_window(ctx: &egui::Context, st: &mut GraphState, layout: &U
             ui.label(egui::RichText::new("Command").strong());
             ui.add(egui::Label::new(egui::RichText::new(&command).monospace()).selectable(true));
 
+            ui.add_space(6.0);
+            ui.label(egui::RichText::new("Live control").strong());
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(connected, egui::Button::new("Pause"))
+                    .on_hover_text("Agent stops broadcasting events")
+                    .clicked()
+                {
+                    control = Some(ControlCmd::Pause);
+                }
+                if ui
+                    .add_enabled(connected, egui::Button::new("Resume"))
+                    .clicked()
+                {
+                    control = Some(ControlCmd::Resume);
+                }
+                if ui
+                    .add_enabled(connected, egui::Button::new("Reload paths"))
+                    .on_hover_text("Agent re-reads its --policy-file and re-arms the FS watcher")
+                    .clicked()
+                {
+                    control = Some(ControlCmd::ReloadPathPolicy);
+                }
+            });
+
             ui.add_space(6.0);
             ui.horizontal(|ui| {
                 if ui.button("Copy").clicked() {
@@ -403,6 +556,9 @@ pub fn agent_command_window(ctx: &egui::Context, st: &mut GraphState, layout: &U
     if close_requested {
         open = false;
     }
+    if let Some(cmd) = control {
+        st.net.commands.push(NetCommand::Control(target, cmd));
+    }
     st.ui.agent_command.open = open;
 }
 
@@ -2,7 +2,8 @@ use bevy::prelude::ResMut;
 use bevy_egui::{egui, EguiContexts};
 use std::sync::atomic::Ordering;
 
-use crate::graph::{GraphState, ViewMode};
+use crate::graph::{GraphState, NavDir, ViewMode};
+use crate::util::ids::node_copy_path;
 
 pub fn handle_shortcuts(mut contexts: EguiContexts, mut st: ResMut<GraphState>) {
     let ctx = contexts.ctx_mut();
@@ -26,11 +27,13 @@ pub fn handle_shortcuts(mut contexts: EguiContexts, mut st: ResMut<GraphState>)
         if st.ui.selected.is_some()
             || st.ui.selected_a.is_some()
             || st.ui.selected_b.is_some()
+            || st.ui.selected_edge.is_some()
             || st.ui.hovered.is_some()
         {
             st.ui.selected = None;
             st.ui.selected_a = None;
             st.ui.selected_b = None;
+            st.ui.selected_edge = None;
             st.ui.hovered = None;
             changed = true;
         }
@@ -46,19 +49,90 @@ pub fn handle_shortcuts(mut contexts: EguiContexts, mut st: ResMut<GraphState>)
     if ctx.input(|i| i.key_pressed(egui::Key::P) && i.modifiers.ctrl) {
         st.ui.search_open = true;
     }
-    if ctx.input(|i| i.key_pressed(egui::Key::Questionmark)) {
-        st.ui.help_open = !st.ui.help_open;
+    if ctx.input(|i| i.key_pressed(egui::Key::P) && !i.modifiers.ctrl) {
+        if let Some(id) = st.ui.hovered.clone() {
+            st.toggle_pin(&id);
+        }
     }
-    if ctx.input(|i| i.key_pressed(egui::Key::F)) {
-        if let Some(id) = st.ui.selected.clone().or_else(|| st.ui.selected_a.clone()) {
-            st.ui.focus = Some(id);
-            st.needs_redraw.store(true, Ordering::Relaxed);
+    // N expands the hovered (else selected) node by one hop, Shift+N takes it back.
+    if ctx.input(|i| i.key_pressed(egui::Key::N)) {
+        let target = st.ui.hovered.clone().or_else(|| st.ui.selected.clone());
+        if let Some(id) = target {
+            if ctx.input(|i| i.modifiers.shift) {
+                st.collapse_node(&id);
+            } else {
+                st.expand_node(&id);
+            }
         }
     }
+    if ctx.input(|i| i.key_pressed(egui::Key::L)) {
+        st.ui.show_event_log = !st.ui.show_event_log;
+    }
+    if ctx.input(|i| i.key_pressed(egui::Key::M)) {
+        st.ui.show_minimap = !st.ui.show_minimap;
+    }
+    // Ctrl+C copies the hovered (else selected) node's path, Ctrl+Shift+C its id. egui
+    // turns the key combination into a Copy event rather than a key press.
+    let copy_id = ctx.input(|i| {
+        i.events
+            .iter()
+            .any(|e| matches!(e, egui::Event::Copy))
+            .then_some(i.modifiers.shift)
+    });
+    if let Some(copy_id) = copy_id {
+        let target = st
+            .ui
+            .hovered
+            .clone()
+            .or_else(|| st.ui.selected.clone())
+            .or_else(|| st.ui.selected_a.clone());
+        let text = target.and_then(|id| {
+            if copy_id {
+                Some(id.0)
+            } else {
+                st.model.nodes.get(&id).map(node_copy_path)
+            }
+        });
+        if let Some(text) = text {
+            ctx.output_mut(|o| o.copied_text = text);
+        }
+    }
+    if ctx.input(|i| i.key_pressed(egui::Key::F12)) {
+        st.ui.screenshot_requested = true;
+    }
+    if ctx.input(|i| i.key_pressed(egui::Key::X)) {
+        st.ui.show_explain = !st.ui.show_explain;
+    }
+    if ctx.input(|i| i.key_pressed(egui::Key::Questionmark)) {
+        st.ui.help_open = !st.ui.help_open;
+    }
+    ctx.input(|i| apply_layout_keys(&mut st, i));
     if ctx.input(|i| i.key_pressed(egui::Key::Space)) && st.ui.view_mode == ViewMode::Timeline {
         let pause = !st.timeline.pause;
         st.set_timeline_pause(pause);
     }
+    if st.ui.view_mode == ViewMode::Spatial && st.ui.selected.is_some() {
+        let (next, prev, down, up) = ctx.input(|i| {
+            let tab = i.key_pressed(egui::Key::Tab);
+            (
+                i.key_pressed(egui::Key::ArrowRight) || (tab && !i.modifiers.shift),
+                i.key_pressed(egui::Key::ArrowLeft) || (tab && i.modifiers.shift),
+                i.key_pressed(egui::Key::ArrowDown),
+                i.key_pressed(egui::Key::ArrowUp),
+            )
+        });
+        if next {
+            st.select_neighbor(NavDir::Next);
+        } else if prev {
+            st.select_neighbor(NavDir::Prev);
+        } else if down {
+            // descend: cycle the neighbors of the current selection
+            st.ui.nav_anchor = None;
+            st.select_neighbor(NavDir::Next);
+        } else if up {
+            st.select_nav_anchor();
+        }
+    }
     if ctx.input(|i| i.key_pressed(egui::Key::T)) {
         st.ui.view_mode = match st.ui.view_mode {
             ViewMode::Spatial => ViewMode::Tree,
@@ -69,3 +143,91 @@ pub fn handle_shortcuts(mut contexts: EguiContexts, mut st: ResMut<GraphState>)
         st.needs_redraw.store(true, Ordering::Relaxed);
     }
 }
+
+/// F / Shift+F, `[` / `]` and E: focus, force layout, focus hops and edges.
+fn apply_layout_keys(st: &mut GraphState, input: &egui::InputState) {
+    // Plain F keeps focusing the selection; Shift+F toggles the force layout.
+    if input.key_pressed(egui::Key::F) && input.modifiers.shift {
+        st.cfg.layout_force = !st.cfg.layout_force;
+        st.needs_redraw.store(true, Ordering::Relaxed);
+    } else if input.key_pressed(egui::Key::F) {
+        if let Some(id) = st.ui.selected.clone().or_else(|| st.ui.selected_a.clone()) {
+            st.ui.focus = Some(id);
+            st.needs_redraw.store(true, Ordering::Relaxed);
+        }
+    }
+    let hops_delta = input.key_pressed(egui::Key::CloseBracket) as i32
+        - input.key_pressed(egui::Key::OpenBracket) as i32;
+    if hops_delta != 0 {
+        st.ui.focus_hops = (st.ui.focus_hops as i32 + hops_delta).clamp(1, 10) as usize;
+        st.needs_redraw.store(true, Ordering::Relaxed);
+    }
+    if input.key_pressed(egui::Key::E) {
+        st.ui.show_edges = !st.ui.show_edges;
+        st.needs_redraw.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(st: &mut GraphState, key: egui::Key, modifiers: egui::Modifiers) {
+        let ctx = egui::Context::default();
+        let event = egui::Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers,
+        };
+        let raw = egui::RawInput {
+            events: vec![event],
+            modifiers,
+            ..Default::default()
+        };
+        ctx.begin_frame(raw);
+        ctx.input(|i| apply_layout_keys(st, i));
+        let _ = ctx.end_frame();
+    }
+
+    #[test]
+    fn brackets_step_focus_hops_within_bounds() {
+        let mut st = GraphState::default();
+        st.ui.focus_hops = 3;
+        press(&mut st, egui::Key::CloseBracket, egui::Modifiers::NONE);
+        assert_eq!(st.ui.focus_hops, 4);
+        press(&mut st, egui::Key::OpenBracket, egui::Modifiers::NONE);
+        press(&mut st, egui::Key::OpenBracket, egui::Modifiers::NONE);
+        assert_eq!(st.ui.focus_hops, 2);
+
+        st.ui.focus_hops = 1;
+        press(&mut st, egui::Key::OpenBracket, egui::Modifiers::NONE);
+        assert_eq!(st.ui.focus_hops, 1);
+        st.ui.focus_hops = 10;
+        press(&mut st, egui::Key::CloseBracket, egui::Modifiers::NONE);
+        assert_eq!(st.ui.focus_hops, 10);
+    }
+
+    #[test]
+    fn shift_f_toggles_force_layout_but_plain_f_does_not() {
+        let mut st = GraphState::default();
+        let force = st.cfg.layout_force;
+        press(&mut st, egui::Key::F, egui::Modifiers::SHIFT);
+        assert_eq!(st.cfg.layout_force, !force);
+        press(&mut st, egui::Key::F, egui::Modifiers::NONE);
+        assert_eq!(st.cfg.layout_force, !force);
+    }
+
+    #[test]
+    fn e_toggles_edges_and_other_keys_leave_them() {
+        let mut st = GraphState::default();
+        let edges = st.ui.show_edges;
+        press(&mut st, egui::Key::E, egui::Modifiers::NONE);
+        assert_eq!(st.ui.show_edges, !edges);
+        st.needs_redraw.store(false, Ordering::Relaxed);
+        press(&mut st, egui::Key::Q, egui::Modifiers::NONE);
+        assert_eq!(st.ui.show_edges, !edges);
+        assert!(!st.needs_redraw.load(Ordering::Relaxed));
+    }
+}
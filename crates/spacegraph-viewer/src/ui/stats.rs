@@ -0,0 +1,98 @@
+use bevy_egui::egui;
+
+use crate::graph::model::edge_class_name;
+use crate::graph::stats::compute_stats;
+use crate::graph::{GraphState, ViewMode};
+use crate::ui::UiLayout;
+
+pub fn stats_window(ctx: &egui::Context, st: &mut GraphState, layout: &UiLayout) {
+    if !st.ui.show_stats {
+        st.ui.stats = None;
+        return;
+    }
+    if st.ui.stats.is_none() {
+        st.ui.stats = Some(compute_stats(&st.model));
+    }
+
+    let mut content_rect = layout.content_rect;
+    if content_rect == egui::Rect::NOTHING {
+        content_rect = ctx.screen_rect();
+    }
+
+    let mut open = st.ui.show_stats;
+    let mut refresh = false;
+    let mut jump = None;
+    egui::Window::new("Graph Stats")
+        .collapsible(true)
+        .resizable(true)
+        .constrain_to(content_rect)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            let Some(stats) = st.ui.stats.as_ref() else {
+                return;
+            };
+            if ui.button("Refresh").clicked() {
+                refresh = true;
+            }
+
+            ui.label(egui::RichText::new("Nodes").strong());
+            ui.label(format!(
+                "processes: {}  files: {}  users: {}  containers: {}  threads: {}",
+                stats.processes, stats.files, stats.users, stats.containers, stats.threads
+            ));
+            ui.label(format!("orphan files: {}", stats.orphan_files));
+
+            ui.add_space(6.0);
+            ui.label(egui::RichText::new("Edges").strong());
+            for (class, count) in stats.edges_by_class.iter() {
+                ui.label(format!("{}: {count}", edge_class_name(*class)));
+            }
+
+            ui.add_space(6.0);
+            ui.label(egui::RichText::new("Top degree").strong());
+            egui::Grid::new("stats_top_degree")
+                .striped(true)
+                .show(ui, |ui| {
+                    for (id, degree) in stats.top_degree.iter() {
+                        ui.label(degree.to_string());
+                        ui.label(st.node_label_with_id(id));
+                        if ui.small_button("Jump").clicked() {
+                            jump = Some(id.clone());
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            ui.add_space(6.0);
+            ui.label(egui::RichText::new("Open files per process").strong());
+            if stats.top_open_files.is_empty() {
+                ui.label(egui::RichText::new("(no open file edges)").weak());
+            }
+            egui::ScrollArea::vertical()
+                .id_source("stats_open_files_scroll")
+                .max_height(240.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("stats_open_files")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (id, count) in stats.top_open_files.iter() {
+                                ui.label(count.to_string());
+                                ui.label(st.node_label_with_id(id));
+                                if ui.small_button("Jump").clicked() {
+                                    jump = Some(id.clone());
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+
+    st.ui.show_stats = open;
+    if refresh {
+        st.ui.stats = Some(compute_stats(&st.model));
+    }
+    if let Some(id) = jump {
+        st.ui.view_mode = ViewMode::Spatial;
+        st.request_jump(id);
+    }
+}
@@ -1,4 +1,7 @@
 use bevy_egui::egui;
+use spacegraph_core::{Node, NodeId};
+
+use crate::util::ids::node_copy_path;
 
 pub fn render_tooltip(
     ctx: &egui::Context,
@@ -16,3 +19,18 @@ pub fn render_tooltip(
             });
         });
 }
+
+/// "Copy id" / "Copy path" buttons for a node; the path button needs the node itself.
+pub fn copy_buttons(ui: &mut egui::Ui, id: &NodeId, node: Option<&Node>) {
+    if ui.small_button("Copy id").clicked() {
+        ui.output_mut(|o| o.copied_text = id.0.clone());
+    }
+    if ui
+        .add_enabled(node.is_some(), egui::Button::new("Copy path").small())
+        .clicked()
+    {
+        if let Some(node) = node {
+            ui.output_mut(|o| o.copied_text = node_copy_path(node));
+        }
+    }
+}
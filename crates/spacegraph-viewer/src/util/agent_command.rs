@@ -47,6 +47,12 @@ mod tests {
             kind: AgentEndpointKind::UdsPath("/tmp/spacegraph.sock".to_string()),
             auto_connect: false,
             mode_override: None,
+            compression: false,
+            max_reconnect_attempts: 0,
+            token_file: None,
+            on_disconnect: Default::default(),
+            token: None,
+            temporary: false,
         };
         let policy = PathPolicyConfig {
             includes: vec!["/var".to_string(), "/etc".to_string()],
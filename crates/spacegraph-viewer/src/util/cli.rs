@@ -0,0 +1,225 @@
+use anyhow::Result;
+use std::ffi::OsString;
+
+use crate::util::config::{AgentEndpoint, AgentEndpointKind};
+
+/// Agent given with `--connect`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectTarget {
+    Uds(String),
+    /// `@name`: a Linux abstract socket.
+    Abstract(String),
+    Tcp {
+        host: String,
+        port: u16,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ViewerArgs {
+    pub connect: Vec<ConnectTarget>,
+}
+
+pub fn parse_args() -> Result<ViewerArgs> {
+    parse_args_from(std::env::args_os().skip(1))
+}
+
+fn parse_args_from<I>(args: I) -> Result<ViewerArgs>
+where
+    I: IntoIterator<Item = OsString>,
+{
+    let mut connect = Vec::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--connect" {
+            let Some(value) = args.next() else {
+                anyhow::bail!("--connect expects a socket path or tcp://host:port");
+            };
+            connect.push(parse_connect(&value.to_string_lossy())?);
+        } else {
+            anyhow::bail!("unknown argument: {:?}", arg);
+        }
+    }
+
+    Ok(ViewerArgs { connect })
+}
+
+/// `tcp://host:port`, `unix:///path`, `@name` or a plain socket path.
+pub fn parse_connect(value: &str) -> Result<ConnectTarget> {
+    let value = value.trim();
+    if let Some(addr) = value.strip_prefix("tcp://") {
+        let Some((host, port)) = addr.rsplit_once(':') else {
+            anyhow::bail!("invalid --connect {value}: expected tcp://host:port");
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let port = port
+            .parse::<u16>()
+            .map_err(|_| anyhow::anyhow!("invalid --connect {value}: bad port {port}"))?;
+        if host.is_empty() {
+            anyhow::bail!("invalid --connect {value}: missing host");
+        }
+        return Ok(ConnectTarget::Tcp {
+            host: host.to_string(),
+            port,
+        });
+    }
+    if let Some(name) = value.strip_prefix('@') {
+        if name.is_empty() {
+            anyhow::bail!("invalid --connect {value}: missing abstract socket name");
+        }
+        return Ok(ConnectTarget::Abstract(name.to_string()));
+    }
+    let path = value.strip_prefix("unix://").unwrap_or(value);
+    if path.is_empty() {
+        anyhow::bail!("--connect expects a socket path or tcp://host:port");
+    }
+    Ok(ConnectTarget::Uds(path.to_string()))
+}
+
+/// Session-only endpoints for the `--connect` targets, named so they do not clash with
+/// `existing` ones.
+pub fn connect_endpoints(
+    targets: &[ConnectTarget],
+    existing: &[AgentEndpoint],
+) -> Vec<AgentEndpoint> {
+    let mut endpoints: Vec<AgentEndpoint> = Vec::new();
+    for target in targets {
+        let kind = match target {
+            ConnectTarget::Uds(path) => AgentEndpointKind::UdsPath(path.clone()),
+            ConnectTarget::Abstract(name) => AgentEndpointKind::Abstract(name.clone()),
+            // IPv6 hosts need their brackets back for `host:port`
+            ConnectTarget::Tcp { host, port } if host.contains(':') => {
+                AgentEndpointKind::Tcp(format!("[{host}]:{port}"))
+            }
+            ConnectTarget::Tcp { host, port } => AgentEndpointKind::Tcp(format!("{host}:{port}")),
+        };
+        let path = kind.address();
+        let taken = |name: &str| {
+            existing
+                .iter()
+                .chain(endpoints.iter())
+                .any(|e| e.name == name)
+        };
+        let mut name = format!("connect:{path}");
+        let mut n = 2;
+        while taken(&name) {
+            name = format!("connect:{path} ({n})");
+            n += 1;
+        }
+        endpoints.push(AgentEndpoint {
+            name,
+            kind,
+            auto_connect: true,
+            temporary: true,
+            ..AgentEndpoint::default()
+        });
+    }
+    endpoints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<OsString> {
+        values.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn parses_uds_and_tcp_connect_targets() {
+        let parsed = parse_args_from(args(&[
+            "--connect",
+            "/run/user/1000/spacegraph.sock",
+            "--connect",
+            "unix:///tmp/agent.sock",
+            "--connect",
+            "tcp://10.0.0.5:7040",
+            "--connect",
+            "tcp://[::1]:7040",
+            "--connect",
+            "@spacegraph",
+        ]))
+        .expect("args parsed");
+
+        assert_eq!(
+            parsed.connect,
+            vec![
+                ConnectTarget::Uds("/run/user/1000/spacegraph.sock".to_string()),
+                ConnectTarget::Uds("/tmp/agent.sock".to_string()),
+                ConnectTarget::Tcp {
+                    host: "10.0.0.5".to_string(),
+                    port: 7040
+                },
+                ConnectTarget::Tcp {
+                    host: "::1".to_string(),
+                    port: 7040
+                },
+                ConnectTarget::Abstract("spacegraph".to_string()),
+            ]
+        );
+        assert_eq!(parse_args_from(args(&[])).unwrap(), ViewerArgs::default());
+    }
+
+    #[test]
+    fn rejects_malformed_connect_arguments() {
+        for bad in [
+            args(&["--connect"]),
+            args(&["--connect", ""]),
+            args(&["--connect", "tcp://host"]),
+            args(&["--connect", "tcp://host:http"]),
+            args(&["--connect", "tcp://:7040"]),
+            args(&["--connect", "@"]),
+            args(&["--verbose"]),
+        ] {
+            assert!(parse_args_from(bad.clone()).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn connect_endpoints_are_temporary_and_uniquely_named() {
+        let existing = vec![AgentEndpoint {
+            name: "connect:/tmp/a.sock".to_string(),
+            ..AgentEndpoint::default()
+        }];
+        let targets = vec![
+            ConnectTarget::Uds("/tmp/a.sock".to_string()),
+            ConnectTarget::Tcp {
+                host: "db".to_string(),
+                port: 1,
+            },
+            ConnectTarget::Abstract("sg".to_string()),
+        ];
+        let endpoints = connect_endpoints(&targets, &existing);
+
+        assert_eq!(endpoints.len(), 3);
+        assert_eq!(endpoints[0].name, "connect:/tmp/a.sock (2)");
+        assert_eq!(endpoints[1].name, "connect:tcp://db:1");
+        assert_eq!(
+            endpoints[1].kind,
+            AgentEndpointKind::Tcp("db:1".to_string())
+        );
+        assert_eq!(endpoints[2].name, "connect:@sg");
+        assert_eq!(
+            endpoints[2].kind,
+            AgentEndpointKind::Abstract("sg".to_string())
+        );
+        assert!(endpoints[0].auto_connect && endpoints[0].temporary);
+    }
+
+    #[test]
+    fn tcp_connect_targets_keep_ipv6_brackets() {
+        let targets = parse_args_from(args(&["--connect", "tcp://[::1]:7040"]))
+            .expect("args parsed")
+            .connect;
+        let endpoints = connect_endpoints(&targets, &[]);
+        assert_eq!(
+            endpoints[0].kind,
+            AgentEndpointKind::Tcp("[::1]:7040".to_string())
+        );
+        assert_ne!(
+            endpoints[0].kind,
+            AgentEndpointKind::Tcp("::1:7040".to_string())
+        );
+    }
+}
@@ -1,51 +1,163 @@
 use anyhow::Context;
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use spacegraph_core::Node;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+use crate::net::AgentAddr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ViewerViewMode {
+    #[default]
     Spatial,
     Tree,
     Timeline,
 }
 
-impl Default for ViewerViewMode {
-    fn default() -> Self {
-        Self::Spatial
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum LodEdgesMode {
     Off,
+    #[default]
     FocusOnly,
     All,
 }
 
-impl Default for LodEdgesMode {
-    fn default() -> Self {
-        Self::FocusOnly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+impl Theme {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Dark => "dark",
+            Self::Light => "light",
+            Self::HighContrast => "high contrast",
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Text shown next to nodes close to the camera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
-pub enum AgentMode {
-    User,
-    Privileged,
+pub enum LabelMode {
+    FullPath,
+    #[default]
+    Basename,
+    Hidden,
 }
 
-impl Default for AgentMode {
-    fn default() -> Self {
-        Self::User
+impl LabelMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::FullPath => "full path",
+            Self::Basename => "basename",
+            Self::Hidden => "hidden",
+        }
+    }
+}
+
+/// How ages and spans are written in tooltips, the HUD and the agents table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DurationUnits {
+    /// `850ms`, `5m 5s`, `2h 10m`.
+    #[default]
+    Human,
+    /// Plain seconds with one decimal.
+    Seconds,
+}
+
+impl DurationUnits {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Human => "human",
+            Self::Seconds => "seconds",
+        }
     }
 }
 
+/// What Demo Mode plays: random churn or one of the scripted scenarios.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DemoScenario {
+    #[default]
+    Random,
+    /// A shell forking in doubling waves until everything is killed at once.
+    ForkBomb,
+    /// logrotate renaming, compressing and recreating log files.
+    LogRotation,
+    /// One process opening and closing hundreds of files in bursts.
+    ManyFiles,
+}
+
+impl DemoScenario {
+    pub const ALL: [Self; 4] = [
+        Self::Random,
+        Self::ForkBomb,
+        Self::LogRotation,
+        Self::ManyFiles,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Random => "random",
+            Self::ForkBomb => "fork_bomb",
+            Self::LogRotation => "log_rotation",
+            Self::ManyFiles => "many_files",
+        }
+    }
+}
+
+/// Initial placement in the spatial view before force layout relaxes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutMode {
+    /// Processes, files and users on separate rings.
+    #[default]
+    Force,
+    /// Each user with its processes on a sub-ring and their files around them.
+    GroupByUser,
+    /// A spot on a disk derived from the node id, the same across sessions and filters.
+    Seeded,
+}
+
+impl LayoutMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Force => "rings",
+            Self::GroupByUser => "group by user",
+            Self::Seeded => "seeded",
+        }
+    }
+}
+
+/// What GC does with orphan file nodes past `gc_ttl`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GcMode {
+    #[default]
+    Remove,
+    /// Keep them dimmed until `gc_hard_ttl_secs`.
+    Dim,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentMode {
+    #[default]
+    User,
+    Privileged,
+}
+
 impl AgentMode {
     pub fn as_str(self) -> &'static str {
         match self {
@@ -55,10 +167,71 @@ impl AgentMode {
     }
 }
 
+/// What happens to an agent's nodes when its connection drops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisconnectPolicy {
+    /// Nodes stay until the agent reconnects and sends a fresh snapshot.
+    #[default]
+    KeepStale,
+    /// Nodes of the agent's host are removed right away.
+    RemoveNodes,
+}
+
+impl DisconnectPolicy {
+    pub const ALL: [Self; 2] = [Self::KeepStale, Self::RemoveNodes];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::KeepStale => "keep",
+            Self::RemoveNodes => "remove nodes",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "kind", content = "value", rename_all = "snake_case")]
 pub enum AgentEndpointKind {
     UdsPath(String),
+    /// Linux abstract socket, stored without the leading `@`.
+    Abstract(String),
+    /// Agent started with `--listen`, stored as `host:port`.
+    Tcp(String),
+}
+
+impl AgentEndpointKind {
+    /// Reads the socket field of the agent editor: `@name` is an abstract socket,
+    /// `tcp://host:port` a TCP agent.
+    pub fn parse(input: &str) -> Self {
+        if let Some(addr) = input.strip_prefix("tcp://") {
+            return Self::Tcp(addr.to_string());
+        }
+        match input.strip_prefix('@') {
+            Some(name) => Self::Abstract(name.to_string()),
+            None => Self::UdsPath(input.to_string()),
+        }
+    }
+
+    /// The form [`Self::parse`] reads back, for display and editing.
+    pub fn address(&self) -> String {
+        match self {
+            Self::UdsPath(path) => path.clone(),
+            Self::Abstract(name) => format!("@{name}"),
+            Self::Tcp(addr) => format!("tcp://{addr}"),
+        }
+    }
+
+    /// Address for the reader; `UnixStream::connect` treats a leading NUL as abstract.
+    pub fn connect_addr(&self) -> anyhow::Result<AgentAddr> {
+        match self {
+            Self::UdsPath(path) => Ok(AgentAddr::Uds(path.clone())),
+            #[cfg(target_os = "linux")]
+            Self::Abstract(name) => Ok(AgentAddr::Uds(format!("\0{name}"))),
+            #[cfg(not(target_os = "linux"))]
+            Self::Abstract(name) => anyhow::bail!("abstract socket @{name} needs Linux"),
+            Self::Tcp(addr) => Ok(AgentAddr::Tcp(addr.clone())),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -68,6 +241,33 @@ pub struct AgentEndpoint {
     pub kind: AgentEndpointKind,
     pub auto_connect: bool,
     pub mode_override: Option<AgentMode>,
+    /// Ask the agent for deflate-compressed frames (ignored by agents without support).
+    pub compression: bool,
+    /// Automatic reconnects before giving up until a manual reconnect; 0 retries forever.
+    pub max_reconnect_attempts: u32,
+    /// File holding the agent's shared secret; only the path is persisted.
+    pub token_file: Option<String>,
+    pub on_disconnect: DisconnectPolicy,
+    /// Session-only secret entered in the UI; never written to the config.
+    #[serde(skip)]
+    pub token: Option<String>,
+    /// Added by `--connect`; dropped when the config is saved.
+    #[serde(skip)]
+    pub temporary: bool,
+}
+
+impl AgentEndpoint {
+    /// Token sent in `Hello`: the session secret if set, else the trimmed token file.
+    pub fn resolved_token(&self) -> anyhow::Result<Option<String>> {
+        if let Some(token) = self.token.as_ref().filter(|t| !t.is_empty()) {
+            return Ok(Some(token.clone()));
+        }
+        let Some(path) = self.token_file.as_ref() else {
+            return Ok(None);
+        };
+        let raw = fs::read_to_string(path).with_context(|| format!("read token file {path}"))?;
+        Ok(Some(raw.trim().to_string()).filter(|t| !t.is_empty()))
+    }
 }
 
 impl Default for AgentEndpoint {
@@ -77,6 +277,132 @@ impl Default for AgentEndpoint {
             kind: AgentEndpointKind::UdsPath(default_uds_path()),
             auto_connect: true,
             mode_override: None,
+            compression: false,
+            max_reconnect_attempts: 0,
+            token_file: None,
+            on_disconnect: DisconnectPolicy::KeepStale,
+            token: None,
+            temporary: false,
+        }
+    }
+}
+
+/// Named snapshot of the filter, focus, view mode, edge toggles and LOD settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewPreset {
+    pub name: String,
+    pub filter: String,
+    pub filter_is_regex: bool,
+    pub focus: Option<String>,
+    pub focus_hops: usize,
+    pub view_mode: ViewerViewMode,
+    pub show_edges: bool,
+    pub show_raw_edges: bool,
+    pub show_agg_edges: bool,
+    pub lod_enabled: bool,
+    pub lod_threshold_nodes: usize,
+    pub lod_edges_mode: LodEdgesMode,
+}
+
+impl Default for ViewPreset {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            filter: String::new(),
+            filter_is_regex: false,
+            focus: None,
+            focus_hops: 2,
+            view_mode: ViewerViewMode::Spatial,
+            show_edges: true,
+            show_raw_edges: false,
+            show_agg_edges: true,
+            lod_enabled: true,
+            lod_threshold_nodes: 1500,
+            lod_edges_mode: LodEdgesMode::FocusOnly,
+        }
+    }
+}
+
+/// Per-kind factors for the force layout, applied on top of `link_distance` and
+/// `repulsion`. A pair of nodes repels with the geometric mean of both kinds' factors.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutProfile {
+    pub link_opens: f32,
+    pub link_execs: f32,
+    pub link_runs_as: f32,
+    pub link_parent_of: f32,
+    pub link_member_of: f32,
+    pub link_points_to: f32,
+    pub link_thread_of: f32,
+    pub repel_process: f32,
+    pub repel_file: f32,
+    pub repel_user: f32,
+    pub repel_container: f32,
+    pub repel_thread: f32,
+}
+
+impl Default for LayoutProfile {
+    fn default() -> Self {
+        Self {
+            link_opens: 1.0,
+            link_execs: 1.0,
+            link_runs_as: 1.0,
+            link_parent_of: 1.0,
+            link_member_of: 1.0,
+            link_points_to: 1.0,
+            link_thread_of: 1.0,
+            repel_process: 1.0,
+            repel_file: 1.0,
+            repel_user: 1.0,
+            repel_container: 1.0,
+            repel_thread: 1.0,
+        }
+    }
+}
+
+/// Per-kind node opacity in the spatial view; 1 is opaque.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NodeOpacity {
+    pub process: f32,
+    pub file: f32,
+    pub user: f32,
+    pub container: f32,
+    pub thread: f32,
+}
+
+impl Default for NodeOpacity {
+    fn default() -> Self {
+        Self {
+            process: 1.0,
+            file: 1.0,
+            user: 1.0,
+            container: 1.0,
+            thread: 1.0,
+        }
+    }
+}
+
+impl NodeOpacity {
+    pub fn for_node(&self, node: &Node) -> f32 {
+        match node {
+            Node::Process { .. } => self.process,
+            Node::File { .. } => self.file,
+            Node::User { .. } => self.user,
+            Node::Container { .. } => self.container,
+            Node::Thread { .. } => self.thread,
+        }
+    }
+
+    pub fn clamped(self) -> Self {
+        Self {
+            process: self.process.clamp(0.05, 1.0),
+            file: self.file.clamp(0.05, 1.0),
+            user: self.user.clamp(0.05, 1.0),
+            container: self.container.clamp(0.05, 1.0),
+            thread: self.thread.clamp(0.05, 1.0),
         }
     }
 }
@@ -95,28 +421,90 @@ pub struct ViewerConfig {
     pub show_edges: bool,
     pub show_raw_edges: bool,
     pub show_agg_edges: bool,
+    pub edge_bundling: bool,
+    pub edge_bundling_strength: f32,
+    pub edges_selection_only: bool,
     pub demo_mode: bool,
+    /// Synthetic events per second in Demo Mode.
+    pub demo_rate: f32,
+    pub demo_entities: usize,
+    /// Fixed seed for a reproducible demo graph; random when unset.
+    pub demo_seed: Option<u64>,
+    pub demo_scenario: DemoScenario,
     pub path_includes: Vec<String>,
     pub path_excludes: Vec<String>,
     pub focus_hops: usize,
+    pub filter_is_regex: bool,
+    pub show_only_active: bool,
+    /// Spatial view shows only nodes seen within the last `spatial_window_secs`.
+    pub spatial_window_enabled: bool,
+    pub spatial_window_secs: u64,
+    pub show_threads: bool,
+    /// Draw same-path files of different hosts as one node.
+    pub merge_files_by_path: bool,
+    pub follow_newest: bool,
     pub max_visible_nodes: usize,
     pub progressive_nodes_per_frame: usize,
     pub layout_force: bool,
+    pub layout_mode: LayoutMode,
     pub link_distance: f32,
     pub repulsion: f32,
+    pub layout_profile: LayoutProfile,
     pub damping: f32,
     pub max_step: f32,
     pub timeline_window_secs: u64,
+    /// Cap on buffered timeline events; the oldest are dropped first.
+    pub timeline_max_events: usize,
     pub timeline_scale: f32,
+    /// Keeps an undo log so past graph states can be rebuilt; costs memory.
+    pub model_history: bool,
     pub lod_enabled: bool,
     pub lod_threshold_nodes: usize,
     pub lod_edges_mode: LodEdgesMode,
+    /// Raw edges drawn per frame at most (0 = no cap); selected and glowing go first.
+    pub max_rendered_edges: usize,
     pub glow_duration_ms: u64,
+    pub glow_emissive: f32,
+    pub bloom_intensity: f32,
+    pub node_opacity: NodeOpacity,
+    pub edge_opacity: f32,
+    /// Reference grid below the spatial graph.
+    pub grid_enabled: bool,
+    pub grid_spacing: f32,
+    /// Half width of the grid around the origin.
+    pub grid_extent: f32,
+    /// Brightness of the ambient light, so the unlit side of nodes stays readable.
+    pub ambient_brightness: f32,
+    pub theme: Theme,
+    pub fade_in_new_nodes: bool,
+    pub limit_fps_when_idle: bool,
+    pub label_mode: LabelMode,
+    /// Only nodes closer to the camera than this get a label.
+    pub label_max_distance: f32,
+    /// Labels are drawn for at most this many of the closest nodes.
+    pub label_max_count: usize,
+    pub duration_units: DurationUnits,
     pub gc_enabled: bool,
     pub gc_ttl_secs: u64,
+    pub gc_mode: GcMode,
+    pub gc_hard_ttl_secs: u64,
+    /// Seconds after which fd (`opens`) edges that were not re-sent are dropped; 0 = never.
+    pub edge_ttl_secs: u64,
+    pub camera_sensitivity: f32,
+    pub size_by_degree: bool,
+    pub color_by_category: bool,
+    pub color_by_open_files: bool,
+    pub node_radius_min: f32,
+    pub node_radius_max: f32,
     pub default_agent_mode: AgentMode,
+    pub heartbeat_timeout_secs: u64,
     #[serde(default = "default_agents")]
     pub agents: Vec<AgentEndpoint>,
+    pub view_presets: Vec<ViewPreset>,
+    /// Directory for PNG screenshots; the working directory when unset.
+    pub screenshot_dir: Option<String>,
+    /// Keep the egui panels and overlays in screenshots.
+    pub screenshot_include_ui: bool,
 }
 
 impl Default for ViewerConfig {
@@ -127,7 +515,14 @@ impl Default for ViewerConfig {
             show_edges: true,
             show_raw_edges: false,
             show_agg_edges: true,
+            edge_bundling: false,
+            edge_bundling_strength: 0.5,
+            edges_selection_only: false,
             demo_mode: false,
+            demo_rate: 20.0,
+            demo_entities: 60,
+            demo_seed: None,
+            demo_scenario: DemoScenario::Random,
             path_includes: vec!["/etc".to_string(), "/home".to_string(), "/var".to_string()],
             path_excludes: vec![
                 "/proc".to_string(),
@@ -136,23 +531,63 @@ impl Default for ViewerConfig {
                 "/run".to_string(),
             ],
             focus_hops: 2,
+            filter_is_regex: false,
+            show_only_active: false,
+            spatial_window_enabled: false,
+            spatial_window_secs: 30,
+            show_threads: false,
+            merge_files_by_path: false,
+            follow_newest: false,
             max_visible_nodes: 1200,
             progressive_nodes_per_frame: 250,
             layout_force: true,
+            layout_mode: LayoutMode::Force,
             link_distance: 6.0,
             repulsion: 22.0,
+            layout_profile: LayoutProfile::default(),
             damping: 0.92,
             max_step: 0.35,
             timeline_window_secs: 60,
+            timeline_max_events: 20_000,
             timeline_scale: 0.35,
+            model_history: false,
             lod_enabled: true,
             lod_threshold_nodes: 1500,
             lod_edges_mode: LodEdgesMode::FocusOnly,
+            max_rendered_edges: 20_000,
             glow_duration_ms: 900,
+            glow_emissive: 4.0,
+            bloom_intensity: 0.2,
+            node_opacity: NodeOpacity::default(),
+            edge_opacity: 1.0,
+            grid_enabled: false,
+            grid_spacing: 5.0,
+            grid_extent: 60.0,
+            ambient_brightness: 80.0,
+            theme: Theme::Dark,
+            fade_in_new_nodes: false,
+            limit_fps_when_idle: true,
+            label_mode: LabelMode::Basename,
+            label_max_distance: 40.0,
+            label_max_count: 30,
+            duration_units: DurationUnits::Human,
             gc_enabled: true,
             gc_ttl_secs: 30,
+            gc_mode: GcMode::Remove,
+            gc_hard_ttl_secs: 600,
+            edge_ttl_secs: 0,
+            camera_sensitivity: 1.0,
+            size_by_degree: false,
+            color_by_category: true,
+            color_by_open_files: false,
+            node_radius_min: 0.2,
+            node_radius_max: 0.8,
             default_agent_mode: AgentMode::User,
+            heartbeat_timeout_secs: 10,
             agents: vec![AgentEndpoint::default()],
+            view_presets: Vec::new(),
+            screenshot_dir: None,
+            screenshot_include_ui: false,
         }
     }
 }
@@ -265,6 +700,12 @@ mod tests {
             kind: AgentEndpointKind::UdsPath("/tmp/spacegraph.sock".to_string()),
             auto_connect: false,
             mode_override: Some(AgentMode::Privileged),
+            compression: true,
+            max_reconnect_attempts: 5,
+            token_file: Some("/etc/spacegraph/token".to_string()),
+            on_disconnect: DisconnectPolicy::RemoveNodes,
+            token: None,
+            temporary: false,
         };
 
         let encoded = toml::to_string(&endpoint).expect("serialize endpoint");
@@ -273,11 +714,155 @@ mod tests {
         assert_eq!(endpoint, decoded);
     }
 
+    #[test]
+    fn endpoint_kind_parses_abstract_names() {
+        let kind = AgentEndpointKind::parse("@spacegraph");
+        assert_eq!(kind, AgentEndpointKind::Abstract("spacegraph".to_string()));
+        assert_eq!(kind.address(), "@spacegraph");
+        #[cfg(target_os = "linux")]
+        assert_eq!(
+            kind.connect_addr().unwrap(),
+            AgentAddr::Uds("\0spacegraph".to_string())
+        );
+        #[cfg(not(target_os = "linux"))]
+        assert!(kind.connect_addr().is_err());
+
+        let kind = AgentEndpointKind::parse("/tmp/spacegraph.sock");
+        assert_eq!(
+            kind,
+            AgentEndpointKind::UdsPath("/tmp/spacegraph.sock".to_string())
+        );
+        assert_eq!(
+            kind.connect_addr().unwrap(),
+            AgentAddr::Uds("/tmp/spacegraph.sock".to_string())
+        );
+
+        let endpoint = AgentEndpoint {
+            kind: AgentEndpointKind::Abstract("spacegraph".to_string()),
+            ..AgentEndpoint::default()
+        };
+        let encoded = toml::to_string(&endpoint).expect("serialize endpoint");
+        let decoded: AgentEndpoint = toml::from_str(&encoded).expect("deserialize endpoint");
+        assert_eq!(endpoint, decoded);
+    }
+
+    #[test]
+    fn endpoint_kind_parses_tcp_addresses() {
+        let kind = AgentEndpointKind::parse("tcp://10.0.0.5:7040");
+        assert_eq!(kind, AgentEndpointKind::Tcp("10.0.0.5:7040".to_string()));
+        assert_eq!(kind.address(), "tcp://10.0.0.5:7040");
+        assert_eq!(
+            kind.connect_addr().unwrap(),
+            AgentAddr::Tcp("10.0.0.5:7040".to_string())
+        );
+        // without the scheme it is still a socket path
+        assert_eq!(
+            AgentEndpointKind::parse("10.0.0.5:7040"),
+            AgentEndpointKind::UdsPath("10.0.0.5:7040".to_string())
+        );
+
+        let endpoint = AgentEndpoint {
+            kind,
+            ..AgentEndpoint::default()
+        };
+        let encoded = toml::to_string(&endpoint).expect("serialize endpoint");
+        let decoded: AgentEndpoint = toml::from_str(&encoded).expect("deserialize endpoint");
+        assert_eq!(endpoint, decoded);
+    }
+
+    #[test]
+    fn view_preset_roundtrip() {
+        let preset = ViewPreset {
+            name: "ssh".to_string(),
+            filter: r"^/etc/ssh/".to_string(),
+            filter_is_regex: true,
+            focus: Some("host:proc:1".to_string()),
+            focus_hops: 3,
+            view_mode: ViewerViewMode::Tree,
+            show_edges: true,
+            show_raw_edges: true,
+            show_agg_edges: false,
+            lod_enabled: false,
+            lod_threshold_nodes: 300,
+            lod_edges_mode: LodEdgesMode::All,
+        };
+
+        let encoded = toml::to_string(&preset).expect("serialize preset");
+        let decoded: ViewPreset = toml::from_str(&encoded).expect("deserialize preset");
+        assert_eq!(preset, decoded);
+
+        let unfocused = ViewPreset {
+            name: "all".to_string(),
+            ..ViewPreset::default()
+        };
+        let encoded = toml::to_string(&unfocused).expect("serialize preset");
+        let decoded: ViewPreset = toml::from_str(&encoded).expect("deserialize preset");
+        assert_eq!(unfocused, decoded);
+    }
+
+    #[test]
+    fn view_presets_persist_in_viewer_config() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("viewer.toml");
+        let cfg = ViewerConfig {
+            view_presets: vec![
+                ViewPreset {
+                    name: "a".to_string(),
+                    ..ViewPreset::default()
+                },
+                ViewPreset {
+                    name: "b".to_string(),
+                    focus: Some("x".to_string()),
+                    ..ViewPreset::default()
+                },
+            ],
+            ..ViewerConfig::default()
+        };
+
+        save_to_path(&cfg, &path).expect("save config");
+        assert_eq!(load_or_default_from_path(&path), cfg);
+    }
+
+    #[test]
+    fn session_token_is_never_serialized() {
+        let endpoint = AgentEndpoint {
+            token: Some("s3cret".to_string()),
+            ..AgentEndpoint::default()
+        };
+        let encoded = toml::to_string(&endpoint).expect("serialize endpoint");
+        assert!(!encoded.contains("s3cret"));
+    }
+
+    #[test]
+    fn resolved_token_prefers_session_then_file() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("token");
+        fs::write(&path, "from-file\n").expect("write token");
+        let mut endpoint = AgentEndpoint {
+            token_file: Some(path.to_string_lossy().to_string()),
+            ..AgentEndpoint::default()
+        };
+        assert_eq!(
+            endpoint.resolved_token().expect("token").as_deref(),
+            Some("from-file")
+        );
+
+        endpoint.token = Some("typed".to_string());
+        assert_eq!(
+            endpoint.resolved_token().expect("token").as_deref(),
+            Some("typed")
+        );
+
+        endpoint.token = None;
+        endpoint.token_file = Some(dir.path().join("missing").to_string_lossy().to_string());
+        assert!(endpoint.resolved_token().is_err());
+    }
+
     #[test]
     fn agent_endpoint_rejects_unknown_kind() {
         let bad = r#"
 name = "bad"
-kind = "tcp"
+kind = "quic"
 value = "127.0.0.1:1234"
 auto_connect = true
 "#;
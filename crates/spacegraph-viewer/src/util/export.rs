@@ -0,0 +1,246 @@
+use anyhow::Context;
+use spacegraph_core::{Edge, Msg, Node, NodeId};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::graph::model::{edge_kind_name, GraphModel};
+use crate::util::ids::node_label_short;
+
+pub const JSON_EXPORT_FILE: &str = "spacegraph-export.json";
+pub const DOT_EXPORT_FILE: &str = "spacegraph-export.dot";
+/// "Before" snapshot for diff mode; the loaded graph is the "after" side.
+pub const DIFF_BASELINE_FILE: &str = "spacegraph-baseline.json";
+
+fn sorted_nodes(model: &GraphModel) -> Vec<(&NodeId, &Node)> {
+    let mut nodes: Vec<(&NodeId, &Node)> = model.nodes.iter().collect();
+    nodes.sort_by(|a, b| a.0 .0.cmp(&b.0 .0));
+    nodes
+}
+
+fn sorted_edges(model: &GraphModel) -> Vec<&Edge> {
+    let mut edges: Vec<&Edge> = model.edges.iter().collect();
+    edges.sort_by(|a, b| {
+        a.from
+            .0
+            .cmp(&b.from.0)
+            .then_with(|| a.to.0.cmp(&b.to.0))
+            .then_with(|| edge_kind_name(&a.kind).cmp(edge_kind_name(&b.kind)))
+    });
+    edges
+}
+
+/// The model as a `Msg::Snapshot`, nodes and edges in stable order.
+pub fn snapshot_msg(model: &GraphModel) -> Msg {
+    Msg::Snapshot {
+        nodes: sorted_nodes(model)
+            .into_iter()
+            .map(|(id, node)| (id.clone(), node.clone()))
+            .collect(),
+        edges: sorted_edges(model).into_iter().cloned().collect(),
+    }
+}
+
+/// Serializes the model in the `Msg::Snapshot` wire shape so it can be loaded again.
+pub fn graph_to_json(model: &GraphModel) -> anyhow::Result<String> {
+    serde_json::to_string_pretty(&snapshot_msg(model)).context("failed to serialize graph snapshot")
+}
+
+pub fn graph_to_dot(model: &GraphModel) -> String {
+    let mut out = String::from("digraph spacegraph {\n  rankdir=LR;\n");
+    for (id, node) in sorted_nodes(model) {
+        out.push_str(&format!(
+            "  \"{}\" [label=\"{}\"];\n",
+            dot_escape(&id.0),
+            dot_escape(&node_label_short(node))
+        ));
+    }
+    for edge in sorted_edges(model) {
+        out.push_str(&format!(
+            "  \"{}\" -> \"{}\" [label=\"{}\"];\n",
+            dot_escape(&edge.from.0),
+            dot_escape(&edge.to.0),
+            edge_kind_name(&edge.kind)
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+pub fn write_export(path: &Path, contents: &str) -> anyhow::Result<()> {
+    fs::write(path, contents).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Parses a previously exported file; only `Msg::Snapshot` payloads are accepted.
+pub fn parse_snapshot(json: &str) -> anyhow::Result<Msg> {
+    let msg: Msg = serde_json::from_str(json).context("failed to parse snapshot JSON")?;
+    match msg {
+        Msg::Snapshot { .. } => Ok(msg),
+        _ => Err(anyhow::anyhow!("file does not contain a snapshot")),
+    }
+}
+
+pub fn read_snapshot(path: &Path) -> anyhow::Result<Msg> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    parse_snapshot(&contents)
+}
+
+/// `<dir>/spacegraph-YYYYMMDD-HHMMSS-mmm.png`, stamped with the UTC time of `t`; the
+/// milliseconds keep two shots within one second apart.
+pub fn screenshot_path(dir: &Path, t: SystemTime) -> PathBuf {
+    let since_epoch = t.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (year, month, day) = civil_date(secs / 86_400);
+    let clock = secs % 86_400;
+    dir.join(format!(
+        "spacegraph-{year:04}{month:02}{day:02}-{:02}{:02}{:02}-{:03}.png",
+        clock / 3600,
+        clock / 60 % 60,
+        clock % 60,
+        since_epoch.subsec_millis()
+    ))
+}
+
+/// Proleptic Gregorian date of a day count since 1970-01-01 (Hinnant's `civil_from_days`).
+fn civil_date(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::{EdgeKind, FileCategory, FileKind};
+    use std::time::{Duration, Instant};
+
+    fn sample_model() -> GraphModel {
+        let mut model = GraphModel::default();
+        let now = Instant::now();
+        let user = NodeId("h:user:1000".to_string());
+        let proc_id = NodeId("h:process:pid:42".to_string());
+        let file = NodeId("h:file:/tmp/\"quoted\".txt".to_string());
+        model.upsert_node(
+            user.clone(),
+            Node::User {
+                uid: 1000,
+                name: "alice".to_string(),
+            },
+            now,
+        );
+        model.upsert_node(
+            proc_id.clone(),
+            Node::Process {
+                pid: 42,
+                ppid: 1,
+                exe: "/bin/cat".to_string(),
+                cmdline: "cat".to_string(),
+                uid: 1000,
+                rss_kb: 0,
+                utime_ticks: 0,
+                container: None,
+            },
+            now,
+        );
+        model.upsert_node(
+            file.clone(),
+            Node::File {
+                path: "/tmp/\"quoted\".txt".to_string(),
+                inode: 7,
+                kind: FileKind::Regular,
+                category: FileCategory::Unknown,
+                link_target: None,
+            },
+            now,
+        );
+        model.upsert_edge(
+            Edge {
+                from: proc_id.clone(),
+                to: user,
+                kind: EdgeKind::RunsAs,
+            },
+            now,
+        );
+        model.upsert_edge(
+            Edge {
+                from: proc_id,
+                to: file,
+                kind: EdgeKind::Opens {
+                    fd: 3,
+                    mode: "r".to_string(),
+                },
+            },
+            now,
+        );
+        model
+    }
+
+    #[test]
+    fn json_export_roundtrips_as_snapshot() {
+        let model = sample_model();
+        let json = graph_to_json(&model).expect("json");
+        let msg: Msg = serde_json::from_str(&json).expect("decode snapshot");
+        let Msg::Snapshot { nodes, edges } = msg else {
+            panic!("expected snapshot");
+        };
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(edges.len(), 2);
+    }
+
+    #[test]
+    fn parse_snapshot_rejects_other_messages() {
+        let json = serde_json::to_string(&Msg::Ping).expect("encode");
+        assert!(parse_snapshot(&json).is_err());
+    }
+
+    #[test]
+    fn dot_export_lists_nodes_and_labeled_edges() {
+        let model = sample_model();
+        let dot = graph_to_dot(&model);
+        assert!(dot.starts_with("digraph spacegraph {\n  rankdir=LR;\n"));
+        assert!(dot.contains("\"h:user:1000\" [label=\"alice\"];"));
+        assert!(dot.contains("\"h:process:pid:42\" -> \"h:user:1000\" [label=\"runs_as\"];"));
+        assert!(dot.contains("[label=\"opens\"]"));
+        assert!(dot.contains("\\\"quoted\\\""));
+        assert_eq!(dot.matches(" -> ").count(), 2);
+    }
+
+    #[test]
+    fn screenshot_names_are_utc_timestamps_in_the_chosen_dir() {
+        let t = UNIX_EPOCH + Duration::from_secs(1_792_152_181);
+        assert_eq!(
+            screenshot_path(Path::new("shots"), t),
+            Path::new("shots").join("spacegraph-20261016-120301-000.png")
+        );
+        assert_eq!(
+            screenshot_path(Path::new(""), UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            Path::new("spacegraph-20000229-000000-000.png")
+        );
+    }
+
+    #[test]
+    fn screenshots_within_one_second_get_distinct_names() {
+        let t = UNIX_EPOCH + Duration::from_millis(1_792_152_181_250);
+        let later = t + Duration::from_millis(500);
+        assert_eq!(
+            screenshot_path(Path::new(""), t),
+            Path::new("spacegraph-20261016-120301-250.png")
+        );
+        assert_ne!(
+            screenshot_path(Path::new(""), t),
+            screenshot_path(Path::new(""), later)
+        );
+    }
+}
@@ -1,4 +1,42 @@
-use spacegraph_core::Node;
+use spacegraph_core::{parse_node_id, FileCategory, Node, NodeId};
+use std::time::Duration;
+
+use crate::util::config::{DurationUnits, LabelMode};
+
+pub const UNKNOWN_HOST: &str = "unknown";
+
+/// Node tint per host, indexed by the host's assignment order.
+pub const HOST_PALETTE: [[f32; 3]; 8] = [
+    [0.55, 0.75, 1.0],
+    [1.0, 0.65, 0.45],
+    [0.55, 0.9, 0.55],
+    [0.9, 0.55, 0.9],
+    [1.0, 0.9, 0.45],
+    [0.45, 0.9, 0.9],
+    [1.0, 0.55, 0.6],
+    [0.75, 0.7, 0.55],
+];
+
+pub fn host_color(index: usize) -> [f32; 3] {
+    HOST_PALETTE[index % HOST_PALETTE.len()]
+}
+
+/// Agent node_id prefix of a scoped id (`<host>:process:pid:1`).
+pub fn host_of(id: &NodeId) -> &str {
+    parse_node_id(id).map_or(UNKNOWN_HOST, |parsed| parsed.host())
+}
+
+/// File tint per content category; `Unknown` keeps the default material.
+pub fn category_color(category: FileCategory) -> Option<[f32; 3]> {
+    match category {
+        FileCategory::Config => Some([0.95, 0.8, 0.35]),
+        FileCategory::Log => Some([0.6, 0.85, 0.6]),
+        FileCategory::Binary => Some([0.9, 0.45, 0.4]),
+        FileCategory::Script => Some([0.7, 0.55, 0.95]),
+        FileCategory::Data => Some([0.4, 0.75, 0.95]),
+        FileCategory::Unknown => None,
+    }
+}
 
 // viewer-side "pretty path" (display only)
 pub fn normalize_display_path(p: &str) -> String {
@@ -35,6 +73,37 @@ pub fn node_label_short(node: &Node) -> String {
         }
         Node::File { path, .. } => normalize_display_path(path),
         Node::User { name, .. } => name.clone(),
+        Node::Container { name, .. } => name.clone(),
+        Node::Thread { name, .. } => name.clone(),
+    }
+}
+
+/// In-scene label text; `Basename` keeps only the file name or the process name.
+pub fn node_label(node: &Node, mode: LabelMode) -> Option<String> {
+    match mode {
+        LabelMode::Hidden => None,
+        LabelMode::FullPath => Some(node_label_short(node)),
+        LabelMode::Basename => Some(match node {
+            Node::Process { exe, cmdline, .. } => {
+                let program = if exe.is_empty() {
+                    cmdline.split_whitespace().next().unwrap_or_default()
+                } else {
+                    exe.as_str()
+                };
+                basename(program).to_string()
+            }
+            Node::File { path, .. } => basename(path).to_string(),
+            Node::User { name, .. } | Node::Container { name, .. } | Node::Thread { name, .. } => {
+                name.clone()
+            }
+        }),
+    }
+}
+
+fn basename(path: &str) -> &str {
+    match path.trim_end_matches('/').rsplit('/').next() {
+        Some(name) if !name.is_empty() => name,
+        _ => path,
     }
 }
 
@@ -46,20 +115,303 @@ pub fn node_label_long(node: &Node) -> Vec<String> {
             exe,
             cmdline,
             uid,
-        } => vec![
-            "kind: process".to_string(),
-            format!("pid: {pid} ppid: {ppid} uid: {uid}"),
-            format!("exe: {}", normalize_display_path(exe)),
-            format!("cmd: {}", cmdline),
-        ],
-        Node::File { path, inode, kind } => vec![
-            "kind: file".to_string(),
-            format!("path: {}", normalize_display_path(path)),
-            format!("inode: {}", inode),
-            format!("filekind: {:?}", kind),
-        ],
+            rss_kb,
+            utime_ticks,
+            container,
+        } => {
+            let mut lines = vec![
+                "kind: process".to_string(),
+                format!("pid: {pid} ppid: {ppid} uid: {uid}"),
+                format!("exe: {}", normalize_display_path(exe)),
+                format!("cmd: {}", cmdline),
+                format!("rss: {rss_kb} KiB cpu: {utime_ticks} ticks"),
+            ];
+            if let Some(container) = container {
+                lines.push(format!("container: {}", short_container_id(container)));
+            }
+            lines
+        }
+        Node::File {
+            path,
+            inode,
+            kind,
+            category,
+            link_target,
+        } => {
+            let mut lines = vec![
+                "kind: file".to_string(),
+                format!("path: {}", normalize_display_path(path)),
+                format!("inode: {}", inode),
+                format!("filekind: {:?} category: {:?}", kind, category),
+            ];
+            if let Some(target) = link_target {
+                lines.push(format!("target: {}", normalize_display_path(target)));
+            }
+            lines
+        }
         Node::User { uid, name } => {
             vec!["kind: user".to_string(), format!("uid: {uid} name: {name}")]
         }
+        Node::Container { id, name } => vec![
+            "kind: container".to_string(),
+            format!("name: {name}"),
+            format!("id: {}", short_container_id(id)),
+        ],
+        Node::Thread { tid, pid, name } => vec![
+            "kind: thread".to_string(),
+            format!("tid: {tid} pid: {pid}"),
+            format!("name: {name}"),
+        ],
+    }
+}
+
+/// Text for "Copy path": the file path, the process executable (or program from the
+/// command line when the exe is unknown), the user or thread name or the full container id.
+pub fn node_copy_path(node: &Node) -> String {
+    match node {
+        Node::Process { exe, cmdline, .. } => {
+            if exe.is_empty() || exe.starts_with('<') {
+                cmdline
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string()
+            } else {
+                exe.clone()
+            }
+        }
+        Node::File { path, .. } => path.clone(),
+        Node::User { name, .. } | Node::Thread { name, .. } => name.clone(),
+        Node::Container { id, .. } => id.clone(),
+    }
+}
+
+/// First 12 characters, like `docker ps`.
+pub fn short_container_id(id: &str) -> &str {
+    id.get(..12).unwrap_or(id)
+}
+
+/// Ages and spans for tooltips and tables: `850ms`, `42s`, `5m 5s`, `2h 10m`, `3d 4h`.
+/// Below a second it is milliseconds, above that the two largest units, rounded down.
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs == 0 {
+        return format!("{}ms", d.subsec_millis());
+    }
+    let (days, hours, mins, secs) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {mins}m")
+    } else if mins > 0 {
+        format!("{mins}m {secs}s")
+    } else {
+        format!("{secs}s")
+    }
+}
+
+pub fn format_duration_in(d: Duration, units: DurationUnits) -> String {
+    match units {
+        DurationUnits::Human => format_duration(d),
+        DurationUnits::Seconds => format!("{:.1}s", d.as_secs_f32()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_of_reads_host_of_scoped_ids() {
+        assert_eq!(host_of(&NodeId("web-1:process:pid:7".to_string())), "web-1");
+        assert_eq!(host_of(&NodeId("db:file:/etc/a:b".to_string())), "db");
+    }
+
+    #[test]
+    fn host_of_falls_back_for_malformed_ids() {
+        assert_eq!(host_of(&NodeId("no-colon".to_string())), UNKNOWN_HOST);
+        assert_eq!(host_of(&NodeId(":file:/x".to_string())), UNKNOWN_HOST);
+        assert_eq!(host_of(&NodeId("web-1:bogus".to_string())), UNKNOWN_HOST);
+        assert_eq!(host_of(&NodeId(String::new())), UNKNOWN_HOST);
+    }
+
+    #[test]
+    fn copy_path_per_node_kind() {
+        let process = |exe: &str| Node::Process {
+            pid: 7,
+            ppid: 1,
+            exe: exe.to_string(),
+            cmdline: "/usr/bin/python3 app.py".to_string(),
+            uid: 0,
+            rss_kb: 0,
+            utime_ticks: 0,
+            container: None,
+        };
+        assert_eq!(
+            node_copy_path(&process("/usr/bin/python3.12")),
+            "/usr/bin/python3.12"
+        );
+        assert_eq!(node_copy_path(&process("<unknown>")), "/usr/bin/python3");
+        let file = Node::File {
+            path: "/etc/ssh/sshd_config".to_string(),
+            inode: 1,
+            kind: spacegraph_core::FileKind::Regular,
+            category: FileCategory::Config,
+            link_target: None,
+        };
+        assert_eq!(node_copy_path(&file), "/etc/ssh/sshd_config");
+        let user = Node::User {
+            uid: 0,
+            name: "root".to_string(),
+        };
+        assert_eq!(node_copy_path(&user), "root");
+        let container = Node::Container {
+            id: "0123456789abcdef".to_string(),
+            name: "web".to_string(),
+        };
+        assert_eq!(node_copy_path(&container), "0123456789abcdef");
+    }
+
+    #[test]
+    fn process_long_label_includes_usage() {
+        let node = Node::Process {
+            pid: 7,
+            ppid: 1,
+            exe: "/bin/sh".to_string(),
+            cmdline: "sh".to_string(),
+            uid: 0,
+            rss_kb: 2048,
+            utime_ticks: 99,
+            container: None,
+        };
+        let lines = node_label_long(&node);
+        assert!(lines.contains(&"rss: 2048 KiB cpu: 99 ticks".to_string()));
+    }
+
+    #[test]
+    fn file_long_label_includes_category() {
+        let node = Node::File {
+            path: "/var/log/syslog".to_string(),
+            inode: 3,
+            kind: spacegraph_core::FileKind::Regular,
+            category: FileCategory::Log,
+            link_target: None,
+        };
+        let lines = node_label_long(&node);
+        assert!(lines.contains(&"filekind: Regular category: Log".to_string()));
+    }
+
+    #[test]
+    fn symlink_long_label_shows_target() {
+        let node = Node::File {
+            path: "/etc/localtime".to_string(),
+            inode: 3,
+            kind: spacegraph_core::FileKind::Symlink,
+            category: FileCategory::Config,
+            link_target: Some("/usr/share/zoneinfo/UTC".to_string()),
+        };
+        let lines = node_label_long(&node);
+        assert!(lines.contains(&"target: /usr/share/zoneinfo/UTC".to_string()));
+    }
+
+    #[test]
+    fn process_long_label_shows_short_container_id() {
+        let node = Node::Process {
+            pid: 7,
+            ppid: 1,
+            exe: "/bin/sh".to_string(),
+            cmdline: "sh".to_string(),
+            uid: 0,
+            rss_kb: 0,
+            utime_ticks: 0,
+            container: Some("3f2a9c1b7d4e5f60718293a4b5c6d7e8".to_string()),
+        };
+        let lines = node_label_long(&node);
+        assert!(lines.contains(&"container: 3f2a9c1b7d4e".to_string()));
+    }
+
+    #[test]
+    fn node_label_follows_label_mode() {
+        let file = Node::File {
+            path: "/etc/ssh/./sshd_config".to_string(),
+            inode: 3,
+            kind: spacegraph_core::FileKind::Regular,
+            category: FileCategory::Config,
+            link_target: None,
+        };
+        let process = Node::Process {
+            pid: 7,
+            ppid: 1,
+            exe: "/usr/sbin/sshd".to_string(),
+            cmdline: "sshd: /usr/sbin/sshd -D".to_string(),
+            uid: 0,
+            rss_kb: 0,
+            utime_ticks: 0,
+            container: None,
+        };
+        let user = Node::User {
+            uid: 0,
+            name: "root".to_string(),
+        };
+
+        assert_eq!(
+            node_label(&file, LabelMode::FullPath).as_deref(),
+            Some("/etc/ssh/sshd_config")
+        );
+        assert_eq!(
+            node_label(&file, LabelMode::Basename).as_deref(),
+            Some("sshd_config")
+        );
+        assert_eq!(
+            node_label(&process, LabelMode::FullPath).as_deref(),
+            Some("sshd: /usr/sbin/sshd -D")
+        );
+        assert_eq!(
+            node_label(&process, LabelMode::Basename).as_deref(),
+            Some("sshd")
+        );
+        assert_eq!(
+            node_label(&user, LabelMode::Basename).as_deref(),
+            Some("root")
+        );
+        for node in [&file, &process, &user] {
+            assert_eq!(node_label(node, LabelMode::Hidden), None);
+        }
+    }
+
+    #[test]
+    fn basename_label_falls_back_to_cmdline_and_root() {
+        let kernel_thread = Node::Process {
+            pid: 2,
+            ppid: 0,
+            exe: String::new(),
+            cmdline: "/usr/lib/systemd/systemd --user".to_string(),
+            uid: 0,
+            rss_kb: 0,
+            utime_ticks: 0,
+            container: None,
+        };
+        assert_eq!(
+            node_label(&kernel_thread, LabelMode::Basename).as_deref(),
+            Some("systemd")
+        );
+        assert_eq!(basename("/"), "/");
+        assert_eq!(basename("/var/log/"), "log");
+    }
+
+    #[test]
+    fn durations_use_the_two_largest_units() {
+        let ms = Duration::from_millis;
+        let s = Duration::from_secs;
+        assert_eq!(format_duration(ms(0)), "0ms");
+        assert_eq!(format_duration(ms(999)), "999ms");
+        assert_eq!(format_duration(ms(1000)), "1s");
+        assert_eq!(format_duration(ms(59_999)), "59s");
+        assert_eq!(format_duration(s(60)), "1m 0s");
+        assert_eq!(format_duration(s(305)), "5m 5s");
+        assert_eq!(format_duration(s(3599)), "59m 59s");
+        assert_eq!(format_duration(s(3600)), "1h 0m");
+        assert_eq!(format_duration(s(2 * 3600 + 10 * 60 + 59)), "2h 10m");
+        assert_eq!(format_duration(s(86_400 + 4 * 3600)), "1d 4h");
     }
 }
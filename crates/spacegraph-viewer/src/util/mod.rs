@@ -1,3 +1,6 @@
 pub mod agent_command;
+pub mod cli;
 pub mod config;
+pub mod export;
 pub mod ids;
+pub mod record;
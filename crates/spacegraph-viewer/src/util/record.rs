@@ -0,0 +1,301 @@
+use anyhow::Context;
+use crossbeam_channel::Sender;
+use serde::{Deserialize, Serialize};
+use spacegraph_core::Msg;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+use crate::net::{Incoming, IncomingKind};
+
+pub const RECORD_FILE: &str = "spacegraph-record.jsonl";
+
+/// One line of a recording: a message and its offset from the start of the session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordLine {
+    pub t_ms: u64,
+    pub msg: Msg,
+}
+
+/// Appends incoming messages to a JSON-lines file from a background thread.
+/// The first line is a `Msg::Snapshot` of the graph at the moment recording started.
+pub struct Recorder {
+    started: Instant,
+    tx: Option<Sender<RecordLine>>,
+    writer: Option<JoinHandle<anyhow::Result<usize>>>,
+}
+
+impl Recorder {
+    pub fn start(path: &Path, snapshot: Msg) -> anyhow::Result<Self> {
+        let file =
+            File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+        let (tx, rx) = crossbeam_channel::unbounded::<RecordLine>();
+        tx.send(RecordLine {
+            t_ms: 0,
+            msg: snapshot,
+        })
+        .ok();
+        let writer = std::thread::Builder::new()
+            .name("spacegraph-record".to_string())
+            .spawn(move || {
+                let mut out = BufWriter::new(file);
+                let mut lines = 0;
+                for line in rx {
+                    write_line(&mut out, &line)?;
+                    lines += 1;
+                }
+                out.flush().context("failed to flush recording")?;
+                Ok(lines)
+            })
+            .context("failed to spawn recorder thread")?;
+        Ok(Self {
+            started: Instant::now(),
+            tx: Some(tx),
+            writer: Some(writer),
+        })
+    }
+
+    /// Queues the graph payload of `inc`; connection status messages are skipped.
+    pub fn record(&self, inc: &Incoming) {
+        let msg = match &inc.kind {
+            IncomingKind::Identity(msg)
+            | IncomingKind::Snapshot(msg)
+            | IncomingKind::Event(msg) => msg,
+            _ => return,
+        };
+        if let Some(tx) = self.tx.as_ref() {
+            // A failed writer surfaces through `stop`.
+            let _ = tx.send(RecordLine {
+                t_ms: self.started.elapsed().as_millis() as u64,
+                msg: msg.clone(),
+            });
+        }
+    }
+
+    /// Flushes and closes the file; returns the number of lines written.
+    pub fn stop(mut self) -> anyhow::Result<usize> {
+        self.finish()
+    }
+
+    fn finish(&mut self) -> anyhow::Result<usize> {
+        self.tx.take();
+        match self.writer.take() {
+            Some(writer) => writer
+                .join()
+                .map_err(|_| anyhow::anyhow!("recorder thread panicked"))?,
+            None => Ok(0),
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+fn write_line(out: &mut impl Write, line: &RecordLine) -> anyhow::Result<()> {
+    serde_json::to_writer(&mut *out, line).context("failed to serialize message")?;
+    out.write_all(b"\n").context("failed to write recording")?;
+    Ok(())
+}
+
+/// Parses a recording; blank lines are skipped and lines are ordered by timestamp.
+pub fn parse_recording(contents: &str) -> anyhow::Result<Vec<RecordLine>> {
+    let mut lines = Vec::new();
+    for (idx, raw) in contents.lines().enumerate() {
+        if raw.trim().is_empty() {
+            continue;
+        }
+        let line: RecordLine = serde_json::from_str(raw)
+            .with_context(|| format!("invalid recording line {}", idx + 1))?;
+        lines.push(line);
+    }
+    if lines.is_empty() {
+        anyhow::bail!("recording is empty");
+    }
+    // Stable, so messages sharing a millisecond keep their file order.
+    lines.sort_by_key(|line| line.t_ms);
+    Ok(lines)
+}
+
+pub fn read_recording(path: &Path) -> anyhow::Result<Vec<RecordLine>> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    parse_recording(&contents)
+}
+
+/// Wraps a replayed message the way the network reader would have.
+pub fn replay_incoming(stream: &str, msg: Msg) -> Incoming {
+    let stream = stream.to_string();
+    match msg {
+        Msg::Identity { .. } => Incoming::identity(stream, msg),
+        Msg::Snapshot { .. }
+        | Msg::SnapshotBegin { .. }
+        | Msg::SnapshotChunk { .. }
+        | Msg::SnapshotEnd => Incoming::snapshot(stream, msg),
+        Msg::Event { .. } => Incoming::event(stream, msg),
+        _ => Incoming::other(stream, msg),
+    }
+}
+
+/// Plays a recording back at its original pacing, scaled by `speed`.
+pub struct Replay {
+    lines: Vec<RecordLine>,
+    cursor: usize,
+    pub position_ms: f64,
+    pub speed: f32,
+    pub playing: bool,
+}
+
+impl Replay {
+    pub fn new(lines: Vec<RecordLine>) -> Self {
+        Self {
+            lines,
+            cursor: 0,
+            position_ms: 0.0,
+            speed: 1.0,
+            playing: true,
+        }
+    }
+
+    pub fn duration_ms(&self) -> u64 {
+        self.lines.last().map(|line| line.t_ms).unwrap_or(0)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.cursor >= self.lines.len()
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        if !self.playing {
+            return;
+        }
+        let duration = self.duration_ms() as f64;
+        self.position_ms =
+            (self.position_ms + f64::from(dt * self.speed.max(0.0)) * 1000.0).min(duration);
+        if self.position_ms >= duration && self.finished() {
+            self.playing = false;
+        }
+    }
+
+    /// Moves the play head. Returns true when seeking backwards, in which case the caller
+    /// must reset the graph: messages are re-applied from the start up to the new position.
+    pub fn seek(&mut self, ms: f64) -> bool {
+        let target = ms.clamp(0.0, self.duration_ms() as f64);
+        let rewind = target < self.position_ms;
+        self.position_ms = target;
+        if rewind {
+            self.cursor = 0;
+        }
+        rewind
+    }
+
+    /// Messages whose timestamp the play head has passed since the last call.
+    pub fn due(&mut self) -> Vec<Msg> {
+        let start = self.cursor;
+        while self
+            .lines
+            .get(self.cursor)
+            .map(|line| line.t_ms as f64 <= self.position_ms)
+            .unwrap_or(false)
+        {
+            self.cursor += 1;
+        }
+        self.lines[start..self.cursor]
+            .iter()
+            .map(|line| line.msg.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spacegraph_core::{Delta, Node, NodeId};
+
+    #[test]
+    fn recording_starts_with_snapshot_and_skips_status_messages() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join(RECORD_FILE);
+        let rec = Recorder::start(
+            &path,
+            Msg::Snapshot {
+                nodes: Vec::new(),
+                edges: Vec::new(),
+            },
+        )
+        .expect("start");
+        rec.record(&Incoming::connected("local".to_string()));
+        rec.record(&Incoming {
+            stream: "local".to_string(),
+            kind: IncomingKind::Event(Msg::Event {
+                delta: Delta::UpsertNode {
+                    id: NodeId("u".to_string()),
+                    node: Node::User {
+                        uid: 0,
+                        name: "root".to_string(),
+                    },
+                },
+            }),
+            bytes: 0,
+            class: None,
+        });
+        assert_eq!(rec.stop().expect("stop"), 2);
+
+        let contents = std::fs::read_to_string(&path).expect("read");
+        let lines = parse_recording(&contents).expect("parse");
+        assert_eq!(lines[0].t_ms, 0);
+        assert!(matches!(lines[0].msg, Msg::Snapshot { .. }));
+        assert!(matches!(lines[1].msg, Msg::Event { .. }));
+    }
+
+    #[test]
+    fn parse_orders_lines_by_timestamp_and_reports_bad_lines() {
+        let contents = concat!(
+            r#"{"t_ms":250,"msg":{"type":"Pong"}}"#,
+            "\n\n",
+            r#"{"t_ms":0,"msg":{"type":"SnapshotEnd"}}"#,
+            "\n",
+            r#"{"t_ms":250,"msg":{"type":"Ping"}}"#,
+            "\n",
+        );
+        let lines = parse_recording(contents).expect("parse");
+        let order: Vec<(u64, bool)> = lines
+            .iter()
+            .map(|line| (line.t_ms, matches!(line.msg, Msg::Pong)))
+            .collect();
+        assert_eq!(order, vec![(0, false), (250, true), (250, false)]);
+
+        let err = parse_recording("{\"t_ms\":0}\nnot json\n").unwrap_err();
+        assert!(format!("{err:#}").contains("line 1"));
+        assert!(parse_recording("\n").is_err());
+    }
+
+    #[test]
+    fn replay_paces_by_speed_and_rewinds_on_backward_seek() {
+        let line = |t_ms| RecordLine {
+            t_ms,
+            msg: Msg::Ping,
+        };
+        let mut replay = Replay::new(vec![line(0), line(1000), line(3000)]);
+        replay.speed = 2.0;
+
+        replay.advance(0.25);
+        assert_eq!(replay.due().len(), 1);
+        replay.advance(0.25);
+        assert_eq!(replay.due().len(), 1);
+        assert!(replay.due().is_empty());
+
+        assert!(replay.seek(500.0));
+        assert_eq!(replay.due().len(), 1);
+        assert!(!replay.seek(5000.0));
+        assert_eq!(replay.position_ms, 3000.0);
+        assert_eq!(replay.due().len(), 2);
+        replay.advance(1.0);
+        assert!(replay.finished());
+        assert!(!replay.playing);
+    }
+}